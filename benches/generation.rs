@@ -0,0 +1,58 @@
+//! Generation primitives: pool and alias-table building, shuffling,
+//! sampling, and each entropy backend. `randpass bench` runs the same cases without criterion.
+
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use randpass::pass::charset::{self, Charset, Table};
+use randpass::pass::{generate_from_charset, sample, shuffle};
+use randpass::rand::{self, Rand};
+use randpass::settings::Settings;
+
+fn pool(c: &mut Criterion) {
+    let settings = Settings::default();
+    let mut chars = charset::build(&settings);
+    let table = Charset::build(&settings);
+    let mut buf = Vec::with_capacity(settings.pass_length);
+
+    c.bench_function("charset_build", |b| {
+        b.iter(|| charset::build(black_box(&settings)))
+    });
+    c.bench_function("shuffle", |b| b.iter(|| shuffle(black_box(&mut chars))));
+    c.bench_function("sample", |b| {
+        b.iter(|| sample(black_box(&chars), settings.pass_length, &mut buf))
+    });
+    c.bench_function("password_pooled", |b| {
+        b.iter(|| generate_from_charset(black_box(&mut chars), settings.pass_length, &mut buf))
+    });
+    c.bench_function("table_build", |b| {
+        b.iter(|| Table::build(black_box(&settings)))
+    });
+    c.bench_function("password", |b| {
+        b.iter(|| black_box(&table).generate(settings.pass_length, &mut buf))
+    });
+}
+
+fn entropy(c: &mut Criterion) {
+    c.bench_function("entropy_hardware", |b| b.iter(rand::hardware_entropy));
+    c.bench_function("rand_hardware", |b| b.iter(Rand::get));
+
+    rand::start_prefetch();
+    c.bench_function("rand_prefetch", |b| b.iter(Rand::get));
+    rand::stop_prefetch();
+
+    if rand::enable_urandom() {
+        let mut hint = 0usize;
+        c.bench_function("entropy_urandom", |b| {
+            b.iter(|| {
+                hint = hint.wrapping_add(1);
+                rand::urand::rand(hint)
+            })
+        });
+        c.bench_function("rand_urandom", |b| b.iter(Rand::get));
+        rand::disable_urandom();
+    }
+}
+
+criterion_group!(benches, pool, entropy);
+criterion_main!(benches);
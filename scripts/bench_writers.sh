@@ -0,0 +1,62 @@
+#!/bin/bash
+# Compare file output through the default writer and --io-uring
+# Usage: scripts/bench_writers.sh [BYTES] [PASSWORDS] [DIR]
+#   BYTES      size of each --bytes run (default 1G)
+#   PASSWORDS  passwords per bulk run (default 2000000)
+#   DIR        where the output files go (default: a temp dir; use a real
+#              disk, tmpfs hides most of the difference)
+
+set -e
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_DIR="$(dirname "$SCRIPT_DIR")"
+BYTES="${1:-1G}"
+PASSWORDS="${2:-2000000}"
+if [ -n "$3" ]; then
+    OUT_DIR="$3"
+else
+    OUT_DIR="$(mktemp -d)"
+    trap 'rm -rf "$OUT_DIR"' EXIT
+fi
+RUNS=3
+
+cd "$PROJECT_DIR"
+echo "Building release binary..."
+cargo build --release --quiet
+BIN="$PROJECT_DIR/target/release/randpass"
+
+# Best of $RUNS wall-clock runs of "$@", in milliseconds
+best_ms() {
+    local best=""
+    for _ in $(seq $RUNS); do
+        rm -f "$OUT_DIR/bench.out"
+        local start end
+        start=$(date +%s%N)
+        "$@" > /dev/null
+        end=$(date +%s%N)
+        local ms=$(( (end - start) / 1000000 ))
+        if [ -z "$best" ] || [ "$ms" -lt "$best" ]; then
+            best=$ms
+        fi
+    done
+    rm -f "$OUT_DIR/bench.out"
+    echo "$best"
+}
+
+report() {
+    local label="$1" base="$2" fast="$3"
+    printf "%-34s %8s ms %8s ms   %sx\n" "$label" "$base" "$fast" \
+        "$(awk "BEGIN { printf \"%.2f\", $base / ($fast > 0 ? $fast : 1) }")"
+}
+
+echo ""
+echo "Output dir: $OUT_DIR (best of $RUNS)"
+printf "%-34s %11s %11s   %s\n" "" "default" "--io-uring" "speedup"
+
+base=$(best_ms "$BIN" --bytes -n "$BYTES" -o "$OUT_DIR/bench.out")
+fast=$(best_ms "$BIN" --bytes -n "$BYTES" -o "$OUT_DIR/bench.out" --io-uring)
+report "--bytes -n $BYTES" "$base" "$fast"
+
+base=$(best_ms "$BIN" -l 32 -n "$PASSWORDS" -q -o "$OUT_DIR/bench.out")
+fast=$(best_ms "$BIN" -l 32 -n "$PASSWORDS" -q -o "$OUT_DIR/bench.out" --io-uring)
+report "-l 32 -n $PASSWORDS" "$base" "$fast"
@@ -0,0 +1,52 @@
+#!/bin/bash
+# Compress a wordlist for src/pass/words/data and print its EMBEDDED entry
+# Usage: scripts/embed_wordlist.sh SRC NAME [LANG] [DESCRIPTION]
+#   SRC          one word per line; diceware "11111<TAB>word" lines work
+#   NAME         list name, e.g. eff-large; writes data/NAME.z
+#   LANG         ISO 639-1 code for --lang (default en)
+#   DESCRIPTION  shown by `randpass wordlist list` (default NAME)
+#
+# Words are stored NFC, one per line with a trailing newline, as inflate()
+# expects. Check the license of a list before vendoring it.
+
+set -e
+
+if [ $# -lt 2 ]; then
+    sed -n '3,7p' "$0" | sed 's/^# \{0,1\}//'
+    exit 1
+fi
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_DIR="$(dirname "$SCRIPT_DIR")"
+SRC="$1"
+NAME="$2"
+LANG_CODE="${3:-en}"
+DESCRIPTION="${4:-$NAME}"
+OUT="$PROJECT_DIR/src/pass/words/data/$NAME.z"
+
+python3 - "$SRC" "$OUT" "$NAME" "$LANG_CODE" "$DESCRIPTION" <<'PY'
+import sys, unicodedata, zlib
+
+src, out, name, lang, description = sys.argv[1:]
+words = []
+for line in open(src, encoding="utf-8"):
+    fields = line.split()
+    if not fields:
+        continue
+    # Diceware lines start with the dice roll
+    word = fields[-1] if len(fields) > 1 and fields[0].isdigit() else fields[0]
+    words.append(unicodedata.normalize("NFC", word))
+if len(set(words)) != len(words):
+    sys.exit(f"{src}: repeated words")
+
+text = "".join(w + "\n" for w in words).encode()
+open(out, "wb").write(zlib.compress(text, 9))
+print(f"""    Embedded {{
+        name: "{name}",
+        description: "{description}",
+        lang: "{lang}",
+        words: {len(words)},
+        size: {len(text)},
+        data: include_bytes!("data/{name}.z"),
+    }},""")
+PY
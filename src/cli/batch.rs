@@ -0,0 +1,104 @@
+//! Per-record (`--for-each`) and crypt(3) (`--hash`) output.
+//!
+//! `--for-each FILE` emits one `record:password` line per record, the
+//! `chpasswd` input format. With `--hash`, stdout carries `[record:]hash`
+//! lines and the plaintext companions go to the `-o` file.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+
+use zeroize::Zeroize;
+
+use crate::pass::charset::Charset;
+use crate::pass::crypt::{self, Scheme};
+use crate::pass::{SecureBufWriter, file_writer, max_len, next_password, pool};
+use crate::settings::Settings;
+
+/// Read non-empty, non-comment lines from a `--for-each` file.
+pub fn read_records(path: &str) -> Result<Vec<String>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    Ok(text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(String::from)
+        .collect())
+}
+
+pub fn output(
+    settings: &Settings,
+    records: Option<&[String]>,
+    count: usize,
+    hash: Option<Scheme>,
+) -> Result<(), String> {
+    let total = records.map_or(count, |r| r.len());
+    let to_file = !settings.output_file_path.is_empty();
+    if hash.is_some() && !to_file {
+        return Err("--hash needs -o FILE to keep the plaintext passwords".to_string());
+    }
+
+    let mut file = if to_file {
+        Some(file_writer(
+            OpenOptions::new()
+                .read(settings.mmap)
+                .create(true)
+                .append(true)
+                .mode(0o600)
+                .open(&settings.output_file_path)
+                .map_err(|e| format!("{}: {}", settings.output_file_path, e))?,
+            settings,
+            None,
+        ))
+    } else {
+        None
+    };
+    let stdout = std::io::stdout();
+    let mut out = SecureBufWriter::new(stdout.lock());
+
+    let chars = Charset::build(settings);
+    let mut pass = pool::take(max_len(settings));
+    // Record, separator, and the password or its hash (under 128 bytes)
+    let longest = records.map_or(0, |r| r.iter().map(String::len).max().unwrap_or(0));
+    let mut line = pool::take(longest + pass.capacity().max(128) + 2);
+
+    for n in 0..total {
+        next_password(settings, &chars, n, &mut pass);
+        let prefix = records.map(|r| r[n].as_str());
+
+        // Plaintext line: to the file when given, otherwise stdout
+        if let Some(p) = prefix {
+            line.extend_from_slice(p.as_bytes());
+            line.push(b':');
+        }
+        line.extend_from_slice(&pass);
+        line.push(b'\n');
+        match file {
+            Some(ref mut f) => f.write_all(&line),
+            None => out.write_all(&line),
+        }
+        .map_err(|e| e.to_string())?;
+        line.zeroize();
+
+        if let Some(scheme) = hash {
+            let hashed = crypt::hash(&pass, scheme);
+            pass.zeroize();
+            let hashed = hashed?;
+            if let Some(p) = prefix {
+                line.extend_from_slice(p.as_bytes());
+                line.push(b':');
+            }
+            line.extend_from_slice(hashed.as_bytes());
+            line.push(b'\n');
+            out.write_all(&line).map_err(|e| e.to_string())?;
+            line.clear();
+        }
+        pass.zeroize();
+    }
+
+    crate::rand::shutdown_urandom();
+    if let Some(f) = file {
+        f.finish().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
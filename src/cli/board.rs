@@ -0,0 +1,55 @@
+//! `--board-select`: print a batch numbered, then copy the entries the
+//! user asks for to the clipboard one at a time, where `-b` copies the
+//! whole batch or nothing.
+
+use std::io::{BufRead, Write};
+
+use copypasta::ClipboardContext;
+
+use super::pick::{self, Candidates};
+use super::prompts;
+use crate::pass::SecureBufWriter;
+use crate::settings::Settings;
+
+/// Print `count` passwords with their numbers and copy each number typed
+/// until an empty line or end of input. Every password is zeroized when
+/// the user is done.
+pub fn output(settings: &Settings, count: usize) -> Result<(), String> {
+    let mut ctx = ClipboardContext::new().map_err(|e| format!("Clipboard unavailable: {}", e))?;
+    let batch = Candidates::generate(settings, count);
+
+    let width = count.to_string().len();
+    let mut out = SecureBufWriter::new(std::io::stdout().lock());
+    for i in 0..batch.len() {
+        let _ = write!(out, "{:>width$}  ", i + 1)
+            .and_then(|_| out.write_all(batch.get(i)))
+            .and_then(|_| out.write_all(b"\n"));
+    }
+    let _ = out.flush();
+    drop(out);
+
+    let mut stdin = std::io::stdin().lock();
+    let mut input = String::new();
+    loop {
+        prompts::board_prompt(count);
+        input.clear();
+        if stdin.read_line(&mut input).unwrap_or(0) == 0 {
+            break;
+        }
+        let choice = input.trim();
+        if choice.is_empty() {
+            break;
+        }
+        match choice.parse::<usize>() {
+            Ok(n) if (1..=count).contains(&n) => match pick::copy(&mut ctx, batch.get(n - 1)) {
+                Ok(()) => {
+                    prompts::board_copied(n);
+                    pick::remember(settings, batch.get(n - 1));
+                }
+                Err(e) => prompts::clipboard_error(&e),
+            },
+            _ => prompts::warn(&format!("Enter a number from 1 to {}", count)),
+        }
+    }
+    Ok(())
+}
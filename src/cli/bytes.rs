@@ -1,6 +1,156 @@
 use crate::rand::Rand;
-use std::fs::OpenOptions;
+use crate::terminal::{Throttle, accessible, format_bytes, progress_bar_box_to};
+use std::fs::{File, OpenOptions};
 use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::time::{Duration, Instant};
+use zeroize::Zeroize;
+
+use super::checksum::{Checksum, HashWriter, Hasher, write_digest_file};
+use super::encode::{ENCODE_GROUP, Encoding, STREAM_CHUNK, encode_into};
+use super::{prompts, quiet};
+use crate::pass::fsync::{self, Fsync};
+use crate::pass::{MmapWriter, RingWriter, Synced};
+
+/// Options for a `--bytes` run.
+#[derive(Debug, Default)]
+pub struct ByteOptions<'a> {
+    pub limit: Option<usize>,
+    pub file_path: Option<&'a str>,
+    pub encoding: Option<Encoding>,
+    pub stats: bool,
+    /// Write size per syscall (`--bs`); 64 KiB when unset
+    pub block_size: Option<usize>,
+    /// Open the output with O_DIRECT (`--direct`)
+    pub direct: bool,
+    /// Write through `RingWriter` (`--io-uring`)
+    pub io_uring: bool,
+    /// Write through `MmapWriter` (`--mmap`)
+    pub mmap: bool,
+    /// When the output file is synced (`--fsync`)
+    pub fsync: Fsync,
+    /// Rotate output across `<file>.000`, `<file>.001`, ... of this size
+    pub split: Option<usize>,
+    /// Digest the emitted stream into `<file>.<ext>` (`--checksum`)
+    pub checksum: Option<Checksum>,
+    /// Create the file exclusively with these permissions instead of
+    /// truncating an existing one
+    pub create_mode: Option<u32>,
+    /// The output is a block device: opened with O_EXCL so the kernel
+    /// refuses one in use, and never created or truncated
+    pub device: bool,
+    /// Skip the startup entropy health test (`--no-health-check`)
+    pub skip_health_check: bool,
+}
+
+/// `--bs` when unset
+pub(crate) const DEFAULT_BLOCK: usize = 65536;
+/// O_DIRECT needs buffer address, length, and file offset aligned to the
+/// logical block size; 4 KiB covers every common device.
+const DIRECT_ALIGN: usize = 4096;
+
+impl ByteOptions<'_> {
+    pub fn validate(&self) -> Result<(), String> {
+        let bs = self.block_size.unwrap_or(DEFAULT_BLOCK);
+        if bs == 0 {
+            return Err("--bs must be greater than 0".to_string());
+        }
+        if let Some(split) = self.split {
+            if split == 0 {
+                return Err("--split-bytes must be greater than 0".to_string());
+            }
+            if self.file_path.is_none() {
+                return Err("--split-bytes needs an output file prefix (-o)".to_string());
+            }
+            if self.limit.is_none() {
+                return Err("--split-bytes needs a total size (-n)".to_string());
+            }
+            if self.direct && !split.is_multiple_of(DIRECT_ALIGN) {
+                return Err(format!(
+                    "--direct needs --split-bytes to be a multiple of {DIRECT_ALIGN}"
+                ));
+            }
+        }
+        if self.io_uring {
+            if self.file_path.is_none() {
+                return Err("--io-uring needs an output file (-o)".to_string());
+            }
+            if self.split.is_some() {
+                return Err("--io-uring cannot be combined with --split-bytes".to_string());
+            }
+        }
+        if self.mmap {
+            if self.file_path.is_none() {
+                return Err("--mmap needs an output file (-o)".to_string());
+            }
+            if self.split.is_some() || self.io_uring || self.direct {
+                return Err(
+                    "--mmap cannot be combined with --split-bytes, --io-uring or --direct"
+                        .to_string(),
+                );
+            }
+        }
+        if self.fsync != Fsync::Never && self.file_path.is_none() {
+            return Err("--fsync needs an output file (-o)".to_string());
+        }
+        if self.direct {
+            if self.file_path.is_none() {
+                return Err("--direct needs an output file (-o)".to_string());
+            }
+            if self.encoding.is_some() {
+                return Err("--direct cannot be combined with --encode".to_string());
+            }
+            if !bs.is_multiple_of(DIRECT_ALIGN) {
+                return Err(format!(
+                    "--direct needs --bs to be a multiple of {DIRECT_ALIGN}"
+                ));
+            }
+            if let Some(limit) = self.limit
+                && !limit.is_multiple_of(DIRECT_ALIGN)
+            {
+                return Err(format!(
+                    "--direct needs the total size to be a multiple of {DIRECT_ALIGN}"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Bytes the run will write, when known up front: raw output with a
+    /// limit (encoded text adds line breaks).
+    fn raw_size(&self) -> Option<usize> {
+        self.limit.filter(|_| self.encoding.is_none())
+    }
+}
+
+/// Page-aligned, zeroized-on-drop buffer (O_DIRECT requires alignment).
+struct AlignedBuf {
+    ptr: *mut u8,
+    layout: std::alloc::Layout,
+}
+
+impl AlignedBuf {
+    fn new(len: usize) -> Self {
+        let layout = std::alloc::Layout::from_size_align(len, DIRECT_ALIGN)
+            .expect("block size overflows layout");
+        let ptr = unsafe { std::alloc::alloc_zeroed(layout) };
+        if ptr.is_null() {
+            std::alloc::handle_alloc_error(layout);
+        }
+        Self { ptr, layout }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.layout.size()) }
+    }
+}
+
+impl Drop for AlignedBuf {
+    fn drop(&mut self) {
+        self.as_mut_slice().zeroize();
+        unsafe { std::alloc::dealloc(self.ptr, self.layout) };
+    }
+}
 
 /// Parse byte count with optional K, M, G suffix
 pub fn parse_byte_count(s: &str) -> Option<usize> {
@@ -17,14 +167,136 @@ pub fn parse_byte_count(s: &str) -> Option<usize> {
     num_str.parse::<usize>().ok().map(|n| n * multiplier)
 }
 
-fn write_bytes<W: Write>(out: &mut W, limit: Option<usize>) {
-    let mut buf = [0u8; 65536];
+/// Fill `buf` with RNG output.
+pub(super) fn fill(buf: &mut [u8]) {
+    for chunk in buf.chunks_mut(8) {
+        let word = (Rand::get() as u64).to_le_bytes();
+        chunk.copy_from_slice(&word[..chunk.len()]);
+    }
+}
+
+/// Progress box on stderr for limited runs, so stdout stays pure data.
+struct Progress {
+    total: usize,
+    throttle: Throttle,
+}
+
+impl Progress {
+    /// Runs below this finish before a box would be readable.
+    const MIN_TOTAL: usize = 1024 * 1024;
+
+    /// Only when the size is known, stderr is a terminal, and not quiet.
+    fn new(limit: Option<usize>) -> Option<Self> {
+        let total = limit.filter(|&n| n >= Self::MIN_TOTAL)?;
+        if quiet::enabled() || unsafe { libc::isatty(2) } != 1 {
+            return None;
+        }
+        if !accessible::enabled() {
+            eprint!("\n\n\n");
+        }
+        Some(Self {
+            total,
+            throttle: Throttle::new(total),
+        })
+    }
+
+    fn update(&mut self, written: usize) {
+        if !self.throttle.due(written) {
+            return;
+        }
+        let rate = self.throttle.rate(written);
+        let eta = self.throttle.eta_text(written);
+        let pct = written as f32 / self.total as f32 * 100.0;
+        let stats = format!(
+            "{} of {} • {:.1}% • {}/s • ETA: {}",
+            format_bytes(written),
+            format_bytes(self.total),
+            pct,
+            format_bytes(rate as usize),
+            eta
+        );
+
+        let stderr = std::io::stderr();
+        let mut err = stderr.lock();
+        if !accessible::enabled() {
+            let _ = err.write_all(b"\x1b[3A");
+        }
+        progress_bar_box_to(&mut err, pct, &stats);
+    }
+}
+
+/// `--stats`: throughput summary on stderr, with periodic updates when
+/// the run has no limit.
+struct Stats {
+    start: Instant,
+    last_report: Instant,
+    periodic: bool,
+}
+
+impl Stats {
+    const INTERVAL: Duration = Duration::from_secs(5);
+
+    fn new(limit: Option<usize>) -> Self {
+        let now = Instant::now();
+        Self {
+            start: now,
+            last_report: now,
+            periodic: limit.is_none(),
+        }
+    }
+
+    fn line(&self, written: usize) -> String {
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let mbps = if elapsed > 0.0 {
+            written as f64 / elapsed / 1_000_000.0
+        } else {
+            0.0
+        };
+        format!(
+            "{} bytes ({}) in {:.2}s • {:.1} MB/s",
+            written,
+            format_bytes(written),
+            elapsed,
+            mbps
+        )
+    }
+
+    fn tick(&mut self, written: usize) {
+        if self.periodic && self.last_report.elapsed() >= Self::INTERVAL {
+            self.last_report = Instant::now();
+            eprintln!("{}", self.line(written));
+        }
+    }
+
+    fn finish(&self, written: usize) {
+        eprintln!("{}", self.line(written));
+    }
+}
+
+/// Stream RNG output into `out` until the limit is reached or a write
+/// fails (e.g. the reader closed the pipe). Returns the bytes written.
+fn write_bytes<W: Write>(out: &mut W, opts: &ByteOptions) -> std::io::Result<usize> {
+    let ByteOptions {
+        limit, encoding, ..
+    } = *opts;
+    let mut block = AlignedBuf::new(opts.block_size.unwrap_or(DEFAULT_BLOCK));
+    let mut text = Vec::new();
     let mut written: usize = 0;
+    let mut progress = Progress::new(limit);
+    let mut stats = opts.stats.then(|| Stats::new(limit));
+
+    // Encoded streams work in whole encoding groups
+    let buf = block.as_mut_slice();
+    let chunk_len = match encoding {
+        Some(_) if opts.block_size.is_none() => STREAM_CHUNK,
+        Some(_) => (buf.len() / ENCODE_GROUP * ENCODE_GROUP).max(ENCODE_GROUP),
+        None => buf.len(),
+    }
+    .min(buf.len());
+    let buf = &mut buf[..chunk_len];
 
     loop {
-        for chunk in buf.chunks_exact_mut(8) {
-            chunk.copy_from_slice(&(Rand::get() as u64).to_le_bytes());
-        }
+        fill(buf);
 
         let to_write = if let Some(limit) = limit {
             let remaining = limit.saturating_sub(written);
@@ -36,10 +308,27 @@ fn write_bytes<W: Write>(out: &mut W, limit: Option<usize>) {
             buf.len()
         };
 
-        if out.write_all(&buf[..to_write]).is_err() {
-            break;
+        let result = match encoding {
+            Some(enc) => {
+                text.clear();
+                encode_into(&buf[..to_write], enc, &mut text);
+                out.write_all(&text)
+            }
+            None => out.write_all(&buf[..to_write]),
+        };
+        if let Err(e) = result {
+            if let Some(s) = stats {
+                s.finish(written);
+            }
+            return Err(e);
         }
         written += to_write;
+        if let Some(ref mut p) = progress {
+            p.update(written);
+        }
+        if let Some(ref mut s) = stats {
+            s.tick(written);
+        }
 
         if let Some(limit) = limit
             && written >= limit
@@ -47,21 +336,227 @@ fn write_bytes<W: Write>(out: &mut W, limit: Option<usize>) {
             break;
         }
     }
+
+    // Text output ends with a newline so the shell prompt isn't glued on
+    let mut result = match encoding {
+        Some(_) => out.write_all(b"\n"),
+        None => Ok(()),
+    };
+    result = result.and_then(|_| out.flush());
+
+    if let Some(s) = stats {
+        s.finish(written);
+    }
+    result.map(|_| written)
 }
 
-pub fn output(limit: Option<usize>, file_path: Option<&str>) {
-    if let Some(path) = file_path {
-        let mut file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(path)
-            .expect("Failed to open output file");
-        write_bytes(&mut file, limit);
+/// Write a `--bytes` run to the output file or stdout. Returns the number
+/// of bytes written. Refuses to write anything if the entropy health test
+/// fails, unless overridden.
+pub fn output(opts: &ByteOptions) -> std::io::Result<usize> {
+    if !opts.skip_health_check
+        && let Err(reason) = crate::rand::failover::health_check()
+    {
+        crate::rand::shutdown_urandom();
+        return Err(std::io::Error::other(format!(
+            "entropy health check failed: {}. Refusing to write; pass --no-health-check to override",
+            reason
+        )));
+    }
+
+    let result = if let Some(path) = opts.file_path {
+        write_file(path, opts)
     } else {
         let stdout = std::io::stdout();
-        let mut out = stdout.lock();
-        write_bytes(&mut out, limit);
+        let out = stdout.lock();
+        match opts.checksum {
+            Some(checksum) => {
+                let mut out = HashWriter::new(out, checksum);
+                let written = write_bytes(&mut out, opts)?;
+                prompts::checksum_line(&out.finish().0, "-");
+                Ok(written)
+            }
+            None => write_bytes(&mut { out }, opts),
+        }
+    };
+    crate::rand::shutdown_urandom();
+    result
+}
+
+fn open_output(path: &str, opts: &ByteOptions) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    let direct = if opts.direct { libc::O_DIRECT } else { 0 };
+    options.read(opts.mmap).write(true).custom_flags(direct);
+    match opts.create_mode {
+        _ if opts.device => options.custom_flags(direct | libc::O_EXCL),
+        Some(mode) => options.create_new(true).mode(mode),
+        None => options.create(true).truncate(true),
+    };
+    options.open(path)
+}
+
+fn write_file(path: &str, opts: &ByteOptions) -> std::io::Result<usize> {
+    let (written, digests) = if let Some(part_size) = opts.split {
+        let total = opts.limit.unwrap_or(0);
+        let mut out = SplitWriter::new(path, part_size, total.div_ceil(part_size), opts);
+        let written = write_bytes(&mut out, opts)?;
+        (written, out.finish()?)
+    } else {
+        let file = open_output(path, opts)?;
+        if let Some(len) = opts.raw_size() {
+            fsync::preallocate(&file, len as u64);
+        }
+        let handle = file.try_clone()?;
+        let (written, digest) = if opts.mmap {
+            let reserve = opts.raw_size().unwrap_or(0) as u64;
+            write_synced(MmapWriter::new(file, reserve)?, handle, opts)?
+        } else if opts.io_uring {
+            write_synced(RingWriter::new(file)?, handle, opts)?
+        } else {
+            write_synced(file, handle, opts)?
+        };
+        let digests = digest.map(|hex| vec![(path.to_string(), hex)]);
+        (written, digests.unwrap_or_default())
+    };
+
+    if let Some(checksum) = opts.checksum {
+        let digest_path = write_digest_file(path, checksum, &digests)?;
+        prompts::checksum_written(&digest_path);
     }
+    Ok(written)
+}
+
+/// Write a run into `out` (writing `file`) under the `--fsync` policy,
+/// digesting it on the way with `--checksum`.
+fn write_synced<W: Write>(
+    out: W,
+    file: File,
+    opts: &ByteOptions,
+) -> std::io::Result<(usize, Option<String>)> {
+    let mut out = Synced::new(out, file, opts.fsync);
+    let (written, digest) = match opts.checksum {
+        Some(checksum) => {
+            let mut hashed = HashWriter::new(&mut out, checksum);
+            let written = write_bytes(&mut hashed, opts)?;
+            (written, Some(hashed.finish().0))
+        }
+        None => (write_bytes(&mut out, opts)?, None),
+    };
+    out.finish()?;
+    Ok((written, digest))
+}
+
+// ============================================================================
+// Split Output
+// ============================================================================
+
+/// `--split-bytes`: a writer that rotates through `<prefix>.000`,
+/// `<prefix>.001`, ... once each part reaches `part_size` bytes.
+struct SplitWriter<'a> {
+    prefix: &'a str,
+    part_size: usize,
+    width: usize,
+    opts: &'a ByteOptions<'a>,
+    index: usize,
+    current: Option<(Synced<File>, usize)>,
+    current_path: String,
+    hasher: Option<Hasher>,
+    digests: Vec<(String, String)>,
+}
+
+impl<'a> SplitWriter<'a> {
+    fn new(prefix: &'a str, part_size: usize, parts: usize, opts: &'a ByteOptions<'a>) -> Self {
+        // At least three digits, more when the part count needs them
+        let width = parts.saturating_sub(1).to_string().len().max(3);
+        Self {
+            prefix,
+            part_size,
+            width,
+            opts,
+            index: 0,
+            current: None,
+            current_path: String::new(),
+            hasher: None,
+            digests: Vec::new(),
+        }
+    }
+
+    fn close_current(&mut self) -> std::io::Result<()> {
+        if let Some((file, _)) = self.current.take() {
+            file.finish()?;
+            if let Some(hasher) = self.hasher.take() {
+                let path = std::mem::take(&mut self.current_path);
+                self.digests.push((path, hasher.finalize_hex()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Close the last part. Returns the per-part digests (empty without
+    /// `--checksum`).
+    fn finish(mut self) -> std::io::Result<Vec<(String, String)>> {
+        self.close_current()?;
+        Ok(self.digests)
+    }
+}
+
+impl Write for SplitWriter<'_> {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        if let Some((_, len)) = self.current
+            && len >= self.part_size
+        {
+            self.close_current()?;
+        }
+        if self.current.is_none() {
+            let path = format!("{}.{:0width$}", self.prefix, self.index, width = self.width);
+            let file = open_output(&path, self.opts)?;
+            if let Some(total) = self.opts.raw_size() {
+                let left = total - self.index * self.part_size;
+                fsync::preallocate(&file, left.min(self.part_size) as u64);
+            }
+            let handle = file.try_clone()?;
+            self.current = Some((Synced::new(file, handle, self.opts.fsync), 0));
+            self.hasher = self.opts.checksum.map(Checksum::hasher);
+            self.current_path = path;
+            self.index += 1;
+        }
+
+        let (file, len) = self.current.as_mut().expect("part file is open");
+        let take = data.len().min(self.part_size - *len);
+        let n = file.write(&data[..take])?;
+        *len += n;
+        if let Some(ref mut hasher) = self.hasher {
+            hasher.update(&data[..n]);
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self.current {
+            Some((ref mut file, _)) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Longest `--salt`, in bytes; more is bulk output for `--bytes`.
+pub const MAX_SALT: usize = 1024;
+
+/// Print `len` random bytes as a single encoded line (salts, keys).
+pub fn output_key_material(len: usize, encoding: Encoding) {
+    let mut raw = vec![0u8; len];
+    fill(&mut raw);
+
+    let mut line = Vec::new();
+    encode_into(&raw, encoding, &mut line);
+    line.push(b'\n');
+    raw.zeroize();
+
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    let _ = out.write_all(&line);
+    let _ = out.flush();
+    line.zeroize();
+
     crate::rand::shutdown_urandom();
 }
@@ -0,0 +1,129 @@
+//! `--checksum`: digests of `--bytes` output, written in `sha256sum` /
+//! `b3sum` format so the files can be verified with `-c` after transfer.
+
+use std::io::Write;
+
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Checksum {
+    Sha256,
+    Blake3,
+}
+
+impl Checksum {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "sha256" => Some(Self::Sha256),
+            "blake3" | "b3" => Some(Self::Blake3),
+            _ => None,
+        }
+    }
+
+    /// Extension of the digest file, matching the tool that verifies it.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha256",
+            Self::Blake3 => "blake3",
+        }
+    }
+
+    pub fn hasher(self) -> Hasher {
+        match self {
+            Self::Sha256 => Hasher::Sha256(Sha256::new()),
+            Self::Blake3 => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+        }
+    }
+}
+
+pub enum Hasher {
+    Sha256(Sha256),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Sha256(h) => h.update(data),
+            Self::Blake3(h) => {
+                h.update(data);
+            }
+        }
+    }
+
+    pub fn finalize_hex(self) -> String {
+        let digest: Vec<u8> = match self {
+            Self::Sha256(h) => h.finalize().to_vec(),
+            Self::Blake3(h) => h.finalize().as_bytes().to_vec(),
+        };
+        digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+/// Writer that hashes everything successfully written through it.
+pub struct HashWriter<W: Write> {
+    inner: W,
+    hasher: Hasher,
+}
+
+impl<W: Write> HashWriter<W> {
+    pub fn new(inner: W, checksum: Checksum) -> Self {
+        Self {
+            inner,
+            hasher: checksum.hasher(),
+        }
+    }
+
+    /// Hex digest and the wrapped writer.
+    pub fn finish(self) -> (String, W) {
+        (self.hasher.finalize_hex(), self.inner)
+    }
+}
+
+impl<W: Write> Write for HashWriter<W> {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(data)?;
+        self.hasher.update(&data[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Write `<path>.<ext>` listing `(file, digest)` pairs. File names are
+/// stored relative to the digest file so `sha256sum -c` works from its
+/// directory.
+pub fn write_digest_file(
+    path: &str,
+    checksum: Checksum,
+    entries: &[(String, String)],
+) -> std::io::Result<String> {
+    let digest_path = format!("{}.{}", path, checksum.extension());
+    let mut file = std::fs::File::create(&digest_path)?;
+    for (name, hex) in entries {
+        let base = std::path::Path::new(name)
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| name.clone());
+        writeln!(file, "{}  {}", hex, base)?;
+    }
+    Ok(digest_path)
+}
+
+/// Hex digest of a file's contents, read back from disk.
+pub fn digest_file(path: &str, checksum: Checksum) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = checksum.hasher();
+    let mut buf = [0u8; 65536];
+    loop {
+        let n = std::io::Read::read(&mut file, &mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    zeroize::Zeroize::zeroize(&mut buf);
+    Ok(hasher.finalize_hex())
+}
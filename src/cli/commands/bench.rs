@@ -0,0 +1,202 @@
+//! `randpass bench [--json]`: time the generation primitives on this
+//! machine. The cases mirror `benches/generation.rs`; `--json` prints one
+//! object for regression tracking. The prefetch case also reports how full
+//! the ring stayed while it ran.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use zeroize::Zeroize;
+
+use super::super::prompts;
+use crate::pass::charset::{self, Charset, Table};
+use crate::pass::{generate_from_charset, sample, shuffle};
+use crate::rand::{self, Rand, prefetch};
+use crate::settings::Settings;
+use crate::terminal::{box_bottom, box_line, box_top};
+
+/// Each sample runs at least this long.
+const SAMPLE_TIME: Duration = Duration::from_millis(40);
+/// Samples per case; the median is reported.
+const SAMPLES: usize = 5;
+
+struct Timing {
+    name: &'static str,
+    ns_per_op: f64,
+    iterations: u64,
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let json = match args.first().map(String::as_str) {
+        None => false,
+        Some("--json") => true,
+        Some(arg) => return Err(format!("Unknown argument: {}", arg)),
+    };
+
+    let settings = Settings::default();
+    let mut chars = charset::build(&settings);
+    let table = Charset::build(&settings);
+    let mut buf = Vec::with_capacity(settings.pass_length);
+
+    let mut results = vec![
+        measure("charset_build", || {
+            let mut built = charset::build(black_box(&settings));
+            built.zeroize();
+        }),
+        measure("shuffle", || shuffle(black_box(&mut chars))),
+        measure("sample", || {
+            sample(black_box(&chars), settings.pass_length, &mut buf)
+        }),
+        measure("password_pooled", || {
+            generate_from_charset(black_box(&mut chars), settings.pass_length, &mut buf)
+        }),
+        measure("table_build", || {
+            black_box(Table::build(black_box(&settings)));
+        }),
+        measure("password", || {
+            black_box(&table).generate(settings.pass_length, &mut buf)
+        }),
+        measure("entropy_hardware", || {
+            black_box(rand::hardware_entropy());
+        }),
+        measure("rand_hardware", || {
+            black_box(Rand::get());
+        }),
+    ];
+
+    // Stats cover the whole case, calibration included
+    prefetch::start();
+    results.push(measure("rand_prefetch", || {
+        black_box(Rand::get());
+    }));
+    let ring = prefetch::stats();
+    prefetch::stop();
+
+    if rand::enable_urandom() {
+        let mut hint = 0usize;
+        results.push(measure("entropy_urandom", || {
+            hint = hint.wrapping_add(1);
+            black_box(rand::urand::rand(hint));
+        }));
+        results.push(measure("rand_urandom", || {
+            black_box(Rand::get());
+        }));
+        rand::disable_urandom();
+    } else if !json {
+        prompts::warn("/dev/urandom unavailable, skipping urandom cases");
+    }
+
+    buf.zeroize();
+    chars.zeroize();
+    rand::zeroize_state();
+
+    if json {
+        println!("{}", to_json(&results, &ring));
+    } else {
+        show(&results, &ring);
+    }
+    Ok(())
+}
+
+/// Time `op`: calibrate an iteration count that fills `SAMPLE_TIME`, then
+/// take the median of `SAMPLES` runs.
+fn measure(name: &'static str, mut op: impl FnMut()) -> Timing {
+    let mut iterations: u64 = 1;
+    loop {
+        let start = Instant::now();
+        for _ in 0..iterations {
+            op();
+        }
+        if start.elapsed() >= SAMPLE_TIME || iterations >= 1 << 32 {
+            break;
+        }
+        iterations *= 2;
+    }
+
+    let mut samples: Vec<f64> = (0..SAMPLES)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..iterations {
+                op();
+            }
+            start.elapsed().as_nanos() as f64 / iterations as f64
+        })
+        .collect();
+    samples.sort_by(f64::total_cmp);
+
+    Timing {
+        name,
+        ns_per_op: samples[SAMPLES / 2],
+        iterations,
+    }
+}
+
+fn ops_per_sec(ns_per_op: f64) -> f64 {
+    if ns_per_op > 0.0 {
+        1e9 / ns_per_op
+    } else {
+        0.0
+    }
+}
+
+fn underrun_rate(ring: &prefetch::Stats) -> f64 {
+    if ring.pops > 0 {
+        ring.underruns as f64 / ring.pops as f64
+    } else {
+        0.0
+    }
+}
+
+fn show(results: &[Timing], ring: &prefetch::Stats) {
+    println!();
+    box_top("Benchmark");
+    box_line(&format!(
+        "randpass {} • hardware source: {}",
+        env!("CARGO_PKG_VERSION"),
+        rand::hardware_source()
+    ));
+    box_line("");
+    for r in results {
+        box_line(&format!(
+            "{:<18} {:>12.1} ns/op {:>16.0} ops/s",
+            r.name,
+            r.ns_per_op,
+            ops_per_sec(r.ns_per_op)
+        ));
+    }
+    box_line("");
+    box_line(&format!(
+        "prefetch ring: {} words • mean fill {:.1}% • underruns {:.1}%",
+        ring.capacity,
+        ring.mean_fill * 100.0,
+        underrun_rate(ring) * 100.0
+    ));
+    box_bottom();
+    println!();
+}
+
+fn to_json(results: &[Timing], ring: &prefetch::Stats) -> String {
+    let entries: Vec<String> = results
+        .iter()
+        .map(|r| {
+            format!(
+                "    {{\"name\": \"{}\", \"ns_per_op\": {:.3}, \"ops_per_sec\": {:.1}, \"iterations\": {}}}",
+                r.name,
+                r.ns_per_op,
+                ops_per_sec(r.ns_per_op),
+                r.iterations
+            )
+        })
+        .collect();
+    format!(
+        "{{\n  \"version\": \"{}\",\n  \"hardware_source\": \"{}\",\n  \"results\": [\n{}\n  ],\n  \"prefetch\": {{\"capacity\": {}, \"draws\": {}, \"underruns\": {}, \"underrun_rate\": {:.4}, \"mean_fill\": {:.4}}}\n}}",
+        env!("CARGO_PKG_VERSION"),
+        rand::hardware_source(),
+        entries.join(",\n"),
+        ring.capacity,
+        ring.pops,
+        ring.underruns,
+        underrun_rate(ring),
+        ring.mean_fill
+    )
+}
@@ -0,0 +1,164 @@
+//! `randpass compare-rng [--bytes SIZE]`: draw the same volume from every
+//! RNG backend on this machine and compare them side by side: throughput,
+//! the share of one bits, and a chi-square of byte values. A quick check
+//! before reaching for `scripts/run_rng_tests.sh` and the full batteries.
+
+use std::time::{Duration, Instant};
+
+use zeroize::Zeroize;
+
+use super::super::bytes::{fill, parse_byte_count};
+use super::super::prompts;
+use super::stats::chi2_upper_tail;
+use super::value;
+use crate::rand::{self, prefetch};
+use crate::settings::Settings;
+use crate::terminal::{box_bottom, box_line, box_top, format_bytes};
+
+const USAGE: &str = "Usage: randpass compare-rng [--bytes SIZE]";
+
+/// Volume per backend when `--bytes` is not given.
+const DEFAULT_BYTES: usize = 16 * 1024 * 1024;
+/// Bytes drawn per call to the generator.
+const CHUNK: usize = 64 * 1024;
+/// Below this p-value, or past this bit-balance z-score, a backend fails.
+const ALPHA: f64 = 0.001;
+const MAX_Z: f64 = 3.29;
+
+struct Report {
+    name: &'static str,
+    bytes: usize,
+    elapsed: Duration,
+    /// One bits minus half of all bits, in standard deviations
+    bit_z: f64,
+    ones: f64,
+    chi2: f64,
+    p: f64,
+}
+
+impl Report {
+    fn passed(&self) -> bool {
+        self.p >= ALPHA && self.bit_z.abs() <= MAX_Z
+    }
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let mut bytes = DEFAULT_BYTES;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--bytes" => {
+                let v = value(args, &mut i)?;
+                bytes = parse_byte_count(v)
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| format!("Invalid size: {}", v))?;
+            }
+            arg => return Err(format!("Unknown argument: {}\n{}", arg, USAGE)),
+        }
+        i += 1;
+    }
+
+    let mut reports = vec![measure(rand::hardware_source(), bytes)];
+
+    if prefetch::start() {
+        reports.push(measure("prefetch", bytes));
+        prefetch::stop();
+    }
+
+    if rand::enable_urandom() {
+        reports.push(measure("/dev/urandom", bytes));
+        rand::disable_urandom();
+    } else {
+        prompts::warn("/dev/urandom unavailable, skipping it");
+    }
+
+    // A TRNG is optional hardware: only mentioned when one is configured
+    let settings = Settings::load_from_file().unwrap_or_default();
+    if rand::hwrng::is_available(&settings.hwrng_device) {
+        match rand::enable_hwrng(&settings.hwrng_device, settings.hwrng_read_size) {
+            Ok(()) => {
+                reports.push(measure("hwrng", bytes));
+                rand::disable_hwrng();
+            }
+            Err(e) => prompts::warn(&format!("hwrng: {}, skipping it", e)),
+        }
+    }
+
+    show(&reports);
+    Ok(())
+}
+
+/// Draw `total` bytes from the current backend and tally them.
+fn measure(name: &'static str, total: usize) -> Report {
+    // Reseed from this backend rather than carry state over
+    rand::zeroize_state();
+
+    let mut buf = vec![0u8; CHUNK];
+    let mut counts = [0u64; 256];
+    let mut ones = 0u64;
+    let mut elapsed = Duration::ZERO;
+    let mut left = total;
+    while left > 0 {
+        let chunk = &mut buf[..left.min(CHUNK)];
+        let start = Instant::now();
+        fill(chunk);
+        elapsed += start.elapsed();
+
+        for &b in chunk.iter() {
+            counts[b as usize] += 1;
+            ones += b.count_ones() as u64;
+        }
+        left -= chunk.len();
+    }
+    buf.zeroize();
+    rand::zeroize_state();
+
+    let bits = total as f64 * 8.0;
+    let expected = total as f64 / 256.0;
+    let chi2: f64 = counts
+        .iter()
+        .map(|&n| (n as f64 - expected).powi(2) / expected)
+        .sum();
+    Report {
+        name,
+        bytes: total,
+        elapsed,
+        bit_z: (ones as f64 - bits / 2.0) / (bits / 4.0).sqrt(),
+        ones: ones as f64 / bits,
+        chi2,
+        p: chi2_upper_tail(chi2, 255.0),
+    }
+}
+
+fn show(reports: &[Report]) {
+    println!();
+    box_top("RNG Comparison");
+    box_line(&format!(
+        "{} per backend • fails at p < {} or |z| > {}",
+        format_bytes(reports[0].bytes),
+        ALPHA,
+        MAX_Z
+    ));
+    box_line("");
+    box_line(&format!(
+        "{:<14} {:>12} {:>9} {:>7} {:>9} {:>7} {}",
+        "backend", "throughput", "ones", "bit z", "chi-sq", "p", "result"
+    ));
+    for r in reports {
+        let rate = r.bytes as f64 / r.elapsed.as_secs_f64().max(1e-9);
+        box_line(&format!(
+            "{:<14} {:>10}/s {:>8.4}% {:>7.2} {:>9.1} {:>7.4} {}",
+            r.name,
+            format_bytes(rate as usize),
+            r.ones * 100.0,
+            r.bit_z,
+            r.chi2,
+            r.p,
+            if r.passed() { "pass" } else { "FAIL" }
+        ));
+    }
+    box_line("");
+    box_line("chi-square over 256 byte values, 255 degrees of freedom");
+    box_bottom();
+    println!();
+}
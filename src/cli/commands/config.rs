@@ -0,0 +1,103 @@
+//! `randpass config`: read and change saved settings without the TUI.
+
+use std::io::Write;
+
+use crate::settings::{self, FIELDS, FieldKind, Settings, field_kind};
+
+const USAGE: &str = "Usage: randpass config show [--json] | get <KEY> | set <KEY> <VALUE>";
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    // set reads the file under the lock it saves with
+    if args.first().map(String::as_str) == Some("set") {
+        return set(args);
+    }
+    let settings = Settings::load_from_file().map_err(|e| format!("Settings: {}", e))?;
+
+    match args.first().map(String::as_str) {
+        Some("show") => match args.get(1).map(String::as_str) {
+            None => show(&settings),
+            Some("--json") => println!("{}", to_json(&settings)),
+            Some(arg) => return Err(format!("Unknown argument: {}", arg)),
+        },
+        Some("get") => {
+            let key = args.get(1).ok_or(USAGE)?;
+            let value = settings.get_field(key).ok_or_else(|| unknown_key(key))?;
+            println!("{}", value);
+        }
+        _ => return Err(USAGE.to_string()),
+    }
+    Ok(())
+}
+
+/// `config set KEY VALUE`: change one field, keeping the rest as the
+/// file has them when it is written.
+fn set(args: &[String]) -> Result<(), String> {
+    let key = args.get(1).ok_or(USAGE)?;
+    if args.len() < 3 {
+        return Err(USAGE.to_string());
+    }
+    if field_kind(key).is_none() {
+        return Err(unknown_key(key));
+    }
+    // Values with spaces (cli_command) may arrive unquoted
+    let value = args[2..].join(" ");
+    settings::update(|settings| {
+        settings.set_field(key, &value)?;
+        // Refuse values the next load would replace
+        let issues = settings.clone().validate();
+        if !issues.is_empty() {
+            let problems: Vec<&str> = issues.iter().map(|i| i.problem.as_str()).collect();
+            return Err(problems.join("; "));
+        }
+        Ok(())
+    })?;
+    Ok(())
+}
+
+fn unknown_key(key: &str) -> String {
+    format!("Unknown setting: {} (known: {})", key, FIELDS.join(", "))
+}
+
+fn show(settings: &Settings) {
+    let mut out = std::io::stdout().lock();
+    for key in FIELDS {
+        let value = settings.get_field(key).unwrap_or_default();
+        // Stop quietly if the reader went away (`config show | head`)
+        if writeln!(out, "{} = {}", key, value).is_err() {
+            break;
+        }
+    }
+}
+
+fn to_json(settings: &Settings) -> String {
+    let entries: Vec<String> = FIELDS
+        .iter()
+        .map(|key| {
+            let value = settings.get_field(key).unwrap_or_default();
+            let value = match field_kind(key) {
+                Some(FieldKind::Number | FieldKind::Bool) => value,
+                _ => json_string(&value),
+            };
+            format!("  \"{}\": {}", key, value)
+        })
+        .collect();
+    format!("{{\n{}\n}}", entries.join(",\n"))
+}
+
+pub(super) fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -0,0 +1,333 @@
+//! `randpass dedupe <FILES...> [-o FILE] [--memory SIZE]`: merge generated
+//! files into one sorted file with every duplicate line removed.
+//!
+//! Lines are gathered into sorted runs of at most `--memory` bytes. When
+//! everything fits in one run it is written out directly; otherwise each
+//! run goes to a private temporary file beside the output and the runs are
+//! merged, so memory stays bounded however large the inputs are. The run
+//! files are zeroed before they're removed.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+
+use zeroize::Zeroize;
+
+use super::super::bytes::parse_byte_count;
+use super::super::prompts;
+use super::value;
+use crate::terminal::format_number;
+
+const USAGE: &str = "Usage: randpass dedupe <FILES...> [-o FILE] [--memory SIZE]";
+
+/// Memory for one run when `--memory` is not given.
+const DEFAULT_MEMORY: usize = 64 * 1024 * 1024;
+/// Bookkeeping charged per line on top of its bytes.
+const LINE_OVERHEAD: usize = std::mem::size_of::<Vec<u8>>();
+
+struct Options {
+    inputs: Vec<String>,
+    output: Option<String>,
+    memory: usize,
+}
+
+#[derive(Default)]
+struct Counts {
+    read: usize,
+    written: usize,
+}
+
+fn parse(args: &[String]) -> Result<Options, String> {
+    let mut opts = Options {
+        inputs: Vec::new(),
+        output: None,
+        memory: DEFAULT_MEMORY,
+    };
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "-o" | "--output" => opts.output = Some(value(args, &mut i)?.to_string()),
+            "--memory" => {
+                let v = value(args, &mut i)?;
+                opts.memory = parse_byte_count(v)
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| format!("Invalid size: {}", v))?;
+            }
+            "-" => opts.inputs.push("-".to_string()),
+            arg if arg.starts_with('-') => return Err(format!("Unknown argument: {}", arg)),
+            arg => opts.inputs.push(arg.to_string()),
+        }
+        i += 1;
+    }
+    if opts.inputs.is_empty() {
+        return Err(USAGE.to_string());
+    }
+    Ok(opts)
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let opts = parse(args)?;
+
+    // Writing over an input would truncate it before it is read
+    if let Some(ref output) = opts.output
+        && let Ok(out) = fs::canonicalize(output)
+        && opts
+            .inputs
+            .iter()
+            .any(|input| fs::canonicalize(input).is_ok_and(|p| p == out))
+    {
+        return Err(format!("{} is also an input; write somewhere else", output));
+    }
+
+    let mut readers = Vec::with_capacity(opts.inputs.len());
+    for input in &opts.inputs {
+        let reader: Box<dyn BufRead> = if input == "-" {
+            Box::new(io::stdin().lock())
+        } else {
+            let file = File::open(input).map_err(|e| format!("Cannot open {}: {}", input, e))?;
+            Box::new(BufReader::new(file))
+        };
+        readers.push((input.as_str(), reader));
+    }
+
+    let mut out: Box<dyn Write> = match opts.output {
+        Some(ref path) => Box::new(BufWriter::new(
+            OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .mode(0o600)
+                .open(path)
+                .map_err(|e| format!("Cannot create {}: {}", path, e))?,
+        )),
+        None => Box::new(io::stdout().lock()),
+    };
+
+    let counts = dedupe(readers, &mut out, &opts)?;
+    out.flush().map_err(|e| format!("Write failed: {}", e))?;
+
+    prompts::dedupe_done(
+        &format_number(counts.read),
+        &format_number(counts.written),
+        &format_number(counts.read - counts.written),
+        opts.output.as_deref().unwrap_or("stdout"),
+    );
+    Ok(())
+}
+
+/// Sort and deduplicate every line of `readers` into `out`.
+fn dedupe(
+    readers: Vec<(&str, Box<dyn BufRead + '_>)>,
+    out: &mut dyn Write,
+    opts: &Options,
+) -> Result<Counts, String> {
+    let mut counts = Counts::default();
+    let mut run: Vec<Vec<u8>> = Vec::new();
+    let mut used = 0;
+    let mut spill: Option<Spill> = None;
+    let mut line = Vec::new();
+
+    for (name, mut reader) in readers {
+        loop {
+            line.clear();
+            let n = reader
+                .read_until(b'\n', &mut line)
+                .map_err(|e| format!("Cannot read {}: {}", name, e))?;
+            if n == 0 {
+                break;
+            }
+            while matches!(line.last(), Some(b'\n' | b'\r')) {
+                line.pop();
+            }
+            if line.is_empty() {
+                continue;
+            }
+            counts.read += 1;
+            used += line.len() + LINE_OVERHEAD;
+            run.push(line.clone());
+
+            if used >= opts.memory {
+                let spill = match spill {
+                    Some(ref mut spill) => spill,
+                    None => spill.insert(Spill::new(opts.output.as_deref())?),
+                };
+                spill.write_run(&mut run)?;
+                used = 0;
+            }
+        }
+    }
+    line.zeroize();
+
+    let write = |out: &mut dyn Write, line: &[u8]| {
+        out.write_all(line)
+            .and_then(|_| out.write_all(b"\n"))
+            .map_err(|e| format!("Write failed: {}", e))
+    };
+
+    match spill {
+        None => {
+            sort_unique(&mut run);
+            for line in &run {
+                write(out, line)?;
+            }
+            counts.written = run.len();
+            wipe_lines(&mut run);
+        }
+        Some(mut spill) => {
+            if !run.is_empty() {
+                spill.write_run(&mut run)?;
+            }
+            counts.written = spill.merge(|line| write(out, line))?;
+        }
+    }
+    Ok(counts)
+}
+
+fn sort_unique(run: &mut Vec<Vec<u8>>) {
+    run.sort_unstable();
+    run.dedup_by(|line, kept| {
+        let same = line == kept;
+        if same {
+            line.zeroize();
+        }
+        same
+    });
+}
+
+fn wipe_lines(lines: &mut Vec<Vec<u8>>) {
+    for line in lines.iter_mut() {
+        line.zeroize();
+    }
+    lines.clear();
+}
+
+/// Sorted runs on disk, in a private directory removed on drop.
+struct Spill {
+    dir: PathBuf,
+    runs: Vec<PathBuf>,
+}
+
+impl Spill {
+    /// A directory beside `output`, or in the temp directory for stdout.
+    fn new(output: Option<&str>) -> Result<Self, String> {
+        let parent = output
+            .and_then(|p| Path::new(p).parent())
+            .filter(|p| !p.as_os_str().is_empty())
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| match output {
+                Some(_) => PathBuf::from("."),
+                None => std::env::temp_dir(),
+            });
+        let dir = parent.join(format!(".randpass-dedupe-{}", std::process::id()));
+        fs::DirBuilder::new()
+            .mode(0o700)
+            .create(&dir)
+            .map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+        Ok(Self {
+            dir,
+            runs: Vec::new(),
+        })
+    }
+
+    /// Sort `run`, write it out without duplicates, and empty it.
+    fn write_run(&mut self, run: &mut Vec<Vec<u8>>) -> Result<(), String> {
+        sort_unique(run);
+        let path = self.dir.join(format!("run{:05}", self.runs.len()));
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path)
+            .map_err(|e| format!("Cannot create {}: {}", path.display(), e))?;
+        let mut w = BufWriter::new(file);
+        for line in run.iter() {
+            w.write_all(line)
+                .and_then(|_| w.write_all(b"\n"))
+                .map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+        }
+        w.flush()
+            .map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+        wipe_lines(run);
+        self.runs.push(path);
+        Ok(())
+    }
+
+    /// Merge the runs, handing each unique line to `emit`. Returns how
+    /// many there were.
+    fn merge(&self, mut emit: impl FnMut(&[u8]) -> Result<(), String>) -> Result<usize, String> {
+        let mut readers = Vec::with_capacity(self.runs.len());
+        for path in &self.runs {
+            let file =
+                File::open(path).map_err(|e| format!("Cannot open {}: {}", path.display(), e))?;
+            readers.push(BufReader::new(file));
+        }
+
+        let next = |reader: &mut BufReader<File>| -> Result<Option<Vec<u8>>, String> {
+            let mut line = Vec::new();
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) => Ok(None),
+                Ok(_) => {
+                    line.pop();
+                    Ok(Some(line))
+                }
+                Err(e) => Err(format!("Cannot read a run: {}", e)),
+            }
+        };
+
+        let mut heap = BinaryHeap::new();
+        for (i, reader) in readers.iter_mut().enumerate() {
+            if let Some(line) = next(reader)? {
+                heap.push(Reverse((line, i)));
+            }
+        }
+
+        let mut last: Option<Vec<u8>> = None;
+        let mut written = 0;
+        while let Some(Reverse((line, i))) = heap.pop() {
+            if let Some(next_line) = next(&mut readers[i])? {
+                heap.push(Reverse((next_line, i)));
+            }
+            if last.as_ref() == Some(&line) {
+                let mut line = line;
+                line.zeroize();
+                continue;
+            }
+            emit(&line)?;
+            written += 1;
+            if let Some(mut old) = last.replace(line) {
+                old.zeroize();
+            }
+        }
+        if let Some(mut old) = last {
+            old.zeroize();
+        }
+        Ok(written)
+    }
+}
+
+impl Drop for Spill {
+    /// Zero each run before removing it: they hold passwords.
+    fn drop(&mut self) {
+        for path in &self.runs {
+            if let Ok(len) = fs::metadata(path).map(|m| m.len() as usize)
+                && let Ok(mut file) = OpenOptions::new().write(true).open(path)
+            {
+                let zeros = vec![0u8; len.min(1 << 20)];
+                let mut left = len;
+                while left > 0 {
+                    let n = left.min(zeros.len());
+                    if file.write_all(&zeros[..n]).is_err() {
+                        break;
+                    }
+                    left -= n;
+                }
+                let _ = file.sync_all();
+            }
+            let _ = fs::remove_file(path);
+        }
+        let _ = fs::remove_dir(&self.dir);
+    }
+}
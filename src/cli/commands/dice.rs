@@ -0,0 +1,173 @@
+//! `randpass dice`: build a passphrase from physical dice rolls instead of
+//! the RNG. Each word takes one roll of several dice, read as a base-6
+//! number and mapped onto the wordlist.
+
+use zeroize::Zeroize;
+
+use super::{deliver_secret, number, value};
+use crate::cli::prompts;
+use crate::pass::passphrase::{DEFAULT_WORDS, WordSep, push_separator, push_word};
+use crate::pass::wordlist;
+use crate::pass::words::WordList;
+use crate::settings::Settings;
+
+struct Options {
+    words: Option<usize>,
+    separator: Option<WordSep>,
+    list: Option<String>,
+    lang: Option<String>,
+    wordlist: Option<String>,
+    clipboard: bool,
+}
+
+fn parse(args: &[String]) -> Result<Options, String> {
+    let mut opts = Options {
+        words: None,
+        separator: None,
+        list: None,
+        lang: None,
+        wordlist: None,
+        clipboard: false,
+    };
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--words" => opts.words = Some(number(args, &mut i)?),
+            "--word-sep" => {
+                let v = value(args, &mut i)?;
+                opts.separator = Some(WordSep::parse(v).ok_or_else(|| {
+                    format!(
+                        "Invalid value: {} (expected one character, none or camel)",
+                        v
+                    )
+                })?);
+            }
+            "--list" => opts.list = Some(value(args, &mut i)?.to_string()),
+            "--lang" => opts.lang = Some(value(args, &mut i)?.to_string()),
+            "--wordlist" => opts.wordlist = Some(value(args, &mut i)?.to_string()),
+            "-b" | "--board" => opts.clipboard = true,
+            arg => return Err(format!("Unknown argument: {}", arg)),
+        }
+        i += 1;
+    }
+
+    if opts.words == Some(0) {
+        return Err("--words must be at least 1".to_string());
+    }
+    Ok(opts)
+}
+
+/// Dice needed per word: the fewest whose 6^k outcomes cover the list
+/// (5 for the 7776-word diceware lists and for 2048-word lists).
+fn dice_per_word(len: usize) -> u32 {
+    let mut k = 1;
+    while 6usize.pow(k) < len {
+        k += 1;
+    }
+    k
+}
+
+/// Outcomes at or above this are rerolled so every word stays equally
+/// likely: the largest multiple of the list size within 6^k.
+fn accept_below(len: usize, dice: u32) -> usize {
+    let outcomes = 6usize.pow(dice);
+    outcomes - outcomes % len
+}
+
+/// Read `dice` faces (1-6, spaces allowed) as a base-6 number.
+fn read_roll(input: &str, dice: u32) -> Result<usize, String> {
+    let mut value = 0;
+    let mut count = 0;
+    for c in input.chars().filter(|c| !c.is_whitespace()) {
+        let face = c
+            .to_digit(10)
+            .filter(|d| (1..=6).contains(d))
+            .ok_or_else(|| format!("'{}' is not a die face (1-6)", c))?;
+        value = value * 6 + (face as usize - 1);
+        count += 1;
+    }
+    if count != dice {
+        return Err(format!("expected {} dice, got {}", dice, count));
+    }
+    Ok(value)
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let opts = parse(args)?;
+    let saved = Settings::load_from_file().unwrap_or_default();
+    let list = wordlist::select(
+        opts.list.as_deref(),
+        opts.lang.as_deref(),
+        opts.wordlist.as_deref(),
+        &saved.wordlist,
+    )?;
+    // Saved passphrase settings fill in what the flags leave out
+    let words = opts
+        .words
+        .or((saved.passphrase_words > 0).then_some(saved.passphrase_words))
+        .unwrap_or(DEFAULT_WORDS);
+    let separator = opts.separator.unwrap_or(saved.word_sep);
+
+    let dice = dice_per_word(list.len());
+    let limit = accept_below(list.len(), dice);
+    eprintln!(
+        "{}: {} words, {} dice per word, {:.1} bits per word, {:.1} bits total",
+        list.name(),
+        list.len(),
+        dice,
+        list.bits_per_word(),
+        list.bits_per_word() * words as f64
+    );
+    eprintln!("Input is hidden. Empty line cancels.");
+
+    let mut secret = Vec::new();
+    let mut n = 0;
+    while n < words {
+        match roll_word(list, dice, limit, n + 1) {
+            Ok(Some(word)) => {
+                if n > 0 {
+                    push_separator(&mut secret, separator);
+                }
+                push_word(&mut secret, word, separator == WordSep::Camel);
+                n += 1;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                secret.zeroize();
+                return Err(e);
+            }
+        }
+    }
+
+    deliver_secret(secret, "Passphrase", opts.clipboard);
+    Ok(())
+}
+
+/// Prompt for one roll. Returns None when the roll must be repeated.
+fn roll_word(
+    list: &'static WordList,
+    dice: u32,
+    limit: usize,
+    number: usize,
+) -> Result<Option<&'static str>, String> {
+    let mut input = prompts::hidden_input(&format!("Word {} - roll {} dice: ", number, dice))
+        .ok_or("Failed to read input")?;
+    if input.trim().is_empty() {
+        return Err("Cancelled".to_string());
+    }
+    let roll = read_roll(&input, dice);
+    input.zeroize();
+
+    match roll {
+        Ok(v) if v < limit => Ok(Some(list.word(v % list.len()))),
+        Ok(_) => {
+            prompts::warn("That roll has no word in this list, roll again");
+            Ok(None)
+        }
+        Err(e) => {
+            prompts::warn(&format!("{}, roll again", e));
+            Ok(None)
+        }
+    }
+}
@@ -0,0 +1,290 @@
+//! `randpass doctor`: check the environment and print a pass/fail report.
+
+use std::fs::File;
+use std::io::Read;
+use std::os::unix::fs::PermissionsExt;
+
+use copypasta::ClipboardContext;
+
+use crate::rand;
+use crate::settings::{self, FileStatus, Settings};
+use crate::terminal::{RESET, box_bottom, box_line, box_top, format_bytes};
+
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const RED: &str = "\x1b[31m";
+
+/// Memory the urandom pool locks (see `rand::urand`), plus output buffers.
+const MLOCK_NEEDED: u64 = 2 * 1024 * 1024 + 64 * 1024;
+
+/// Box inner width minus the detail indent.
+const DETAIL_WIDTH: usize = crate::terminal::BOX_WIDTH - 4 - 7;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+struct Check {
+    name: &'static str,
+    status: Status,
+    detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Status, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    if let Some(arg) = args.first() {
+        return Err(format!("Unknown argument: {}", arg));
+    }
+
+    let checks = [
+        config(),
+        config_permissions(),
+        memlock(),
+        urandom(),
+        hwrng(),
+        clipboard(),
+        terminal(),
+        entropy("Entropy (hardware)", false),
+        entropy("Entropy (urandom)", true),
+    ];
+
+    box_top("Doctor");
+    for check in &checks {
+        let (color, label) = match check.status {
+            Status::Pass => (GREEN, "PASS"),
+            Status::Warn => (YELLOW, "WARN"),
+            Status::Fail => (RED, "FAIL"),
+        };
+        box_line(&format!("{color}[{label}]{RESET} {}", check.name));
+        for line in wrap(&check.detail, DETAIL_WIDTH) {
+            box_line(&format!("       {}", line));
+        }
+    }
+    box_bottom();
+
+    if checks.iter().any(|c| c.status == Status::Fail) {
+        return Err("One or more checks failed".to_string());
+    }
+    Ok(())
+}
+
+/// Word-wrap `text` to `width` columns (long words are left whole).
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    lines.push(current);
+    lines
+}
+
+fn config() -> Check {
+    let name = "Config file";
+    match settings::inspect_file() {
+        Ok(FileStatus::Missing) => {
+            Check::new(name, Status::Pass, "not created yet, using defaults")
+        }
+        Ok(FileStatus::Unreadable) => Check::new(
+            name,
+            Status::Fail,
+            "unrecognized format, next run resets it (backup kept)",
+        ),
+        Ok(FileStatus::Loaded { version, issues }) if issues.is_empty() => {
+            if version > settings::VERSION {
+                Check::new(
+                    name,
+                    Status::Warn,
+                    format!("version {} is newer than this binary", version),
+                )
+            } else if version < settings::VERSION {
+                Check::new(
+                    name,
+                    Status::Pass,
+                    format!(
+                        "version {}, all fields valid, upgraded to version {} on next run",
+                        version,
+                        settings::VERSION
+                    ),
+                )
+            } else {
+                Check::new(
+                    name,
+                    Status::Pass,
+                    format!("version {}, all fields valid", version),
+                )
+            }
+        }
+        Ok(FileStatus::Loaded { issues, .. }) => Check::new(name, Status::Fail, issues.join("; ")),
+        Err(e) => Check::new(name, Status::Fail, format!("cannot read: {}", e)),
+    }
+}
+
+fn config_permissions() -> Check {
+    let name = "Config permissions";
+    let path = settings::file_path();
+    match std::fs::metadata(&path) {
+        Ok(meta) => {
+            let mode = meta.permissions().mode() & 0o777;
+            if mode & 0o022 != 0 {
+                Check::new(
+                    name,
+                    Status::Warn,
+                    format!("{:o}, writable by other users (chmod 600 {})", mode, path),
+                )
+            } else {
+                Check::new(name, Status::Pass, format!("{:o}", mode))
+            }
+        }
+        Err(_) => Check::new(name, Status::Pass, "no file yet"),
+    }
+}
+
+fn memlock() -> Check {
+    let name = "RLIMIT_MEMLOCK";
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    if unsafe { libc::getrlimit(libc::RLIMIT_MEMLOCK, &mut limit) } != 0 {
+        return Check::new(name, Status::Warn, "getrlimit failed");
+    }
+    if limit.rlim_cur == libc::RLIM_INFINITY {
+        return Check::new(name, Status::Pass, "unlimited");
+    }
+    let current = format_bytes(limit.rlim_cur as usize);
+    if limit.rlim_cur >= MLOCK_NEEDED {
+        Check::new(name, Status::Pass, current)
+    } else {
+        Check::new(
+            name,
+            Status::Warn,
+            format!(
+                "{}, -u pool cannot be locked (ulimit -l unlimited)",
+                current
+            ),
+        )
+    }
+}
+
+fn urandom() -> Check {
+    let name = "/dev/urandom";
+    let mut buf = [0u8; 16];
+    match File::open("/dev/urandom").and_then(|mut f| f.read_exact(&mut buf)) {
+        Ok(()) => Check::new(name, Status::Pass, "readable"),
+        Err(e) => Check::new(name, Status::Fail, format!("{}, -u unavailable", e)),
+    }
+}
+
+/// The saved hwrng device, filled through its health tests like a
+/// `--rng hwrng` run would. Most machines have none, which is fine.
+fn hwrng() -> Check {
+    let name = "Hardware TRNG";
+    let settings = Settings::load_from_file().unwrap_or_default();
+    let device = &settings.hwrng_device;
+    if !rand::hwrng::is_available(device) {
+        return Check::new(
+            name,
+            Status::Pass,
+            format!("no {}, only needed for --rng hwrng", device),
+        );
+    }
+    let result = rand::enable_hwrng(device, settings.hwrng_read_size);
+    rand::disable_hwrng();
+    match result {
+        Ok(()) => Check::new(
+            name,
+            Status::Pass,
+            format!("{} read, health tests passed", device),
+        ),
+        Err(e) => Check::new(
+            name,
+            Status::Warn,
+            format!("{}, --rng hwrng unavailable", e),
+        ),
+    }
+}
+
+/// Connects to the backend only: reading the clipboard would copy whatever
+/// secret is on it into this process.
+fn clipboard() -> Check {
+    let name = "Clipboard";
+    match ClipboardContext::new() {
+        Ok(_) => Check::new(name, Status::Pass, "backend reachable"),
+        Err(e) => Check::new(
+            name,
+            Status::Warn,
+            format!("{}, -b falls back to stdout", e),
+        ),
+    }
+}
+
+fn terminal() -> Check {
+    let name = "Terminal";
+    let term = std::env::var("TERM").unwrap_or_default();
+    let tty = unsafe { libc::isatty(0) == 1 && libc::isatty(1) == 1 };
+    if !tty {
+        return Check::new(
+            name,
+            Status::Warn,
+            "not a tty, interactive mode unavailable",
+        );
+    }
+    if term.is_empty() || term == "dumb" {
+        return Check::new(
+            name,
+            Status::Warn,
+            format!("TERM={:?}, box drawing and colors may not render", term),
+        );
+    }
+    match crossterm::terminal::size() {
+        Ok((cols, rows)) if (cols as usize) < crate::terminal::BOX_WIDTH => Check::new(
+            name,
+            Status::Warn,
+            format!(
+                "{}x{}, narrower than the {}-column UI; compact layout in use",
+                cols,
+                rows,
+                crate::terminal::BOX_WIDTH
+            ),
+        ),
+        Ok((cols, rows)) => Check::new(
+            name,
+            Status::Pass,
+            format!("TERM={}, {}x{}", term, cols, rows),
+        ),
+        Err(e) => Check::new(name, Status::Warn, format!("size unknown: {}", e)),
+    }
+}
+
+fn entropy(name: &'static str, use_urandom: bool) -> Check {
+    if use_urandom && !rand::enable_urandom() {
+        return Check::new(name, Status::Warn, "skipped, /dev/urandom unavailable");
+    }
+    let result = rand::health_check();
+    if use_urandom {
+        rand::disable_urandom();
+    }
+    match result {
+        Ok(()) => Check::new(name, Status::Pass, "health test passed"),
+        Err(reason) => Check::new(name, Status::Fail, reason),
+    }
+}
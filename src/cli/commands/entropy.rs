@@ -0,0 +1,61 @@
+//! `randpass entropy`: estimate the entropy of a password someone already
+//! has. It is read without echo (or from stdin when that isn't a terminal)
+//! and never printed; findings give positions, not text.
+
+use zeroize::Zeroize;
+
+use super::super::prompts;
+use crate::pass::strength;
+use crate::terminal::{box_bottom, box_line, box_top, crack_times, entropy_strength};
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    if let Some(arg) = args.first() {
+        return Err(format!(
+            "Unknown argument: {} (randpass entropy reads the password from a prompt or stdin)",
+            arg
+        ));
+    }
+    let mut input = prompts::hidden_input("Password: ").ok_or("Cannot read the password")?;
+    if input.is_empty() {
+        input.zeroize();
+        return Err("Nothing to estimate".to_string());
+    }
+    let estimate = strength::estimate(&input);
+    input.zeroize();
+
+    println!();
+    box_top("Entropy Estimate");
+    box_line(&format!(
+        "{} characters • {}",
+        estimate.len,
+        estimate.classes.join(", ")
+    ));
+    box_line("");
+    box_line(&format!(
+        "Shannon  {:>7.1} bits   from the string's own character frequencies",
+        estimate.shannon
+    ));
+    box_line(&format!(
+        "Charset  {:>7.1} bits   brute force over the classes it uses",
+        estimate.charset
+    ));
+    box_line(&format!(
+        "Pattern  {:>7.1} bits   {} after guessable parts",
+        estimate.pattern,
+        entropy_strength(estimate.pattern)
+    ));
+    for f in &estimate.findings {
+        box_line(&format!(
+            "  {} at {}-{}: {:.1} bits",
+            f.kind,
+            f.start + 1,
+            f.start + f.len,
+            f.bits
+        ));
+    }
+    box_line("");
+    box_line(&format!("Crack time: {}", crack_times(estimate.pattern)));
+    box_bottom();
+    println!();
+    Ok(())
+}
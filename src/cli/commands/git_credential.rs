@@ -0,0 +1,203 @@
+//! `randpass git-credential <get|store|erase>`: a git credential helper
+//! (`git config credential.helper '!randpass git-credential'`). Speaks
+//! git's `key=value` protocol on stdin/stdout and keeps one entry per
+//! `protocol://host[/path]` in the helper store. With `--generate`, a
+//! `get` that finds nothing answers with a new password, stored first, so
+//! provisioning scripts can `git credential fill` a fresh secret.
+
+use std::io::{BufRead, Write};
+
+use zeroize::Zeroize;
+
+use super::{generate_secret, number, store};
+use crate::settings::ephemeral;
+
+const USAGE: &str = "Usage: randpass git-credential [--generate] [-l N] <get|store|erase>";
+
+struct Options {
+    action: String,
+    generate: bool,
+    length: usize,
+}
+
+fn parse(args: &[String]) -> Result<Options, String> {
+    let mut action = None;
+    let mut opts = Options {
+        action: String::new(),
+        generate: false,
+        length: 32,
+    };
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--generate" => opts.generate = true,
+            "-l" | "--length" => opts.length = number(args, &mut i)?,
+            arg if arg.starts_with('-') => return Err(format!("Unknown argument: {}", arg)),
+            arg if action.is_none() => action = Some(arg.to_string()),
+            arg => return Err(format!("Unexpected argument: {}", arg)),
+        }
+        i += 1;
+    }
+
+    opts.action = action.ok_or(USAGE)?;
+    if opts.length == 0 {
+        return Err("Password length must be at least 1".to_string());
+    }
+    Ok(opts)
+}
+
+/// `key=value` attributes as git sends them, in order.
+struct Attributes(Vec<(String, String)>);
+
+impl Attributes {
+    /// Read attributes up to a blank line or end of input.
+    fn read() -> Result<Self, String> {
+        let mut attrs = Vec::new();
+        let stdin = std::io::stdin();
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match stdin.lock().read_line(&mut line) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) => {
+                    line.zeroize();
+                    return Err(format!("Failed to read stdin: {}", e));
+                }
+            }
+            let entry = line.trim_end_matches(['\n', '\r']);
+            if entry.is_empty() {
+                break;
+            }
+            if let Some((key, value)) = entry.split_once('=') {
+                attrs.push((key.to_string(), value.to_string()));
+            }
+        }
+        line.zeroize();
+        Ok(Attributes(attrs))
+    }
+
+    /// Parse a stored entry, the same format without the blank line.
+    fn decode(data: &[u8]) -> Self {
+        let text = String::from_utf8_lossy(data);
+        Attributes(
+            text.lines()
+                .filter_map(|l| l.split_once('='))
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        )
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        // git sends repeated keys last-wins
+        self.0
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// `protocol://host[/path]`, or None when git left out the host.
+    fn url(&self) -> Option<String> {
+        let protocol = self.get("protocol")?;
+        let host = self.get("host")?;
+        Some(match self.get("path") {
+            Some(path) => format!("{}://{}/{}", protocol, host, path),
+            None => format!("{}://{}", protocol, host),
+        })
+    }
+}
+
+impl Drop for Attributes {
+    fn drop(&mut self) {
+        for (_, value) in &mut self.0 {
+            value.zeroize();
+        }
+    }
+}
+
+/// `username=` (if any) and `password=` lines for git or the store.
+fn encode(username: Option<&str>, password: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(password.len() + 64);
+    if let Some(user) = username {
+        out.extend_from_slice(format!("username={}\n", user).as_bytes());
+    }
+    out.extend_from_slice(b"password=");
+    out.extend_from_slice(password);
+    out.push(b'\n');
+    out
+}
+
+/// Answer one helper call. Unknown actions are ignored, as git asks of
+/// helpers, so future protocol additions don't break the credential chain.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let opts = parse(args)?;
+    if !matches!(opts.action.as_str(), "get" | "store" | "erase") {
+        return Ok(());
+    }
+    let query = Attributes::read()?;
+    let Some(url) = query.url() else {
+        return Ok(());
+    };
+    let path = store::dir("git-credentials").join(store::file_name(&url));
+
+    let stored = match store::read(&path)? {
+        Some(mut data) => {
+            let entry = Attributes::decode(&data);
+            data.zeroize();
+            Some(entry)
+        }
+        None => None,
+    };
+    // An entry for another user on the same URL is not ours to touch
+    let matches = stored.as_ref().is_some_and(|entry| {
+        query.get("username").is_none() || query.get("username") == entry.get("username")
+    });
+
+    match opts.action.as_str() {
+        "get" => {
+            let mut answer = match stored {
+                Some(ref entry) if matches => match entry.get("password") {
+                    Some(password) => encode(entry.get("username"), password.as_bytes()),
+                    None => return Ok(()),
+                },
+                None if opts.generate => {
+                    let mut password = generate_secret(opts.length);
+                    let mut entry = encode(query.get("username"), &password);
+                    password.zeroize();
+                    let written = store::write(&path, &entry, false);
+                    if let Err(e) = written {
+                        entry.zeroize();
+                        return Err(e);
+                    }
+                    entry
+                }
+                _ => return Ok(()),
+            };
+            let stdout = std::io::stdout();
+            let mut out = stdout.lock();
+            let written = out.write_all(&answer).and_then(|_| out.flush());
+            answer.zeroize();
+            written.map_err(|e| e.to_string())
+        }
+        "store" => {
+            let Some(password) = query.get("password") else {
+                return Ok(());
+            };
+            if ephemeral::enabled() {
+                return Ok(());
+            }
+            let mut entry = encode(query.get("username"), password.as_bytes());
+            let written = store::write(&path, &entry, false);
+            entry.zeroize();
+            written
+        }
+        _ => {
+            if matches {
+                store::remove(&path)?;
+            }
+            Ok(())
+        }
+    }
+}
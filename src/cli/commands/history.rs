@@ -0,0 +1,183 @@
+//! `randpass history enable | list | show | copy | purge | disable`: the
+//! encrypted history of generated passwords (`pass::history`). Recording
+//! needs nothing; reading an entry back asks for the passphrase set at
+//! `enable`.
+
+use std::io::Write;
+
+use copypasta::ClipboardContext;
+use zeroize::Zeroize;
+
+use super::super::{pick, prompts};
+use super::number;
+use crate::pass::SecureBufWriter;
+use crate::pass::audit::timestamp;
+use crate::pass::history::{self, DEFAULT_EXPIRE_DAYS, MIN_PASSPHRASE};
+
+const USAGE: &str = "Usage: randpass history enable [--expire DAYS] | list | show ID | copy ID | purge [ID] | disable";
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let Some(cmd) = args.first() else {
+        return Err(USAGE.to_string());
+    };
+    let rest = &args[1..];
+    match cmd.as_str() {
+        "enable" => enable(rest),
+        "list" => no_args(rest).and_then(|_| list()),
+        "show" => id(rest).and_then(|i| show(i, false)),
+        "copy" => id(rest).and_then(|i| show(i, true)),
+        "purge" => match rest {
+            [] => purge(None),
+            _ => id(rest).and_then(|i| purge(Some(i))),
+        },
+        "disable" => no_args(rest).and_then(|_| disable()),
+        other => Err(format!("Unknown history command: {}", other)),
+    }
+}
+
+fn no_args(rest: &[String]) -> Result<(), String> {
+    match rest.first() {
+        Some(arg) => Err(format!("Unknown argument: {}", arg)),
+        None => Ok(()),
+    }
+}
+
+/// The entry number in `rest`, 0-based.
+fn id(rest: &[String]) -> Result<usize, String> {
+    match rest {
+        [id] => id
+            .parse::<usize>()
+            .ok()
+            .filter(|&n| n > 0)
+            .map(|n| n - 1)
+            .ok_or_else(|| format!("Invalid entry number: {} (see randpass history list)", id)),
+        [] => Err(USAGE.to_string()),
+        [_, arg, ..] => Err(format!("Unknown argument: {}", arg)),
+    }
+}
+
+fn enable(rest: &[String]) -> Result<(), String> {
+    let mut expire = DEFAULT_EXPIRE_DAYS;
+    let mut i = 0;
+    while i < rest.len() {
+        match rest[i].as_str() {
+            "--expire" => expire = number(rest, &mut i)? as u64,
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+        i += 1;
+    }
+    if history::enabled() {
+        return Err("history is already enabled (history disable removes it)".into());
+    }
+
+    let mut passphrase =
+        prompts::hidden_input("History passphrase: ").ok_or("failed to read the passphrase")?;
+    if passphrase.chars().count() < MIN_PASSPHRASE {
+        passphrase.zeroize();
+        return Err(format!(
+            "the passphrase needs at least {} characters",
+            MIN_PASSPHRASE
+        ));
+    }
+    let mut again = prompts::hidden_input("Repeat passphrase: ").unwrap_or_default();
+    let same = again == passphrase;
+    again.zeroize();
+    if !same {
+        passphrase.zeroize();
+        return Err("the passphrases don't match".into());
+    }
+    let result = history::enable(passphrase.as_bytes(), expire);
+    passphrase.zeroize();
+    result?;
+
+    println!("History enabled in {}", history::dir().display());
+    match expire {
+        0 => println!("Entries are kept until purged"),
+        days => println!("Entries expire after {} days", days),
+    }
+    println!(
+        "Runs of up to {} passwords are recorded; --label files them under a name",
+        history::MAX_RECORDED
+    );
+    Ok(())
+}
+
+/// Every live entry, opened with a passphrase asked for on the terminal.
+fn open() -> Result<Vec<history::Entry>, String> {
+    // Fail on a missing history before asking for anything
+    history::expire_days()?;
+    let mut passphrase =
+        prompts::hidden_input("History passphrase: ").ok_or("failed to read the passphrase")?;
+    let entries = history::open(passphrase.as_bytes());
+    passphrase.zeroize();
+    entries
+}
+
+fn list() -> Result<(), String> {
+    let entries = open()?;
+    if entries.is_empty() {
+        println!("No entries in {}", history::dir().display());
+        return Ok(());
+    }
+    let width = entries.len().to_string().len();
+    for (i, entry) in entries.iter().enumerate() {
+        println!(
+            "{:>width$}  {}  {}",
+            i + 1,
+            timestamp(entry.time),
+            entry.label
+        );
+    }
+    Ok(())
+}
+
+/// Print entry `index`, or copy it to the clipboard with `copy`.
+fn show(index: usize, copy: bool) -> Result<(), String> {
+    // Before the passphrase: nothing to copy to means nothing to ask for
+    let ctx = if copy {
+        Some(
+            ClipboardContext::new()
+                .map_err(|e| format!("Clipboard unavailable: {} (history show prints)", e))?,
+        )
+    } else {
+        None
+    };
+    let entries = open()?;
+    let entry = entries
+        .get(index)
+        .ok_or_else(|| format!("no entry {} (see randpass history list)", index + 1))?;
+    match ctx {
+        Some(mut ctx) => {
+            pick::copy(&mut ctx, &entry.password)?;
+            prompts::clipboard_copied();
+        }
+        None => {
+            let mut out = SecureBufWriter::new(std::io::stdout().lock());
+            let _ = out
+                .write_all(&entry.password)
+                .and_then(|_| out.write_all(b"\n"))
+                .and_then(|_| out.flush());
+        }
+    }
+    Ok(())
+}
+
+fn purge(index: Option<usize>) -> Result<(), String> {
+    history::expire_days()?;
+    history::purge(index)?;
+    match index {
+        Some(i) => println!("Purged entry {}", i + 1),
+        None => println!("Purged every entry"),
+    }
+    Ok(())
+}
+
+fn disable() -> Result<(), String> {
+    if !history::enabled() {
+        println!("History is not enabled");
+        return Ok(());
+    }
+    history::disable()?;
+    println!("History disabled; key and entries removed");
+    Ok(())
+}
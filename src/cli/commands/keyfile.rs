@@ -0,0 +1,83 @@
+//! `randpass keyfile`: create a random keyfile for LUKS, VeraCrypt, etc.
+
+use std::path::Path;
+
+use super::super::bytes::{ByteOptions, output, parse_byte_count};
+use super::super::checksum::{Checksum, digest_file};
+use super::super::prompts;
+use super::value;
+use crate::pass::fsync::Fsync;
+use crate::terminal::format_bytes;
+
+struct Options {
+    path: String,
+    size: usize,
+    digest: Option<Checksum>,
+}
+
+fn parse(args: &[String]) -> Result<Options, String> {
+    let mut path = None;
+    let mut size = 4096;
+    let mut digest = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--size" => {
+                let v = value(args, &mut i)?;
+                size = parse_byte_count(v)
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| format!("Invalid size: {}", v))?;
+            }
+            "--digest" => {
+                // Optional algorithm, sha256 when omitted
+                digest = Some(Checksum::Sha256);
+                if let Some(alg) = args.get(i + 1).and_then(|a| Checksum::parse(a)) {
+                    digest = Some(alg);
+                    i += 1;
+                }
+            }
+            arg if arg.starts_with('-') => return Err(format!("Unknown argument: {}", arg)),
+            arg if path.is_none() => path = Some(arg.to_string()),
+            arg => return Err(format!("Unexpected argument: {}", arg)),
+        }
+        i += 1;
+    }
+
+    let path =
+        path.ok_or("Usage: randpass keyfile <PATH> [--size N] [--digest [sha256|blake3]]")?;
+    Ok(Options { path, size, digest })
+}
+
+/// Write `size` random bytes to a new read-only (0400) file, fsync it,
+/// and optionally print its digest. Never overwrites an existing file.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let opts = parse(args)?;
+
+    if Path::new(&opts.path).exists() {
+        return Err(format!(
+            "{} already exists, refusing to overwrite",
+            opts.path
+        ));
+    }
+
+    let byte_opts = ByteOptions {
+        limit: Some(opts.size),
+        file_path: Some(&opts.path),
+        fsync: Fsync::End,
+        create_mode: Some(0o400),
+        ..Default::default()
+    };
+    let written = output(&byte_opts).map_err(|e| {
+        // A partial keyfile is worse than none
+        let _ = std::fs::remove_file(&opts.path);
+        format!("{}: {}", opts.path, e)
+    })?;
+
+    prompts::keyfile_written(&opts.path, &format_bytes(written));
+    if let Some(checksum) = opts.digest {
+        let hex = digest_file(&opts.path, checksum).map_err(|e| format!("{}: {}", opts.path, e))?;
+        println!("{}  {}", hex, opts.path);
+    }
+    Ok(())
+}
@@ -0,0 +1,61 @@
+//! `randpass log show | clear`: read or empty the provenance log that
+//! `randpass config set audit_log true` turns on (`pass::audit`).
+
+use crate::pass::audit;
+use crate::settings::{Settings, ephemeral};
+
+const USAGE: &str = "Usage: randpass log show | clear";
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    match args {
+        [cmd] if cmd == "show" => show(),
+        [cmd] if cmd == "clear" => clear(),
+        [cmd, arg, ..] if cmd == "show" || cmd == "clear" => {
+            Err(format!("Unknown argument: {}", arg))
+        }
+        [other, ..] => Err(format!("Unknown log command: {}", other)),
+        [] => Err(USAGE.to_string()),
+    }
+}
+
+fn show() -> Result<(), String> {
+    let path = audit::path();
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("{}: {}", path.display(), e)),
+    };
+    if text.is_empty() {
+        println!("No entries in {}", path.display());
+        let enabled = Settings::load_from_file().is_ok_and(|s| s.audit_log);
+        if !enabled {
+            println!("Logging is off; turn it on with randpass config set audit_log true");
+        }
+        return Ok(());
+    }
+    print!("{}", text);
+    Ok(())
+}
+
+fn clear() -> Result<(), String> {
+    if ephemeral::enabled() {
+        return Err("ephemeral mode (--no-save / RANDPASS_EPHEMERAL=1), nothing removed".into());
+    }
+    let path = audit::path();
+    let entries = match std::fs::read_to_string(&path) {
+        Ok(text) => text.lines().count(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            println!("No entries in {}", path.display());
+            return Ok(());
+        }
+        Err(e) => return Err(format!("{}: {}", path.display(), e)),
+    };
+    std::fs::remove_file(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    println!(
+        "Cleared {} {} from {}",
+        entries,
+        if entries == 1 { "entry" } else { "entries" },
+        path.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,155 @@
+//! Subcommands (`randpass <command> ...`) that wrap generation in a workflow.
+
+mod bench;
+mod compare_rng;
+mod config;
+mod dedupe;
+mod dice;
+mod doctor;
+mod entropy;
+mod entropy_audit;
+mod git_credential;
+mod history;
+mod keyfile;
+mod log;
+mod pattern;
+mod policy;
+mod profile;
+mod protect;
+mod protocol;
+mod script;
+mod ssh;
+mod stats;
+mod store;
+mod systemd_cred;
+mod username;
+mod vault_pass;
+mod wipe;
+mod wordlist;
+
+use copypasta::{ClipboardContext, ClipboardProvider};
+use zeroize::Zeroize;
+
+use super::prompts;
+use crate::pass::charset::Charset;
+use crate::settings::Settings;
+
+/// Run the subcommand named by `args[1]`, if any. Returns false when
+/// `args[1]` is not a subcommand so flag parsing can take over.
+pub fn dispatch(args: &[String]) -> bool {
+    // --no-save (settings::ephemeral), --accessible (terminal::accessible)
+    // and --session-stats (session) are global and may sit anywhere
+    let args: Vec<&String> = args
+        .iter()
+        .filter(|a| *a != "--no-save" && *a != "--accessible" && *a != "--session-stats")
+        .collect();
+    let Some(name) = args.get(1) else {
+        return false;
+    };
+    let rest: Vec<String> = args[2..].iter().map(|a| a.to_string()).collect();
+    let rest = rest.as_slice();
+
+    let result = match name.as_str() {
+        "ssh-keygen" => ssh::run(rest),
+        "protect-key" => protect::run(rest),
+        "wipe" => wipe::run(rest),
+        "systemd-cred" => systemd_cred::run(rest),
+        "vault-pass" => vault_pass::run(rest),
+        "git-credential" => git_credential::run(rest),
+        "keyfile" => keyfile::run(rest),
+        "pattern" => pattern::run(rest),
+        "policy" => policy::run(rest),
+        "profile" => profile::run(rest),
+        "config" => config::run(rest),
+        "doctor" => doctor::run(rest),
+        "bench" | "--bench-self" => bench::run(rest),
+        "compare-rng" => compare_rng::run(rest),
+        "dice" => dice::run(rest),
+        "stats" => stats::run(rest),
+        "entropy" => entropy::run(rest),
+        "entropy-audit" => entropy_audit::run(rest),
+        "dedupe" => dedupe::run(rest),
+        "log" => log::run(rest),
+        "history" => history::run(rest),
+        "username" => username::run(rest),
+        "wordlist" => wordlist::run(rest),
+        "--protocol" => protocol::run(rest),
+        "--script" => script::run(rest),
+        _ => return false,
+    };
+
+    if let Err(e) = result {
+        prompts::fail(&format!("Error: {}", e));
+    }
+    true
+}
+
+/// Return the value following the flag at `args[*i]`, advancing `i`.
+fn value<'a>(args: &'a [String], i: &mut usize) -> Result<&'a str, String> {
+    let flag = &args[*i];
+    *i += 1;
+    args.get(*i)
+        .map(String::as_str)
+        .ok_or_else(|| format!("Missing value for {}", flag))
+}
+
+/// Parse the value following the flag at `args[*i]` as a number.
+fn number(args: &[String], i: &mut usize) -> Result<usize, String> {
+    let v = value(args, i)?;
+    v.parse().map_err(|_| format!("Invalid number: {}", v))
+}
+
+/// Generate a passphrase from the default charset. Caller zeroizes.
+fn generate_secret(length: usize) -> Vec<u8> {
+    let settings = Settings {
+        pass_length: length,
+        ..Default::default()
+    };
+    let chars = Charset::build(&settings);
+    let mut secret = Vec::with_capacity(length);
+    chars.generate(length, &mut secret);
+    crate::rand::shutdown_urandom();
+    secret
+}
+
+/// Hand a generated secret to the user: clipboard when requested (falling
+/// back to stdout if unavailable), otherwise printed with `label`.
+/// Zeroizes the secret.
+fn deliver_secret(secret: Vec<u8>, label: &str, clipboard: bool) {
+    // Safety: secrets are ASCII from the charset or UTF-8 words
+    let mut secret = unsafe { String::from_utf8_unchecked(secret) };
+    let copied = clipboard
+        && ClipboardContext::new()
+            .and_then(|mut ctx| ctx.set_contents(secret.clone()))
+            .is_ok();
+
+    if copied {
+        prompts::clipboard_copied();
+    } else {
+        if clipboard {
+            prompts::clipboard_error("unavailable, printing instead");
+        }
+        println!("{}: {}", label, secret);
+    }
+    secret.zeroize();
+}
+
+/// `--store`: keep `secret` as vault id `id`, so `randpass vault-pass
+/// --vault-id ID` prints it again. The key already exists by then, so a
+/// failure only warns and the secret is still delivered.
+fn store_secret(id: &str, secret: &[u8]) {
+    let path = store::dir("vault").join(store::file_name(id));
+    match store::write(&path, secret, false) {
+        Ok(()) => eprintln!(
+            "Stored the passphrase as vault id {} (randpass vault-pass --vault-id '{}')",
+            id, id
+        ),
+        Err(e) => prompts::warn(&format!("Passphrase not stored: {}", e)),
+    }
+}
+
+/// The vault id `--store` uses for a key file: `kind:` and its absolute path.
+fn key_id(kind: &str, path: &str) -> String {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.into());
+    format!("{}:{}", kind, path.display())
+}
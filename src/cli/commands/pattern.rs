@@ -0,0 +1,123 @@
+//! `randpass pattern`: save, show, list and remove named patterns, so a
+//! team can settle an identifier format once and generate it with
+//! `--pattern-name NAME`.
+
+use super::wordlist::write_atomic;
+use crate::pass::pattern::{self, Pattern};
+use crate::settings::{Settings, ephemeral};
+
+const USAGE: &str =
+    "Usage: randpass pattern list | add <NAME> <PATTERN> [--force] | show <NAME> | remove <NAME>";
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let rest = args.get(1..).unwrap_or_default();
+    match args.first().map(String::as_str) {
+        Some("list") if rest.is_empty() => {
+            list();
+            Ok(())
+        }
+        Some("add") => add(rest),
+        Some("show") => match rest {
+            [name] => show(name),
+            _ => Err(USAGE.to_string()),
+        },
+        Some("remove") => match rest {
+            [name] => remove(name),
+            _ => Err(USAGE.to_string()),
+        },
+        Some("list") => Err(format!("Unknown argument: {}", rest[0])),
+        Some(other) => Err(format!("Unknown pattern command: {}", other)),
+        None => Err(USAGE.to_string()),
+    }
+}
+
+/// Parse `template` the way a run with the default `--special` set would.
+fn parse(template: &str) -> Result<Pattern, String> {
+    Pattern::parse(template, &Settings::default().special_chars)
+}
+
+fn list() {
+    let names = pattern::names();
+    if names.is_empty() {
+        println!("No saved patterns");
+        return;
+    }
+    for name in names {
+        match pattern::load(&name) {
+            Ok(template) => match parse(&template) {
+                Ok(p) => println!("{:<18} {:>5.1} bits  {}", name, p.entropy(), template),
+                Err(e) => println!("{:<18} {:>10}  {} ({})", name, "-", template, e),
+            },
+            Err(e) => println!("{:<18} {:>10}  ({})", name, "-", e),
+        }
+    }
+}
+
+/// Check a pattern and save it under `name`.
+fn add(args: &[String]) -> Result<(), String> {
+    let mut positional = Vec::new();
+    let mut force = false;
+    for arg in args {
+        match arg.as_str() {
+            "--force" => force = true,
+            arg => positional.push(arg),
+        }
+    }
+    let [name, template] = positional[..] else {
+        return Err(USAGE.to_string());
+    };
+
+    check_name(name)?;
+    let p = parse(template)?;
+    if ephemeral::enabled() {
+        return Err("ephemeral mode (--no-save / RANDPASS_EPHEMERAL=1), nothing saved".into());
+    }
+    if pattern::path(name).exists() && !force {
+        return Err(format!("{} is already saved (--force to replace it)", name));
+    }
+
+    let dir = pattern::dir();
+    std::fs::create_dir_all(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    write_atomic(&pattern::path(name), format!("{}\n", template).as_bytes())?;
+    println!(
+        "Saved {}: {} characters, {:.1} bits",
+        name,
+        p.width(),
+        p.entropy()
+    );
+    Ok(())
+}
+
+fn show(name: &str) -> Result<(), String> {
+    check_name(name)?;
+    println!("{}", pattern::load(name)?);
+    Ok(())
+}
+
+fn remove(name: &str) -> Result<(), String> {
+    check_name(name)?;
+    if ephemeral::enabled() {
+        return Err("ephemeral mode (--no-save / RANDPASS_EPHEMERAL=1), nothing removed".into());
+    }
+    let path = pattern::path(name);
+    std::fs::remove_file(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    println!("Removed {}", name);
+    Ok(())
+}
+
+/// Names become file names.
+fn check_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"._-".contains(&b));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid name '{}' (letters, digits, '.', '_' and '-')",
+            name
+        ))
+    }
+}
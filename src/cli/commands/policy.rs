@@ -0,0 +1,402 @@
+//! `randpass policy`: work with the policy files and presets that
+//! `--policy` / `--preset` generate with. `check` audits existing
+//! passwords (one per line, reported by number, never echoed); `explain`
+//! summarizes a policy and the entropy it leaves; `export` writes one out
+//! in the file format; `import` installs a vetted file so `--policy NAME`
+//! finds it; `list` shows what is installed.
+
+use std::io::{BufRead, BufReader, Write};
+
+use zeroize::Zeroize;
+
+use super::value;
+use super::wordlist::write_atomic;
+use crate::pass::policy::{self, Class, Policy, Position, preset};
+use crate::pass::{charset, entropy as pass_entropy, trial_policy};
+use crate::settings::{Settings, ephemeral};
+use crate::terminal::calculate_entropy;
+
+const USAGE: &str = "Usage: randpass policy check | explain <POLICY> | export <POLICY> [-o FILE] | import <FILE> [--name NAME] [--force] | list";
+
+const CHECK_USAGE: &str = "Usage: randpass policy check [--policy FILE | --preset NAME] [--forbid FILE] [--must-match RE]... [--must-not-match RE]... [--digit-at POS]... [FILE]";
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let rest = args.get(1..).unwrap_or_default();
+    match args.first().map(String::as_str) {
+        Some("check") => check(rest),
+        Some("explain") => explain(rest),
+        Some("export") => export(rest),
+        Some("import") => import(rest),
+        Some("list") if rest.is_empty() => {
+            list();
+            Ok(())
+        }
+        Some("list") => Err(format!("Unknown argument: {}", rest[0])),
+        Some(other) => Err(format!("Unknown policy command: {}", other)),
+        None => Err(USAGE.to_string()),
+    }
+}
+
+/// The policy named by `--policy FILE` or `--preset NAME`, plus any
+/// `--forbid` words, `--must-match` / `--must-not-match` patterns and
+/// `--digit-at`-style position rules, and the remaining positional
+/// arguments.
+fn parse(args: &[String]) -> Result<(Policy, Vec<&str>), String> {
+    let mut policy = None;
+    let mut forbid = None;
+    let (mut must, mut must_not) = (Vec::new(), Vec::new());
+    let mut positions = Vec::new();
+    let mut positional = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        let loaded = match args[i].as_str() {
+            "--policy" => Some(policy::load(value(args, &mut i)?)?),
+            "--preset" => {
+                let name = value(args, &mut i)?;
+                Some(preset::get(name).ok_or_else(|| {
+                    format!(
+                        "Invalid value '{}' (expected aws-iam, active-directory, mysql or pin4)",
+                        name
+                    )
+                })?)
+            }
+            "--forbid" => {
+                forbid = Some(value(args, &mut i)?);
+                None
+            }
+            "--must-match" => {
+                must.push(value(args, &mut i)?.to_string());
+                None
+            }
+            "--must-not-match" => {
+                must_not.push(value(args, &mut i)?.to_string());
+                None
+            }
+            flag @ ("--lower-at" | "--upper-at" | "--digit-at" | "--special-at") => {
+                let class = match flag {
+                    "--lower-at" => Class::Lowercase,
+                    "--upper-at" => Class::Uppercase,
+                    "--digit-at" => Class::Digits,
+                    _ => Class::Special,
+                };
+                let spec = value(args, &mut i)?;
+                positions.extend(Position::parse(class, spec).ok_or_else(|| {
+                    format!("Invalid value '{}' (expected {})", spec, policy::SPECS)
+                })?);
+                None
+            }
+            arg if arg.starts_with('-') && arg != "-" => {
+                return Err(format!("Unknown argument: {}", arg));
+            }
+            arg => {
+                positional.push(arg);
+                None
+            }
+        };
+        if loaded.is_some() && policy.is_some() {
+            return Err("--policy and --preset both set the rules, pick one".to_string());
+        }
+        policy = policy.or(loaded);
+        i += 1;
+    }
+    let mut policy = match policy {
+        Some(policy) => policy,
+        None if forbid.is_some()
+            || !must.is_empty()
+            || !must_not.is_empty()
+            || !positions.is_empty() =>
+        {
+            Policy {
+                source: "(command line)".to_string(),
+                ..Default::default()
+            }
+        }
+        None => return Err(CHECK_USAGE.to_string()),
+    };
+    if let Some(path) = forbid {
+        policy.forbidden_words.extend(policy::read_words(path)?);
+    }
+    policy
+        .must_match
+        .extend(policy::compile("--must-match", &must)?);
+    policy
+        .must_not_match
+        .extend(policy::compile("--must-not-match", &must_not)?);
+    policy.positions.extend(positions);
+    Ok((policy, positional))
+}
+
+/// Print `N: PASS` or `N: FAIL: <every broken rule>` per non-empty line
+/// of the input (stdin without a file, or with `-`). Fails when any line
+/// breaks the policy, so scripts can test the exit status.
+fn check(args: &[String]) -> Result<(), String> {
+    let (policy, positional) = parse(args)?;
+    let input: Box<dyn BufRead> = match positional[..] {
+        [] | ["-"] => Box::new(BufReader::new(std::io::stdin())),
+        [path] => Box::new(BufReader::new(
+            std::fs::File::open(path).map_err(|e| format!("{}: {}", path, e))?,
+        )),
+        _ => return Err(CHECK_USAGE.to_string()),
+    };
+
+    let (checked, failed) = audit(&policy, input)?;
+    if failed > 0 {
+        return Err(format!(
+            "{} of {} passwords break policy {}",
+            failed, checked, policy.source
+        ));
+    }
+    eprintln!("All {} passwords satisfy policy {}", checked, policy.source);
+    Ok(())
+}
+
+/// Check every line of `input`, zeroizing each once checked. Returns the
+/// lines checked and how many failed.
+fn audit(policy: &Policy, mut input: Box<dyn BufRead>) -> Result<(usize, usize), String> {
+    let mut line = String::new();
+    let (mut checked, mut failed) = (0, 0);
+    for number in 1.. {
+        line.zeroize();
+        match input.read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(e) => {
+                line.zeroize();
+                return Err(format!("Failed to read input: {}", e));
+            }
+        }
+        let password = line.trim_end_matches(['\n', '\r']);
+        if password.is_empty() {
+            continue;
+        }
+        checked += 1;
+        let broken = policy.violations(password);
+        if broken.is_empty() {
+            println!("{}: PASS", number);
+        } else {
+            failed += 1;
+            let reasons: Vec<String> = broken.into_iter().map(|r| policy.describe(r)).collect();
+            println!("{}: FAIL: {}", number, reasons.join("; "));
+        }
+    }
+    line.zeroize();
+    Ok((checked, failed))
+}
+
+/// The policy `explain` and `export` work on: a bare FILE or installed
+/// NAME, or the same flags `check` takes. Returns the leftover arguments.
+fn selected(args: &[String]) -> Result<(Policy, Vec<&str>), String> {
+    match args {
+        [arg] if !arg.starts_with('-') => Ok((policy::load(arg)?, Vec::new())),
+        _ => parse(args),
+    }
+}
+
+/// Print what a policy asks for in plain words, and the entropy left in
+/// a password that satisfies it.
+fn explain(args: &[String]) -> Result<(), String> {
+    let (policy, rest) = selected(args)?;
+    if let Some(arg) = rest.first() {
+        return Err(format!("Unexpected argument: {}", arg));
+    }
+
+    println!("Policy {}", policy.source);
+    println!("  Length: {}", policy.length_range());
+    if !policy.require.is_empty() {
+        let counts: Vec<String> = policy
+            .require
+            .iter()
+            .map(|&(class, n)| match n {
+                1 => format!("1 {}", class.noun()),
+                _ => format!("{} {}s", n, class.noun()),
+            })
+            .collect();
+        println!("  Requires at least: {}", counts.join(", "));
+    }
+    if !policy.positions.is_empty() {
+        let rules: Vec<String> = policy.positions.iter().map(|p| p.summary()).collect();
+        println!("  Positions: {}", rules.join(", "));
+    }
+    if let Some(ref allowed) = policy.allowed_chars {
+        println!("  Only these characters: {}", allowed);
+    }
+    if !policy.forbidden_chars.is_empty() {
+        println!("  Never these characters: {}", policy.forbidden_chars);
+    }
+    let lists: [(&str, Vec<&str>); 4] = [
+        (
+            "Never contains",
+            policy
+                .forbidden_substrings
+                .iter()
+                .map(String::as_str)
+                .collect(),
+        ),
+        (
+            "Never contains, in any case or leetspeak",
+            policy.forbidden_words.iter().map(String::as_str).collect(),
+        ),
+        (
+            "Must match",
+            policy.must_match.iter().map(|re| re.as_str()).collect(),
+        ),
+        (
+            "Must not match",
+            policy.must_not_match.iter().map(|re| re.as_str()).collect(),
+        ),
+    ];
+    for (label, items) in lists {
+        if !items.is_empty() {
+            println!("  {}: {}", label, items.join("  "));
+        }
+    }
+
+    // Fitted to the policy the way generation does it; without a
+    // min_length, the shortest password considered is the default length
+    let mut settings = Settings {
+        policy: Some(policy.clone()),
+        ..Default::default()
+    };
+    if let Some(min) = policy.min_length {
+        settings.pass_length = min;
+    }
+    policy.prepare(&mut settings, false)?;
+    let min = settings.pass_length;
+    let pool = charset::size(&settings);
+    let low = entropy(&settings, min)?;
+    match policy.max_length {
+        Some(max) if max > min => {
+            let high = entropy(&settings, max)?;
+            println!(
+                "  Entropy: {:.1} bits at {} characters to {:.1} bits at {} (default character set, {} characters)",
+                low, min, high, max, pool
+            );
+        }
+        Some(_) => println!(
+            "  Entropy: {:.1} bits at {} characters (default character set, {} characters)",
+            low, min, pool
+        ),
+        None => println!(
+            "  Entropy: {:.1} bits at {} characters, about {:.1} more per extra character (default character set, {} characters)",
+            low,
+            min,
+            calculate_entropy(1, pool),
+            pool
+        ),
+    }
+    Ok(())
+}
+
+/// Bits in a `length`-character password drawn from the default character
+/// set under the settings' policy: the entropy sampling gives (positions
+/// included), less what the rejected share of a trial takes away.
+fn entropy(settings: &Settings, length: usize) -> Result<f64, String> {
+    let mut settings = settings.clone();
+    settings.pass_length = length;
+    match trial_policy(&settings, policy::TRIAL_DRAWS) {
+        Some(Ok(trial)) => Ok(pass_entropy(&settings, Some(&trial))),
+        Some(Err(e)) => Err(format!("at {} characters, {}", length, e)),
+        None => Ok(pass_entropy(&settings, None)),
+    }
+}
+
+/// Print the policy in the file format, to stdout or `-o FILE`.
+fn export(args: &[String]) -> Result<(), String> {
+    let mut output = None;
+    let mut rest = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "-o" | "--output" => output = Some(value(args, &mut i)?),
+            _ => rest.push(args[i].clone()),
+        }
+        i += 1;
+    }
+    let (policy, extra) = selected(&rest)?;
+    if let Some(arg) = extra.first() {
+        return Err(format!("Unexpected argument: {}", arg));
+    }
+
+    let text = format!(
+        "# randpass policy, exported from {}\n{}",
+        policy.source,
+        policy::render(&policy)
+    );
+    match output {
+        Some(path) => std::fs::write(path, text).map_err(|e| format!("{}: {}", path, e)),
+        None => std::io::stdout()
+            .write_all(text.as_bytes())
+            .map_err(|e| e.to_string()),
+    }
+}
+
+/// Check a policy file and install it under `~/.config/randpass/policies`.
+fn import(args: &[String]) -> Result<(), String> {
+    let mut name = None;
+    let mut force = false;
+    let mut positional = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--name" => name = Some(value(args, &mut i)?.to_string()),
+            "--force" => force = true,
+            arg if arg.starts_with('-') => return Err(format!("Unknown argument: {}", arg)),
+            arg => positional.push(arg),
+        }
+        i += 1;
+    }
+    let [path] = positional[..] else {
+        return Err(USAGE.to_string());
+    };
+    let name = match name {
+        Some(name) => name,
+        None => std::path::Path::new(path)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_string(),
+    };
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"._-".contains(&b));
+    if !valid {
+        return Err(format!(
+            "Invalid name '{}' (letters, digits, '.', '_' and '-'; set one with --name)",
+            name
+        ));
+    }
+
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    // Refuse a file that wouldn't load, before anyone relies on it
+    policy::parse(&text, path)?;
+    if ephemeral::enabled() {
+        return Err("ephemeral mode (--no-save / RANDPASS_EPHEMERAL=1), nothing installed".into());
+    }
+    let target = policy::installed_path(&name);
+    if target.exists() && !force {
+        return Err(format!(
+            "policy {} is already installed (--force to replace it)",
+            name
+        ));
+    }
+    let dir = policy::installed_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    write_atomic(&target, text.as_bytes())?;
+    println!("Installed policy {}: use --policy {}", name, name);
+    Ok(())
+}
+
+fn list() {
+    for (name, summary, _) in preset::PRESETS {
+        println!("{:<18} preset     {}", name, summary);
+    }
+    for name in policy::installed() {
+        println!(
+            "{:<18} installed  {}",
+            name,
+            policy::installed_path(&name).display()
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! `randpass profile`: create, show and remove the named flag sets that
+//! `--profile NAME` applies (see `cli::profile`).
+
+use std::path::Path;
+
+use super::wordlist::write_atomic;
+use crate::cli::{parse, profile};
+use crate::settings::ephemeral;
+
+const USAGE: &str =
+    "Usage: randpass profile set <NAME> [FLAGS...] | show <NAME> | list | remove <NAME>";
+
+/// Flags whose value is a file; saved as absolute paths so the profile
+/// works from any directory.
+const PATH_FLAGS: &[&str] = &["--policy", "--forbid", "--wordlist", "--for-each"];
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let rest = args.get(1..).unwrap_or_default();
+    match (args.first().map(String::as_str), rest) {
+        (Some("set"), [name, flags @ ..]) => set(name, flags),
+        (Some("show"), [name]) => {
+            for arg in profile::load(name)? {
+                println!("{}", arg);
+            }
+            Ok(())
+        }
+        (Some("list"), []) => {
+            let mut names: Vec<String> = std::fs::read_dir(profile::dir())
+                .into_iter()
+                .flatten()
+                .flatten()
+                .filter_map(|e| e.file_name().into_string().ok())
+                .filter(|n| profile::check_name(n).is_ok())
+                .collect();
+            names.sort();
+            for name in names {
+                let args = profile::load(&name).unwrap_or_default();
+                println!("{:<18} {}", name, args.join(" "));
+            }
+            Ok(())
+        }
+        (Some("remove"), [name]) => {
+            profile::check_name(name)?;
+            if ephemeral::enabled() {
+                return Err(
+                    "ephemeral mode (--no-save / RANDPASS_EPHEMERAL=1), nothing removed".into(),
+                );
+            }
+            let path = profile::path(name);
+            std::fs::remove_file(&path).map_err(|e| format!("{}: {}", path.display(), e))
+        }
+        (Some("set" | "show" | "list" | "remove"), _) | (None, _) => Err(USAGE.to_string()),
+        (Some(other), _) => Err(format!("Unknown profile command: {}", other)),
+    }
+}
+
+/// Save `flags` as profile `name`, replacing any profile of that name.
+fn set(name: &str, flags: &[String]) -> Result<(), String> {
+    profile::check_name(name)?;
+    // Refuse a profile that would fail on every use
+    let mut check = vec!["randpass".to_string()];
+    check.extend(flags.iter().cloned());
+    let parsed = parse(&check).map_err(|e| e.to_string())?;
+    if parsed.profile.is_some() {
+        return Err("a profile can't name another profile".to_string());
+    }
+    if ephemeral::enabled() {
+        return Err("ephemeral mode (--no-save / RANDPASS_EPHEMERAL=1), nothing saved".into());
+    }
+
+    let mut text = String::new();
+    let mut after_path_flag = false;
+    for arg in flags {
+        let value = match after_path_flag && Path::new(arg).exists() {
+            true => std::fs::canonicalize(arg)
+                .map_or_else(|_| arg.clone(), |abs| abs.to_string_lossy().into_owned()),
+            false => arg.clone(),
+        };
+        after_path_flag = PATH_FLAGS.contains(&arg.as_str());
+        text.push_str(&value);
+        text.push('\n');
+    }
+
+    let dir = profile::dir();
+    std::fs::create_dir_all(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    write_atomic(&profile::path(name), text.as_bytes())?;
+    println!("Saved profile {}: randpass --profile {}", name, name);
+    Ok(())
+}
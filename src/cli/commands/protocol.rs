@@ -0,0 +1,405 @@
+//! `randpass --protocol`: a long-lived child process for editors and
+//! scripts. Reads one JSON request per line from stdin and answers each
+//! with one JSON line on stdout, until `quit` or end of input.
+//!
+//! Requests are flat objects:
+//!
+//! ```text
+//! {"cmd":"generate","length":32,"count":5}
+//! {"cmd":"generate","id":7,"length":20,"special":"-_","no_special":false}
+//! {"cmd":"ping"}
+//! {"cmd":"quit"}
+//! ```
+//!
+//! Responses carry `"ok"`, echo the request's `"id"` when given, and hold
+//! either the result (`"passwords"`, `"version"`) or an `"error"` string.
+
+use std::io::{BufRead, Write};
+
+use zeroize::Zeroize;
+
+use super::config::json_string;
+use crate::pass::SecureBufWriter;
+use crate::pass::charset::{self, Charset};
+use crate::settings::Settings;
+
+/// Largest `count` one request may ask for.
+const MAX_COUNT: usize = 10_000;
+/// Longest password one request may ask for.
+const MAX_LENGTH: usize = 4096;
+
+enum Value {
+    Str(String),
+    /// Number as written, parsed by whoever reads it
+    Num(String),
+    Bool(bool),
+    Null,
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    if let Some(arg) = args.first() {
+        return Err(format!("Unknown argument: {}", arg));
+    }
+
+    let stdin = std::io::stdin();
+    let mut out = SecureBufWriter::new(std::io::stdout().lock());
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(e) => return Err(format!("Failed to read stdin: {}", e)),
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (mut response, quit) = handle(line.trim());
+        let written = out
+            .write_all(&response)
+            .and_then(|_| out.write_all(b"\n"))
+            .and_then(|_| out.flush());
+        response.zeroize();
+        // The client went away; nothing left to answer
+        if written.is_err() || quit {
+            break;
+        }
+    }
+    line.zeroize();
+    crate::rand::shutdown_urandom();
+    Ok(())
+}
+
+/// Answer one request line. Returns the response and whether to stop.
+fn handle(line: &str) -> (Vec<u8>, bool) {
+    let fields = match parse_object(line) {
+        Ok(fields) => fields,
+        Err(e) => return (error(None, &format!("Invalid request: {}", e)), false),
+    };
+    let id = fields.iter().find(|(k, _)| k == "id").map(|(_, v)| v);
+    let id = match id {
+        None | Some(Value::Null) => None,
+        Some(Value::Str(s)) => Some(json_string(s)),
+        Some(Value::Num(n)) => Some(n.clone()),
+        Some(Value::Bool(_)) => return (error(None, "id must be a string or number"), false),
+    };
+    let id = id.as_deref();
+
+    let cmd = match fields.iter().find(|(k, _)| k == "cmd") {
+        Some((_, Value::Str(cmd))) => cmd.as_str(),
+        Some(_) => return (error(id, "cmd must be a string"), false),
+        None => return (error(id, "Missing cmd"), false),
+    };
+    match cmd {
+        "generate" => match generate(&fields, id) {
+            Ok(response) => (response, false),
+            Err(e) => (error(id, &e), false),
+        },
+        "ping" => (
+            ok(
+                id,
+                &format!(",\"version\":{}", json_string(env!("CARGO_PKG_VERSION"))),
+            ),
+            false,
+        ),
+        "quit" => (ok(id, ""), true),
+        other => (error(id, &format!("Unknown cmd: {}", other)), false),
+    }
+}
+
+/// `generate`: `count` passwords (default 1) of `length` characters
+/// (default 74, as on the command line), with `special` replacing the special set and
+/// `no_special` dropping it.
+fn generate(fields: &[(String, Value)], id: Option<&str>) -> Result<Vec<u8>, String> {
+    let mut settings = Settings::default();
+    let mut count = 1;
+    for (key, value) in fields {
+        match (key.as_str(), value) {
+            ("cmd" | "id", _) => {}
+            ("length", Value::Num(n)) => settings.pass_length = bounded(key, n, MAX_LENGTH)?,
+            ("count", Value::Num(n)) => count = bounded(key, n, MAX_COUNT)?,
+            ("special", Value::Str(s)) => {
+                if s.is_empty() || !s.bytes().all(|b| b.is_ascii_graphic()) {
+                    return Err("special must be printable ASCII".to_string());
+                }
+                settings.special_chars = s.bytes().collect();
+            }
+            ("no_special", Value::Bool(b)) => {
+                if *b {
+                    settings.special_char_density = 0;
+                }
+            }
+            ("length" | "count", _) => return Err(format!("{} must be a number", key)),
+            ("special", _) => return Err("special must be a string".to_string()),
+            ("no_special", _) => return Err("no_special must be true or false".to_string()),
+            _ => return Err(format!("Unknown field: {}", key)),
+        }
+    }
+    if charset::size(&settings) == 0 {
+        return Err("Character set is empty".to_string());
+    }
+
+    let chars = Charset::build(&settings);
+    let mut buf = Vec::with_capacity(settings.pass_length);
+    let prefix = prefix(id);
+    // Room for every character escaped, so the buffer never reallocates
+    let mut response =
+        Vec::with_capacity(prefix.len() + count * (2 * settings.pass_length + 3) + 32);
+    response.extend_from_slice(prefix.as_bytes());
+    response.extend_from_slice(b"\"ok\":true,\"passwords\":[");
+    for n in 0..count {
+        if n > 0 {
+            response.push(b',');
+        }
+        chars.generate(settings.pass_length, &mut buf);
+        push_escaped(&mut response, &buf);
+        buf.zeroize();
+    }
+    response.extend_from_slice(b"]}");
+    Ok(response)
+}
+
+/// Parse a number field as an integer in `1..=max`.
+fn bounded(key: &str, n: &str, max: usize) -> Result<usize, String> {
+    n.parse::<usize>()
+        .ok()
+        .filter(|&v| (1..=max).contains(&v))
+        .ok_or_else(|| format!("{} must be a whole number from 1 to {}", key, max))
+}
+
+/// Opening brace and `"id"` member, if any.
+fn prefix(id: Option<&str>) -> String {
+    match id {
+        Some(id) => format!("{{\"id\":{},", id),
+        None => "{".to_string(),
+    }
+}
+
+/// `{"ok":true` plus `rest` (leading comma included).
+fn ok(id: Option<&str>, rest: &str) -> Vec<u8> {
+    format!("{}\"ok\":true{}}}", prefix(id), rest).into_bytes()
+}
+
+fn error(id: Option<&str>, message: &str) -> Vec<u8> {
+    format!(
+        "{}\"ok\":false,\"error\":{}}}",
+        prefix(id),
+        json_string(message)
+    )
+    .into_bytes()
+}
+
+/// Append `bytes` as a JSON string without an intermediate copy.
+fn push_escaped(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.push(b'"');
+    for &b in bytes {
+        match b {
+            b'"' => out.extend_from_slice(b"\\\""),
+            b'\\' => out.extend_from_slice(b"\\\\"),
+            b if b < 0x20 => out.extend_from_slice(format!("\\u{:04x}", b).as_bytes()),
+            b => out.push(b),
+        }
+    }
+    out.push(b'"');
+}
+
+// =============================================================================
+// Request parsing
+// =============================================================================
+
+/// Parse one flat JSON object: string keys, and string, number, boolean
+/// or null values. Nested objects and arrays are refused.
+fn parse_object(line: &str) -> Result<Vec<(String, Value)>, String> {
+    let mut p = Parser {
+        s: line.as_bytes(),
+        i: 0,
+    };
+    let mut fields = Vec::new();
+    p.expect(b'{')?;
+    if p.peek() == Some(b'}') {
+        p.i += 1;
+    } else {
+        loop {
+            let key = p.string()?;
+            p.expect(b':')?;
+            fields.push((key, p.value()?));
+            match p.next() {
+                Some(b',') => continue,
+                Some(b'}') => break,
+                _ => return Err(format!("expected , or }} at {}", p.i)),
+            }
+        }
+    }
+    if p.peek().is_some() {
+        return Err(format!("trailing data at {}", p.i));
+    }
+    Ok(fields)
+}
+
+struct Parser<'a> {
+    s: &'a [u8],
+    i: usize,
+}
+
+impl Parser<'_> {
+    /// Next non-whitespace byte, not consumed.
+    fn peek(&mut self) -> Option<u8> {
+        while self.i < self.s.len() && self.s[self.i].is_ascii_whitespace() {
+            self.i += 1;
+        }
+        self.s.get(self.i).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let b = self.peek()?;
+        self.i += 1;
+        Some(b)
+    }
+
+    fn expect(&mut self, want: u8) -> Result<(), String> {
+        match self.next() {
+            Some(b) if b == want => Ok(()),
+            _ => Err(format!("expected {} at {}", want as char, self.i)),
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        match self.peek() {
+            Some(b'"') => self.string().map(Value::Str),
+            Some(b'-' | b'0'..=b'9') => self.number().map(Value::Num),
+            _ => {
+                let rest = &self.s[self.i..];
+                for (word, value) in [
+                    (&b"true"[..], Value::Bool(true)),
+                    (b"false", Value::Bool(false)),
+                    (b"null", Value::Null),
+                ] {
+                    if rest.starts_with(word) {
+                        self.i += word.len();
+                        return Ok(value);
+                    }
+                }
+                Err(format!("unsupported value at {}", self.i))
+            }
+        }
+    }
+
+    /// A number as JSON writes it, `-?(0|[1-9][0-9]*)(.[0-9]+)?([eE][+-]?[0-9]+)?`,
+    /// so echoing it back (as an `id`) keeps the response valid.
+    fn number(&mut self) -> Result<String, String> {
+        let start = self.i;
+        let bad = |at: usize| format!("bad number at {}", at);
+        self.skip(|b| b == b'-', 1);
+        match self.s.get(self.i) {
+            Some(b'0') => self.i += 1,
+            Some(b'1'..=b'9') => {
+                self.skip(|b| b.is_ascii_digit(), usize::MAX);
+            }
+            _ => return Err(bad(start)),
+        }
+        if self.s.get(self.i) == Some(&b'.') {
+            self.i += 1;
+            if self.skip(|b| b.is_ascii_digit(), usize::MAX) == 0 {
+                return Err(bad(start));
+            }
+        }
+        if matches!(self.s.get(self.i), Some(b'e' | b'E')) {
+            self.i += 1;
+            self.skip(|b| b == b'+' || b == b'-', 1);
+            if self.skip(|b| b.is_ascii_digit(), usize::MAX) == 0 {
+                return Err(bad(start));
+            }
+        }
+        // 1-2 or 01 would otherwise end the number early and fail later
+        // with a less helpful message
+        if matches!(self.s.get(self.i), Some(b'0'..=b'9' | b'-' | b'+' | b'.')) {
+            return Err(bad(start));
+        }
+        Ok(std::str::from_utf8(&self.s[start..self.i])
+            .unwrap_or_default()
+            .to_string())
+    }
+
+    /// Step over up to `max` bytes matching `want`; returns how many.
+    fn skip(&mut self, want: impl Fn(u8) -> bool, max: usize) -> usize {
+        let start = self.i;
+        while self.i - start < max && self.s.get(self.i).is_some_and(|&b| want(b)) {
+            self.i += 1;
+        }
+        self.i - start
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect(b'"')?;
+        let mut out = String::new();
+        loop {
+            let Some(&b) = self.s.get(self.i) else {
+                return Err("unterminated string".to_string());
+            };
+            self.i += 1;
+            match b {
+                b'"' => return Ok(out),
+                b'\\' => {
+                    let Some(&e) = self.s.get(self.i) else {
+                        return Err("unterminated string".to_string());
+                    };
+                    self.i += 1;
+                    out.push(match e {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode()?,
+                        _ => return Err(format!("bad escape at {}", self.i - 1)),
+                    });
+                }
+                b if b < 0x20 => return Err(format!("control character at {}", self.i - 1)),
+                _ => {
+                    // Copy the whole UTF-8 sequence; the line is a valid &str
+                    let start = self.i - 1;
+                    while self.i < self.s.len() && (self.s[self.i] & 0xC0) == 0x80 {
+                        self.i += 1;
+                    }
+                    out.push_str(std::str::from_utf8(&self.s[start..self.i]).unwrap_or_default());
+                }
+            }
+        }
+    }
+
+    /// The character after `\u`, joining surrogate pairs.
+    fn unicode(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.s[self.i..].starts_with(b"\\u") {
+                return Err(format!("lone surrogate at {}", self.i));
+            }
+            self.i += 2;
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(format!("lone surrogate at {}", self.i));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| format!("bad \\u escape at {}", self.i))
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits = self
+            .s
+            .get(self.i..self.i + 4)
+            // from_str_radix alone would take a leading +
+            .filter(|h| h.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u32::from_str_radix(h, 16).ok())
+            .ok_or_else(|| format!("bad \\u escape at {}", self.i))?;
+        self.i += 4;
+        Ok(digits)
+    }
+}
@@ -0,0 +1,136 @@
+//! `randpass --script FILE`: run the interactive menu headless, typing
+//! keys from FILE instead of the keyboard, so TUI-only flows such as the
+//! file-exists dialog can run from CI. Everything the menu draws goes to
+//! stdout; add `--accessible` for plain lines without escape codes.
+//!
+//! One step per line:
+//!
+//! ```text
+//! # a comment; blank lines are skipped
+//! 1                 typed, then Enter
+//! <ctrl-u>out.txt   Ctrl+U, then "out.txt", then Enter
+//! <esc>             a line of key names alone sends no Enter
+//! ? Settings Menu   wait until the output since the last ? shows this
+//! ```
+//!
+//! Key names: `enter`, `esc`, `tab`, `backspace`, `delete`, `up`, `down`,
+//! `left`, `right`, `home`, `end`, `pageup`, `pagedown`, `space` and
+//! `ctrl-<letter>`. One character in brackets types it, so `<<>` is `<`
+//! and `<#>` or `<?>` start a line with `#` or `?`.
+//!
+//! The run fails when an expectation isn't met or the script runs out
+//! of keys while the menu still waits for one; end it by quitting the
+//! menu (`5`).
+
+use crossterm::event::KeyCode;
+
+use super::prompts;
+use crate::terminal::console::{Script, Step, ctrl, key};
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let path = match args {
+        [] => return Err("Missing value for --script".to_string()),
+        [path] => path,
+        [_, extra, ..] => return Err(format!("Unknown argument: {}", extra)),
+    };
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let steps = parse(&text).map_err(|e| format!("{}: {}", path, e))?;
+
+    let script = Script::steps(steps).headless();
+    crate::tui::run();
+    script.finish()?;
+    let left = script.pending();
+    if left > 0 {
+        prompts::warn(&format!(
+            "--script: the menu was quit with {} key(s) left unread",
+            left
+        ));
+    }
+    Ok(())
+}
+
+/// The steps of a script file, in order.
+fn parse(text: &str) -> Result<Vec<Step>, String> {
+    let mut steps = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(expect) = line.strip_prefix('?') {
+            let expect = expect.trim();
+            if expect.is_empty() {
+                return Err(format!("line {}: ? needs text to expect", n + 1));
+            }
+            steps.push(Step::Expect(expect.to_string()));
+            continue;
+        }
+        let (keys, typed) = keys(line).map_err(|e| format!("line {}: {}", n + 1, e))?;
+        steps.extend(keys.into_iter().map(Step::Key));
+        if typed {
+            steps.push(Step::Key(key(KeyCode::Enter)));
+        }
+    }
+    Ok(steps)
+}
+
+/// The keys one line sends, and whether it typed any text (and so ends
+/// with Enter).
+fn keys(line: &str) -> Result<(Vec<crossterm::event::Event>, bool), String> {
+    let mut keys = Vec::new();
+    let mut typed = false;
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        if c != '<' {
+            keys.push(key(KeyCode::Char(c)));
+            typed = true;
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        // The name runs to the first > after at least one character
+        let close = rest[1..]
+            .char_indices()
+            .skip(1)
+            .find(|&(_, c)| c == '>')
+            .map(|(i, _)| i + 1)
+            .ok_or_else(|| format!("unclosed < in {}; type < as <<>", line))?;
+        let name = &rest[1..close];
+        rest = &rest[close + 1..];
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            keys.push(key(KeyCode::Char(c)));
+            typed = true;
+            continue;
+        }
+        keys.push(named(name).ok_or_else(|| format!("unknown key <{}>", name))?);
+    }
+    Ok((keys, typed))
+}
+
+fn named(name: &str) -> Option<crossterm::event::Event> {
+    if let Some(letter) = name.strip_prefix("ctrl-") {
+        let mut chars = letter.chars();
+        return match (chars.next(), chars.next()) {
+            (Some(c), None) if c.is_ascii_alphabetic() => Some(ctrl(c.to_ascii_lowercase())),
+            _ => None,
+        };
+    }
+    let code = match name {
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "delete" => KeyCode::Delete,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "space" => KeyCode::Char(' '),
+        _ => return None,
+    };
+    Some(key(code))
+}
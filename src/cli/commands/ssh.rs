@@ -0,0 +1,129 @@
+//! `randpass ssh-keygen`: create an SSH key protected by a generated passphrase.
+
+use std::io::Write;
+use std::os::unix::process::CommandExt;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use zeroize::Zeroize;
+
+use super::{deliver_secret, generate_secret, key_id, number, store_secret, value};
+
+struct Options {
+    key_type: String,
+    path: String,
+    comment: Option<String>,
+    length: usize,
+    clipboard: bool,
+    store: bool,
+}
+
+fn parse(args: &[String]) -> Result<Options, String> {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".into());
+    let mut opts = Options {
+        key_type: "ed25519".to_string(),
+        path: String::new(),
+        comment: None,
+        length: 32,
+        clipboard: false,
+        store: false,
+    };
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "-t" | "--type" => opts.key_type = value(args, &mut i)?.to_string(),
+            "-f" | "--file" => opts.path = value(args, &mut i)?.to_string(),
+            "-C" | "--comment" => opts.comment = Some(value(args, &mut i)?.to_string()),
+            "-l" | "--length" => opts.length = number(args, &mut i)?,
+            "-b" | "--board" => opts.clipboard = true,
+            "--store" => opts.store = true,
+            arg => return Err(format!("Unknown argument: {}", arg)),
+        }
+        i += 1;
+    }
+
+    if opts.path.is_empty() {
+        opts.path = format!("{}/.ssh/id_{}", home, opts.key_type);
+    }
+    if opts.length == 0 {
+        return Err("Passphrase length must be at least 1".to_string());
+    }
+    Ok(opts)
+}
+
+/// Generate a passphrase, hand it to `ssh-keygen` over stdin, and print the
+/// public key. The passphrase never appears in argv or the environment.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let opts = parse(args)?;
+
+    if Path::new(&opts.path).exists() {
+        return Err(format!(
+            "{} already exists, refusing to overwrite",
+            opts.path
+        ));
+    }
+    if let Some(parent) = Path::new(&opts.path).parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent).map_err(|e| format!("{}: {}", parent.display(), e))?;
+    }
+
+    let mut passphrase = generate_secret(opts.length);
+
+    let mut cmd = Command::new("ssh-keygen");
+    cmd.args(["-q", "-t", &opts.key_type, "-f", &opts.path]);
+    if let Some(ref comment) = opts.comment {
+        cmd.args(["-C", comment]);
+    }
+    // Detach from the controlling terminal so ssh-keygen reads the
+    // passphrase from stdin instead of prompting on /dev/tty.
+    unsafe {
+        cmd.pre_exec(|| {
+            libc::setsid();
+            Ok(())
+        });
+    }
+
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to run ssh-keygen: {}", e))?;
+
+    let mut input = Vec::with_capacity(passphrase.len() * 2 + 2);
+    for _ in 0..2 {
+        input.extend_from_slice(&passphrase);
+        input.push(b'\n');
+    }
+    let written = child
+        .stdin
+        .take()
+        .map(|mut stdin| stdin.write_all(&input))
+        .unwrap_or(Ok(()));
+    input.zeroize();
+
+    // stderr only carries the passphrase prompts unless something went wrong
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if written.is_err() || !output.status.success() {
+        passphrase.zeroize();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "ssh-keygen failed ({}): {}",
+            output.status,
+            stderr.trim()
+        ));
+    }
+
+    if opts.store {
+        store_secret(&key_id("ssh", &opts.path), &passphrase);
+    }
+    deliver_secret(passphrase, "Passphrase", opts.clipboard);
+
+    let public = std::fs::read_to_string(format!("{}.pub", opts.path))
+        .map_err(|e| format!("{}.pub: {}", opts.path, e))?;
+    print!("{}", public);
+    eprintln!("Private key: {}", opts.path);
+    Ok(())
+}
@@ -0,0 +1,258 @@
+//! `randpass stats <FILE>`: how the characters of a generated file are
+//! spread. Reports each character's frequency, the class mix at each
+//! position, a histogram of password lengths, and a chi-square test of
+//! whether the characters seen were drawn uniformly. `-` reads stdin.
+//!
+//! The test assumes every character in the pool was equally likely, which
+//! holds for the default densities. A density above 1 weights its class,
+//! and passphrases follow their words, so neither should come out uniform.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+use zeroize::Zeroize;
+
+use crate::terminal::{box_bottom, box_line, box_top, format_number};
+
+const USAGE: &str = "Usage: randpass stats <FILE|->";
+
+/// Positions shown in the class table.
+const POSITIONS: usize = 32;
+/// Lengths shown in the histogram.
+const LENGTHS: usize = 20;
+/// Characters per row of the frequency table.
+const PER_ROW: usize = 6;
+/// Below this p-value the characters are reported as non-uniform.
+const ALPHA: f64 = 0.001;
+/// Fewest expected draws per character for the chi-square test to hold.
+const MIN_EXPECTED: f64 = 5.0;
+
+const CLASSES: [&str; 4] = ["lower", "upper", "digit", "symbol"];
+
+#[derive(Default)]
+struct Counts {
+    passwords: u64,
+    chars: u64,
+    /// Draws of each character
+    freq: BTreeMap<char, u64>,
+    /// Class counts for the first `POSITIONS` positions
+    positions: Vec<[u64; 4]>,
+    /// Passwords of each length, in characters
+    lengths: BTreeMap<usize, u64>,
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let path = match args {
+        [path] => path.as_str(),
+        [] => return Err(USAGE.to_string()),
+        [_, arg, ..] => return Err(format!("Unknown argument: {}", arg)),
+    };
+    let counts = if path == "-" {
+        count(io::stdin().lock())
+    } else {
+        let file = File::open(path).map_err(|e| format!("Cannot open {}: {}", path, e))?;
+        count(BufReader::new(file))
+    }
+    .map_err(|e| format!("Cannot read {}: {}", path, e))?;
+
+    if counts.passwords == 0 {
+        return Err(format!("{} has no passwords in it", path));
+    }
+    show(path, &counts);
+    Ok(())
+}
+
+fn class(c: char) -> usize {
+    if c.is_lowercase() {
+        0
+    } else if c.is_uppercase() {
+        1
+    } else if c.is_ascii_digit() {
+        2
+    } else {
+        3
+    }
+}
+
+/// Tally one password per line; blank lines are skipped.
+fn count(mut reader: impl BufRead) -> io::Result<Counts> {
+    let mut counts = Counts::default();
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        while matches!(line.last(), Some(b'\n' | b'\r')) {
+            line.pop();
+        }
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut len = 0;
+        for c in String::from_utf8_lossy(&line).chars() {
+            *counts.freq.entry(c).or_default() += 1;
+            if len < POSITIONS {
+                if counts.positions.len() == len {
+                    counts.positions.push([0; 4]);
+                }
+                counts.positions[len][class(c)] += 1;
+            }
+            len += 1;
+        }
+        counts.passwords += 1;
+        counts.chars += len as u64;
+        *counts.lengths.entry(len).or_default() += 1;
+    }
+    line.zeroize();
+    Ok(counts)
+}
+
+fn percent(part: u64, whole: u64) -> f64 {
+    part as f64 / whole.max(1) as f64 * 100.0
+}
+
+fn show(path: &str, counts: &Counts) {
+    println!();
+    box_top("Stats");
+    box_line(&format!(
+        "{} • {} passwords • {} characters • {} distinct",
+        path,
+        format_number(counts.passwords as usize),
+        format_number(counts.chars as usize),
+        counts.freq.len()
+    ));
+    box_bottom();
+
+    box_top("Character Frequency");
+    let cells: Vec<String> = counts
+        .freq
+        .iter()
+        .map(|(&c, &n)| {
+            let label = if c == ' ' {
+                "sp".to_string()
+            } else {
+                c.to_string()
+            };
+            format!("{:>2} {:>5.2}%", label, percent(n, counts.chars))
+        })
+        .collect();
+    for row in cells.chunks(PER_ROW) {
+        box_line(&row.join("  "));
+    }
+    box_bottom();
+
+    box_top("Classes by Position");
+    box_line(&format!(
+        "{:>5}  {:>8}  {:>8}  {:>8}  {:>8}",
+        "pos", CLASSES[0], CLASSES[1], CLASSES[2], CLASSES[3]
+    ));
+    for (i, classes) in counts.positions.iter().enumerate() {
+        let total: u64 = classes.iter().sum();
+        box_line(&format!(
+            "{:>5}  {:>7.1}%  {:>7.1}%  {:>7.1}%  {:>7.1}%",
+            i + 1,
+            percent(classes[0], total),
+            percent(classes[1], total),
+            percent(classes[2], total),
+            percent(classes[3], total)
+        ));
+    }
+    let longest = counts.lengths.keys().next_back().copied().unwrap_or(0);
+    if longest > POSITIONS {
+        box_line(&format!("positions past {} not shown", POSITIONS));
+    }
+    box_bottom();
+
+    box_top("Lengths");
+    let most = counts.lengths.values().copied().max().unwrap_or(1);
+    for (&len, &n) in counts.lengths.iter().take(LENGTHS) {
+        let bar = "█".repeat((n * 40 / most).max(1) as usize);
+        box_line(&format!(
+            "{:>5}  {:>12}  {}",
+            len,
+            format_number(n as usize),
+            bar
+        ));
+    }
+    if counts.lengths.len() > LENGTHS {
+        box_line(&format!(
+            "{} more lengths, up to {}",
+            counts.lengths.len() - LENGTHS,
+            longest
+        ));
+    }
+    box_line(&format!(
+        "mean {:.1} characters",
+        counts.chars as f64 / counts.passwords as f64
+    ));
+    box_bottom();
+
+    box_top("Uniformity");
+    for line in verdict(counts) {
+        box_line(&line);
+    }
+    box_bottom();
+    println!();
+}
+
+/// Chi-square of the character counts against an even spread over the
+/// characters seen.
+fn verdict(counts: &Counts) -> Vec<String> {
+    let k = counts.freq.len();
+    let expected = counts.chars as f64 / k as f64;
+    if k < 2 {
+        return vec!["only one character seen, nothing to test".to_string()];
+    }
+    if expected < MIN_EXPECTED {
+        return vec![format!(
+            "too few characters for a verdict ({:.1} per character, {} needed)",
+            expected, MIN_EXPECTED
+        )];
+    }
+
+    let chi2: f64 = counts
+        .freq
+        .values()
+        .map(|&n| (n as f64 - expected).powi(2) / expected)
+        .sum();
+    let df = (k - 1) as f64;
+    let p = chi2_upper_tail(chi2, df);
+    let verdict = if p < ALPHA {
+        format!("non-uniform: p is under {}", ALPHA)
+    } else {
+        "consistent with uniform".to_string()
+    };
+    vec![
+        format!(
+            "chi-square {:.1} on {} degrees of freedom, p = {:.4}",
+            chi2,
+            k - 1,
+            p
+        ),
+        verdict,
+    ]
+}
+
+/// P(X >= chi2) for X chi-square with `df` degrees of freedom, by the
+/// Wilson-Hilferty cube-root normal approximation.
+pub(super) fn chi2_upper_tail(chi2: f64, df: f64) -> f64 {
+    let v = 2.0 / (9.0 * df);
+    let z = ((chi2 / df).cbrt() - (1.0 - v)) / v.sqrt();
+    0.5 * erfc(z / std::f64::consts::SQRT_2)
+}
+
+/// Complementary error function (Abramowitz and Stegun 7.1.26, error
+/// under 1.5e-7).
+fn erfc(x: f64) -> f64 {
+    if x < 0.0 {
+        return 2.0 - erfc(-x);
+    }
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    poly * (-x * x).exp()
+}
@@ -0,0 +1,119 @@
+//! Secrets kept for helper modes (`vault-pass`, `git-credential`): one
+//! 0600 file per entry under `$XDG_DATA_HOME/randpass` (else
+//! `~/.local/share/randpass`), in 0700 directories. Nothing is stored in
+//! ephemeral mode.
+
+use std::fs::{DirBuilder, OpenOptions};
+use std::io::{Read, Write};
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+
+use zeroize::Zeroize;
+
+use crate::settings::ephemeral;
+
+/// `$XDG_DATA_HOME/randpass/<kind>`, else `~/.local/share/randpass/<kind>`.
+pub fn dir(kind: &str) -> PathBuf {
+    let data = std::env::var("XDG_DATA_HOME")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| {
+            let home = std::env::var("HOME").unwrap_or_else(|_| ".".into());
+            format!("{}/.local/share", home)
+        });
+    PathBuf::from(data).join("randpass").join(kind)
+}
+
+/// File name for `key`: bytes outside `[A-Za-z0-9_.-]`, and a leading
+/// dot, become `%XX`, so any key maps to one plain name.
+pub fn file_name(key: &str) -> String {
+    let mut name = String::with_capacity(key.len());
+    for (i, b) in key.bytes().enumerate() {
+        if b.is_ascii_alphanumeric() || b == b'_' || b == b'-' || (b == b'.' && i > 0) {
+            name.push(b as char);
+        } else {
+            name.push_str(&format!("%{:02X}", b));
+        }
+    }
+    name
+}
+
+/// Contents of the entry at `path`, or None if there is none. Caller
+/// zeroizes.
+pub fn read(path: &Path) -> Result<Option<Vec<u8>>, String> {
+    let mut file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("{}: {}", path.display(), e)),
+    };
+    let len = file.metadata().map(|m| m.len() as usize).unwrap_or(0);
+    // Sized up front so the secret is never copied by a reallocation
+    let mut data = Vec::with_capacity(len + 1);
+    if let Err(e) = file.read_to_end(&mut data) {
+        data.zeroize();
+        return Err(format!("{}: {}", path.display(), e));
+    }
+    Ok(Some(data))
+}
+
+/// Store `data` at `path`, replacing any entry atomically (temp file,
+/// sync, rename) unless `create_new`, where an existing entry is an
+/// error.
+pub fn write(path: &Path, data: &[u8], create_new: bool) -> Result<(), String> {
+    if ephemeral::enabled() {
+        return Err(
+            "ephemeral mode (--no-save / RANDPASS_EPHEMERAL=1), nothing is stored".to_string(),
+        );
+    }
+    if let Some(parent) = path.parent() {
+        DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(parent)
+            .map_err(|e| format!("{}: {}", parent.display(), e))?;
+    }
+
+    let target = if create_new {
+        path.to_path_buf()
+    } else {
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(format!(".tmp.{}", std::process::id()));
+        PathBuf::from(tmp)
+    };
+    let mut options = OpenOptions::new();
+    options.write(true).mode(0o600);
+    if create_new {
+        options.create_new(true);
+    } else {
+        options.create(true).truncate(true);
+    }
+    let result = options.open(&target).and_then(|mut file| {
+        file.write_all(data)?;
+        file.sync_all()?;
+        if create_new {
+            Ok(())
+        } else {
+            std::fs::rename(&target, path)
+        }
+    });
+    result.map_err(|e| {
+        // A partial entry is worse than none
+        if !(create_new && e.kind() == std::io::ErrorKind::AlreadyExists) {
+            let _ = std::fs::remove_file(&target);
+        }
+        format!("{}: {}", path.display(), e)
+    })
+}
+
+/// Remove the entry at `path`; a missing entry is not an error.
+pub fn remove(path: &Path) -> Result<(), String> {
+    if ephemeral::enabled() {
+        return Ok(());
+    }
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("{}: {}", path.display(), e))
+        }
+        _ => Ok(()),
+    }
+}
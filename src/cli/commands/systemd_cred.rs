@@ -0,0 +1,184 @@
+//! `randpass systemd-cred <NAME>`: provision a generated secret as a
+//! systemd service credential. By default the secret is piped through
+//! `systemd-creds encrypt` into `/etc/credstore.encrypted/NAME`, where
+//! `LoadCredentialEncrypted=NAME` finds it; `--plain` writes it as a 0600
+//! file under `/etc/credstore` for `LoadCredential=NAME` instead.
+
+use std::fs::{DirBuilder, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use zeroize::Zeroize;
+
+use super::{deliver_secret, generate_secret, number, value};
+
+const ENCRYPTED_DIR: &str = "/etc/credstore.encrypted";
+const PLAIN_DIR: &str = "/etc/credstore";
+
+const USAGE: &str = "Usage: randpass systemd-cred <NAME> [-l N] [--plain] [--dir DIR] \
+                     [--with-key KEY] [--show | -b]";
+
+struct Options {
+    name: String,
+    length: usize,
+    plain: bool,
+    dir: Option<String>,
+    with_key: Option<String>,
+    show: bool,
+    clipboard: bool,
+}
+
+fn parse(args: &[String]) -> Result<Options, String> {
+    let mut name = None;
+    let mut opts = Options {
+        name: String::new(),
+        length: 32,
+        plain: false,
+        dir: None,
+        with_key: None,
+        show: false,
+        clipboard: false,
+    };
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "-l" | "--length" => opts.length = number(args, &mut i)?,
+            "--plain" => opts.plain = true,
+            "--dir" => opts.dir = Some(value(args, &mut i)?.to_string()),
+            "--with-key" => opts.with_key = Some(value(args, &mut i)?.to_string()),
+            "--show" => opts.show = true,
+            "-b" | "--board" => opts.clipboard = true,
+            arg if arg.starts_with('-') => return Err(format!("Unknown argument: {}", arg)),
+            arg if name.is_none() => name = Some(arg.to_string()),
+            arg => return Err(format!("Unexpected argument: {}", arg)),
+        }
+        i += 1;
+    }
+
+    opts.name = name.ok_or(USAGE)?;
+    // systemd refuses names that could escape the credential directory
+    if matches!(opts.name.as_str(), "." | "..") || opts.name.contains('/') {
+        return Err(format!("Invalid credential name: {}", opts.name));
+    }
+    if opts.length == 0 {
+        return Err("Secret length must be at least 1".to_string());
+    }
+    if opts.plain && opts.with_key.is_some() {
+        return Err("--with-key only applies to encrypted credentials".to_string());
+    }
+    Ok(opts)
+}
+
+/// Generate a secret and store it as credential `NAME`. The secret only
+/// ever travels over a pipe or into the 0600 file, never argv.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let opts = parse(args)?;
+
+    let default_dir = if opts.plain { PLAIN_DIR } else { ENCRYPTED_DIR };
+    let dir = PathBuf::from(opts.dir.as_deref().unwrap_or(default_dir));
+    let path = dir.join(&opts.name);
+    if path.exists() {
+        return Err(format!(
+            "{} already exists, refusing to overwrite",
+            path.display()
+        ));
+    }
+    DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(&dir)
+        .map_err(|e| format!("{}: {}", dir.display(), e))?;
+
+    let mut secret = generate_secret(opts.length);
+    let stored = if opts.plain {
+        write_plain(&path, &secret)
+    } else {
+        encrypt(&opts, &path, &secret)
+    };
+    if let Err(e) = stored {
+        secret.zeroize();
+        return Err(e);
+    }
+
+    if opts.show || opts.clipboard {
+        deliver_secret(secret, "Secret", opts.clipboard);
+    } else {
+        secret.zeroize();
+    }
+    let directive = if opts.plain {
+        "LoadCredential"
+    } else {
+        "LoadCredentialEncrypted"
+    };
+    // Units need an absolute path outside the credstore
+    let path = std::fs::canonicalize(&path).unwrap_or(path);
+    eprintln!("Credential: {}", path.display());
+    if opts.dir.is_none() {
+        eprintln!("In the unit: {}={}", directive, opts.name);
+    } else {
+        eprintln!(
+            "In the unit: {}={}:{}",
+            directive,
+            opts.name,
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Write the secret to a new 0600 file and sync it.
+fn write_plain(path: &Path, secret: &[u8]) -> Result<(), String> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    file.write_all(secret)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| {
+            // A partial credential is worse than none
+            let _ = std::fs::remove_file(path);
+            format!("{}: {}", path.display(), e)
+        })
+}
+
+/// Pipe the secret through `systemd-creds encrypt` into `path`.
+fn encrypt(opts: &Options, path: &Path, secret: &[u8]) -> Result<(), String> {
+    let mut cmd = Command::new("systemd-creds");
+    cmd.arg("encrypt").arg(format!("--name={}", opts.name));
+    if let Some(ref key) = opts.with_key {
+        cmd.arg(format!("--with-key={}", key));
+    }
+    cmd.arg("-").arg(path);
+
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to run systemd-creds: {} (--plain skips it)", e))?;
+
+    let written = child
+        .stdin
+        .take()
+        .map(|mut stdin| stdin.write_all(secret))
+        .unwrap_or(Ok(()));
+
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if written.is_err() || !output.status.success() {
+        let _ = std::fs::remove_file(path);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "systemd-creds failed ({}): {}",
+            output.status,
+            stderr.trim()
+        ));
+    }
+    // systemd-creds leaves the umask's mode; the blob needs no readers
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
@@ -0,0 +1,93 @@
+//! `randpass username`: random, non-identifying usernames, optionally
+//! each with a password as `user<TAB>pass` lines for provisioning.
+
+use std::io::Write;
+
+use zeroize::Zeroize;
+
+use super::{generate_secret, number, value};
+use crate::cli::prompts;
+use crate::pass::username::{self, ALNUM_LEN, Style};
+use crate::settings::Settings;
+
+struct Options {
+    style: Style,
+    max_len: Option<usize>,
+    number: usize,
+    with_pass: bool,
+    length: usize,
+}
+
+fn parse(args: &[String]) -> Result<Options, String> {
+    let mut opts = Options {
+        style: Style::AdjectiveNoun,
+        max_len: None,
+        number: 1,
+        with_pass: false,
+        length: Settings::default().pass_length,
+    };
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--style" => {
+                let v = value(args, &mut i)?;
+                opts.style = Style::parse(v)
+                    .ok_or_else(|| format!("Invalid value: {} (expected {})", v, Style::NAMES))?;
+            }
+            "--max-len" => opts.max_len = Some(number(args, &mut i)?),
+            "-n" | "--number" => opts.number = number(args, &mut i)?,
+            "--with-pass" => opts.with_pass = true,
+            "-l" | "--length" => opts.length = number(args, &mut i)?,
+            "-V" | "--verbose" => crate::cli::verbose::set(crate::cli::verbose::level() + 1),
+            arg => return Err(format!("Unknown argument: {}", arg)),
+        }
+        i += 1;
+    }
+
+    if let Some(max) = opts.max_len
+        && max < opts.style.min_len()
+    {
+        return Err(format!(
+            "--max-len {} is too short for this style (at least {})",
+            max,
+            opts.style.min_len()
+        ));
+    }
+    if opts.length == 0 {
+        return Err("Password length must be at least 1".to_string());
+    }
+    Ok(opts)
+}
+
+/// Print `-n` usernames, one per line, each followed by a tab and a fresh
+/// password with `--with-pass`.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let opts = parse(args)?;
+    let max_len = match opts.style {
+        Style::Alnum => opts.max_len.unwrap_or(ALNUM_LEN),
+        _ => opts.max_len.unwrap_or(usize::MAX),
+    };
+    prompts::verbose(&format!(
+        "username: {:.1} bits each before the length limit",
+        opts.style.bits(max_len)
+    ));
+
+    let mut out = std::io::stdout().lock();
+    for _ in 0..opts.number {
+        let name = username::generate(opts.style, max_len);
+        let mut line = name.into_bytes();
+        if opts.with_pass {
+            line.push(b'\t');
+            let mut secret = generate_secret(opts.length);
+            line.extend_from_slice(&secret);
+            secret.zeroize();
+        }
+        line.push(b'\n');
+        let written = out.write_all(&line);
+        line.zeroize();
+        written.map_err(|e| format!("stdout: {}", e))?;
+    }
+    crate::rand::shutdown_urandom();
+    Ok(())
+}
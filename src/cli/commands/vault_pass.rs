@@ -0,0 +1,90 @@
+//! `randpass vault-pass`: a password script for `ansible-vault`. Prints
+//! the password for a vault id (stored on first use) as one line on
+//! stdout, which is all `--vault-password-file` reads. Ansible calls
+//! scripts named `*-client` with `--vault-id ID`, which is accepted here,
+//! so a one-line wrapper serves every id.
+
+use std::io::Write;
+
+use zeroize::Zeroize;
+
+use super::{generate_secret, number, store, value};
+
+const DEFAULT_ID: &str = "default";
+
+struct Options {
+    id: String,
+    length: usize,
+    no_store: bool,
+}
+
+fn parse(args: &[String]) -> Result<Options, String> {
+    let mut opts = Options {
+        id: DEFAULT_ID.to_string(),
+        length: 32,
+        no_store: false,
+    };
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--vault-id" => opts.id = value(args, &mut i)?.to_string(),
+            "-l" | "--length" => opts.length = number(args, &mut i)?,
+            "--no-store" => opts.no_store = true,
+            arg => return Err(format!("Unknown argument: {}", arg)),
+        }
+        i += 1;
+    }
+
+    if opts.id.is_empty() {
+        return Err("Vault id must not be empty".to_string());
+    }
+    if opts.length == 0 {
+        return Err("Password length must be at least 1".to_string());
+    }
+    Ok(opts)
+}
+
+/// Print the stored password for the vault id, generating and storing
+/// one first if there is none. `--no-store` prints a fresh password and
+/// keeps nothing.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let opts = parse(args)?;
+    let path = store::dir("vault").join(store::file_name(&opts.id));
+
+    let mut password = if opts.no_store {
+        generate_secret(opts.length)
+    } else if let Some(stored) = store::read(&path)? {
+        stored
+    } else {
+        let mut secret = generate_secret(opts.length);
+        if let Err(e) = store::write(&path, &secret, true) {
+            secret.zeroize();
+            // Printing a password nobody can get back would lock the vault
+            return Err(format!(
+                "no password stored for vault id {}: {}",
+                opts.id, e
+            ));
+        }
+        eprintln!(
+            "Stored a new password for vault id {} in {}",
+            opts.id,
+            path.display()
+        );
+        secret
+    };
+
+    // ansible strips trailing whitespace, so the newline is harmless
+    let len = password
+        .iter()
+        .rposition(|&b| b != b'\n')
+        .map_or(0, |p| p + 1);
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    let written = out
+        .write_all(&password[..len])
+        .and_then(|_| out.write_all(b"\n"))
+        .and_then(|_| out.flush());
+    password.zeroize();
+    written.map_err(|e| e.to_string())
+}
@@ -0,0 +1,187 @@
+//! `randpass wipe`: overwrite a block device with RNG output.
+
+use std::fs::{self, OpenOptions};
+use std::io::{Seek, SeekFrom};
+use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+
+use super::super::bytes::{ByteOptions, output, parse_byte_count};
+use super::super::prompts;
+use super::value;
+use crate::pass::fsync::Fsync;
+use crate::terminal::format_bytes;
+
+struct Options {
+    device: String,
+    block_size: usize,
+    direct: bool,
+}
+
+fn parse(args: &[String]) -> Result<Options, String> {
+    let mut device = None;
+    let mut block_size = 1024 * 1024;
+    let mut direct = false;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--bs" => {
+                let v = value(args, &mut i)?;
+                block_size = parse_byte_count(v)
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| format!("Invalid size: {}", v))?;
+            }
+            "--direct" => direct = true,
+            arg if arg.starts_with('-') => return Err(format!("Unknown argument: {}", arg)),
+            arg if device.is_none() => device = Some(arg.to_string()),
+            arg => return Err(format!("Unexpected argument: {}", arg)),
+        }
+        i += 1;
+    }
+
+    let device = device.ok_or("Usage: randpass wipe <DEVICE> [--bs SIZE] [--direct]")?;
+    Ok(Options {
+        device,
+        block_size,
+        direct,
+    })
+}
+
+/// Kernel names of the device and its partitions (`sda`, `sda1`, ...).
+fn device_names(dev: &Path) -> Vec<String> {
+    let Some(name) = dev.file_name().and_then(|n| n.to_str()) else {
+        return Vec::new();
+    };
+    let mut names = vec![name.to_string()];
+    if let Ok(entries) = fs::read_dir(format!("/sys/class/block/{}", name)) {
+        for entry in entries.flatten() {
+            if entry.path().join("partition").exists()
+                && let Some(part) = entry.file_name().to_str()
+            {
+                names.push(part.to_string());
+            }
+        }
+    }
+    names
+}
+
+/// Devices stacked on `names` (LVM and dm-crypt mappings, md arrays),
+/// following `holders/` all the way up, as (holder, held) pairs.
+fn holders(names: &[String]) -> Vec<(String, String)> {
+    let mut found: Vec<(String, String)> = Vec::new();
+    let mut queue: Vec<String> = names.to_vec();
+    while let Some(name) = queue.pop() {
+        let Ok(entries) = fs::read_dir(format!("/sys/class/block/{}/holders", name)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Some(holder) = entry.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            if !found.iter().any(|(h, _)| *h == holder) {
+                queue.push(holder.clone());
+                found.push((holder, name.clone()));
+            }
+        }
+    }
+    found
+}
+
+/// A holder as the user knows it: `dm-0 (vg-root)`, or just `md0`.
+fn holder_label(holder: &str) -> String {
+    match fs::read_to_string(format!("/sys/class/block/{}/dm/name", holder)) {
+        Ok(name) => format!("{} ({})", holder, name.trim()),
+        Err(_) => holder.to_string(),
+    }
+}
+
+/// Return the first mount point, swap area or stacked device using the
+/// device, one of its partitions, or anything built on them.
+fn find_in_use(dev: &Path) -> Option<String> {
+    let mut names = device_names(dev);
+    let stacked = holders(&names);
+    names.extend(stacked.iter().map(|(h, _)| h.clone()));
+    let uses = |source: &str| {
+        fs::canonicalize(source)
+            .ok()
+            .and_then(|p| p.file_name().and_then(|n| n.to_str()).map(str::to_string))
+            .is_some_and(|n| names.contains(&n))
+    };
+
+    let mounts = fs::read_to_string("/proc/self/mounts").unwrap_or_default();
+    for line in mounts.lines() {
+        let mut fields = line.split_whitespace();
+        if let (Some(source), Some(target)) = (fields.next(), fields.next())
+            && uses(source)
+        {
+            return Some(format!("{} is mounted on {}", source, target));
+        }
+    }
+
+    let swaps = fs::read_to_string("/proc/swaps").unwrap_or_default();
+    for line in swaps.lines().skip(1) {
+        if let Some(source) = line.split_whitespace().next()
+            && uses(source)
+        {
+            return Some(format!("{} is in use as swap", source));
+        }
+    }
+
+    // Active even when nothing on top is mounted: an open LUKS mapping or
+    // a running array would be corrupted under it
+    stacked
+        .first()
+        .map(|(holder, held)| format!("/dev/{} is held by {}", held, holder_label(holder)))
+}
+
+/// Fill a block device with random bytes after the user types its name
+/// back. Refuses devices that are mounted, used as swap or held by a
+/// mapping or array, and syncs before reporting success.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let opts = parse(args)?;
+    let dev: PathBuf =
+        fs::canonicalize(&opts.device).map_err(|e| format!("{}: {}", opts.device, e))?;
+
+    let meta = fs::metadata(&dev).map_err(|e| format!("{}: {}", opts.device, e))?;
+    if !meta.file_type().is_block_device() {
+        return Err(format!("{} is not a block device", opts.device));
+    }
+    if let Some(reason) = find_in_use(&dev) {
+        return Err(format!("{}, refusing to wipe", reason));
+    }
+
+    // O_EXCL on a block device fails with EBUSY while the kernel has it
+    // claimed, which also covers users the checks above can't see
+    let size = OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_EXCL)
+        .open(&dev)
+        .and_then(|mut f| f.seek(SeekFrom::End(0)))
+        .map_err(|e| match e.raw_os_error() {
+            Some(libc::EBUSY) => format!("{} is in use, refusing to wipe", opts.device),
+            _ => format!("{}: {}", opts.device, e),
+        })? as usize;
+    if size == 0 {
+        return Err(format!("{} reports a size of 0", opts.device));
+    }
+
+    if !prompts::wipe_confirm(&opts.device, &format_bytes(size)) {
+        return Err("Confirmation did not match, nothing written".to_string());
+    }
+
+    let dev_path = dev.to_string_lossy();
+    let byte_opts = ByteOptions {
+        limit: Some(size),
+        file_path: Some(&dev_path),
+        block_size: Some(opts.block_size),
+        direct: opts.direct,
+        fsync: Fsync::End,
+        device: true,
+        ..Default::default()
+    };
+    byte_opts.validate()?;
+
+    let written = output(&byte_opts).map_err(|e| format!("{}: {}", opts.device, e))?;
+    prompts::wipe_done(&opts.device, &format_bytes(written));
+    Ok(())
+}
@@ -0,0 +1,334 @@
+//! `randpass wordlist`: show the wordlists passphrase mode can use, and
+//! install, remove or verify custom lists in the cache directory.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+
+use super::value;
+use crate::pass::{wordlist, words};
+use crate::settings::ephemeral;
+
+/// Largest list `add` accepts.
+const MAX_SIZE: usize = 16 * 1024 * 1024;
+
+const USAGE: &str = "Usage: randpass wordlist list | add <NAME> <PATH|URL> [--sha256 HEX] | remove <NAME> | verify [NAME] | check <PATH|URL>";
+
+/// Examples shown per finding in `check`.
+const EXAMPLES: usize = 3;
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let rest = args.get(1..).unwrap_or_default();
+    match args.first().map(String::as_str) {
+        Some("list") if rest.is_empty() => {
+            list();
+            Ok(())
+        }
+        Some("add") => add(rest),
+        Some("remove") => match rest {
+            [name] => remove(name),
+            _ => Err(USAGE.to_string()),
+        },
+        Some("verify") => match rest {
+            [] => verify_all(),
+            [name] => verify(name).map(|words| println!("{}: OK ({} words)", name, words)),
+            _ => Err(USAGE.to_string()),
+        },
+        Some("check") => match rest {
+            [source] => check(source),
+            _ => Err(USAGE.to_string()),
+        },
+        Some("list") => Err(format!("Unknown argument: {}", rest[0])),
+        Some(other) => Err(format!("Unknown wordlist command: {}", other)),
+        None => Err(USAGE.to_string()),
+    }
+}
+
+fn list() {
+    for info in words::available() {
+        let default = if info.name == words::DEFAULT {
+            " (default)"
+        } else {
+            ""
+        };
+        println!(
+            "{:<18} {:<3} {:>5} words  {:>4.1} bits/word  {}{}",
+            info.name,
+            info.lang,
+            info.words,
+            info.bits_per_word(),
+            info.description,
+            default
+        );
+    }
+    for name in wordlist::installed() {
+        println!(
+            "{:<18} {:<3} {}",
+            name,
+            "-",
+            wordlist::installed_path(&name).display()
+        );
+    }
+}
+
+/// Fetch a list, check it, and install it with its SHA-256 recorded.
+fn add(args: &[String]) -> Result<(), String> {
+    let mut positional = Vec::new();
+    let mut expected = None;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--sha256" => expected = Some(value(args, &mut i)?.to_ascii_lowercase()),
+            arg if arg.starts_with('-') => return Err(format!("Unknown argument: {}", arg)),
+            arg => positional.push(arg),
+        }
+        i += 1;
+    }
+    let [name, source] = positional[..] else {
+        return Err(USAGE.to_string());
+    };
+
+    check_name(name)?;
+    if ephemeral::enabled() {
+        return Err("ephemeral mode (--no-save / RANDPASS_EPHEMERAL=1), nothing installed".into());
+    }
+    if wordlist::installed_path(name).exists() {
+        return Err(format!(
+            "{} is already installed (randpass wordlist remove {} first)",
+            name, name
+        ));
+    }
+
+    let data = fetch(source)?;
+    let digest = wordlist::sha256_hex(&data);
+    if let Some(expected) = expected
+        && expected != digest
+    {
+        return Err(format!(
+            "SHA-256 mismatch for {}: expected {}, got {}",
+            source, expected, digest
+        ));
+    }
+    let list = wordlist::check(name, &data).map_err(|e| format!("{}: {}", source, e))?;
+
+    let dir = wordlist::installed_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    write_atomic(&wordlist::installed_path(name), &data)?;
+    let record = format!("{}  {}.txt\n", digest, name);
+    if let Err(e) = write_atomic(&wordlist::digest_path(name), record.as_bytes()) {
+        let _ = std::fs::remove_file(wordlist::installed_path(name));
+        return Err(e);
+    }
+
+    println!(
+        "Installed {}: {} words, {:.1} bits/word",
+        name,
+        list.len(),
+        list.bits_per_word()
+    );
+    println!("sha256 {}", digest);
+    Ok(())
+}
+
+/// Screen a list before adopting it and show what each weakness costs
+/// per word.
+fn check(source: &str) -> Result<(), String> {
+    let data = fetch(source)?;
+    let found = wordlist::overlap(&data).map_err(|e| format!("{}: {}", source, e))?;
+    let words = &found.words;
+    let examples = |pairs: &[(usize, usize)], sep: &str| {
+        pairs
+            .iter()
+            .take(EXAMPLES)
+            .map(|&(a, b)| format!("{}{}{}", words[a], sep, words[b]))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    println!(
+        "{}: {} unique words, {:.2} bits/word",
+        source,
+        words.len(),
+        found.bits()
+    );
+    if found.duplicates > 0 {
+        println!(
+            "duplicates      {} (dropped when the list is used)",
+            found.duplicates
+        );
+    }
+    if !found.near.is_empty() {
+        println!(
+            "near-duplicates {} pairs one edit apart: {}",
+            found.near.len(),
+            examples(&found.near, "/")
+        );
+    }
+    if !found.embedded.is_empty() {
+        println!(
+            "embedded        {} words inside others, {} at the start: {}",
+            found.embedded.len(),
+            found.prefixes,
+            examples(&found.embedded, " in ")
+        );
+    }
+    if !found.short.is_empty() {
+        let short: Vec<&str> = found
+            .short
+            .iter()
+            .take(EXAMPLES)
+            .map(|&i| words[i].as_str())
+            .collect();
+        println!(
+            "short           {} under {} characters: {}",
+            found.short.len(),
+            wordlist::SHORT_WORD,
+            short.join(", ")
+        );
+    }
+
+    println!();
+    println!("{:>6.2} bits/word as listed", found.bits());
+    println!(
+        "{:>6.2} bits/word if near-duplicates are confused ({} groups)",
+        found.distinct_bits(),
+        found.groups
+    );
+    println!(
+        "{:>6.2} bits/word joined without a separator (words that start no other)",
+        found.joined_bits()
+    );
+    println!(
+        "{:>6.2} bits for the shortest word guessed letter by letter",
+        found.shortest_bits()
+    );
+    let effective = found
+        .bits()
+        .min(found.distinct_bits())
+        .min(found.shortest_bits());
+    println!(
+        "Effective: {:.2} bits/word with a separator, {:.2} without",
+        effective,
+        effective.min(found.joined_bits())
+    );
+    if words.len() < wordlist::MIN_WORDS {
+        crate::cli::prompts::warn(&format!(
+            "Wordlist: only {} words, add words to reach {}+",
+            words.len(),
+            wordlist::MIN_WORDS
+        ));
+    }
+    Ok(())
+}
+
+fn remove(name: &str) -> Result<(), String> {
+    check_name(name)?;
+    if ephemeral::enabled() {
+        return Err("ephemeral mode (--no-save / RANDPASS_EPHEMERAL=1), nothing removed".into());
+    }
+    let path = wordlist::installed_path(name);
+    std::fs::remove_file(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let _ = std::fs::remove_file(wordlist::digest_path(name));
+    println!("Removed {}", name);
+    Ok(())
+}
+
+/// Check an installed list against its recorded digest. Returns its
+/// word count.
+fn verify(name: &str) -> Result<usize, String> {
+    check_name(name)?;
+    let path = wordlist::installed_path(name);
+    let data = std::fs::read(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    wordlist::verify_digest(name, &data).map_err(|e| format!("{}: {}", name, e))?;
+    let list = wordlist::check(name, &data).map_err(|e| format!("{}: {}", name, e))?;
+    Ok(list.len())
+}
+
+fn verify_all() -> Result<(), String> {
+    let names = wordlist::installed();
+    if names.is_empty() {
+        println!("No installed wordlists");
+        return Ok(());
+    }
+    let mut failed = 0;
+    for name in &names {
+        match verify(name) {
+            Ok(words) => println!("{}: OK ({} words)", name, words),
+            Err(e) => {
+                println!("{}: FAILED", name);
+                crate::cli::prompts::error(&format!("Error: {}", e));
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(format!(
+            "{} of {} lists failed verification",
+            failed,
+            names.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Names become file names and must not shadow an embedded list.
+fn check_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"._-".contains(&b));
+    if !valid {
+        return Err(format!(
+            "Invalid name '{}' (letters, digits, '.', '_' and '-')",
+            name
+        ));
+    }
+    if words::get_info(name).is_some() {
+        return Err(format!("{} is an embedded list", name));
+    }
+    Ok(())
+}
+
+/// Read a local file, or download an https:// URL with curl.
+fn fetch(source: &str) -> Result<Vec<u8>, String> {
+    let data = if source.starts_with("https://") {
+        let output = Command::new("curl")
+            .args(["-fsSL", "--proto", "=https", "--max-filesize"])
+            .arg(MAX_SIZE.to_string())
+            .arg(source)
+            .output()
+            .map_err(|e| format!("curl: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "download failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        output.stdout
+    } else if source.contains("://") {
+        return Err(format!("{}: only https:// URLs are supported", source));
+    } else {
+        std::fs::read(source).map_err(|e| format!("{}: {}", source, e))?
+    };
+    if data.len() > MAX_SIZE {
+        return Err(format!("{}: larger than {} bytes", source, MAX_SIZE));
+    }
+    Ok(data)
+}
+
+/// Write through a temp file and rename, so a file is never half-written.
+pub(super) fn write_atomic(path: &Path, data: &[u8]) -> Result<(), String> {
+    let tmp = path.with_extension(format!("tmp.{}", std::process::id()));
+    let result = (|| {
+        let mut file = OpenOptions::new().write(true).create_new(true).open(&tmp)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp, path)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    result.map_err(|e| format!("{}: {}", path.display(), e))
+}
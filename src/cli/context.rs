@@ -1,53 +1,109 @@
 //! CLI context - bundles settings, flags, and clipboard state.
 
+use std::io::{ErrorKind, IsTerminal, Write};
+use std::time::Instant;
+
 use copypasta::{ClipboardContext, ClipboardProvider};
 use zeroize::Zeroize;
 
-use super::{CliFlags, CommandMode, output_bytes, parse_byte_count, prompts, quiet};
+use super::batch;
+use super::board;
+use super::encode::{Encoding, encode_into};
+use super::format;
+use super::license;
+use super::mnemonic;
+use super::pick;
+use super::stretch;
+use super::template::{self, Template};
+use super::{
+    ByteOptions, CliFlags, CommandMode, DEFAULT_BLOCK, output_bytes, output_key_material,
+    parse_byte_count, profile, prompts, quiet, verbose,
+};
 use crate::pass;
+use crate::pass::chain;
+use crate::pass::charset;
+use crate::pass::hooks::Hook;
+use crate::pass::pad::{self, Fill, Pad};
+use crate::pass::passphrase::{self, Caps, Passphrase, Source, WordSep};
+use crate::pass::pattern::{self, Pattern};
+use crate::pass::policy;
+use crate::pass::{acronym, memorable, pronounceable, pseudo, wordlist};
 use crate::rand;
-use crate::settings::Settings;
+use crate::session::{self, RawUse};
+use crate::settings::{self, Settings};
+use crate::terminal::notify::{self, Outcome};
+use crate::terminal::{format_bytes, format_number};
 use crate::tui::print_help;
 
+/// Shortest `--for-each` name `--forbid` also screens for.
+const MIN_SCREENED_NAME: usize = 3;
+
 /// Early exit - not an error, just done.
 pub struct Done;
 
 /// Application context for CLI mode.
 pub struct Context {
     pub settings: Settings,
-    pub saved_settings: Settings,
     pub clipboard: Option<ClipboardContext>,
     pub flags: CliFlags,
     args: Vec<String>,
+    /// When `new` started, for `-VV` timings
+    started: Instant,
+    /// Raw entropy drawn before the run, for `--meta json`
+    raw_start: [RawUse; 3],
 }
 
 impl Context {
     /// Create a new context by parsing command-line arguments.
     /// Returns Err with the error message if parsing fails.
     pub fn new(args: Vec<String>) -> Result<Self, String> {
-        let flags = super::parse(&args).map_err(|e| e.to_string())?;
+        let started = Instant::now();
+        let mut flags = super::parse(&args).map_err(|e| e.to_string())?;
+        // Early, so the config layers below are reported
+        verbose::set(flags.verbose);
+        if let Some(name) = flags.profile.clone() {
+            flags = profile::merge(&name, &args)?;
+            prompts::verbose(&format!(
+                "Config: profile {} merged under the command-line flags",
+                name
+            ));
+        }
 
+        let path = settings::file_path();
+        if std::path::Path::new(&path).is_file() {
+            prompts::verbose(&format!("Config: settings file {}", path));
+        } else {
+            prompts::verbose(&format!("Config: no settings file at {}, defaults", path));
+        }
+        if settings::ephemeral::enabled() {
+            prompts::verbose("Config: ephemeral mode, nothing under ~/.config/randpass is written");
+        }
         let saved_settings = Settings::load_from_file().unwrap_or_else(|e| {
             prompts::warn(&format!("Failed to load settings: {}", e));
             Settings::default()
         });
+        if flags.saved {
+            prompts::verbose("Config: --saved, every saved setting applies");
+        } else {
+            prompts::verbose(&format!(
+                "Config: CLI defaults; saved {} apply",
+                settings::CLI_FIELDS.join(", ")
+            ));
+        }
 
         let settings = if flags.saved {
             saved_settings.clone()
         } else {
-            Settings {
-                cli_command: saved_settings.cli_command.clone(),
-                number_of_passwords: 1, // CLI default, not interactive default (19)
-                ..Default::default()
-            }
+            saved_settings.for_cli()
         };
 
         Ok(Self {
             settings,
-            saved_settings,
             clipboard: None,
             flags,
             args,
+            started,
+            raw_start: session::raw_use(),
         })
     }
 
@@ -57,12 +113,217 @@ impl Context {
         self.handle_command_mode()?;
         self.apply_flags();
         quiet::set(self.flags.quiet);
-        self.handle_urandom();
+        verbose::set(self.flags.verbose);
+        self.apply_passphrase();
+        self.apply_pattern();
+        self.apply_acronym();
+        self.apply_pad();
+        self.apply_policy();
+        self.apply_start_with();
+        self.report_settings();
+        self.handle_entropy();
+        let setup = self.started.elapsed();
+        // After the entropy source is chosen, the trial draws from it
+        let trial = self.trial_policy();
+        let trial_time = self.started.elapsed() - setup;
+        if self.flags.dry_run {
+            self.dry_run(trial.as_ref());
+            return Err(Done);
+        }
+        self.handle_key_material()?;
+        self.handle_license_key()?;
         self.handle_bytes()?;
-        self.generate_output();
+        self.check_entropy_floor(trial.as_ref());
+        self.warn_weak(trial.as_ref());
+        let bits = pass::entropy(&self.settings, trial.as_ref());
+        let count = match self.flags.pick_from {
+            Some(_) => 1,
+            None => self
+                .flags
+                .number
+                .unwrap_or(self.settings.number_of_passwords.max(1)),
+        };
+        if let Err(e) = pass::hooks::run(Hook::PreGenerate, &self.settings, count, bits) {
+            prompts::fail(&format!("Error: {}; nothing generated", e));
+        }
+        // A stream ends only when its reader goes away, which exits
+        if count == 0 {
+            self.audit(count, bits);
+            if self.flags.notify {
+                prompts::warn("Warning: --notify ignored, a stream (-n 0) never finishes");
+            }
+            if self.flags.meta {
+                prompts::warn("Warning: --meta ignored, a stream (-n 0) never finishes");
+            }
+        } else if self.flags.notify {
+            notify::start(format!(
+                "{} password(s){}",
+                format_number(count),
+                destination(Some(self.settings.output_file_path.as_str()))
+            ));
+        }
+        self.generate_output(bits);
+        if count > 0 {
+            self.audit(count, bits);
+            for hook in [Hook::PostFile, Hook::PostGenerate] {
+                if let Err(e) = pass::hooks::run(hook, &self.settings, count, bits) {
+                    prompts::warn(&format!("Warning: {}", e));
+                }
+            }
+            notify::finish(Outcome::Finished);
+            self.meta(&format!("\"count\":{},\"entropy_bits\":{:.1}", count, bits));
+        }
+        self.report_policy();
+        prompts::debug(&format!(
+            "Timing: setup {:.1} ms, policy trial {:.1} ms, generation {:.1} ms",
+            setup.as_secs_f64() * 1000.0,
+            trial_time.as_secs_f64() * 1000.0,
+            (self.started.elapsed() - setup - trial_time).as_secs_f64() * 1000.0
+        ));
         Ok(())
     }
 
+    /// The settings generation will use, after every layer, as key and
+    /// value: the persisted fields, then the runtime ones that are set.
+    /// The chain seed is never printed.
+    fn resolved_settings(&self) -> Vec<(&'static str, String)> {
+        let s = &self.settings;
+        let mut lines: Vec<(&'static str, String)> = settings::FIELDS
+            .iter()
+            .map(|key| (*key, s.get_field(key).unwrap_or_default()))
+            .collect();
+        let runtime = [
+            ("chain_seed", s.chain_seed.map(|_| "(redacted)".to_string())),
+            (
+                "passphrase",
+                s.passphrase.as_ref().map(|p| format!("{} words", p.words)),
+            ),
+            ("policy", s.policy.as_ref().map(|p| p.source.clone())),
+            ("start_with", s.start_with.map(|c| format!("{:?}", c))),
+            ("pattern", s.pattern.as_ref().map(|p| p.source.clone())),
+            ("pad", s.pad.as_ref().map(|p| p.describe())),
+        ];
+        lines.extend(
+            runtime
+                .into_iter()
+                .filter_map(|(key, value)| Some((key, value?))),
+        );
+        let writer = if s.io_uring {
+            "io_uring"
+        } else if s.mmap {
+            "mmap"
+        } else {
+            "write"
+        };
+        lines.push(("writer", writer.to_string()));
+        lines.push(("fsync", s.fsync.to_string()));
+        lines
+    }
+
+    /// `-VV`: the resolved settings on stderr.
+    fn report_settings(&self) {
+        if verbose::level() < 2 {
+            return;
+        }
+        for (key, value) in self.resolved_settings() {
+            prompts::debug(&format!("Settings: {} = {}", key, value));
+        }
+    }
+
+    /// `--dry-run`: what a run would do, from the same resolution as a
+    /// real one, without generating or writing anything.
+    fn dry_run(&self, trial: Option<&policy::Trial>) {
+        if self.flags.bytes
+            || self.flags.salt.is_some()
+            || self.flags.key.is_some()
+            || self.flags.license_key.is_some()
+        {
+            prompts::fail(
+                "Error: --dry-run resolves password runs; --bytes, --salt, --key and --license-key have nothing to show",
+            );
+        }
+        let s = &self.settings;
+        let count = self.flags.number.unwrap_or(s.number_of_passwords.max(1));
+        let mut out = std::io::stdout().lock();
+        let mut lines = self.resolved_settings();
+
+        let mode = if let Some(ref p) = s.pattern {
+            format!("pattern {}", p.source)
+        } else if let Some(ref p) = s.passphrase {
+            format!("passphrase of {} words", p.words)
+        } else if let Some(ref pad) = s.pad {
+            format!("{}-character core, {}", s.pass_length, pad.describe())
+        } else {
+            let table = charset::Table::build(s);
+            format!(
+                "{} characters from a pool of {} ({} distinct)",
+                s.pass_length,
+                charset::size(s),
+                table.len()
+            )
+        };
+        lines.push(("mode", mode));
+        lines.push((
+            "entropy",
+            format!("{:.1} bits per password", pass::entropy(s, trial)),
+        ));
+        lines.push((
+            "crack_time",
+            crate::terminal::crack_times(pass::entropy(s, trial)),
+        ));
+        let floor = self.flags.min_entropy.unwrap_or(s.min_entropy_bits);
+        if floor > 0 {
+            let verdict = if pass::entropy(s, trial) >= floor as f64 {
+                "met"
+            } else if self.flags.allow_weak {
+                "not met, --allow-weak"
+            } else {
+                "not met, the run would be refused"
+            };
+            lines.push(("entropy_floor", format!("{} bits ({})", floor, verdict)));
+        }
+        for reason in pass::weaknesses(s, trial) {
+            lines.push(("caution", reason));
+        }
+        lines.push((
+            "count",
+            if let Some(candidates) = self.flags.pick_from {
+                format!("1, picked from {} candidates", candidates)
+            } else if count == 0 {
+                "unlimited (stream)".to_string()
+            } else {
+                count.to_string()
+            },
+        ));
+
+        let destination = if s.to_clipboard {
+            "clipboard".to_string()
+        } else if !s.output_file_path.is_empty() {
+            let path = std::path::absolute(&s.output_file_path)
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|_| s.output_file_path.clone());
+            match std::fs::metadata(&s.output_file_path) {
+                Ok(meta) => format!("{} (exists, {} bytes; appended to)", path, meta.len()),
+                Err(_) => format!("{} (new file)", path),
+            }
+        } else {
+            "stdout".to_string()
+        };
+        lines.push(("output", destination));
+        let hooks = pass::hooks::configured(s);
+        if !hooks.is_empty() {
+            let names: Vec<&str> = hooks.iter().map(|h| h.name()).collect();
+            lines.push(("hooks", names.join(", ")));
+        }
+
+        for (key, value) in lines {
+            // Stop quietly if the reader went away
+            if writeln!(out, "{} = {}", key, value).is_err() {
+                break;
+            }
+        }
+    }
+
     fn handle_info_flags(&self) -> Result<(), Done> {
         if self.flags.help {
             print_help();
@@ -76,6 +337,11 @@ impl Context {
     }
 
     fn handle_command_mode(&mut self) -> Result<(), Done> {
+        if self.flags.dry_run && self.flags.command != CommandMode::None {
+            prompts::fail(
+                "Error: --dry-run can't be combined with -c, which reads or saves the command",
+            );
+        }
         match self.flags.command {
             CommandMode::Get => {
                 if self.settings.cli_command.is_empty() {
@@ -86,8 +352,10 @@ impl Context {
                 Err(Done)
             }
             CommandMode::Unset => {
-                self.saved_settings.cli_command.clear();
-                if let Err(e) = self.saved_settings.save_to_file() {
+                if let Err(e) = settings::update(|s| {
+                    s.cli_command.clear();
+                    Ok(())
+                }) {
                     prompts::warn(&format!("Failed to clear command: {}", e));
                 }
                 Err(Done)
@@ -96,25 +364,172 @@ impl Context {
         }
     }
 
-    fn handle_urandom(&self) {
-        if self.flags.urandom && !rand::enable_urandom() {
-            prompts::urandom_unavailable();
+    fn handle_entropy(&self) {
+        rand::set_warmup(self.flags.warmup.unwrap_or(self.settings.warmup_discard));
+        // Validated when the settings were loaded
+        let sources = self
+            .flags
+            .rng
+            .clone()
+            .unwrap_or_else(|| rand::failover::parse(&self.settings.rng).unwrap_or_default());
+        let device = self
+            .flags
+            .hwrng_device
+            .as_deref()
+            .unwrap_or(&self.settings.hwrng_device);
+        let read_size = self
+            .flags
+            .hwrng_read
+            .unwrap_or(self.settings.hwrng_read_size);
+        if !sources.contains(&rand::failover::Source::Hwrng)
+            && (self.flags.hwrng_device.is_some() || self.flags.hwrng_read.is_some())
+        {
+            prompts::warn("Warning: --hwrng-device and --hwrng-read need hwrng in --rng");
+        }
+        // Only sources that were listed stand in for a failed one
+        if let Err(e) = rand::failover::select(&sources, device, read_size) {
+            prompts::fail(&format!(
+                "Error: --rng {}: {}",
+                rand::failover::describe(&sources),
+                e
+            ));
+        }
+        // After urandom, so the producer draws from the chosen source
+        if self.flags.prefetch {
+            rand::start_prefetch();
+        }
+        let mut source = if rand::is_hwrng_enabled() {
+            format!("hwrng ({})", rand::hwrng::device())
+        } else {
+            rand::entropy_source().to_string()
+        };
+        if let Some(i) = sources.iter().position(|&s| s == rand::failover::current())
+            && i + 1 < sources.len()
+        {
+            source.push_str(&format!(
+                ", failing over to {}",
+                rand::failover::describe(&sources[i + 1..])
+            ));
         }
+        prompts::verbose(&format!(
+            "RNG: warm-up discards the first {} outputs of each seeded generator",
+            rand::warmup()
+        ));
+        prompts::verbose(&format!(
+            "RNG: {}{}",
+            source,
+            if self.flags.prefetch {
+                ", prefetched by a background thread"
+            } else {
+                ""
+            }
+        ));
     }
 
     fn handle_bytes(&self) -> Result<(), Done> {
         if self.flags.bytes {
-            let limit = self
-                .flags
-                .number_raw
-                .as_ref()
-                .and_then(|s| parse_byte_count(s));
-            output_bytes(limit, self.flags.output.as_deref());
+            // dd-style --count multiplies the block size; otherwise -n
+            let limit = match self.flags.block_count {
+                // parse refuses a product that overflows
+                Some(count) => Some(self.flags.block_size.unwrap_or(DEFAULT_BLOCK) * count),
+                // -n 0 streams without a limit, like no -n at all
+                None => self
+                    .flags
+                    .number_raw
+                    .as_ref()
+                    .and_then(|s| parse_byte_count(s))
+                    .filter(|&n| n > 0),
+            };
+            let opts = ByteOptions {
+                limit,
+                file_path: self.flags.output.as_deref(),
+                encoding: self.flags.encode,
+                stats: self.flags.stats,
+                block_size: self.flags.block_size,
+                direct: self.flags.direct,
+                io_uring: self.flags.io_uring,
+                mmap: self.flags.mmap,
+                fsync: self.flags.fsync.unwrap_or_default(),
+                split: self.flags.split_bytes,
+                checksum: self.flags.checksum,
+                skip_health_check: self.flags.no_health_check,
+                ..Default::default()
+            };
+            if let Err(e) = opts.validate() {
+                prompts::fail(&format!("Error: {}", e));
+            }
+            match opts.limit {
+                Some(limit) if self.flags.notify => notify::start(format!(
+                    "{} of random bytes{}",
+                    format_bytes(limit),
+                    destination(opts.file_path)
+                )),
+                None if self.flags.notify => prompts::warn(
+                    "Warning: --notify ignored, --bytes without a size never finishes",
+                ),
+                _ => {}
+            }
+            if opts.limit.is_none() && self.flags.meta {
+                prompts::warn("Warning: --meta ignored, --bytes without a size never finishes");
+            }
+            let written = match output_bytes(&opts) {
+                Ok(written) => written,
+                Err(e) => {
+                    if e.kind() == ErrorKind::BrokenPipe {
+                        crate::exits::broken_pipe(opts.limit.is_some());
+                    }
+                    prompts::fail(&format!(
+                        "Error: {}: {}",
+                        opts.file_path.unwrap_or("stdout"),
+                        e
+                    ));
+                }
+            };
+            notify::finish(Outcome::Finished);
+            self.meta(&format!("\"bytes\":{}", written));
             return Err(Done);
         }
         Ok(())
     }
 
+    /// `--salt <BYTES>` / `--key <BITS>`: one encoded line of key material.
+    fn handle_key_material(&self) -> Result<(), Done> {
+        let len = match (self.flags.salt, self.flags.key) {
+            (Some(bytes), _) => bytes,
+            (None, Some(bits)) => bits / 8,
+            (None, None) => return Ok(()),
+        };
+        output_key_material(len, self.flags.encode.unwrap_or_default());
+        Err(Done)
+    }
+
+    /// `--license-key`: grouped serial keys on stdout.
+    fn handle_license_key(&self) -> Result<(), Done> {
+        let Some((groups, len)) = self.flags.license_key else {
+            if self.flags.check_char {
+                prompts::fail("Error: --check-char goes with --license-key");
+            }
+            return Ok(());
+        };
+        if self.flags.passphrase()
+            || self.flags.policy()
+            || self.flags.clipboard
+            || self.flags.output.is_some()
+            || self.flags.format.is_some()
+        {
+            prompts::fail(
+                "Error: --license-key prints keys on stdout; drop passphrase, policy, -b, -o and --format flags",
+            );
+        }
+        prompts::verbose(&format!(
+            "license key: {:.1} bits each",
+            license::entropy(groups, len)
+        ));
+        let count = self.flags.number.unwrap_or(1).max(1);
+        license::output(count, groups, len, self.flags.check_char);
+        Err(Done)
+    }
+
     /// Apply CLI flags to settings.
     fn apply_flags(&mut self) {
         // Handle command set mode
@@ -125,8 +540,10 @@ impl Context {
                 .cloned()
                 .collect::<Vec<_>>()
                 .join(" ");
-            self.saved_settings.cli_command = command.clone();
-            if let Err(e) = self.saved_settings.save_to_file() {
+            if let Err(e) = settings::update(|s| {
+                s.cli_command = command.clone();
+                Ok(())
+            }) {
                 prompts::warn(&format!("Failed to save command: {}", e));
             }
             self.settings.cli_command = command;
@@ -136,6 +553,7 @@ impl Context {
         if !self.settings.cli_command.is_empty()
             && self.flags.command == CommandMode::None
             && !self.flags.has_explicit_args()
+            && !self.flags.no_command
         {
             let mut combined_args = vec![self.args[0].clone()];
             combined_args.extend(
@@ -144,10 +562,41 @@ impl Context {
                     .split_whitespace()
                     .map(String::from),
             );
-            if let Ok(saved_flags) = super::parse(&combined_args) {
-                // Replace flags with saved flags so all flag handling applies
-                self.flags = saved_flags;
+            match super::parse(&combined_args) {
+                Ok(mut saved_flags) => {
+                    prompts::verbose(&format!(
+                        "Config: no flags given, saved command applies: {}",
+                        self.settings.cli_command
+                    ));
+                    // -V, -q, --dry-run, --color-classes, --notify and
+                    // --meta on the command line still count
+                    saved_flags.verbose = saved_flags.verbose.max(self.flags.verbose);
+                    saved_flags.quiet |= self.flags.quiet;
+                    saved_flags.color_classes |= self.flags.color_classes;
+                    saved_flags.dry_run |= self.flags.dry_run;
+                    saved_flags.notify |= self.flags.notify;
+                    saved_flags.meta |= self.flags.meta;
+                    // Replace flags with saved flags so all flag handling applies
+                    self.flags = saved_flags;
+                    // quiet::set comes after; -q from either side silences it
+                    quiet::set(self.flags.quiet);
+                    prompts::saved_command(&self.settings.cli_command);
+                }
+                Err(e) => prompts::verbose(&format!(
+                    "Config: saved command ignored, it doesn't parse ({})",
+                    e
+                )),
             }
+        } else if !self.settings.cli_command.is_empty() && self.flags.command == CommandMode::None {
+            prompts::verbose(&format!(
+                "Config: saved command ({}) skipped, {}",
+                self.settings.cli_command,
+                if self.flags.no_command {
+                    "--no-command"
+                } else {
+                    "flags were given"
+                }
+            ));
         }
 
         // Apply explicit length/number
@@ -174,6 +623,30 @@ impl Context {
             self.settings.special_chars = chars.bytes().collect();
         }
 
+        // Reproducible batch: a fresh seed is reported so the run can be re-derived
+        if self.flags.chain {
+            if self.passphrase_mode() {
+                prompts::fail("Error: --chain does not support passphrases");
+            }
+            if self.flags.policy() {
+                prompts::fail(
+                    "Error: --chain derives fixed passwords and can't redraw them for --policy",
+                );
+            }
+            let seed = self.flags.chain_seed.unwrap_or_else(|| {
+                let seed = chain::new_seed();
+                let mut hex = Vec::new();
+                encode_into(&seed, Encoding::Hex, &mut hex);
+                // A dry run never uses it
+                if !self.flags.dry_run {
+                    prompts::chain_seed(&String::from_utf8_lossy(&hex));
+                }
+                hex.zeroize();
+                seed
+            });
+            self.settings.chain_seed = Some(seed);
+        }
+
         // Apply output file
         if let Some(ref path) = self.flags.output {
             self.settings.output_file_path = if path.ends_with('/') || path == "." {
@@ -182,7 +655,12 @@ impl Context {
                 } else {
                     format!("{}rand_pass.txt", path)
                 }
-            } else if !path.ends_with(".txt") {
+            } else if !path.ends_with(".txt")
+                && self.flags.format.is_none()
+                && self.flags.template.is_none()
+            {
+                // Manifests, env files and templated lines keep their own
+                // extension
                 format!("{}.txt", path)
             } else {
                 path.clone()
@@ -190,6 +668,27 @@ impl Context {
             self.settings.output_to_terminal = false;
         }
 
+        if self.flags.mmap && self.flags.io_uring {
+            prompts::fail("Error: --mmap cannot be combined with --io-uring");
+        }
+        // Colors only where someone reads them: never into a pipe or file
+        self.settings.color_classes = self.flags.color_classes
+            && quiet::is_terminal_output()
+            && self.settings.output_file_path.is_empty();
+        self.settings.io_uring = self.flags.io_uring;
+        self.settings.mmap = self.flags.mmap;
+        self.settings.fsync = self.flags.fsync.unwrap_or_default();
+        if self.flags.label.is_some() && !pass::history::enabled() {
+            prompts::warn(
+                "Warning: --label files history entries, but history is off (randpass history enable)",
+            );
+        }
+        self.settings.history_label = self.flags.label.clone();
+        if let Some(secs) = self.flags.countdown {
+            self.settings.skip_countdown = false;
+            self.settings.countdown_secs = secs;
+        }
+
         // Handle clipboard
         if self.flags.clipboard {
             match ClipboardContext::new() {
@@ -208,18 +707,538 @@ impl Context {
         }
     }
 
-    /// Generate passwords and handle output.
-    pub fn generate_output(&mut self) {
+    /// Passphrase mode (--words, --pseudo-words, --list, --lang, --wordlist);
+    /// see `wordlist::select` for which list is used.
+    fn apply_passphrase(&mut self) {
+        if !self.passphrase_mode() {
+            if self.flags.decorations() {
+                prompts::fail(
+                    "Error: --caps, --digits, --symbols, --leet and --word-sep need passphrase mode (--words or --pseudo-words)",
+                );
+            }
+            return;
+        }
+        if self.flags.memorable.is_some() && self.flags.word_sep.is_some() {
+            prompts::fail(
+                "Error: --memorable joins words with digits and a symbol, drop --word-sep",
+            );
+        }
+        let separator = self.flags.word_sep.unwrap_or(self.settings.word_sep);
+        if separator == WordSep::Camel
+            && self.flags.memorable.is_none()
+            && self.flags.caps.is_some_and(|c| c != Caps::First)
+        {
+            prompts::fail("Error: --word-sep camel capitalizes every word, drop --caps");
+        }
+
+        let (words, source) = if let Some(n) = self.flags.pseudo_words {
+            let f = &self.flags;
+            if f.words.is_some()
+                || f.list.is_some()
+                || f.lang.is_some()
+                || f.wordlist.is_some()
+                || f.pronounceable.is_some()
+            {
+                prompts::fail(
+                    "Error: --pseudo-words makes up its own words, drop --words, --list, --lang, --wordlist and --pronounceable",
+                );
+            }
+            (n, Source::Pseudo(pseudo::model()))
+        } else if let Some(syllables) = self.flags.pronounceable {
+            let f = &self.flags;
+            if f.list.is_some() || f.wordlist.is_some() || f.memorable.is_some() {
+                prompts::fail(
+                    "Error: --pronounceable builds words from syllables, drop --list, --wordlist and --memorable",
+                );
+            }
+            let code = f.lang.as_deref().unwrap_or("en");
+            let Some(lang) = pronounceable::get(code) else {
+                prompts::fail(&format!(
+                    "Error: --pronounceable knows {}, not --lang {}",
+                    pronounceable::CODES,
+                    code
+                ));
+            };
+            let syllables = syllables.unwrap_or_else(|| lang.default_syllables());
+            (f.words.unwrap_or(1), Source::Syllables(lang, syllables))
+        } else {
+            let list = wordlist::select(
+                self.flags.list.as_deref(),
+                self.flags.lang.as_deref(),
+                self.flags.wordlist.as_deref(),
+                &self.settings.wordlist,
+            )
+            .unwrap_or_else(|e| {
+                prompts::fail(&format!("Error: {}", e));
+            });
+
+            // Saved word count applies with -s (defaults have 0)
+            let words = self
+                .flags
+                .words
+                .or(self.flags.memorable.map(memorable::words))
+                .or((self.settings.passphrase_words > 0).then_some(self.settings.passphrase_words))
+                .unwrap_or(passphrase::DEFAULT_WORDS);
+            (words, Source::List(list))
+        };
+        let symbols = self.flags.symbols.unwrap_or(0);
+        let symbol_set = if symbols > 0 || self.flags.memorable.is_some() {
+            let mut set = self.settings.special_chars.clone();
+            set.sort_unstable();
+            set.dedup();
+            if set.is_empty() {
+                prompts::fail("Error: --symbols and --memorable need a non-empty --special set");
+            }
+            set
+        } else {
+            Vec::new()
+        };
+        let mut passphrase = match self.flags.memorable {
+            Some(level) => memorable::passphrase(level, words, source, symbol_set.clone()),
+            None => Passphrase::new(words, separator, source),
+        };
+        passphrase.caps = self.flags.caps.unwrap_or_default();
+        passphrase.digits = self.flags.digits.unwrap_or(0);
+        passphrase.symbols = symbols;
+        passphrase.leet = self.flags.leet.clone();
+        if symbols > 0 {
+            passphrase.symbol_set = symbol_set;
+        }
+        self.settings.passphrase = Some(passphrase);
+    }
+
+    /// `--policy FILE` / `--preset NAME` (from the command line or the
+    /// profile, else the saved `policy_file`), plus `--forbid` and
+    /// `--must-match` / `--must-not-match` rules on top: load the rules
+    /// and fit the settings to them, or exit naming the rule no password
+    /// could satisfy.
+    fn apply_policy(&mut self) {
+        // Lowest precedence: the saved policy_file, for password runs only
+        let raw = self.flags.bytes
+            || self.flags.salt.is_some()
+            || self.flags.key.is_some()
+            || self.flags.license_key.is_some();
+        if self.flags.policy.is_none()
+            && self.flags.preset.is_none()
+            && !self.settings.policy_file.is_empty()
+            && !raw
+            && !self.flags.chain
+            && self.flags.pad.is_none()
+        {
+            prompts::verbose(&format!(
+                "Config: saved policy_file {} applies (no policy flag given)",
+                self.settings.policy_file
+            ));
+            self.flags.policy = Some(self.settings.policy_file.clone());
+        }
+        if !self.flags.policy() {
+            return;
+        }
+        let mut policy = match (&self.flags.policy, &self.flags.preset) {
+            (None, None) => policy::Policy {
+                source: "(command line)".to_string(),
+                ..Default::default()
+            },
+            (Some(_), Some(_)) => {
+                prompts::fail("Error: --policy and --preset both set the rules, pick one");
+            }
+            (Some(path), None) => policy::load(path).unwrap_or_else(|e| {
+                prompts::fail(&format!("Error: {}", e));
+            }),
+            // Checked by the parser
+            (None, Some(name)) => policy::preset::get(name).unwrap_or_default(),
+        };
+        if let Some(ref path) = self.flags.forbid {
+            let words = policy::read_words(path).unwrap_or_else(|e| {
+                prompts::fail(&format!("Error: {}", e));
+            });
+            policy.forbidden_words.extend(words);
+            // A record's name in any password gives it away; short names
+            // would reject most draws for little gain
+            if let Some(ref records) = self.flags.for_each
+                && let Ok(names) = batch::read_records(records)
+            {
+                let names = names.iter().map(|n| n.to_lowercase());
+                policy
+                    .forbidden_words
+                    .extend(names.filter(|n| n.chars().count() >= MIN_SCREENED_NAME));
+            }
+        }
+        let patterns = policy::compile("--must-match", &self.flags.must_match).and_then(|must| {
+            let must_not = policy::compile("--must-not-match", &self.flags.must_not_match)?;
+            Ok((must, must_not))
+        });
+        policy
+            .positions
+            .extend(self.flags.positions.iter().copied());
+        match patterns {
+            Ok((must, must_not)) => {
+                policy.must_match.extend(must);
+                policy.must_not_match.extend(must_not);
+            }
+            Err(e) => {
+                prompts::fail(&format!("Error: {}", e));
+            }
+        }
+        if raw {
+            prompts::fail(
+                "Error: password rules apply to passwords, not --bytes, --salt, --key or --license-key",
+            );
+        }
+        let explicit_length = self.flags.length.is_some() || self.flags.saved;
+        // Set first: the charset checks in prepare leave forbidden characters out
+        self.settings.policy = Some(policy.clone());
+        if let Err(e) = policy.prepare(&mut self.settings, explicit_length) {
+            prompts::fail(&format!("Error: policy {}: {}", policy.source, e));
+        }
+    }
+
+    /// `--pattern` / `--pattern-name`: every password follows the template,
+    /// which also sets the length. Parsed after `apply_flags` so `s` and
+    /// `*` draw from the `--special` set.
+    fn apply_pattern(&mut self) {
+        let template = match (&self.flags.pattern, &self.flags.pattern_name) {
+            (None, None) => return,
+            (Some(_), Some(_)) => {
+                prompts::fail(
+                    "Error: --pattern and --pattern-name both give the template, pick one",
+                );
+            }
+            (Some(template), None) => template.clone(),
+            (None, Some(name)) => pattern::load(name).unwrap_or_else(|e| {
+                prompts::fail(&format!("Error: --pattern-name: {}", e));
+            }),
+        };
+        if self.passphrase_mode()
+            || self.flags.chain
+            || self.flags.start_with.is_some()
+            || self.flags.length.is_some()
+            || self.flags.license_key.is_some()
+        {
+            prompts::fail(
+                "Error: a pattern sets the whole password; drop passphrase, --chain, --start-with, -l and --license-key flags",
+            );
+        }
+        // --no-special leaves `s` and `*` nothing to add
+        let special = match self.settings.special_char_density {
+            0 => &[][..],
+            _ => &self.settings.special_chars[..],
+        };
+        let pattern = Pattern::parse(&template, special).unwrap_or_else(|e| {
+            prompts::fail(&format!("Error: --pattern: {}", e));
+        });
+        prompts::verbose(&format!(
+            "Pattern {}: {} characters, {:.1} bits each",
+            pattern.source,
+            pattern.width(),
+            pattern.entropy()
+        ));
+        self.settings.pass_length = pattern.width();
+        self.settings.pattern = Some(pattern);
+    }
+
+    /// `--acronym`: the phrase's initials with random case and symbols,
+    /// padded from the character set up to `-l` (or by `DEFAULT_PAD`).
+    /// Runs as a pattern, so only the random parts count as entropy.
+    fn apply_acronym(&mut self) {
+        let Some(ref arg) = self.flags.acronym else {
+            return;
+        };
+        if self.passphrase_mode()
+            || self.settings.pattern.is_some()
+            || self.flags.chain
+            || self.flags.start_with.is_some()
+            || self.flags.license_key.is_some()
+        {
+            prompts::fail(
+                "Error: --acronym builds the whole password; drop passphrase, --pattern, --chain, --start-with and --license-key flags",
+            );
+        }
+        // `-` keeps the phrase out of the shell history and process list
+        let mut phrase = match arg.as_str() {
+            "-" => prompts::hidden_input("Phrase: ").unwrap_or_default(),
+            phrase => phrase.to_string(),
+        };
+        let initials = phrase.split_whitespace().count();
+        let pad = match self.flags.length {
+            Some(len) if len < initials => {
+                phrase.zeroize();
+                prompts::fail(&format!(
+                    "Error: --acronym: -l {} is shorter than the phrase's {} initials",
+                    len, initials
+                ));
+            }
+            Some(len) => len - initials,
+            None => acronym::DEFAULT_PAD,
+        };
+        let built = acronym::build(&phrase, pad, &charset::build(&self.settings));
+        phrase.zeroize();
+        let built = built.unwrap_or_else(|e| {
+            prompts::fail(&format!("Error: --acronym: {}", e));
+        });
+        let total = built.pattern.entropy();
+        prompts::verbose(&format!(
+            "Acronym: {} initials carry {:.1} bits (case and symbols), {} padding {:.1} bits; the phrase itself counts for none",
+            built.initials,
+            built.skeleton_bits,
+            pad,
+            total - built.skeleton_bits
+        ));
+        self.settings.pass_length = built.pattern.width();
+        self.settings.pattern = Some(built.pattern);
+    }
+
+    /// `--pad` / `--pad-to`: a `-l` core (`DEFAULT_CORE` without it) padded
+    /// with one character, fixed or drawn once per password. Character
+    /// passwords only, and no policy: its lengths would see the core.
+    fn apply_pad(&mut self) {
+        let (fill, to) = match (&self.flags.pad, self.flags.pad_to) {
+            (None, None) => return,
+            (Some(fill), Some(to)) => (fill, to),
+            _ => {
+                prompts::fail("Error: --pad and --pad-to go together");
+            }
+        };
+        if self.passphrase_mode()
+            || self.settings.pattern.is_some()
+            || self.flags.chain
+            || self.flags.policy()
+            || self.flags.license_key.is_some()
+        {
+            prompts::fail(
+                "Error: --pad lengthens character passwords; drop passphrase, pattern, --chain, policy and --license-key flags",
+            );
+        }
+        let core = self.flags.length.unwrap_or(pad::DEFAULT_CORE);
+        if to <= core {
+            prompts::fail(&format!(
+                "Error: --pad-to {} leaves no room for padding after a {}-character core",
+                to, core
+            ));
+        }
+        let fill = match fill.as_str() {
+            "random" => {
+                let mut chars = self.settings.special_chars.clone();
+                chars.sort_unstable();
+                chars.dedup();
+                if chars.is_empty() {
+                    prompts::fail("Error: --pad random draws from --special, which is empty");
+                }
+                Fill::Random(chars)
+            }
+            // Checked by the parser
+            c => Fill::Char(c.as_bytes()[0]),
+        };
+        self.settings.pass_length = core;
+        let pad = Pad { fill, to };
+        prompts::verbose(&format!(
+            "Padding: {} characters of {} after the {}-character core; counted as {:.1} bits, not {} characters",
+            to - core,
+            pad.describe(),
+            core,
+            pad.entropy(),
+            to - core
+        ));
+        self.settings.pad = Some(pad);
+    }
+
+    /// `--start-with`: character passwords only, and the pool (after the
+    /// policy's forbidden characters) must hold one of the class.
+    fn apply_start_with(&mut self) {
+        let Some(start) = self.flags.start_with else {
+            return;
+        };
+        if self.passphrase_mode() || self.flags.chain {
+            prompts::fail(
+                "Error: --start-with needs character passwords (no passphrase or --chain)",
+            );
+        }
+        self.settings.start_with = Some(start);
+        if charset::size_at(&self.settings, 0) == 0 {
+            prompts::fail("Error: --start-with: the character set has no character of that class");
+        }
+    }
+
+    /// Draw a sample against the policy before generating, so one no
+    /// password can satisfy fails here instead of after the retry limit.
+    fn trial_policy(&self) -> Option<policy::Trial> {
+        let policy = self.settings.policy.as_ref()?;
+        match pass::trial_policy(&self.settings, policy::TRIAL_DRAWS)? {
+            Ok(trial) => {
+                let broken = trial.most_broken.map_or_else(String::new, |(rule, n)| {
+                    format!(
+                        "; most rejected: {} ({})",
+                        policy.describe(rule),
+                        policy::percent(n as u64, trial.draws as u64)
+                    )
+                });
+                prompts::verbose(&format!(
+                    "Policy {}: {} of {} trial passwords passed, about {:.1} draws per password{}",
+                    policy.source,
+                    trial.accepted,
+                    trial.draws,
+                    trial.draws_per_password(),
+                    broken
+                ));
+                Some(trial)
+            }
+            Err(e) => {
+                prompts::fail(&format!("Error: policy {}: {}", policy.source, e));
+            }
+        }
+    }
+
+    /// Refuse to generate below the entropy floor (`--min-entropy`, else
+    /// the saved `min_entropy_bits`) unless `--allow-weak`.
+    fn check_entropy_floor(&self, trial: Option<&policy::Trial>) {
+        let floor = self
+            .flags
+            .min_entropy
+            .unwrap_or(self.settings.min_entropy_bits);
+        if floor == 0 {
+            return;
+        }
+        let bits = pass::entropy(&self.settings, trial);
+        if bits >= floor as f64 {
+            return;
+        }
+        let msg = format!(
+            "passwords would carry {:.1} bits of entropy, under the {}-bit floor",
+            bits, floor
+        );
+        if self.flags.allow_weak {
+            prompts::warn(&format!("Warning: {} (--allow-weak)", msg));
+            return;
+        }
+        prompts::fail(&format!(
+            "Error: {}; make them longer, widen the character set, or pass --allow-weak",
+            msg
+        ));
+    }
+
+    /// Caution before generating weak passwords; `--force` (or
+    /// `--allow-weak`, which already warned) skips it.
+    fn warn_weak(&self, trial: Option<&policy::Trial>) {
+        if self.flags.force || self.flags.allow_weak {
+            return;
+        }
+        for reason in pass::weaknesses(&self.settings, trial) {
+            prompts::warn(&format!(
+                "Warning: weak settings: {} (--force to skip)",
+                reason
+            ));
+        }
+    }
+
+    /// `--verbose`: what enforcing the policy took.
+    fn report_policy(&self) {
+        let Some(ref policy) = self.settings.policy else {
+            return;
+        };
+        let stats = policy::stats();
+        if stats.passwords == 0 {
+            return;
+        }
+        let rejected: Vec<String> = stats
+            .rejected
+            .iter()
+            .map(|(kind, n)| format!("{} {}", kind, n))
+            .collect();
+        prompts::verbose(&format!(
+            "Policy {}: {} passwords in {} draws ({:.2} per password, at most {}); rejected: {}",
+            policy.source,
+            stats.passwords,
+            stats.draws,
+            stats.draws as f64 / stats.passwords as f64,
+            stats.most_draws,
+            if rejected.is_empty() {
+                "none".to_string()
+            } else {
+                rejected.join(", ")
+            }
+        ));
+    }
+
+    /// True when flags or saved settings (`passphrase_words`) ask for words.
+    fn passphrase_mode(&self) -> bool {
+        self.flags.passphrase() || self.settings.passphrase_words > 0
+    }
+
+    /// Generate passwords and handle output; `bits` is the entropy of each.
+    pub fn generate_output(&mut self, bits: f64) {
         // Use explicit flag, else settings (which may come from saved command)
         let count = self
             .flags
             .number
             .unwrap_or(self.settings.number_of_passwords.max(1));
+        if let Err(e) = self.settings.check_output() {
+            prompts::fail(&format!("Error: {}", e));
+        }
 
-        if self.settings.to_clipboard {
+        if let Some(ref template) = self.flags.template {
+            self.template(&template.clone(), count, bits);
+        } else if let Some(candidates) = self.flags.pick_from {
+            self.pick(candidates);
+        } else if self.flags.board_select {
+            self.board_select(count);
+        } else if let Some(format) = self.flags.format {
+            if self.flags.clipboard
+                || count == 0
+                || self.flags.for_each.is_some()
+                || self.flags.hash.is_some()
+                || self.flags.mnemonic
+                || self.flags.stretch.is_some()
+            {
+                prompts::fail(
+                    "Error: --format can't be combined with -b, -n 0, --for-each, --hash, --mnemonic or --stretch",
+                );
+            }
+            if let Err(e) = format::output(
+                &self.settings,
+                count,
+                format,
+                self.flags.secret_name.as_deref(),
+                self.flags.secret_key.as_deref(),
+            ) {
+                prompts::fail(&format!("Error: {}", e));
+            }
+        } else if self.flags.for_each.is_some() || self.flags.hash.is_some() {
+            let records = match self.flags.for_each {
+                Some(ref path) => match batch::read_records(path) {
+                    Ok(r) => Some(r),
+                    Err(e) => {
+                        prompts::fail(&format!("Error: {}", e));
+                    }
+                },
+                None => None,
+            };
+            if let Err(e) =
+                batch::output(&self.settings, records.as_deref(), count, self.flags.hash)
+            {
+                prompts::fail(&format!("Error: {}", e));
+            }
+        } else if self.flags.mnemonic {
+            if self.passphrase_mode() || self.flags.stretch.is_some() || count == 0 {
+                prompts::fail(
+                    "Error: --mnemonic needs character passwords (no passphrase, --stretch or -n 0)",
+                );
+            }
+            if self.flags.clipboard || self.flags.output.is_some() {
+                prompts::fail("Error: --mnemonic prints to the terminal, drop -b and -o");
+            }
+            mnemonic::output(&self.settings, count);
+        } else if let Some(kdf) = self.flags.stretch {
+            if self.flags.clipboard || self.flags.output.is_some() || self.flags.chain {
+                prompts::fail("Error: --stretch prints to the terminal, drop -b, -o and --chain");
+            }
+            stretch::output(&self.settings, count, kdf);
+        } else if count == 0 {
+            self.stream();
+        } else if self.settings.to_clipboard {
             let passwords = pass::generate_batch(&self.settings, count);
-            if let (Some(ctx), Some(mut passwords)) = (self.clipboard.as_mut(), passwords) {
-                match ctx.set_contents(passwords.clone()) {
+            // Unused payloads zeroize themselves on drop
+            if let (Some(ctx), Some(passwords)) = (self.clipboard.as_mut(), passwords) {
+                match ctx.set_contents(passwords.into_string()) {
                     Ok(_) => {
                         if let Ok(mut retrieved) = ctx.get_contents() {
                             retrieved.zeroize();
@@ -230,15 +1249,16 @@ impl Context {
                         prompts::clipboard_error(&e.to_string());
                     }
                 }
-                passwords.zeroize();
             }
         } else if !self.settings.output_file_path.is_empty()
             && count >= 500_000
             && !self.flags.quiet
+            && quiet::is_interactive()
+            && quiet::is_terminal_output()
         {
-            // Bulk file output: use TUI progress bar
+            // Bulk file output: use TUI progress bar (it reads keys and
+            // draws on stdout, so only with a terminal on both)
             let mut cli_settings = self.settings.clone();
-            cli_settings.skip_countdown = true;
             cli_settings.number_of_passwords = count;
             pass::output::with_progress(&cli_settings);
         } else if !self.settings.output_file_path.is_empty() {
@@ -253,4 +1273,170 @@ impl Context {
             pass::generate_batch(&self.settings, count);
         }
     }
+
+    /// `--template`: write each password through the template.
+    fn template(&mut self, template: &str, count: usize, bits: f64) {
+        if self.settings.to_clipboard
+            || (count == 0 && self.flags.for_each.is_none())
+            || self.flags.format.is_some()
+            || self.flags.hash.is_some()
+            || self.flags.mnemonic
+            || self.flags.stretch.is_some()
+            || self.flags.pick_from.is_some()
+            || self.flags.board_select
+        {
+            prompts::fail(
+                "Error: --template can't be combined with -b, -n 0, --format, --hash, --mnemonic, --stretch, --pick-from or --board-select",
+            );
+        }
+        let template = Template::parse(template).unwrap_or_else(|e| {
+            prompts::fail(&format!("Error: {}", e));
+        });
+        let records = match self.flags.for_each {
+            Some(ref path) => Some(batch::read_records(path).unwrap_or_else(|e| {
+                prompts::fail(&format!("Error: {}", e));
+            })),
+            None if template.wants_record() => {
+                prompts::fail("Error: {record} in --template needs --for-each FILE");
+            }
+            None => None,
+        };
+        if let Err(e) = template::output(&self.settings, &template, records.as_deref(), count, bits)
+        {
+            prompts::fail(&format!("Error: {}", e));
+        }
+    }
+
+    /// `--pick-from`: output the one password chosen from `candidates`.
+    fn pick(&mut self, candidates: usize) {
+        if self.flags.number.is_some()
+            || !self.settings.output_file_path.is_empty()
+            || self.flags.for_each.is_some()
+            || self.flags.hash.is_some()
+            || self.flags.mnemonic
+            || self.flags.stretch.is_some()
+        {
+            prompts::fail(
+                "Error: --pick-from outputs the one password picked; drop -n, -o, --for-each, --hash, --mnemonic and --stretch",
+            );
+        }
+        if !quiet::is_interactive() || !std::io::stderr().is_terminal() {
+            prompts::fail("Error: --pick-from needs a terminal to pick on");
+        }
+        if let Err(e) = pick::output(&self.settings, candidates, self.clipboard.as_mut()) {
+            prompts::fail(&format!("Error: {}", e));
+        }
+    }
+
+    /// `--board-select`: print `count` passwords numbered and copy the
+    /// ones asked for.
+    fn board_select(&mut self, count: usize) {
+        if count == 0
+            || count > pick::MAX
+            || self.settings.to_clipboard
+            || !self.settings.output_file_path.is_empty()
+            || self.flags.format.is_some()
+            || self.flags.for_each.is_some()
+            || self.flags.hash.is_some()
+            || self.flags.mnemonic
+            || self.flags.stretch.is_some()
+        {
+            prompts::fail(&format!(
+                "Error: --board-select numbers 1 to {} passwords on the terminal; drop -n 0, -b, -o, --format, --for-each, --hash, --mnemonic and --stretch",
+                pick::MAX
+            ));
+        }
+        if !quiet::is_interactive() || !quiet::is_terminal_output() {
+            prompts::fail("Error: --board-select needs a terminal to list on and read from");
+        }
+        if let Err(e) = board::output(&self.settings, count) {
+            prompts::fail(&format!("Error: {}", e));
+        }
+    }
+
+    /// Add the run to the provenance log when `audit_log` is on.
+    /// `--meta json`: one JSON object on stderr after a finished run:
+    /// `fields` (what was made), the RNG backend, the time taken, and the
+    /// raw entropy each backend contributed. Pooled backends add the bits
+    /// read into their pool and the bits drawn per bit read.
+    fn meta(&self, fields: &str) {
+        if !self.flags.meta {
+            return;
+        }
+        let backend = pass::audit::backend();
+        // The run is over; stopping the producer folds its reads in
+        rand::stop_prefetch();
+        let raw: Vec<String> = rand::failover::Source::ALL
+            .iter()
+            .zip(session::raw_use_since(&self.raw_start))
+            .filter(|(_, usage)| usage.reads > 0 || usage.pool_bytes > 0)
+            .map(|(source, usage)| {
+                let mut entry = format!(
+                    "\"{}\":{{\"reads\":{},\"bits\":{}",
+                    source.name(),
+                    usage.reads,
+                    usage.bits_drawn()
+                );
+                if let Some(reuse) = usage.reuse() {
+                    entry.push_str(&format!(
+                        ",\"pool_bits_read\":{},\"reuse\":{:.4}",
+                        usage.bits_read(),
+                        reuse
+                    ));
+                }
+                entry.push('}');
+                entry
+            })
+            .collect();
+        eprintln!(
+            "{{{},\"rng\":\"{}\",\"secs\":{:.3},\"raw_entropy\":{{{}}}}}",
+            fields,
+            backend,
+            self.started.elapsed().as_secs_f64(),
+            raw.join(",")
+        );
+    }
+
+    fn audit(&self, count: usize, bits: f64) {
+        if let Err(e) = pass::audit::record(&self.settings, count, bits) {
+            prompts::warn(&format!("Audit log not written: {}", e));
+        }
+    }
+
+    /// `-n 0`: generate until the reader goes away.
+    fn stream(&self) {
+        if self.settings.to_clipboard {
+            prompts::fail("Error: -n 0 streams without end and cannot go to the clipboard");
+        }
+        // Endless output only stops by itself when a reader closes the pipe
+        if !self.flags.force {
+            let target = if !self.settings.output_file_path.is_empty() {
+                Some("a file")
+            } else if quiet::is_terminal_output() {
+                Some("the terminal")
+            } else {
+                None
+            };
+            if let Some(target) = target {
+                prompts::fail(&format!(
+                    "Error: -n 0 streams without end; pipe it into a reader, or add --force to write it to {}",
+                    target
+                ));
+            }
+        }
+        if let Err(e) = pass::generate_stream(&self.settings) {
+            if e.kind() == ErrorKind::BrokenPipe {
+                crate::exits::broken_pipe(false);
+            }
+            prompts::fail(&format!("Error: {}", e));
+        }
+    }
+}
+
+/// " to <path>" for a `--notify` message, nothing for stdout.
+fn destination(path: Option<&str>) -> String {
+    match path {
+        Some(path) if !path.is_empty() => format!(" to {}", path),
+        _ => String::new(),
+    }
 }
@@ -0,0 +1,90 @@
+//! Text encodings for binary output (keys, salts).
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE32: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Least common multiple of every encoding's input group (3 for base64,
+/// 5 for base32). Streaming in multiples of it keeps padding at the end.
+pub const ENCODE_GROUP: usize = 15;
+
+/// Default stream chunk size, a multiple of `ENCODE_GROUP`.
+pub const STREAM_CHUNK: usize = 61440;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    #[default]
+    Hex,
+    Base64,
+    Base32,
+}
+
+impl Encoding {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "hex" => Some(Encoding::Hex),
+            "base64" => Some(Encoding::Base64),
+            "base32" => Some(Encoding::Base32),
+            _ => None,
+        }
+    }
+}
+
+/// Encode bytes into `out` (appends). Caller owns `out` and zeroizes it.
+pub fn encode_into(bytes: &[u8], encoding: Encoding, out: &mut Vec<u8>) {
+    match encoding {
+        Encoding::Hex => hex_into(bytes, out),
+        Encoding::Base64 => base64_into(bytes, out),
+        Encoding::Base32 => base32_into(bytes, out),
+    }
+}
+
+fn hex_into(bytes: &[u8], out: &mut Vec<u8>) {
+    out.reserve(bytes.len() * 2);
+    for &b in bytes {
+        out.push(HEX[(b >> 4) as usize]);
+        out.push(HEX[(b & 0x0f) as usize]);
+    }
+}
+
+fn base64_into(bytes: &[u8], out: &mut Vec<u8>) {
+    out.reserve(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = chunk.get(1).copied().unwrap_or(0) as u32;
+        let b2 = chunk.get(2).copied().unwrap_or(0) as u32;
+        let n = (b0 << 16) | (b1 << 8) | b2;
+
+        out.push(BASE64[(n >> 18) as usize & 63]);
+        out.push(BASE64[(n >> 12) as usize & 63]);
+        out.push(if chunk.len() > 1 {
+            BASE64[(n >> 6) as usize & 63]
+        } else {
+            b'='
+        });
+        out.push(if chunk.len() > 2 {
+            BASE64[n as usize & 63]
+        } else {
+            b'='
+        });
+    }
+}
+
+fn base32_into(bytes: &[u8], out: &mut Vec<u8>) {
+    out.reserve(bytes.len().div_ceil(5) * 8);
+    for chunk in bytes.chunks(5) {
+        let mut group = [0u8; 5];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let n = group.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64);
+
+        // Output characters carrying at least one input bit; pad the rest
+        let used = (chunk.len() * 8).div_ceil(5);
+        for i in 0..8 {
+            if i < used {
+                out.push(BASE32[(n >> (35 - i * 5)) as usize & 31]);
+            } else {
+                out.push(b'=');
+            }
+        }
+    }
+}
@@ -1,3 +1,16 @@
+use super::checksum::Checksum;
+use super::encode::Encoding;
+use super::format::Format;
+use super::stretch::Kdf;
+use crate::pass::chain;
+use crate::pass::charset::StartWith;
+use crate::pass::crypt::Scheme;
+use crate::pass::fsync::Fsync;
+use crate::pass::leet::Leet;
+use crate::pass::passphrase::{Caps, WordSep};
+use crate::pass::policy::Position;
+use crate::rand::failover::Source;
+
 #[derive(Debug, Default, Clone, PartialEq)]
 pub enum CommandMode {
     #[default]
@@ -11,13 +24,26 @@ pub enum CommandMode {
 pub struct CliFlags {
     pub help: bool,
     pub version: bool,
+    /// A flag outside parse's `KEEP_SAVED_COMMAND` was given
+    pub explicit: bool,
     pub bytes: bool,
-    pub urandom: bool,
+    /// `--rng`, `-u`: entropy sources in failover order
+    pub rng: Option<Vec<Source>>,
+    /// `--hwrng-device`, `--hwrng-read`: override the saved hwrng settings
+    pub hwrng_device: Option<String>,
+    pub hwrng_read: Option<usize>,
+    /// `--warmup`: override the saved warmup_discard
+    pub warmup: Option<usize>,
+    /// `--meta json`: the run's metadata as JSON on stderr
+    pub meta: bool,
+    pub prefetch: bool,
     pub clipboard: bool,
     pub saved: bool,
     pub default: bool,
     pub command: CommandMode,
     pub quiet: bool,
+    /// `-V` count: 1 reports decisions, 2 adds settings and timings
+    pub verbose: u8,
     pub no_special: bool,
     pub hex: bool,
     pub length: Option<usize>,
@@ -25,17 +51,132 @@ pub struct CliFlags {
     pub number_raw: Option<String>,
     pub special: Option<String>,
     pub output: Option<String>,
+    pub salt: Option<usize>,
+    pub key: Option<usize>,
+    pub encode: Option<Encoding>,
+    pub stats: bool,
+    pub block_size: Option<usize>,
+    pub block_count: Option<usize>,
+    pub direct: bool,
+    pub io_uring: bool,
+    pub mmap: bool,
+    pub fsync: Option<Fsync>,
+    /// `--color-classes`: color character classes on a terminal
+    pub color_classes: bool,
+    /// `--no-command`: don't apply the saved command to a bare run
+    pub no_command: bool,
+    /// `--force`: no caution for weak settings
+    pub force: bool,
+    /// `--dry-run`: print the resolved run instead of generating
+    pub dry_run: bool,
+    /// `--notify`: announce the end of the run on the desktop
+    pub notify: bool,
+    /// `--countdown`: seconds before a large `-o` run; 0 for none
+    pub countdown: Option<usize>,
+    pub split_bytes: Option<usize>,
+    pub checksum: Option<Checksum>,
+    pub no_health_check: bool,
+    pub stretch: Option<Kdf>,
+    pub chain: bool,
+    pub mnemonic: bool,
+    /// `--pick-from`: candidates to choose one password from
+    pub pick_from: Option<usize>,
+    /// `--board-select`: number the batch and copy entries on request
+    pub board_select: bool,
+    /// `--label`: what history entries from this run are filed under
+    pub label: Option<String>,
+    /// `--template`: line layout, parsed in `Context::template`
+    pub template: Option<String>,
+    /// `--license-key`: groups and characters per group
+    pub license_key: Option<(usize, usize)>,
+    /// `--check-char`: mod 31 check character on license keys
+    pub check_char: bool,
+    pub chain_seed: Option<[u8; chain::SEED_LEN]>,
+    pub hash: Option<Scheme>,
+    pub for_each: Option<String>,
+    /// `--policy`: policy file path, loaded in `Context::apply_policy`
+    pub policy: Option<String>,
+    /// `--preset`: built-in policy name
+    pub preset: Option<String>,
+    /// `--min-entropy`: floor for this run, over `min_entropy_bits`
+    pub min_entropy: Option<usize>,
+    /// `--allow-weak`: generate even below the entropy floor
+    pub allow_weak: bool,
+    /// `--profile`: named set of saved flags, merged in `Context::new`
+    pub profile: Option<String>,
+    /// `--forbid`: file of words no password may contain
+    pub forbid: Option<String>,
+    /// `--must-match` patterns, in order given
+    pub must_match: Vec<String>,
+    /// `--must-not-match` patterns, in order given
+    pub must_not_match: Vec<String>,
+    /// `--digit-at` and friends: position rules added to the policy
+    pub positions: Vec<Position>,
+    pub format: Option<Format>,
+    /// `--name`: Secret name for `--format k8s-secret`
+    pub secret_name: Option<String>,
+    /// `--key-name`: the Secret key or variable name under `--format`
+    pub secret_key: Option<String>,
+    pub words: Option<usize>,
+    pub pseudo_words: Option<usize>,
+    pub word_sep: Option<WordSep>,
+    pub list: Option<String>,
+    pub wordlist: Option<String>,
+    pub lang: Option<String>,
+    pub caps: Option<Caps>,
+    pub digits: Option<usize>,
+    pub symbols: Option<usize>,
+    pub leet: Option<Leet>,
+    /// `--memorable`: level, 1 to 4
+    pub memorable: Option<usize>,
+    pub start_with: Option<StartWith>,
+    /// `--pattern`: template for every password
+    pub pattern: Option<String>,
+    /// `--pattern-name`: a pattern saved with `randpass pattern add`
+    pub pattern_name: Option<String>,
+    /// `--acronym`: phrase whose initials start the password; `-` asks
+    pub acronym: Option<String>,
+    /// `--pad`: one character, or `random`
+    pub pad: Option<String>,
+    /// `--pad-to`: length with the padding
+    pub pad_to: Option<usize>,
+    /// `--pronounceable`: syllables per word; None for the 60-bit default
+    pub pronounceable: Option<Option<usize>>,
 }
 
 impl CliFlags {
+    /// True when a flag that replaces the saved `-c` command was given.
     pub fn has_explicit_args(&self) -> bool {
-        self.length.is_some()
-            || self.number.is_some()
-            || self.saved
-            || self.default
-            || self.no_special
-            || self.hex
-            || self.special.is_some()
-            || self.output.is_some()
+        self.explicit
+    }
+
+    /// True when any passphrase-only option is given.
+    pub fn decorations(&self) -> bool {
+        self.caps.is_some()
+            || self.digits.is_some()
+            || self.symbols.is_some()
+            || self.word_sep.is_some()
+            || self.leet.is_some()
+    }
+
+    /// True when any flag sets password rules.
+    pub fn policy(&self) -> bool {
+        self.policy.is_some()
+            || self.preset.is_some()
+            || self.forbid.is_some()
+            || !self.must_match.is_empty()
+            || !self.must_not_match.is_empty()
+            || !self.positions.is_empty()
+    }
+
+    /// True when any flag selects passphrase mode.
+    pub fn passphrase(&self) -> bool {
+        self.words.is_some()
+            || self.memorable.is_some()
+            || self.pronounceable.is_some()
+            || self.pseudo_words.is_some()
+            || self.list.is_some()
+            || self.wordlist.is_some()
+            || self.lang.is_some()
     }
 }
@@ -0,0 +1,176 @@
+//! `--format k8s-secret|env`: passwords wrapped for provisioning
+//! pipelines. `k8s-secret` prints a Kubernetes Secret manifest with the
+//! values base64-encoded under `data`, ready for `kubectl apply -f -`;
+//! `env` prints `KEY=value` lines for `docker --env-file` or systemd's
+//! `EnvironmentFile=`. Several passwords get numbered keys (`KEY_1`, ...).
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+
+use zeroize::Zeroize;
+
+use super::encode::{Encoding, encode_into};
+use crate::pass::charset::Charset;
+use crate::pass::{SecureBufWriter, file_writer, max_len, next_password, pool};
+use crate::settings::Settings;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    K8sSecret,
+    Env,
+}
+
+impl Format {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "k8s-secret" => Some(Format::K8sSecret),
+            "env" => Some(Format::Env),
+            _ => None,
+        }
+    }
+
+    fn default_key(self) -> &'static str {
+        match self {
+            Format::K8sSecret => "password",
+            Format::Env => "PASSWORD",
+        }
+    }
+}
+
+/// Check `--name` and `--key-name` for `format`; returns the key to use.
+fn check_names<'a>(
+    format: Format,
+    name: Option<&str>,
+    key: Option<&'a str>,
+) -> Result<&'a str, String> {
+    let key = key.unwrap_or(format.default_key());
+    match format {
+        Format::K8sSecret => {
+            let name = name.ok_or("--format k8s-secret needs --name <NAME>")?;
+            if !is_dns_subdomain(name) {
+                return Err(format!(
+                    "Invalid Secret name: {} (lowercase letters, digits, '-' and '.', \
+                     starting and ending with a letter or digit)",
+                    name
+                ));
+            }
+            let valid = !key.is_empty()
+                && key.len() <= 253
+                && key
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b"-._".contains(&b));
+            if !valid {
+                return Err(format!(
+                    "Invalid Secret key: {} (letters, digits, '-', '_' and '.')",
+                    key
+                ));
+            }
+        }
+        Format::Env => {
+            if name.is_some() {
+                return Err("--name only applies to --format k8s-secret".to_string());
+            }
+            let valid = key.bytes().next().is_some_and(|b| !b.is_ascii_digit())
+                && key.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_');
+            if !valid {
+                return Err(format!(
+                    "Invalid variable name: {} (letters, digits and '_', not starting with a digit)",
+                    key
+                ));
+            }
+        }
+    }
+    Ok(key)
+}
+
+/// RFC 1123 subdomain, what Kubernetes requires of object names.
+fn is_dns_subdomain(name: &str) -> bool {
+    let b = name.as_bytes();
+    !b.is_empty()
+        && b.len() <= 253
+        && b[0].is_ascii_alphanumeric()
+        && b[b.len() - 1].is_ascii_alphanumeric()
+        && b.iter()
+            .all(|&c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == b'-' || c == b'.')
+}
+
+/// Generate `count` passwords and print them as `format`, to the `-o`
+/// file (created 0600, appended; manifests start with `---` so appended
+/// ones stay separate documents) or stdout.
+pub fn output(
+    settings: &Settings,
+    count: usize,
+    format: Format,
+    name: Option<&str>,
+    key: Option<&str>,
+) -> Result<(), String> {
+    let key = check_names(format, name, key)?;
+
+    let mut file = if settings.output_file_path.is_empty() {
+        None
+    } else {
+        Some(file_writer(
+            OpenOptions::new()
+                .read(settings.mmap)
+                .create(true)
+                .append(true)
+                .mode(0o600)
+                .open(&settings.output_file_path)
+                .map_err(|e| format!("{}: {}", settings.output_file_path, e))?,
+            settings,
+            None,
+        ))
+    };
+    let stdout = std::io::stdout();
+    let mut out = SecureBufWriter::new(stdout.lock());
+    let mut write = |data: &[u8]| match file {
+        Some(ref mut f) => f.write_all(data),
+        None => out.write_all(data),
+    };
+
+    if format == Format::K8sSecret {
+        let header = format!(
+            "---\napiVersion: v1\nkind: Secret\nmetadata:\n  name: {}\ntype: Opaque\ndata:\n",
+            name.unwrap_or_default()
+        );
+        write(header.as_bytes()).map_err(|e| e.to_string())?;
+    }
+
+    let chars = Charset::build(settings);
+    let mut pass = pool::take(max_len(settings));
+    // Indent, key and number, then the value base64-encoded
+    let mut line = pool::take(key.len() + 32 + pass.capacity().div_ceil(3) * 4);
+
+    for n in 0..count {
+        next_password(settings, &chars, n, &mut pass);
+        if format == Format::K8sSecret {
+            line.extend_from_slice(b"  ");
+        }
+        line.extend_from_slice(key.as_bytes());
+        if count > 1 {
+            line.extend_from_slice(format!("_{}", n + 1).as_bytes());
+        }
+        match format {
+            Format::K8sSecret => {
+                line.extend_from_slice(b": ");
+                encode_into(&pass, Encoding::Base64, &mut line);
+            }
+            Format::Env => {
+                line.push(b'=');
+                line.extend_from_slice(&pass);
+            }
+        }
+        line.push(b'\n');
+        pass.zeroize();
+        let written = write(&line);
+        line.zeroize();
+        written.map_err(|e| e.to_string())?;
+    }
+
+    crate::rand::shutdown_urandom();
+    if let Some(f) = file {
+        f.finish().map_err(|e| e.to_string())?;
+    }
+    out.flush().map_err(|e| e.to_string())
+}
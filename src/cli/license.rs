@@ -0,0 +1,92 @@
+//! `--license-key [GROUPS]x[LEN]`: serial keys like `7F3K9-Q2MWD-8ZR4T-J6NXB`
+//! in an uppercase alphabet without the characters people misread (0/O,
+//! 1/I/L). `--check-char` appends a weighted mod 31 check character to
+//! the last group, which catches any single mistyped character and any
+//! swap of neighbours.
+
+use std::io::Write;
+
+use zeroize::Zeroize;
+
+use crate::pass::charset::Charset;
+use crate::settings::Settings;
+
+/// Digits 2-9 and the letters other than I, L and O.
+const ALPHABET: &[u8] = b"23456789ABCDEFGHJKMNPQRSTUVWXYZ";
+
+pub const DEFAULT_GROUPS: usize = 4;
+pub const DEFAULT_LEN: usize = 5;
+
+/// Longest key accepted, in characters.
+const MAX_CHARS: usize = 1024;
+
+/// Parse `[GROUPS]x[LEN]`; either side may be left out for its default.
+pub fn parse_spec(s: &str) -> Option<(usize, usize)> {
+    let (groups, len) = s.split_once('x')?;
+    let side = |v: &str, default| match v {
+        "" => Some(default),
+        v => v.parse().ok().filter(|&n| n > 0),
+    };
+    let (groups, len) = (side(groups, DEFAULT_GROUPS)?, side(len, DEFAULT_LEN)?);
+    (groups.checked_mul(len)? <= MAX_CHARS).then_some((groups, len))
+}
+
+/// Bits in one key (the check character adds none).
+pub fn entropy(groups: usize, len: usize) -> f64 {
+    (groups * len) as f64 * (ALPHABET.len() as f64).log2()
+}
+
+/// The character to append so that the weighted sum of the whole key,
+/// check character included, is a multiple of 31. Counting from the end,
+/// the check character weighs 1 and the key's characters 2, 3, ... 30,
+/// then 1 again. 31 is prime and every weight is nonzero mod 31, so
+/// changing one character always changes the sum; neighbours always weigh
+/// differently, so swapping two that differ does too.
+fn check_char(key: &[u8]) -> u8 {
+    let n = ALPHABET.len();
+    let sum: usize = key
+        .iter()
+        .rev()
+        .filter_map(|c| ALPHABET.iter().position(|a| a == c))
+        .enumerate()
+        .map(|(i, v)| v * ((i + 1) % (n - 1) + 1))
+        .sum();
+    ALPHABET[(n - sum % n) % n]
+}
+
+/// Print `count` keys of `groups` groups of `len` characters.
+pub fn output(count: usize, groups: usize, len: usize, check: bool) {
+    let settings = Settings {
+        lowercase_char_density: 0,
+        uppercase_char_density: 0,
+        numeric_char_density: 0,
+        special_char_density: 1,
+        special_chars: ALPHABET.to_vec(),
+        ..Default::default()
+    };
+    let chars = Charset::build(&settings);
+    let mut key = Vec::with_capacity(groups * len);
+    let mut line = Vec::with_capacity(groups * (len + 1) + 2);
+
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    for _ in 0..count {
+        chars.generate(groups * len, &mut key);
+        for (i, group) in key.chunks(len).enumerate() {
+            if i > 0 {
+                line.push(b'-');
+            }
+            line.extend_from_slice(group);
+        }
+        if check {
+            line.push(check_char(&key));
+        }
+        line.push(b'\n');
+        let _ = out.write_all(&line);
+        line.zeroize();
+        key.zeroize();
+    }
+
+    let _ = out.flush();
+    crate::rand::shutdown_urandom();
+}
@@ -0,0 +1,116 @@
+//! `--mnemonic`: print a spoken-word hint under each password. Letters
+//! become NATO words (uppercase letters as uppercase words), digits and
+//! symbols their names. The hint only spells the password out, so it adds
+//! nothing an attacker could use and takes nothing away.
+
+use std::io::Write;
+
+use zeroize::Zeroize;
+
+use crate::pass::charset::Charset;
+use crate::pass::{max_len, next_password, pool};
+use crate::settings::Settings;
+
+const NATO: [&str; 26] = [
+    "alfa", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliett",
+    "kilo", "lima", "mike", "november", "oscar", "papa", "quebec", "romeo", "sierra", "tango",
+    "uniform", "victor", "whiskey", "x-ray", "yankee", "zulu",
+];
+
+const DIGITS: [&str; 10] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+/// Spoken name of a printable ASCII symbol.
+fn symbol(c: u8) -> Option<&'static str> {
+    Some(match c {
+        b' ' => "space",
+        b'!' => "bang",
+        b'"' => "quote",
+        b'#' => "hash",
+        b'$' => "dollar",
+        b'%' => "percent",
+        b'&' => "ampersand",
+        b'\'' => "apostrophe",
+        b'(' => "open-paren",
+        b')' => "close-paren",
+        b'*' => "star",
+        b'+' => "plus",
+        b',' => "comma",
+        b'-' => "dash",
+        b'.' => "dot",
+        b'/' => "slash",
+        b':' => "colon",
+        b';' => "semicolon",
+        b'<' => "less-than",
+        b'=' => "equals",
+        b'>' => "greater-than",
+        b'?' => "question",
+        b'@' => "at",
+        b'[' => "open-bracket",
+        b'\\' => "backslash",
+        b']' => "close-bracket",
+        b'^' => "caret",
+        b'_' => "underscore",
+        b'`' => "backtick",
+        b'{' => "open-brace",
+        b'|' => "pipe",
+        b'}' => "close-brace",
+        b'~' => "tilde",
+        _ => return None,
+    })
+}
+
+/// Append the hint for `password`, one word per character.
+fn hint_into(password: &[u8], out: &mut Vec<u8>) {
+    for (i, &c) in password.iter().enumerate() {
+        if i > 0 {
+            out.push(b' ');
+        }
+        match c {
+            b'a'..=b'z' => out.extend_from_slice(NATO[(c - b'a') as usize].as_bytes()),
+            b'A'..=b'Z' => out.extend(
+                NATO[(c - b'A') as usize]
+                    .bytes()
+                    .map(|b| b.to_ascii_uppercase()),
+            ),
+            b'0'..=b'9' => out.extend_from_slice(DIGITS[(c - b'0') as usize].as_bytes()),
+            _ => match symbol(c) {
+                Some(name) => out.extend_from_slice(name.as_bytes()),
+                // Anything else from --special is shown as itself
+                None => out.push(c),
+            },
+        }
+    }
+}
+
+/// Generate `count` passwords and print each with its hint.
+pub fn output(settings: &Settings, count: usize) {
+    let chars = Charset::build(settings);
+    let mut pass = pool::take(max_len(settings));
+    // Labels, password, and up to 13 bytes ("greater-than ") per character
+    let mut line = pool::take(24 + pass.capacity() * 14);
+
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+
+    for n in 0..count {
+        next_password(settings, &chars, n, &mut pass);
+
+        if n > 0 {
+            line.push(b'\n');
+        }
+        line.extend_from_slice(b"password: ");
+        line.extend_from_slice(&pass);
+        line.extend_from_slice(b"\nhint:     ");
+        hint_into(&pass, &mut line);
+        line.push(b'\n');
+
+        let _ = out.write_all(&line);
+        line.zeroize();
+        pass.zeroize();
+    }
+
+    let _ = out.flush();
+    crate::rand::shutdown_urandom();
+}
@@ -1,26 +1,52 @@
 //! CLI argument parsing and execution.
 
+mod batch;
+mod board;
 mod bytes;
+mod checksum;
+mod commands;
 mod context;
+mod encode;
 mod flags;
+mod format;
+mod license;
+mod mnemonic;
 mod parse;
+mod pick;
+mod profile;
 pub mod prompts;
 pub mod quiet;
+mod stretch;
+mod template;
+pub mod verbose;
 
 use crate::terminal::clear;
 use crate::tui::print_help;
 use context::Context;
 
+pub use bytes::ByteOptions;
+pub(crate) use bytes::DEFAULT_BLOCK;
 pub use bytes::output as output_bytes;
+pub use bytes::output_key_material;
 pub use bytes::parse_byte_count;
 pub use flags::{CliFlags, CommandMode};
 pub use parse::parse;
 
 /// Run CLI mode with given arguments.
 pub fn run(args: Vec<String>) {
+    if commands::dispatch(&args) {
+        return;
+    }
+
     let mut ctx = match Context::new(args) {
         Ok(c) => c,
         Err(e) => {
+            // Keep the help screen out of pipes and logs
+            if !quiet::is_terminal_output() {
+                prompts::error(&format!("Error: {}", e));
+                eprintln!("See randpass --help for usage.");
+                std::process::exit(1);
+            }
             clear();
             prompts::error(&format!("Error: {}", e));
             print_help();
@@ -1,31 +1,143 @@
+use super::bytes::{DEFAULT_BLOCK, MAX_SALT};
+use super::checksum::Checksum;
+use super::encode::Encoding;
+use super::format::Format;
+use super::license;
+use super::parse_byte_count;
+use super::stretch::Kdf;
 use super::{CliFlags, CommandMode};
+use crate::pass::chain;
+use crate::pass::charset::StartWith;
+use crate::pass::crypt::Scheme;
+use crate::pass::fsync::Fsync;
+use crate::pass::leet::{self, Leet};
+use crate::pass::memorable;
+use crate::pass::passphrase::{Caps, WordSep};
+use crate::pass::policy::preset::PRESETS;
+use crate::pass::policy::{self, Class, Position};
+use crate::rand::failover::{self, Source};
 
 #[derive(Debug)]
 pub enum ParseError {
     InvalidNumber(String),
+    InvalidChoice(String, &'static str),
+    MissingValue(String),
     UnknownArg(String),
+    /// Two flags that can't be given together
+    Conflict(&'static str, &'static str),
 }
 
 impl std::fmt::Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ParseError::InvalidNumber(s) => write!(f, "Invalid number: {}", s),
+            ParseError::InvalidChoice(s, expected) => {
+                write!(f, "Invalid value: {} (expected {})", s, expected)
+            }
+            ParseError::MissingValue(s) => write!(f, "Missing value for {}", s),
             ParseError::UnknownArg(s) => write!(f, "Unknown argument: {}", s),
+            ParseError::Conflict(a, b) => write!(f, "{} cannot be combined with {}", a, b),
         }
     }
 }
 
+/// Flags that leave a saved `-c` command in force: they change how the
+/// run is reported or where settings live, not what it generates, and
+/// `Context` merges them into the saved flags. Any other flag replaces
+/// the saved command.
+const KEEP_SAVED_COMMAND: &[&str] = &[
+    "-q",
+    "--quiet",
+    "-V",
+    "--verbose",
+    "--meta",
+    "--dry-run",
+    "--notify",
+    "--color-classes",
+    "--no-command",
+    "--no-save",
+    "--accessible",
+    "--session-stats",
+];
+
+fn keeps_saved_command(arg: &str) -> bool {
+    KEEP_SAVED_COMMAND.contains(&arg)
+        || (arg.len() > 2 && arg.starts_with('-') && arg[1..].bytes().all(|b| b == b'V'))
+}
+
 pub fn parse(args: &[String]) -> Result<CliFlags, ParseError> {
     let mut flags = CliFlags::default();
     let mut i = 1;
 
     while i < args.len() {
+        if !keeps_saved_command(&args[i]) {
+            flags.explicit = true;
+        }
         match args[i].as_str() {
             "-h" | "--help" => flags.help = true,
             "-v" | "--version" => flags.version = true,
             "-q" | "--quiet" => flags.quiet = true,
+            "-V" | "--verbose" => flags.verbose = flags.verbose.saturating_add(1),
+            // -VV, -VVV: stacked verbose
+            s if s.len() > 2 && s.starts_with('-') && s[1..].bytes().all(|b| b == b'V') => {
+                let count = u8::try_from(s.len() - 1).unwrap_or(u8::MAX);
+                flags.verbose = flags.verbose.saturating_add(count);
+            }
             "--bytes" => flags.bytes = true,
-            "-u" | "--urandom" => flags.urandom = true,
+            "-u" | "--urandom" => flags.rng = Some(vec![Source::Urandom]),
+            "--rng" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--rng".to_string()))?;
+                flags.rng = Some(failover::parse(value).map_err(|_| {
+                    ParseError::InvalidChoice(
+                        value.clone(),
+                        "counter, urandom or hwrng, or a comma-separated list of them",
+                    )
+                })?);
+            }
+            "--meta" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--meta".to_string()))?;
+                if value != "json" {
+                    return Err(ParseError::InvalidChoice(value.clone(), "json"));
+                }
+                flags.meta = true;
+            }
+            "--warmup" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--warmup".to_string()))?;
+                flags.warmup = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|&n| n <= crate::rand::MAX_WARMUP)
+                        .ok_or_else(|| ParseError::InvalidNumber(value.clone()))?,
+                );
+            }
+            "--hwrng-device" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--hwrng-device".to_string()))?;
+                flags.hwrng_device = Some(value.clone());
+            }
+            "--hwrng-read" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--hwrng-read".to_string()))?;
+                flags.hwrng_read = Some(
+                    parse_byte_count(value)
+                        .ok_or_else(|| ParseError::InvalidNumber(value.clone()))?,
+                );
+            }
+            "--prefetch" => flags.prefetch = true,
             "-b" | "--board" => flags.clipboard = true,
             "-s" | "--saved" => flags.saved = true,
             "-d" | "--default" => flags.default = true,
@@ -60,11 +172,16 @@ pub fn parse(args: &[String]) -> Result<CliFlags, ParseError> {
             "-l" | "--length" => {
                 i += 1;
                 if i < args.len() {
-                    flags.length = Some(
-                        args[i]
-                            .parse()
-                            .map_err(|_| ParseError::InvalidNumber(args[i].clone()))?,
-                    );
+                    let length = args[i]
+                        .parse()
+                        .map_err(|_| ParseError::InvalidNumber(args[i].clone()))?;
+                    if length == 0 {
+                        return Err(ParseError::InvalidChoice(
+                            args[i].clone(),
+                            "a length of at least 1",
+                        ));
+                    }
+                    flags.length = Some(length);
                 }
             }
             "-n" | "--number" => {
@@ -82,6 +199,488 @@ pub fn parse(args: &[String]) -> Result<CliFlags, ParseError> {
                     flags.special = Some(args[i].clone());
                 }
             }
+            "--salt" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--salt".to_string()))?;
+                flags.salt = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|n| (1..=MAX_SALT).contains(n))
+                        .ok_or_else(|| {
+                            ParseError::InvalidChoice(value.clone(), "a length from 1 to 1024")
+                        })?,
+                );
+            }
+            "--key" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--key".to_string()))?;
+                flags.key = match value.as_str() {
+                    "128" => Some(128),
+                    "256" => Some(256),
+                    _ => return Err(ParseError::InvalidChoice(value.clone(), "128 or 256")),
+                };
+            }
+            "--key-name" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--key-name".to_string()))?;
+                flags.secret_key = Some(value.clone());
+            }
+            "--stats" => flags.stats = true,
+            "--bs" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--bs".to_string()))?;
+                flags.block_size = Some(
+                    parse_byte_count(value)
+                        .ok_or_else(|| ParseError::InvalidNumber(value.clone()))?,
+                );
+            }
+            "--count" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--count".to_string()))?;
+                flags.block_count = Some(
+                    value
+                        .parse()
+                        .map_err(|_| ParseError::InvalidNumber(value.clone()))?,
+                );
+            }
+            "--direct" => flags.direct = true,
+            "--io-uring" => flags.io_uring = true,
+            "--mmap" => flags.mmap = true,
+            "--fsync" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--fsync".to_string()))?;
+                flags.fsync = Some(Fsync::parse(value).ok_or_else(|| {
+                    ParseError::InvalidChoice(value.clone(), "never, end or interval[:SIZE]")
+                })?);
+            }
+            "--no-health-check" => flags.no_health_check = true,
+            // Handled before parsing (settings::ephemeral::init)
+            "--no-save" => {}
+            // Handled before parsing (terminal::accessible::init)
+            "--accessible" => {}
+            // Handled before parsing (session::init)
+            "--session-stats" => {}
+            "--checksum" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--checksum".to_string()))?;
+                flags.checksum =
+                    Some(Checksum::parse(value).ok_or_else(|| {
+                        ParseError::InvalidChoice(value.clone(), "sha256 or blake3")
+                    })?);
+            }
+            "--split-bytes" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--split-bytes".to_string()))?;
+                flags.split_bytes = Some(
+                    parse_byte_count(value)
+                        .ok_or_else(|| ParseError::InvalidNumber(value.clone()))?,
+                );
+            }
+            "--base64" => flags.encode = Some(Encoding::Base64),
+            "--encode" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--encode".to_string()))?;
+                flags.encode = Some(Encoding::parse(value).ok_or_else(|| {
+                    ParseError::InvalidChoice(value.clone(), "hex, base64 or base32")
+                })?);
+            }
+            "--chain" => flags.chain = true,
+            "--mnemonic" => flags.mnemonic = true,
+            "--board-select" => flags.board_select = true,
+            "--label" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--label".to_string()))?;
+                if value.chars().any(char::is_control) {
+                    return Err(ParseError::InvalidChoice(
+                        value.clone(),
+                        "a label without control characters",
+                    ));
+                }
+                flags.label = Some(value.clone());
+            }
+            "--pick-from" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--pick-from".to_string()))?;
+                flags.pick_from = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|n| (2..=super::pick::MAX).contains(n))
+                        .ok_or_else(|| {
+                            ParseError::InvalidChoice(value.clone(), "a count from 2 to 1000")
+                        })?,
+                );
+            }
+            "--start-with" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--start-with".to_string()))?;
+                flags.start_with = Some(StartWith::parse(value).ok_or_else(|| {
+                    ParseError::InvalidChoice(value.clone(), "letter, lower, upper or alnum")
+                })?);
+            }
+            "--pattern" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--pattern".to_string()))?;
+                flags.pattern = Some(value.clone());
+            }
+            "--pattern-name" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--pattern-name".to_string()))?;
+                flags.pattern_name = Some(value.clone());
+            }
+            "--acronym" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--acronym".to_string()))?;
+                flags.acronym = Some(value.clone());
+            }
+            "--pad" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--pad".to_string()))?;
+                let valid = value == "random"
+                    || matches!(value.as_bytes(), [c] if c.is_ascii_graphic() || *c == b' ');
+                if !valid {
+                    return Err(ParseError::InvalidChoice(
+                        value.clone(),
+                        "one ASCII character or random",
+                    ));
+                }
+                flags.pad = Some(value.clone());
+            }
+            "--pad-to" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--pad-to".to_string()))?;
+                flags.pad_to = Some(
+                    value
+                        .parse()
+                        .map_err(|_| ParseError::InvalidNumber(value.clone()))?,
+                );
+            }
+            "--license-key" => {
+                // Optional [GROUPS]x[LEN]
+                let spec = args.get(i + 1).and_then(|a| license::parse_spec(a));
+                if spec.is_some() {
+                    i += 1;
+                } else if let Some(next) = args.get(i + 1).filter(|a| a.contains('x')) {
+                    return Err(ParseError::InvalidChoice(
+                        next.clone(),
+                        "[GROUPS]x[LEN], e.g. 4x5",
+                    ));
+                }
+                flags.license_key =
+                    Some(spec.unwrap_or((license::DEFAULT_GROUPS, license::DEFAULT_LEN)));
+            }
+            "--check-char" => flags.check_char = true,
+            "--chain-seed" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--chain-seed".to_string()))?;
+                flags.chain = true;
+                flags.chain_seed =
+                    Some(chain::parse_seed(value).ok_or_else(|| {
+                        ParseError::InvalidChoice(value.clone(), "64 hex digits")
+                    })?);
+            }
+            "--hash" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--hash".to_string()))?;
+                flags.hash = Some(Scheme::parse(value).ok_or_else(|| {
+                    ParseError::InvalidChoice(value.clone(), "sha512-crypt or yescrypt")
+                })?);
+            }
+            "--template" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--template".to_string()))?;
+                flags.template = Some(value.clone());
+            }
+            "--for-each" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--for-each".to_string()))?;
+                flags.for_each = Some(value.clone());
+            }
+            "--policy" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--policy".to_string()))?;
+                flags.policy = Some(value.clone());
+            }
+            "--min-entropy" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--min-entropy".to_string()))?;
+                flags.min_entropy = Some(
+                    value
+                        .parse()
+                        .map_err(|_| ParseError::InvalidNumber(value.clone()))?,
+                );
+            }
+            "--allow-weak" => flags.allow_weak = true,
+            "--profile" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--profile".to_string()))?;
+                flags.profile = Some(value.clone());
+            }
+            "--forbid" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--forbid".to_string()))?;
+                flags.forbid = Some(value.clone());
+            }
+            "--must-match" | "--must-not-match" => {
+                let flag = &args[i];
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue(flag.clone()))?;
+                match flag.as_str() {
+                    "--must-match" => flags.must_match.push(value.clone()),
+                    _ => flags.must_not_match.push(value.clone()),
+                }
+            }
+            "--lower-at" | "--upper-at" | "--digit-at" | "--special-at" => {
+                let flag = &args[i];
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue(flag.clone()))?;
+                let class = match flag.as_str() {
+                    "--lower-at" => Class::Lowercase,
+                    "--upper-at" => Class::Uppercase,
+                    "--digit-at" => Class::Digits,
+                    _ => Class::Special,
+                };
+                flags.positions.extend(
+                    Position::parse(class, value)
+                        .ok_or_else(|| ParseError::InvalidChoice(value.clone(), policy::SPECS))?,
+                );
+            }
+            "--preset" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--preset".to_string()))?;
+                if !PRESETS.iter().any(|(name, _, _)| name == value) {
+                    return Err(ParseError::InvalidChoice(
+                        value.clone(),
+                        "aws-iam, active-directory, mysql or pin4",
+                    ));
+                }
+                flags.preset = Some(value.clone());
+            }
+            "--format" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--format".to_string()))?;
+                flags.format = Some(Format::parse(value).ok_or_else(|| {
+                    ParseError::InvalidChoice(value.clone(), "k8s-secret or env")
+                })?);
+            }
+            "--name" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--name".to_string()))?;
+                flags.secret_name = Some(value.clone());
+            }
+            "--stretch" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--stretch".to_string()))?;
+                flags.stretch = Some(Kdf::parse(value).ok_or_else(|| {
+                    ParseError::InvalidChoice(
+                        value.clone(),
+                        "pbkdf2[:ITERATIONS] or scrypt[:LOG_N,R,P]",
+                    )
+                })?);
+            }
+            "--words" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--words".to_string()))?;
+                flags.words = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|&n| n > 0)
+                        .ok_or_else(|| ParseError::InvalidNumber(value.clone()))?,
+                );
+            }
+            "--pseudo-words" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--pseudo-words".to_string()))?;
+                flags.pseudo_words = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|&n| n > 0)
+                        .ok_or_else(|| ParseError::InvalidNumber(value.clone()))?,
+                );
+            }
+            "--memorable" => {
+                // Optional level
+                let level = args.get(i + 1).and_then(|a| a.parse().ok());
+                if let Some(level) = level {
+                    i += 1;
+                    if !memorable::valid_level(level) {
+                        return Err(ParseError::InvalidChoice(
+                            args[i].clone(),
+                            "a level, 1 to 4",
+                        ));
+                    }
+                }
+                flags.memorable = Some(level.unwrap_or(memorable::DEFAULT_LEVEL));
+            }
+            "--pronounceable" => {
+                // Optional syllable count
+                let syllables = args
+                    .get(i + 1)
+                    .and_then(|a| a.parse().ok())
+                    .filter(|&n: &usize| n > 0);
+                if syllables.is_some() {
+                    i += 1;
+                }
+                flags.pronounceable = Some(syllables);
+            }
+            "--word-sep" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--word-sep".to_string()))?;
+                flags.word_sep = Some(WordSep::parse(value).ok_or_else(|| {
+                    ParseError::InvalidChoice(value.clone(), "one character, none or camel")
+                })?);
+            }
+            "--list" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--list".to_string()))?;
+                flags.list = Some(value.clone());
+            }
+            "--wordlist" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--wordlist".to_string()))?;
+                flags.wordlist = Some(value.clone());
+            }
+            "--lang" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--lang".to_string()))?;
+                flags.lang = Some(value.clone());
+            }
+            "--caps" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--caps".to_string()))?;
+                flags.caps = Some(Caps::parse(value).ok_or_else(|| {
+                    ParseError::InvalidChoice(value.clone(), "none, first or random")
+                })?);
+            }
+            "--leet" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--leet".to_string()))?;
+                flags.leet = Some(
+                    Leet::parse(value)
+                        .ok_or_else(|| ParseError::InvalidChoice(value.clone(), leet::CHOICES))?,
+                );
+            }
+            "--digits" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--digits".to_string()))?;
+                flags.digits = Some(
+                    value
+                        .parse()
+                        .map_err(|_| ParseError::InvalidNumber(value.clone()))?,
+                );
+            }
+            "--symbols" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--symbols".to_string()))?;
+                flags.symbols = Some(
+                    value
+                        .parse()
+                        .map_err(|_| ParseError::InvalidNumber(value.clone()))?,
+                );
+            }
+            "--dry-run" => flags.dry_run = true,
+            "--notify" => flags.notify = true,
+            "--force" => flags.force = true,
+            "--no-command" => flags.no_command = true,
+            "--color-classes" => flags.color_classes = true,
+            "--countdown" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| ParseError::MissingValue("--countdown".to_string()))?;
+                flags.countdown = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|&n| n <= crate::pass::output::MAX_COUNTDOWN)
+                        .ok_or_else(|| ParseError::InvalidNumber(value.clone()))?,
+                );
+            }
             "-o" | "--output" => {
                 // Check if next arg exists and isn't another flag
                 if i + 1 < args.len() && !args[i + 1].starts_with('-') {
@@ -97,5 +696,29 @@ pub fn parse(args: &[String]) -> Result<CliFlags, ParseError> {
         i += 1;
     }
 
+    // --count sets the byte total, so an -n beside it would be ignored
+    if flags.block_count.is_some() && flags.number_raw.is_some() {
+        return Err(ParseError::Conflict("--count", "-n"));
+    }
+
+    // --count is a limit in blocks; a product past usize would mean none
+    if let Some(count) = flags.block_count
+        && flags
+            .block_size
+            .unwrap_or(DEFAULT_BLOCK)
+            .checked_mul(count)
+            .is_none()
+    {
+        return Err(ParseError::InvalidNumber(format!(
+            "--count {} (--bs times --count overflows)",
+            count
+        )));
+    }
+
+    // --key prints key material; --format names its key with --key-name
+    if flags.key.is_some() && flags.format.is_some() {
+        return Err(ParseError::Conflict("--key", "--format"));
+    }
+
     Ok(flags)
 }
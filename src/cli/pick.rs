@@ -0,0 +1,106 @@
+//! `--pick-from N`: generate N candidates, let the user choose the one
+//! that looks easiest to type in the picker (`tui::pick`), and print or
+//! copy only that one. Every candidate is zeroized afterwards, the choice
+//! included.
+
+use std::io::Write;
+use std::ops::Range;
+
+use copypasta::{ClipboardContext, ClipboardProvider};
+use zeroize::Zeroize;
+
+use super::prompts;
+use crate::pass::charset::Charset;
+use crate::pass::history::Recorder;
+use crate::pass::pool::{self, PoolBuf};
+use crate::pass::{ClipBuf, max_len, next_password};
+use crate::settings::Settings;
+
+/// Most candidates offered at once.
+pub const MAX: usize = 1000;
+
+/// Passwords generated back to back into one locked buffer, zeroized on
+/// drop.
+pub(super) struct Candidates {
+    all: PoolBuf,
+    ranges: Vec<Range<usize>>,
+}
+
+impl Candidates {
+    pub fn generate(settings: &Settings, count: usize) -> Self {
+        let chars = Charset::build(settings);
+        let mut pass = pool::take(max_len(settings));
+        let mut all = pool::take(count * max_len(settings));
+        let mut ranges = Vec::with_capacity(count);
+        for n in 0..count {
+            next_password(settings, &chars, n, &mut pass);
+            let start = all.len();
+            all.extend_from_slice(&pass);
+            ranges.push(start..all.len());
+            pass.zeroize();
+        }
+        crate::rand::shutdown_urandom();
+        Self { all, ranges }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn get(&self, i: usize) -> &[u8] {
+        &self.all[self.ranges[i].clone()]
+    }
+}
+
+/// Put `password` on the clipboard.
+pub(super) fn copy(ctx: &mut ClipboardContext, password: &[u8]) -> Result<(), String> {
+    let mut payload = ClipBuf::with_capacity(password.len());
+    payload.push(password);
+    ctx.set_contents(payload.into_string())
+        .map_err(|e| e.to_string())?;
+    if let Ok(mut retrieved) = ctx.get_contents() {
+        retrieved.zeroize();
+    }
+    Ok(())
+}
+
+/// Add `password` to the history, when it's on.
+pub(super) fn remember(settings: &Settings, password: &[u8]) {
+    let Some(mut history) = Recorder::new(settings, 1) else {
+        return;
+    };
+    history.add(password);
+    if let Err(e) = history.finish() {
+        prompts::warn(&format!("Warning: history not written: {}", e));
+    }
+}
+
+/// Generate `count` candidates and output the one picked: to the
+/// clipboard when `settings.to_clipboard`, otherwise to stdout. Err when
+/// nothing was picked.
+pub fn output(
+    settings: &Settings,
+    count: usize,
+    clipboard: Option<&mut ClipboardContext>,
+) -> Result<(), String> {
+    let batch = Candidates::generate(settings, count);
+    let candidates: Vec<&[u8]> = (0..batch.len()).map(|i| batch.get(i)).collect();
+    let Some(choice) = crate::tui::pick(&candidates) else {
+        return Err("Nothing picked".to_string());
+    };
+    let picked = candidates[choice];
+
+    match clipboard {
+        Some(ctx) if settings.to_clipboard => match copy(ctx, picked) {
+            Ok(()) => prompts::clipboard_copied(),
+            Err(e) => prompts::clipboard_error(&e),
+        },
+        _ => {
+            let mut out = crate::pass::stdout_writer(settings);
+            let _ = out.write_all(picked).and_then(|_| out.write_all(b"\n"));
+            let _ = out.flush();
+        }
+    }
+    remember(settings, picked);
+    Ok(())
+}
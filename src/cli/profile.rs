@@ -0,0 +1,91 @@
+//! Named profiles (`--profile NAME`): a saved set of flags, typically a
+//! `--policy`, kept as `~/.config/randpass/profiles/NAME` with one
+//! argument per line, so arguments with spaces survive.
+//!
+//! Precedence, highest first: flags on the command line, then the
+//! profile, then the global config (`policy_file` and the other saved
+//! settings). Profile flags are parsed ahead of the command line, so a
+//! value given on both is taken from the command line; a `--policy` or
+//! `--preset` on the command line replaces the profile's rules outright.
+
+use std::path::PathBuf;
+
+use super::{CliFlags, parse};
+
+/// Flags that pick the rule set; the command line's replace the profile's.
+const RULE_FLAGS: &[&str] = &["--policy", "--preset"];
+
+pub fn dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".into());
+    PathBuf::from(home).join(".config/randpass/profiles")
+}
+
+pub fn path(name: &str) -> PathBuf {
+    dir().join(name)
+}
+
+/// Profile names: letters, digits, '.', '_' and '-', not starting with a dot.
+pub fn check_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"._-".contains(&b));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid profile name '{}' (letters, digits, '.', '_' and '-')",
+            name
+        ))
+    }
+}
+
+/// The saved arguments of profile `name`.
+pub fn load(name: &str) -> Result<Vec<String>, String> {
+    check_name(name)?;
+    let path = path(name);
+    let text = std::fs::read_to_string(&path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => format!(
+            "no profile named {} (randpass profile set {} [FLAGS] creates it)",
+            name, name
+        ),
+        _ => format!("{}: {}", path.display(), e),
+    })?;
+    Ok(text
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Flags for a command line naming `--profile`: the profile's arguments
+/// parsed first, then `args` on top.
+pub fn merge(name: &str, args: &[String]) -> Result<CliFlags, String> {
+    let mut saved = load(name)?;
+    if saved.iter().any(|a| a == "--profile") {
+        return Err(format!("profile {} can't name another profile", name));
+    }
+    if args.iter().any(|a| RULE_FLAGS.contains(&a.as_str())) {
+        saved = without_rules(saved);
+    }
+
+    let mut combined = vec![args[0].clone()];
+    combined.extend(saved);
+    combined.extend(args[1..].iter().cloned());
+    parse(&combined).map_err(|e| format!("profile {}: {}", name, e))
+}
+
+/// `args` without `--policy` / `--preset` and their values.
+fn without_rules(args: Vec<String>) -> Vec<String> {
+    let mut kept = Vec::with_capacity(args.len());
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if RULE_FLAGS.contains(&arg.as_str()) {
+            args.next();
+        } else {
+            kept.push(arg);
+        }
+    }
+    kept
+}
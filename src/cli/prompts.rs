@@ -2,7 +2,7 @@
 
 use std::io::Write;
 
-use super::quiet;
+use super::{quiet, verbose};
 
 // ANSI color codes
 const YELLOW: &str = "\x1b[33m";
@@ -21,6 +21,26 @@ pub fn error(msg: &str) {
     eprintln!("{RED}{msg}{RESET}");
 }
 
+/// Print an error message and exit with status 1
+pub fn fail(msg: &str) -> ! {
+    error(msg);
+    std::process::exit(1);
+}
+
+/// Print a diagnostic to stderr - only in verbose mode
+pub fn verbose(msg: &str) {
+    if verbose::enabled() {
+        eprintln!("{msg}");
+    }
+}
+
+/// Print a detailed diagnostic to stderr - only at `-VV` and above
+pub fn debug(msg: &str) {
+    if verbose::level() >= 2 {
+        eprintln!("{msg}");
+    }
+}
+
 /// Print mlock failure warning with fix instructions
 pub fn mlock_failed() {
     warn("Warning: mlock failed - entropy pool may be swapped to disk.");
@@ -46,13 +66,21 @@ pub fn mlock_continue_prompt() -> bool {
         }
     }
 
-    eprintln!("Aborted. Using hardware RNG instead.");
+    eprintln!("Aborted. Not using the pool.");
     false
 }
 
-/// Print urandom unavailable warning
-pub fn urandom_unavailable() {
-    warn("Warning: /dev/urandom not available, using hardware entropy");
+/// Print an entropy source failover to stderr - NOT suppressed, the run
+/// is no longer drawing from the source that was asked for
+pub fn rng_failover(from: &str, reason: &str, to: &str) {
+    eprintln!("{YELLOW}RNG: {from} failed ({reason}); failing over to {to}{RESET}");
+}
+
+/// Print the seed of a `--chain` run to stderr - NOT suppressed, the run
+/// cannot be reproduced without it
+pub fn chain_seed(hex: &str) {
+    eprintln!("Chain seed: {hex}");
+    eprintln!("Keep it to re-derive this batch with --chain-seed <SEED> and the same flags.");
 }
 
 /// Print clipboard copied confirmation - suppressed in quiet mode
@@ -62,16 +90,30 @@ pub fn clipboard_copied() {
     }
 }
 
+/// Ask which `--board-select` entry to copy - NOT suppressed (the run waits on it)
+pub fn board_prompt(count: usize) {
+    eprint!("Copy which (1-{count}, Enter to finish)? ");
+    let _ = std::io::stderr().flush();
+}
+
+/// Print `--board-select` copy confirmation - suppressed in quiet mode
+pub fn board_copied(n: usize) {
+    if !quiet::enabled() {
+        eprintln!("Copied {n} to the clipboard");
+    }
+}
+
 /// Print clipboard error - NOT suppressed (errors are always shown)
 pub fn clipboard_error(err: &str) {
     eprintln!("Clipboard error: {err}");
 }
 
 /// Prompt user when clipboard is unavailable. Returns true to fallback to terminal, false to abort.
-/// In quiet/non-interactive mode, silently falls back to terminal.
+/// Without a tty on stdin, falls back with a warning (silently in quiet mode).
 pub fn clipboard_fallback_prompt() -> bool {
     if quiet::skip_prompt() {
-        return true; // Fallback silently
+        warn("Clipboard unavailable, printing to stdout instead");
+        return true;
     }
 
     eprint!("Clipboard unavailable. Print to terminal instead? [Y/n]: ");
@@ -92,9 +134,90 @@ pub fn clipboard_fallback_prompt() -> bool {
     false
 }
 
+/// Ask the user to type the device name back before `wipe` overwrites it.
+/// Always prompts, even in quiet mode. Returns true only on an exact match.
+pub fn wipe_confirm(device: &str, size: &str) -> bool {
+    eprintln!("{RED}This will overwrite ALL data on {device} ({size}).{RESET}");
+    eprint!("Type the device name ({device}) to continue: ");
+    let _ = std::io::stderr().flush();
+
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input).is_ok() && input.trim_end_matches(['\r', '\n']) == device
+}
+
+/// Print wipe completion summary - suppressed in quiet mode
+pub fn wipe_done(device: &str, size: &str) {
+    if !quiet::enabled() {
+        eprintln!("{size} written to {device} and synced.");
+    }
+}
+
+/// Print a `--checksum` digest line for stdout output to stderr - NOT
+/// suppressed, it is requested output
+pub fn checksum_line(hex: &str, name: &str) {
+    eprintln!("{hex}  {name}");
+}
+
+/// Print where the `--checksum` digest file went - suppressed in quiet mode
+pub fn checksum_written(path: &str) {
+    if !quiet::enabled() {
+        eprintln!("Checksum \u{2192} {path}");
+    }
+}
+
+/// Print keyfile creation summary - suppressed in quiet mode
+pub fn keyfile_written(path: &str, size: &str) {
+    if !quiet::enabled() {
+        eprintln!("{size} keyfile \u{2192} {path} (0400, synced)");
+    }
+}
+
+/// Print `randpass dedupe` summary - suppressed in quiet mode
+pub fn dedupe_done(read: &str, unique: &str, removed: &str, dest: &str) {
+    if !quiet::enabled() {
+        eprintln!("{read} lines \u{2192} {unique} unique in {dest} ({removed} duplicates removed)");
+    }
+}
+
+/// Print the saved command a bare run is about to apply - suppressed in
+/// quiet mode
+pub fn saved_command(command: &str) {
+    if !quiet::enabled() {
+        eprintln!("Using saved command: {command} (--no-command to skip)");
+    }
+}
+
 /// Print password output summary - suppressed in quiet mode
 pub fn passwords_written(count: usize, path: &str) {
     if !quiet::enabled() {
         println!("{count} password(s) \u{2192} {path}");
     }
 }
+
+/// Read a line from the terminal with echo disabled. Falls back to a plain
+/// stdin read when stdin is not a tty. Returns None on read error.
+pub fn hidden_input(prompt: &str) -> Option<String> {
+    eprint!("{prompt}");
+    let _ = std::io::stderr().flush();
+
+    let mut saved: libc::termios = unsafe { std::mem::zeroed() };
+    let is_tty = unsafe { libc::tcgetattr(0, &mut saved) } == 0;
+    if is_tty {
+        let mut silent = saved;
+        silent.c_lflag &= !libc::ECHO;
+        unsafe { libc::tcsetattr(0, libc::TCSANOW, &silent) };
+    }
+
+    let mut input = String::new();
+    let result = std::io::stdin().read_line(&mut input);
+
+    if is_tty {
+        unsafe { libc::tcsetattr(0, libc::TCSANOW, &saved) };
+        eprintln!();
+    }
+
+    result.ok()?;
+    let len = input.trim_end_matches(['\r', '\n']).len();
+    input.truncate(len);
+    Some(input)
+}
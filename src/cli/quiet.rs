@@ -15,9 +15,14 @@ pub fn enabled() -> bool {
     QUIET.load(Ordering::Relaxed)
 }
 
-/// Check if stdin is a tty (interactive)
+/// Check if stdin is a tty (interactive), or a script stands in for one
 pub fn is_interactive() -> bool {
-    unsafe { libc::isatty(0) == 1 }
+    crate::terminal::console::scripted() || unsafe { libc::isatty(0) == 1 }
+}
+
+/// Check if stdout is a tty (not piped or redirected)
+pub fn is_terminal_output() -> bool {
+    unsafe { libc::isatty(1) == 1 }
 }
 
 /// Returns true if we should skip interactive prompts.
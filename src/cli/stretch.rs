@@ -0,0 +1,124 @@
+//! `--stretch`: derive a reproducible key from each generated password.
+
+use std::io::Write;
+
+use sha2::Sha256;
+use zeroize::Zeroize;
+
+use super::encode::{Encoding, encode_into};
+use crate::pass::charset::Charset;
+use crate::pass::{max_len, next_password, pool};
+use crate::rand::Rand;
+use crate::settings::Settings;
+
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+
+/// Key derivation function and its cost parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kdf {
+    Pbkdf2 { iterations: u32 },
+    Scrypt { log_n: u8, r: u32, p: u32 },
+}
+
+impl Kdf {
+    /// Parse `pbkdf2[:ITERATIONS]` or `scrypt[:LOG_N,R,P]`.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (name, params) = match spec.split_once(':') {
+            Some((name, params)) => (name, Some(params)),
+            None => (spec, None),
+        };
+
+        match name {
+            "pbkdf2" => {
+                let iterations = match params {
+                    Some(p) => p.parse().ok().filter(|&n| n > 0)?,
+                    None => 600_000,
+                };
+                Some(Kdf::Pbkdf2 { iterations })
+            }
+            "scrypt" => {
+                let (log_n, r, p) = match params {
+                    Some(params) => {
+                        let mut it = params.split(',');
+                        let log_n = it.next()?.parse().ok()?;
+                        let r = it.next().map_or(Some(8), |v| v.parse().ok())?;
+                        let p = it.next().map_or(Some(1), |v| v.parse().ok())?;
+                        if it.next().is_some() {
+                            return None;
+                        }
+                        (log_n, r, p)
+                    }
+                    None => (17, 8, 1),
+                };
+                scrypt::Params::new(log_n, r, p, KEY_LEN).ok()?;
+                Some(Kdf::Scrypt { log_n, r, p })
+            }
+            _ => None,
+        }
+    }
+
+    /// Canonical spec string, printed alongside the key so it can be re-derived.
+    fn describe(&self) -> String {
+        match self {
+            Kdf::Pbkdf2 { iterations } => format!("pbkdf2-sha256:{}", iterations),
+            Kdf::Scrypt { log_n, r, p } => format!("scrypt:{},{},{}", log_n, r, p),
+        }
+    }
+
+    fn derive(&self, password: &[u8], salt: &[u8], key: &mut [u8]) {
+        match *self {
+            Kdf::Pbkdf2 { iterations } => {
+                pbkdf2::pbkdf2_hmac::<Sha256>(password, salt, iterations, key);
+            }
+            Kdf::Scrypt { log_n, r, p } => {
+                // Params were validated in parse()
+                let params = scrypt::Params::new(log_n, r, p, key.len()).expect("scrypt params");
+                scrypt::scrypt(password, salt, &params, key).expect("scrypt output length");
+            }
+        }
+    }
+}
+
+/// Generate `count` passwords and print each with its salt and derived key.
+pub fn output(settings: &Settings, count: usize, kdf: Kdf) {
+    let chars = Charset::build(settings);
+    let mut pass = pool::take(max_len(settings));
+    let mut salt = [0u8; SALT_LEN];
+    let mut key = [0u8; KEY_LEN];
+    let kdf_line = kdf.describe();
+    // Labels, password, kdf, and salt and key in hex
+    let mut line = pool::take(64 + pass.capacity() + kdf_line.len() + 2 * (SALT_LEN + KEY_LEN));
+
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+
+    for n in 0..count {
+        next_password(settings, &chars, n, &mut pass);
+        for chunk in salt.chunks_mut(8) {
+            chunk.copy_from_slice(&(Rand::get() as u64).to_le_bytes()[..chunk.len()]);
+        }
+        kdf.derive(&pass, &salt, &mut key);
+
+        if n > 0 {
+            line.push(b'\n');
+        }
+        line.extend_from_slice(b"password: ");
+        line.extend_from_slice(&pass);
+        line.extend_from_slice(b"\nkdf:      ");
+        line.extend_from_slice(kdf_line.as_bytes());
+        line.extend_from_slice(b"\nsalt:     ");
+        encode_into(&salt, Encoding::Hex, &mut line);
+        line.extend_from_slice(b"\nkey:      ");
+        encode_into(&key, Encoding::Hex, &mut line);
+        line.push(b'\n');
+
+        let _ = out.write_all(&line);
+        line.zeroize();
+        pass.zeroize();
+        key.zeroize();
+    }
+
+    let _ = out.flush();
+    crate::rand::shutdown_urandom();
+}
@@ -0,0 +1,206 @@
+//! `--template '{index}\t{password}'`: each password written through a
+//! template, for tools that want more than one password per line.
+//! Placeholders are `{index}` (from 1), `{password}`, `{entropy}` (bits),
+//! `{length}` (characters), `{timestamp}` (RFC 3339, UTC) and, with
+//! `--for-each`, `{record}`. `\t`, `\n` and `\\` are escapes, `{{` and
+//! `}}` literal braces.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use zeroize::Zeroize;
+
+use crate::pass::audit::timestamp;
+use crate::pass::charset::Charset;
+use crate::pass::{SecureBufWriter, file_writer, max_len, next_password, pool};
+use crate::settings::Settings;
+
+const PLACEHOLDERS: &str = "index, password, entropy, length, timestamp or record";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Index,
+    Password,
+    Entropy,
+    Length,
+    Timestamp,
+    Record,
+}
+
+#[derive(Debug, Clone)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('t') => text.push('\t'),
+                    Some('n') => text.push('\n'),
+                    Some('\\') => text.push('\\'),
+                    Some(other) => {
+                        return Err(format!(
+                            "Unknown escape in --template: \\{} (\\t, \\n or \\\\)",
+                            other
+                        ));
+                    }
+                    None => return Err("--template ends with a lone \\".to_string()),
+                },
+                '}' => match chars.next() {
+                    Some('}') => text.push('}'),
+                    _ => return Err("Unmatched } in --template (}} for a brace)".to_string()),
+                },
+                '{' => {
+                    let rest = chars.as_str();
+                    if let Some(after) = rest.strip_prefix('{') {
+                        text.push('{');
+                        chars = after.chars();
+                        continue;
+                    }
+                    let Some(end) = rest.find('}') else {
+                        return Err("Unclosed { in --template ({{ for a brace)".to_string());
+                    };
+                    let part = match &rest[..end] {
+                        "index" => Part::Index,
+                        "password" => Part::Password,
+                        "entropy" => Part::Entropy,
+                        "length" => Part::Length,
+                        "timestamp" => Part::Timestamp,
+                        "record" => Part::Record,
+                        other => {
+                            return Err(format!(
+                                "Unknown placeholder in --template: {{{}}} ({})",
+                                other, PLACEHOLDERS
+                            ));
+                        }
+                    };
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(part);
+                    chars = rest[end + 1..].chars();
+                }
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        if !parts.contains(&Part::Password) {
+            return Err("--template needs a {password} placeholder".to_string());
+        }
+        Ok(Self { parts })
+    }
+
+    /// Whether the template uses `{record}`, which needs `--for-each`.
+    pub fn wants_record(&self) -> bool {
+        self.parts.contains(&Part::Record)
+    }
+
+    /// Append password `index` (0-based) rendered through the template to
+    /// `line`, followed by a newline.
+    fn render(&self, line: &mut Vec<u8>, index: usize, password: &[u8], record: &str, bits: f64) {
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => line.extend_from_slice(text.as_bytes()),
+                Part::Index => line.extend_from_slice((index + 1).to_string().as_bytes()),
+                Part::Password => line.extend_from_slice(password),
+                Part::Entropy => line.extend_from_slice(format!("{:.1}", bits).as_bytes()),
+                Part::Length => {
+                    let length =
+                        std::str::from_utf8(password).map_or(password.len(), |p| p.chars().count());
+                    line.extend_from_slice(length.to_string().as_bytes());
+                }
+                Part::Timestamp => {
+                    let now = SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |d| d.as_secs());
+                    line.extend_from_slice(timestamp(now).as_bytes());
+                }
+                Part::Record => line.extend_from_slice(record.as_bytes()),
+            }
+        }
+        line.push(b'\n');
+    }
+
+    /// Bytes of everything but the passwords and records in one line, an
+    /// upper bound.
+    fn overhead(&self) -> usize {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.len(),
+                Part::Password | Part::Record => 0,
+                _ => 32,
+            })
+            .sum::<usize>()
+            + 1
+    }
+}
+
+/// Write `count` passwords, or one per record, through `template` to the
+/// output file or stdout. `bits` is the entropy of each password.
+pub fn output(
+    settings: &Settings,
+    template: &Template,
+    records: Option<&[String]>,
+    count: usize,
+    bits: f64,
+) -> Result<(), String> {
+    let total = records.map_or(count, |r| r.len());
+    let mut file = if settings.output_file_path.is_empty() {
+        None
+    } else {
+        Some(file_writer(
+            OpenOptions::new()
+                .read(settings.mmap)
+                .create(true)
+                .append(true)
+                .mode(0o600)
+                .open(&settings.output_file_path)
+                .map_err(|e| format!("{}: {}", settings.output_file_path, e))?,
+            settings,
+            None,
+        ))
+    };
+    let mut out = SecureBufWriter::new(std::io::stdout().lock());
+
+    let chars = Charset::build(settings);
+    let mut pass = pool::take(max_len(settings));
+    let repeats = template
+        .parts
+        .iter()
+        .filter(|p| matches!(p, Part::Password | Part::Record))
+        .count();
+    let longest = records.map_or(0, |r| r.iter().map(String::len).max().unwrap_or(0));
+    let mut line = pool::take(template.overhead() + repeats * (pass.capacity().max(longest)));
+
+    for n in 0..total {
+        next_password(settings, &chars, n, &mut pass);
+        let record = records.map_or("", |r| r[n].as_str());
+        template.render(&mut line, n, &pass, record, bits);
+        pass.zeroize();
+        let written = match file {
+            Some(ref mut f) => f.write_all(&line),
+            None => out.write_all(&line),
+        };
+        line.zeroize();
+        written.map_err(|e| e.to_string())?;
+    }
+    let _ = out.flush();
+    drop(out);
+
+    crate::rand::shutdown_urandom();
+    if let Some(f) = file {
+        f.finish().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,25 @@
+//! Global verbose mode state for CLI.
+//!
+//! `-V` reports decisions: which config layers applied, the RNG backend,
+//! what a policy cost. `-VV` adds the resolved settings, buffer pool
+//! events and timings.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Global verbose level - 0 is off, each `-V` adds one
+static LEVEL: AtomicU8 = AtomicU8::new(0);
+
+/// Set the verbose level (report what generation did)
+pub fn set(level: u8) {
+    LEVEL.store(level, Ordering::SeqCst);
+}
+
+/// Current verbose level
+pub fn level() -> u8 {
+    LEVEL.load(Ordering::Relaxed)
+}
+
+/// Check if verbose mode is enabled
+pub fn enabled() -> bool {
+    level() >= 1
+}
@@ -1,6 +1,24 @@
 //! Exit handling: signal handlers, cleanup, and graceful shutdown.
+//!
+//! A signal normally exits straight from its handler, and the atexit
+//! cleanup runs there. Work that isn't async-signal-safe, such as the
+//! `--notify` D-Bus call or the `--session-stats` summary, is left to a
+//! watcher thread: the handler only sets a flag and wakes it through a
+//! pipe, and the watcher does the work and then hands the signal back to
+//! the main thread to exit.
 
-use crate::rand;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::{Once, OnceLock};
+
+use crate::terminal::notify;
+use crate::{rand, session};
+
+/// Set by the first SIGINT/SIGTERM/SIGHUP; the next one exits at once.
+static SIGNALED: AtomicBool = AtomicBool::new(false);
+/// Write end of the watcher's pipe, -1 until `watch_signals`.
+static WAKE: AtomicI32 = AtomicI32::new(-1);
+/// The thread that installed the handlers, where the exit happens.
+static MAIN_THREAD: OnceLock<libc::pthread_t> = OnceLock::new();
 
 /// Reset terminal to sane state using termios directly
 fn reset_terminal_termios() {
@@ -27,16 +45,85 @@ extern "C" fn cleanup_on_exit() {
             );
         }
     }
+    // --session-stats, before the source is reset
+    crate::session::report();
     if rand::is_urandom_enabled() {
         rand::disable_urandom();
     }
+    if rand::is_hwrng_enabled() {
+        rand::disable_hwrng();
+    }
+    rand::prefetch::wipe();
+    // Don't leave a --mmap file padded past what was written
+    crate::pass::trim_mmap_on_exit();
+    // A --notify run that never finished was cut short
+    notify::on_exit();
     // Always zeroize hardware RNG state
     rand::zeroize_state();
 }
 
-/// Signal handler for SIGINT/SIGTERM/SIGHUP - exit cleanly, atexit handles cleanup
-extern "C" fn signal_handler(_: libc::c_int) {
-    unsafe { libc::exit(130) }
+/// Signal handler for SIGINT/SIGTERM/SIGHUP - exit cleanly, atexit handles
+/// cleanup. While a `--notify` run is watched or a `--session-stats`
+/// summary is due, the exit waits for the watcher: the bell rings here,
+/// and the notification and summary go out from there.
+extern "C" fn signal_handler(sig: libc::c_int) {
+    let wake = WAKE.load(Ordering::SeqCst);
+    let pending = notify::watching() || session::reporting();
+    if SIGNALED.swap(true, Ordering::SeqCst) || wake < 0 || !pending {
+        unsafe { libc::exit(130) }
+    }
+    if notify::watching() {
+        notify::signal_bell();
+    }
+    unsafe {
+        let byte = sig as u8;
+        if libc::write(wake, &byte as *const u8 as *const libc::c_void, 1) != 1 {
+            libc::exit(130);
+        }
+    }
+}
+
+/// Start the thread that does a signal's exit work outside the handler.
+/// Called when a run needs it; later calls do nothing.
+pub fn watch_signals() {
+    static STARTED: Once = Once::new();
+    STARTED.call_once(|| {
+        let mut fds = [0; 2];
+        if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+            return;
+        }
+        let read = fds[0];
+        let spawned = std::thread::Builder::new()
+            .name("signals".to_string())
+            .spawn(move || watcher(read));
+        if spawned.is_ok() {
+            WAKE.store(fds[1], Ordering::SeqCst);
+        }
+    });
+}
+
+/// Wait for the handler's wake-up, announce the stopped run and print the
+/// session summary, then send the signal back to the main thread, whose
+/// handler now exits.
+fn watcher(read: libc::c_int) {
+    let mut sig = 0u8;
+    loop {
+        let n = unsafe { libc::read(read, &mut sig as *mut u8 as *mut libc::c_void, 1) };
+        if n == 1 {
+            break;
+        }
+        if n == 0 || std::io::Error::last_os_error().kind() != std::io::ErrorKind::Interrupted {
+            return;
+        }
+    }
+    notify::signaled();
+    session::report();
+    match MAIN_THREAD.get() {
+        Some(&main) => unsafe {
+            libc::pthread_kill(main, libc::c_int::from(sig));
+        },
+        None => std::process::exit(130),
+    }
 }
 
 /// Crash handler for SIGSEGV/SIGABRT - zero sensitive memory, then re-raise for core dump
@@ -44,6 +131,9 @@ extern "C" fn crash_handler(sig: libc::c_int) {
     unsafe {
         // Emergency zero the urandom pool (async-signal-safe)
         rand::urand::emergency_zero();
+        rand::hwrng::emergency_zero();
+        // Zero prefetched words
+        rand::prefetch::wipe();
         // Zeroize hardware RNG state
         rand::zeroize_state();
         // Reset signal handler to default and re-raise for proper crash handling
@@ -55,6 +145,7 @@ extern "C" fn crash_handler(sig: libc::c_int) {
 /// Install all signal handlers and register atexit cleanup.
 /// Call this early in main().
 pub fn install_handlers() {
+    MAIN_THREAD.get_or_init(|| unsafe { libc::pthread_self() });
     unsafe {
         libc::atexit(cleanup_on_exit);
         libc::signal(
@@ -81,6 +172,15 @@ pub fn install_handlers() {
     }
 }
 
+/// Exit after the reader closed our output pipe. An unlimited stream
+/// ending this way is normal (`randpass --bytes | head -c 1M`) and exits 0;
+/// a limited run that was cut short exits 141 (128 + SIGPIPE) like a
+/// process killed by the signal would. atexit cleanup zeroizes the pool.
+pub fn broken_pipe(truncated: bool) -> ! {
+    rand::shutdown_urandom();
+    std::process::exit(if truncated { 128 + libc::SIGPIPE } else { 0 })
+}
+
 /// Reset terminal state (public for use in other modules)
 pub fn reset_terminal() {
     reset_terminal_termios();
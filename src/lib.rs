@@ -0,0 +1,11 @@
+//! randpass internals. The binary is a thin wrapper over `cli` and `tui`;
+//! the library target exists so `benches/` can reach the generation code.
+
+pub mod cli;
+pub mod exits;
+pub mod pass;
+pub mod rand;
+pub mod session;
+pub mod settings;
+pub mod terminal;
+pub mod tui;
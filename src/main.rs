@@ -1,14 +1,8 @@
 use std::env;
 
-mod cli;
-mod exits;
-mod pass;
-mod rand;
-mod settings;
-mod terminal;
-mod tui;
-
-use settings::Settings;
+use randpass::settings::{self, Settings};
+use randpass::terminal::accessible;
+use randpass::{cli, exits, session, tui};
 
 fn main() {
     exits::reset_terminal();
@@ -16,9 +10,21 @@ fn main() {
     unsafe { libc::prctl(libc::PR_SET_DUMPABLE, 0) };
 
     let args: Vec<String> = env::args().collect();
+    settings::ephemeral::init(&args);
+    accessible::init(&args);
+    session::init(&args);
 
-    match args.len() {
-        1 if !Settings::has_saved_command() => tui::run(),
+    // The menu needs a terminal both ways; piped or under cron, a bare
+    // `randpass` prints one password with the CLI defaults instead.
+    // `randpass --accessible` or `--session-stats` alone opens the menu too
+    let terminal = cli::quiet::is_interactive() && cli::quiet::is_terminal_output();
+    match args
+        .iter()
+        .filter(|a| *a != "--accessible" && *a != "--session-stats")
+        .count()
+    {
+        1 if terminal && !Settings::has_saved_command() => tui::run(),
         _ => cli::run(args),
     }
+    session::report();
 }
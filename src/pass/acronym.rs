@@ -0,0 +1,61 @@
+//! `--acronym PHRASE`: a password built on the initials of a phrase only
+//! its owner knows, so `correct horse battery staple` gives `cHb$` plus
+//! padding. Each letter initial is redrawn from its two cases and its
+//! heavy `--leet` substitutes, and random padding from the character set
+//! follows.
+//!
+//! The phrase is the user's choice, not a draw, so it counts for nothing:
+//! the entropy is the case and symbol choices plus the padding. Initials
+//! that aren't ASCII letters are kept as they are and add nothing either.
+
+use super::leet::{HEAVY, Leet};
+use super::pattern::{Pattern, Slot};
+
+/// Padding when `-l` doesn't set the length.
+pub const DEFAULT_PAD: usize = 8;
+
+pub struct Acronym {
+    pub pattern: Pattern,
+    pub initials: usize,
+    /// Bits from case and symbol choices on the initials
+    pub skeleton_bits: f64,
+}
+
+/// Build the acronym of `phrase`, padded with `pad` characters drawn
+/// uniformly from `pool` (duplicates are ignored).
+pub fn build(phrase: &str, pad: usize, pool: &[u8]) -> Result<Acronym, String> {
+    let leet = Leet::parse(HEAVY).expect("heavy leet map is valid");
+    let mut slots = Vec::new();
+    for initial in phrase.split_whitespace().filter_map(|w| w.chars().next()) {
+        slots.push(if initial.is_ascii_alphabetic() {
+            let lower = initial.to_ascii_lowercase() as u8;
+            let mut class = vec![lower, lower.to_ascii_uppercase()];
+            class.extend_from_slice(leet.substitutes(lower));
+            Slot::Class(class)
+        } else {
+            Slot::Literal(initial)
+        });
+    }
+    let initials = slots.len();
+    if initials == 0 {
+        return Err("the phrase has no words".to_string());
+    }
+    let skeleton_bits = Pattern::from_slots(String::new(), slots.clone()).entropy();
+
+    if pad > 0 {
+        let mut pool = pool.to_vec();
+        pool.sort_unstable();
+        pool.dedup();
+        if pool.is_empty() {
+            return Err("the character set is empty, nothing to pad with".to_string());
+        }
+        slots.extend(std::iter::repeat_n(Slot::Class(pool), pad));
+    }
+    // The phrase stays out of messages
+    let source = format!("acronym of {} words, {} padding", initials, pad);
+    Ok(Acronym {
+        pattern: Pattern::from_slots(source, slots),
+        initials,
+        skeleton_bits,
+    })
+}
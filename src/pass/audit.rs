@@ -0,0 +1,144 @@
+//! Opt-in provenance log (`audit_log`): one line per generation run, for
+//! users who must show when credentials were made and how. A line holds
+//! the time, count, length, entropy, destination and RNG backend, never a
+//! password. The file is append-only from randpass's side; `randpass log
+//! clear` is the only thing that removes lines.
+//!
+//! ```text
+//! 2026-10-16T09:41:07Z count=5 length=20 entropy=131.1 output=stdout rng=RDRAND
+//! ```
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::settings::{Settings, ephemeral};
+
+/// `$XDG_STATE_HOME/randpass`, or `~/.local/state/randpass`.
+pub fn dir() -> PathBuf {
+    let state = std::env::var("XDG_STATE_HOME")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| {
+            let home = std::env::var("HOME").unwrap_or_else(|_| ".".into());
+            format!("{}/.local/state", home)
+        });
+    PathBuf::from(state).join("randpass")
+}
+
+pub fn path() -> PathBuf {
+    dir().join("audit.log")
+}
+
+/// Record one run of `count` passwords (0 for a stream) of `bits` each,
+/// if `audit_log` is on. Nothing is written in ephemeral mode. A failed
+/// write is returned for the caller to warn about; the run goes ahead.
+pub fn record(settings: &Settings, count: usize, bits: f64) -> Result<(), String> {
+    if !settings.audit_log || ephemeral::enabled() {
+        return Ok(());
+    }
+    let line = format!(
+        "{} count={} {} entropy={:.1} output={} rng={}\n",
+        timestamp(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0)
+        ),
+        if count == 0 {
+            "unlimited".to_string()
+        } else {
+            count.to_string()
+        },
+        shape(settings),
+        bits,
+        destination(settings),
+        backend()
+    );
+
+    let dir = dir();
+    fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(&dir)
+        .map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let path = path();
+    OpenOptions::new()
+        .append(true)
+        .create(true)
+        .mode(0o600)
+        .open(&path)
+        .and_then(|mut f| f.write_all(line.as_bytes()))
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Length in the unit the mode draws: words or characters.
+fn shape(settings: &Settings) -> String {
+    if let Some(ref p) = settings.pattern {
+        format!("length={}", p.width())
+    } else if let Some(ref p) = settings.passphrase {
+        format!("words={}", p.words)
+    } else if let Some(ref pad) = settings.pad {
+        format!("length={} pad={:?}", settings.pass_length, pad.describe())
+    } else {
+        format!("length={}", settings.pass_length)
+    }
+}
+
+/// `stdout`, `clipboard`, or the output file's absolute path, quoted.
+fn destination(settings: &Settings) -> String {
+    if settings.to_clipboard {
+        "clipboard".to_string()
+    } else if !settings.output_file_path.is_empty() {
+        let path = std::path::absolute(&settings.output_file_path)
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|_| settings.output_file_path.clone());
+        format!("{:?}", path)
+    } else {
+        "stdout".to_string()
+    }
+}
+
+/// The RNG backend, with `+prefetch` when the prefetch thread runs and
+/// the sources failed over from before it (`hwrng>/dev/urandom`).
+pub fn backend() -> String {
+    let source = crate::rand::entropy_source();
+    let trail = crate::rand::failover::trail();
+    if crate::rand::prefetch::is_running() {
+        format!("{}{}+prefetch", trail, source)
+    } else {
+        format!("{}{}", trail, source)
+    }
+}
+
+/// `secs` since the epoch as RFC 3339 in UTC, to the second.
+pub fn timestamp(secs: u64) -> String {
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    let (year, month, day) = civil_from_days(days as i64);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem / 60 % 60,
+        rem % 60
+    )
+}
+
+/// Year, month and day of `days` after 1970-01-01, in the proleptic
+/// Gregorian calendar (Howard Hinnant's `civil_from_days`).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
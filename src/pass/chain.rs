@@ -0,0 +1,63 @@
+//! Reproducible batches (`--chain`): every password is derived from one
+//! recorded seed with HMAC-SHA256 in counter mode. Without the seed the
+//! outputs are unpredictable; with it, the whole run can be re-derived.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use zeroize::Zeroize;
+
+use crate::rand::Rand;
+
+pub const SEED_LEN: usize = 32;
+const DOMAIN: &[u8] = b"randpass-chain-v1";
+
+/// Draw a fresh seed from the RNG.
+pub fn new_seed() -> [u8; SEED_LEN] {
+    let mut seed = [0u8; SEED_LEN];
+    for chunk in seed.chunks_mut(8) {
+        chunk.copy_from_slice(&(Rand::get() as u64).to_le_bytes());
+    }
+    seed
+}
+
+/// Parse a 64-character hex seed.
+pub fn parse_seed(hex: &str) -> Option<[u8; SEED_LEN]> {
+    let hex = hex.as_bytes();
+    if hex.len() != SEED_LEN * 2 {
+        return None;
+    }
+    let mut seed = [0u8; SEED_LEN];
+    for (i, pair) in hex.chunks_exact(2).enumerate() {
+        let s = std::str::from_utf8(pair).ok()?;
+        seed[i] = u8::from_str_radix(s, 16).ok()?;
+    }
+    Some(seed)
+}
+
+/// Derive password number `index` into `buf`. `chars` must be the
+/// unshuffled pool so the mapping is stable across runs.
+pub fn derive(seed: &[u8; SEED_LEN], index: u64, chars: &[u8], length: usize, buf: &mut Vec<u8>) {
+    buf.clear();
+    let n = chars.len() as u64;
+    // Largest multiple of n that fits in u32 range: reject above it for uniformity
+    let zone = (1u64 << 32) / n * n;
+
+    let mut block: u64 = 0;
+    while buf.len() < length {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(seed).expect("HMAC takes any key size");
+        mac.update(DOMAIN);
+        mac.update(&(length as u64).to_be_bytes());
+        mac.update(&index.to_be_bytes());
+        mac.update(&block.to_be_bytes());
+        let mut out: [u8; 32] = mac.finalize().into_bytes().into();
+
+        for word in out.chunks_exact(4) {
+            let v = u32::from_be_bytes([word[0], word[1], word[2], word[3]]) as u64;
+            if v < zone && buf.len() < length {
+                buf.push(chars[(v % n) as usize]);
+            }
+        }
+        out.zeroize();
+        block += 1;
+    }
+}
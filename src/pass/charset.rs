@@ -1,4 +1,12 @@
 //! Character set building for password generation.
+//!
+//! Passwords are sampled from an alias table over the distinct characters,
+//! weighted by class density: one RNG draw per character, constant time,
+//! and a table of at most 256 columns however high the densities go. The
+//! density-duplicated pool (`build`) is kept for the seed view and for
+//! `--chain`, whose derivations index into it.
+
+use zeroize::Zeroize;
 
 use crate::settings::Settings;
 
@@ -8,7 +16,14 @@ const DIGITS: &[u8] = b"0123456789";
 
 /// Build the character pool based on density settings.
 pub fn build(settings: &Settings) -> Vec<u8> {
-    let mut chars: Vec<u8> = Vec::new();
+    let mut chars = Vec::with_capacity(size(settings));
+    build_into(settings, &mut chars);
+    chars
+}
+
+/// Build the character pool into `chars` (cleared first); `size` bytes.
+pub fn build_into(settings: &Settings, chars: &mut Vec<u8>) {
+    chars.clear();
 
     for _ in 0..settings.lowercase_char_density {
         chars.extend_from_slice(LOWERCASE);
@@ -26,15 +41,270 @@ pub fn build(settings: &Settings) -> Vec<u8> {
         chars.extend_from_slice(&settings.special_chars);
     }
 
-    chars
+    if let Some(ref policy) = settings.policy {
+        chars.retain(|&c| policy.allows(c as char));
+    }
+}
+
+/// Characters and their weights: each character counts its class density,
+/// summed over repeats in the special set. Same distribution as the pool.
+fn weights(settings: &Settings) -> [u64; 256] {
+    let mut weights = [0u64; 256];
+    let classes: [(&[u8], usize); 4] = [
+        (LOWERCASE, settings.lowercase_char_density),
+        (UPPERCASE, settings.uppercase_char_density),
+        (DIGITS, settings.numeric_char_density),
+        (&settings.special_chars, settings.special_char_density),
+    ];
+    for (chars, density) in classes {
+        for &c in chars {
+            weights[c as usize] = weights[c as usize].saturating_add(density as u64);
+        }
+    }
+    if let Some(ref policy) = settings.policy {
+        for (c, weight) in weights.iter_mut().enumerate() {
+            if !policy.allows(c as u8 as char) {
+                *weight = 0;
+            }
+        }
+    }
+    weights
+}
+
+/// Walker/Vose alias table in integer arithmetic, so class weights are
+/// exact. Column `i` keeps `symbols[i]` when the coin lands below
+/// `keep[i]` (out of `total`), else takes `symbols[alias[i]]`.
+pub struct Table {
+    symbols: [u8; 256],
+    keep: [u64; 256],
+    alias: [u8; 256],
+    len: usize,
+    total: u64,
+}
+
+impl Table {
+    pub fn build(settings: &Settings) -> Self {
+        let weights = weights(settings);
+        let mut table = Table {
+            symbols: [0; 256],
+            keep: [0; 256],
+            alias: [0; 256],
+            len: 0,
+            total: 0,
+        };
+        // Scaled weights: column i holds weight * len, out of `total`
+        let mut scaled = [0u64; 256];
+        for (c, &w) in weights.iter().enumerate() {
+            if w > 0 {
+                table.symbols[table.len] = c as u8;
+                scaled[table.len] = w;
+                table.len += 1;
+                table.total += w;
+            }
+        }
+        if table.len == 0 {
+            return table;
+        }
+        let (len, total) = (table.len as u64, table.total);
+        let mut small = Vec::with_capacity(table.len);
+        let mut large = Vec::with_capacity(table.len);
+        for (i, s) in scaled[..table.len].iter_mut().enumerate() {
+            *s *= len;
+            if *s < total {
+                small.push(i);
+            } else {
+                large.push(i);
+            }
+        }
+        while let (Some(&s), Some(&l)) = (small.last(), large.last()) {
+            small.pop();
+            table.keep[s] = scaled[s];
+            table.alias[s] = l as u8;
+            scaled[l] -= total - scaled[s];
+            if scaled[l] < total {
+                large.pop();
+                small.push(l);
+            }
+        }
+        // What remains is exactly full
+        for i in small.into_iter().chain(large) {
+            table.keep[i] = table.total;
+            table.alias[i] = i as u8;
+        }
+        scaled.zeroize();
+        table
+    }
+
+    /// Distinct characters in the table.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// One character from one RNG draw: the low part picks the column,
+    /// the rest flips its coin.
+    #[inline(always)]
+    pub fn pick(&self, rng: usize) -> u8 {
+        let column = rng % self.len;
+        let coin = (rng / self.len) as u64 % self.total;
+        if coin < self.keep[column] {
+            self.symbols[column]
+        } else {
+            self.symbols[self.alias[column] as usize]
+        }
+    }
+}
+
+impl Zeroize for Table {
+    fn zeroize(&mut self) {
+        self.symbols.zeroize();
+        self.keep.zeroize();
+        self.alias.zeroize();
+        self.len.zeroize();
+        self.total.zeroize();
+    }
+}
+
+impl Drop for Table {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+/// `--start-with`: the class a password's first character must be in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StartWith {
+    Letter,
+    Lower,
+    Upper,
+    Alnum,
+}
+
+impl StartWith {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "letter" => Some(StartWith::Letter),
+            "lower" => Some(StartWith::Lower),
+            "upper" => Some(StartWith::Upper),
+            "alnum" => Some(StartWith::Alnum),
+            _ => None,
+        }
+    }
+
+    pub fn contains(self, c: u8) -> bool {
+        match self {
+            StartWith::Letter => c.is_ascii_alphabetic(),
+            StartWith::Lower => c.is_ascii_lowercase(),
+            StartWith::Upper => c.is_ascii_uppercase(),
+            StartWith::Alnum => c.is_ascii_alphanumeric(),
+        }
+    }
+
+    /// Redraw `password[0]` with `pick` until it's in the class: the
+    /// first character keeps the pool's weights restricted to the class,
+    /// and the others aren't touched, so they stay unbiased. The caller
+    /// checks the pool has such a character.
+    #[inline]
+    pub fn fix(self, password: &mut [u8], mut pick: impl FnMut() -> u8) {
+        if let Some(first) = password.first_mut() {
+            while !self.contains(*first) {
+                *first = pick();
+            }
+        }
+    }
+}
+
+/// A run's prepared character source: the alias table, plus the pool
+/// when `--chain` needs it.
+pub struct Charset {
+    pub table: Table,
+    /// Unshuffled pool for chained runs; empty otherwise
+    pub pool: Vec<u8>,
+}
+
+impl Charset {
+    pub fn build(settings: &Settings) -> Self {
+        let pool = if settings.chain_seed.is_some() {
+            build(settings)
+        } else {
+            Vec::new()
+        };
+        Self {
+            table: Table::build(settings),
+            pool,
+        }
+    }
+
+    /// Fill `buf` (cleared first) with `length` characters.
+    #[inline]
+    pub fn generate(&self, length: usize, buf: &mut Vec<u8>) {
+        buf.clear();
+        buf.extend((0..length).map(|_| self.table.pick(crate::rand::Rand::get())));
+    }
+}
+
+impl Zeroize for Charset {
+    fn zeroize(&mut self) {
+        self.table.zeroize();
+        self.pool.zeroize();
+    }
+}
+
+impl Drop for Charset {
+    fn drop(&mut self) {
+        self.pool.zeroize();
+    }
 }
 
 /// Calculate the effective charset size (for entropy calculation).
+/// Characters a `--policy` forbids don't count.
 pub fn size(settings: &Settings) -> usize {
+    count(settings, |_| true)
+}
+
+/// Indexes `--start-with` or the policy's position rules constrain in a
+/// password of `pass_length`, sorted.
+pub fn constrained(settings: &Settings) -> Vec<usize> {
+    let len = settings.pass_length;
+    let mut indexes: Vec<usize> = settings
+        .policy
+        .iter()
+        .flat_map(|p| p.positions.iter().filter_map(|rule| rule.index(len)))
+        .chain(settings.start_with.and((len > 0).then_some(0)))
+        .collect();
+    indexes.sort_unstable();
+    indexes.dedup();
+    indexes
+}
+
+/// Pool characters that may go at `index`, under `--start-with` and the
+/// policy's position rules.
+pub fn size_at(settings: &Settings, index: usize) -> usize {
+    let len = settings.pass_length;
+    count(settings, |c| {
+        (index > 0 || settings.start_with.is_none_or(|s| s.contains(c)))
+            && settings
+                .policy
+                .as_ref()
+                .is_none_or(|p| p.allows_at(index, len, c as char))
+    })
+}
+
+/// Pool characters (with density repeats) that `keep` accepts.
+fn count(settings: &Settings, keep: impl Fn(u8) -> bool) -> usize {
+    let allowed = |chars: &[u8]| {
+        chars
+            .iter()
+            .filter(|&&c| keep(c) && settings.policy.as_ref().is_none_or(|p| p.allows(c as char)))
+            .count()
+    };
     let mut size = 0;
-    size += 26 * settings.lowercase_char_density;
-    size += 26 * settings.uppercase_char_density;
-    size += 10 * settings.numeric_char_density;
-    size += settings.special_chars.len() * settings.special_char_density;
+    size += allowed(LOWERCASE) * settings.lowercase_char_density;
+    size += allowed(UPPERCASE) * settings.uppercase_char_density;
+    size += allowed(DIGITS) * settings.numeric_char_density;
+    size += allowed(&settings.special_chars) * settings.special_char_density;
     size
 }
@@ -0,0 +1,51 @@
+//! Clipboard payload for a batch: passwords are assembled once into a
+//! pre-sized, mlock'd buffer, which is then handed to the clipboard as-is
+//! instead of being copied.
+
+use zeroize::Zeroize;
+
+pub struct ClipBuf {
+    data: Vec<u8>,
+}
+
+impl ClipBuf {
+    pub fn with_capacity(capacity: usize) -> Self {
+        let data = Vec::with_capacity(capacity);
+        unsafe {
+            libc::mlock(data.as_ptr() as *const libc::c_void, data.capacity());
+        }
+        Self { data }
+    }
+
+    /// Append `data`. Grows into a fresh locked buffer rather than letting
+    /// Vec reallocate and leave a copy behind.
+    pub fn push(&mut self, data: &[u8]) {
+        let needed = self.data.len() + data.len();
+        if needed > self.data.capacity() {
+            let mut grown = ClipBuf::with_capacity(needed.max(self.data.capacity() * 2));
+            grown.data.extend_from_slice(&self.data);
+            std::mem::swap(self, &mut grown);
+        }
+        self.data.extend_from_slice(data);
+    }
+
+    /// Give up the buffer for `ClipboardProvider::set_contents`, which
+    /// takes ownership. The clipboard keeps serving these bytes, so they
+    /// are neither zeroized nor unlocked here.
+    pub fn into_string(mut self) -> String {
+        let data = std::mem::take(&mut self.data);
+        // Safety: passwords are ASCII from the charset or UTF-8 words
+        unsafe { String::from_utf8_unchecked(data) }
+    }
+}
+
+impl Drop for ClipBuf {
+    fn drop(&mut self) {
+        let ptr = self.data.as_ptr();
+        let cap = self.data.capacity();
+        self.data.zeroize();
+        unsafe {
+            libc::munlock(ptr as *const libc::c_void, cap);
+        }
+    }
+}
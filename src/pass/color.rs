@@ -0,0 +1,53 @@
+//! `--color-classes`: digits, uppercase letters and symbols each in their
+//! own color on a terminal, so a password is easier to copy by eye
+//! (`0` vs `O`, `l` vs `1` vs `|`). Lowercase letters and anything
+//! outside ASCII stay plain, and every line ends reset.
+
+use std::io::{self, Write};
+
+const DIGIT: &[u8] = b"\x1b[36m";
+const UPPER: &[u8] = b"\x1b[32m";
+const SYMBOL: &[u8] = b"\x1b[31m";
+const RESET: &[u8] = b"\x1b[0m";
+
+fn color(b: u8) -> Option<&'static [u8]> {
+    match b {
+        b'0'..=b'9' => Some(DIGIT),
+        b'A'..=b'Z' => Some(UPPER),
+        b if b.is_ascii_graphic() && !b.is_ascii_lowercase() => Some(SYMBOL),
+        _ => None,
+    }
+}
+
+/// Writer that colors each character class on the way to `inner`.
+pub(crate) struct ClassColors<W: Write> {
+    inner: W,
+    current: Option<&'static [u8]>,
+}
+
+impl<W: Write> ClassColors<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            current: None,
+        }
+    }
+}
+
+impl<W: Write> Write for ClassColors<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        for &b in data {
+            let next = color(b);
+            if next != self.current {
+                self.inner.write_all(next.unwrap_or(RESET))?;
+                self.current = next;
+            }
+            self.inner.write_all(&[b])?;
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,152 @@
+//! Charset coverage of bulk file runs: every byte written is counted, and
+//! at the end each character is held against the share of the pool it
+//! should have drawn. A character that never appeared, one drawn far more
+//! or less often than its weight, or one from outside the pool points at
+//! a charset-building or sampling regression.
+//!
+//! The expected shares come from the density-duplicated pool
+//! (`charset::build`), not the alias table generation samples from, so
+//! the two are checked against each other.
+
+use super::charset;
+use super::parallel::THRESHOLD;
+use crate::settings::Settings;
+use crate::terminal::format_number;
+
+/// A character is off when its count is more than this many standard
+/// deviations from the expected one: about 2e-9 per character by chance.
+const Z_LIMIT: f64 = 6.0;
+/// A missing character is only reported when this many were expected;
+/// the chance of seeing none is e^-20.
+const MIN_EXPECTED: f64 = 20.0;
+/// Characters named in one finding.
+const NAMED: usize = 8;
+
+pub struct Coverage {
+    /// Share of the pool each byte should draw
+    shares: [f64; 256],
+    counts: [u64; 256],
+}
+
+impl Coverage {
+    /// A counter for a run of `count` passwords, or None when the run is
+    /// too small or its characters aren't drawn straight from the pool:
+    /// stdout, passphrases, patterns, policies, `--start-with` and
+    /// `--pad` all shift the frequencies on purpose.
+    pub fn new(settings: &Settings, count: usize) -> Option<Self> {
+        if count < THRESHOLD
+            || settings.output_file_path.is_empty()
+            || settings.passphrase.is_some()
+            || settings.pattern.is_some()
+            || settings.policy.is_some()
+            || settings.start_with.is_some()
+            || settings.pad.is_some()
+        {
+            return None;
+        }
+        let pool = charset::build(settings);
+        if pool.is_empty() {
+            return None;
+        }
+        let mut shares = [0.0; 256];
+        for &c in &pool {
+            shares[c as usize] += 1.0 / pool.len() as f64;
+        }
+        Some(Self {
+            shares,
+            counts: [0; 256],
+        })
+    }
+
+    /// Count the characters of newline-separated passwords.
+    pub fn add(&mut self, data: &[u8]) {
+        for &b in data {
+            self.counts[b as usize] += 1;
+        }
+    }
+
+    /// What looks wrong, one line per kind of finding. Empty when every
+    /// character came out near its share.
+    pub fn findings(&self) -> Vec<String> {
+        let newlines = self.counts[b'\n' as usize];
+        let total: u64 = self.counts.iter().sum::<u64>() - newlines;
+        let mut missing = Vec::new();
+        let mut outside = Vec::new();
+        let mut off = Vec::new();
+        for c in (0..256).filter(|&c| c != b'\n' as usize) {
+            let (p, seen) = (self.shares[c], self.counts[c]);
+            let expected = total as f64 * p;
+            if p == 0.0 {
+                if seen > 0 {
+                    outside.push(label(c as u8));
+                }
+            } else if seen == 0 {
+                if expected >= MIN_EXPECTED {
+                    missing.push(label(c as u8));
+                }
+            } else {
+                let z = (seen as f64 - expected) / (expected * (1.0 - p)).sqrt();
+                if z.abs() > Z_LIMIT {
+                    off.push(format!(
+                        "{} {:.2}% for {:.2}%",
+                        label(c as u8),
+                        seen as f64 / total as f64 * 100.0,
+                        p * 100.0
+                    ));
+                }
+            }
+        }
+
+        let mut findings = Vec::new();
+        if !missing.is_empty() {
+            findings.push(format!(
+                "never drawn, at least {} expected each: {}",
+                format_number(self.fewest_expected(total) as usize),
+                list(&missing)
+            ));
+        }
+        if !outside.is_empty() {
+            findings.push(format!(
+                "drawn from outside the charset: {}",
+                list(&outside)
+            ));
+        }
+        if !off.is_empty() {
+            findings.push(format!(
+                "far from their share (over {} sigma): {}",
+                Z_LIMIT,
+                list(&off)
+            ));
+        }
+        findings
+    }
+
+    /// Expected count of the rarest character in the pool.
+    fn fewest_expected(&self, total: u64) -> f64 {
+        let rarest = self
+            .shares
+            .iter()
+            .copied()
+            .filter(|&p| p > 0.0)
+            .fold(1.0, f64::min);
+        (total as f64 * rarest).floor()
+    }
+}
+
+fn label(c: u8) -> String {
+    if c.is_ascii_graphic() {
+        format!("'{}'", c as char)
+    } else {
+        format!("0x{:02x}", c)
+    }
+}
+
+/// The first `NAMED` items, and how many more there are.
+fn list(items: &[String]) -> String {
+    let shown = items[..items.len().min(NAMED)].join(", ");
+    if items.len() > NAMED {
+        format!("{} and {} more", shown, items.len() - NAMED)
+    } else {
+        shown
+    }
+}
@@ -0,0 +1,113 @@
+//! crypt(3) hashes (`$6$` sha512-crypt, `$y$` yescrypt) via libxcrypt, for
+//! `/etc/shadow` / `chpasswd -e` input. Salts come from our RNG.
+
+use std::ffi::{CStr, c_char, c_int, c_ulong, c_void};
+
+use zeroize::Zeroize;
+
+use crate::rand::Rand;
+
+/// Size of libxcrypt's `struct crypt_data`.
+const CRYPT_DATA_SIZE: usize = 32768;
+const CRYPT_GENSALT_OUTPUT_SIZE: usize = 192;
+const SALT_BYTES: usize = 16;
+
+#[link(name = "crypt")]
+unsafe extern "C" {
+    fn crypt_gensalt_rn(
+        prefix: *const c_char,
+        count: c_ulong,
+        rbytes: *const c_char,
+        nrbytes: c_int,
+        output: *mut c_char,
+        output_size: c_int,
+    ) -> *mut c_char;
+
+    fn crypt_rn(
+        phrase: *const c_char,
+        setting: *const c_char,
+        data: *mut c_void,
+        size: c_int,
+    ) -> *mut c_char;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scheme {
+    Sha512Crypt,
+    Yescrypt,
+}
+
+impl Scheme {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "sha512-crypt" | "sha512" | "6" => Some(Scheme::Sha512Crypt),
+            "yescrypt" | "y" => Some(Scheme::Yescrypt),
+            _ => None,
+        }
+    }
+
+    fn prefix(&self) -> &'static CStr {
+        match self {
+            Scheme::Sha512Crypt => c"$6$",
+            Scheme::Yescrypt => c"$y$",
+        }
+    }
+}
+
+/// Hash `password` with a fresh random salt. Intermediate state is zeroized.
+pub fn hash(password: &[u8], scheme: Scheme) -> Result<String, String> {
+    let mut rbytes = [0u8; SALT_BYTES];
+    for chunk in rbytes.chunks_mut(8) {
+        chunk.copy_from_slice(&(Rand::get() as u64).to_le_bytes());
+    }
+
+    let mut setting = [0u8; CRYPT_GENSALT_OUTPUT_SIZE];
+    let ok = unsafe {
+        crypt_gensalt_rn(
+            scheme.prefix().as_ptr(),
+            0, // library default cost
+            rbytes.as_ptr() as *const c_char,
+            SALT_BYTES as c_int,
+            setting.as_mut_ptr() as *mut c_char,
+            CRYPT_GENSALT_OUTPUT_SIZE as c_int,
+        )
+    };
+    rbytes.zeroize();
+    if ok.is_null() {
+        return Err(format!(
+            "crypt_gensalt: {} not supported by libcrypt",
+            scheme.prefix().to_string_lossy()
+        ));
+    }
+
+    let mut phrase = Vec::with_capacity(password.len() + 1);
+    phrase.extend_from_slice(password);
+    phrase.push(0);
+
+    let mut data = vec![0u8; CRYPT_DATA_SIZE];
+    let out = unsafe {
+        crypt_rn(
+            phrase.as_ptr() as *const c_char,
+            setting.as_ptr() as *const c_char,
+            data.as_mut_ptr() as *mut c_void,
+            CRYPT_DATA_SIZE as c_int,
+        )
+    };
+    let result = if out.is_null() {
+        Err("crypt: hashing failed".to_string())
+    } else {
+        let hashed = unsafe { CStr::from_ptr(out) }
+            .to_string_lossy()
+            .into_owned();
+        // Failures are reported as a string starting with '*'
+        if hashed.starts_with('*') {
+            Err("crypt: hashing failed".to_string())
+        } else {
+            Ok(hashed)
+        }
+    };
+
+    phrase.zeroize();
+    data.zeroize();
+    result
+}
@@ -0,0 +1,127 @@
+//! `--fsync` durability policy and up-front preallocation for output files.
+
+use std::fs::File;
+use std::io::{self, Write};
+
+use crate::terminal::format_bytes;
+
+/// Bytes between syncs for a bare `--fsync interval`.
+const DEFAULT_INTERVAL: u64 = 256 * 1024 * 1024;
+
+/// When output files are flushed to stable storage.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Fsync {
+    /// Leave it to the kernel's writeback
+    #[default]
+    Never,
+    /// Once, after the last write
+    End,
+    /// Every N bytes, and at the end
+    Interval(u64),
+}
+
+impl Fsync {
+    /// Parse `never`, `end`, or `interval[:SIZE]` (K/M/G suffixes).
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.split_once(':') {
+            None => match s {
+                "never" => Some(Fsync::Never),
+                "end" => Some(Fsync::End),
+                "interval" => Some(Fsync::Interval(DEFAULT_INTERVAL)),
+                _ => None,
+            },
+            Some(("interval", size)) => crate::cli::parse_byte_count(size)
+                .filter(|&n| n > 0)
+                .map(|n| Fsync::Interval(n as u64)),
+            Some(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Fsync {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Fsync::Never => f.write_str("never"),
+            Fsync::End => f.write_str("at end"),
+            Fsync::Interval(n) => write!(f, "every {}", format_bytes(*n as usize)),
+        }
+    }
+}
+
+/// Reserve `len` bytes after the current end of `file` without changing
+/// its size, so a long run doesn't fragment or hit ENOSPC halfway.
+/// Best effort: filesystems without fallocate are left alone.
+pub fn preallocate(file: &File, len: u64) {
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::fd::AsRawFd;
+
+        // Devices (wipe) and pipes have nothing to reserve
+        let Ok(meta) = file.metadata() else {
+            return;
+        };
+        if !meta.is_file() {
+            return;
+        }
+        let start = meta.len();
+        unsafe {
+            libc::fallocate(
+                file.as_raw_fd(),
+                libc::FALLOC_FL_KEEP_SIZE,
+                start as libc::off_t,
+                len as libc::off_t,
+            );
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = (file, len);
+}
+
+/// Writer that applies an `Fsync` policy to the file under `inner`.
+pub(crate) struct Synced<W: Write> {
+    inner: W,
+    /// Duplicate handle for syncing; `inner` may buffer or own the original
+    file: File,
+    policy: Fsync,
+    unsynced: u64,
+}
+
+impl<W: Write> Synced<W> {
+    /// `file` is a handle to the file `inner` writes (see `File::try_clone`).
+    pub fn new(inner: W, file: File, policy: Fsync) -> Self {
+        Self {
+            inner,
+            file,
+            policy,
+            unsynced: 0,
+        }
+    }
+
+    /// Flush, then sync data and metadata unless the policy is `Never`.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        if self.policy != Fsync::Never {
+            self.file.sync_all()?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for Synced<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(data)?;
+        if let Fsync::Interval(every) = self.policy {
+            self.unsynced += n as u64;
+            if self.unsynced >= every {
+                self.inner.flush()?;
+                self.file.sync_data()?;
+                self.unsynced = 0;
+            }
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
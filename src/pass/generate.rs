@@ -5,17 +5,32 @@ use std::io::Write;
 
 use zeroize::Zeroize;
 
-use super::charset;
+use super::charset::{self, Charset};
+use super::coverage::Coverage;
+use super::history::Recorder;
+use super::{ClipBuf, chain, parallel, policy, pool};
 use crate::rand::Rand;
 use crate::settings::Settings;
 
 /// Generate multiple passwords to clipboard buffer, file, or stdout.
 /// Urandom pool (if active) is shut down and zeroized after generation.
-pub fn generate_batch(settings: &Settings, count: usize) -> Option<String> {
-    // Fast path: pre-build charset when not viewing seeds
-    let result = if !settings.view_chars_str {
-        let mut chars = charset::build(settings);
-        generate_batch_fast(settings, count, &mut chars)
+pub fn generate_batch(settings: &Settings, count: usize) -> Option<ClipBuf> {
+    // Fast path: pre-build charset when not viewing seeds (chained runs
+    // always take it, their pool must stay unshuffled; passphrases have no
+    // seed to view, nor do patterns)
+    let result = if !settings.view_chars_str
+        || settings.chain_seed.is_some()
+        || settings.passphrase.is_some()
+        || settings.pattern.is_some()
+    {
+        let chars = Charset::build(settings);
+        if settings.passphrase.is_none() && settings.pattern.is_none() {
+            crate::cli::prompts::debug(&format!(
+                "Pool: {} characters from the class densities",
+                charset::size(settings)
+            ));
+        }
+        generate_batch_fast(settings, count, &chars)
     } else {
         // Slow path: rebuild charset each time (for debug seed view)
         generate_batch_slow(settings, count)
@@ -25,86 +40,196 @@ pub fn generate_batch(settings: &Settings, count: usize) -> Option<String> {
     result
 }
 
-fn generate_batch_fast(settings: &Settings, count: usize, chars: &mut [u8]) -> Option<String> {
-    let mut passwords = String::with_capacity(count * (settings.pass_length + 1));
-    let mut buf = Vec::with_capacity(settings.pass_length + 1);
+/// Clipboard buffer sized for the whole batch: exact for character
+/// passwords, an upper bound for passphrases. Empty unless copying.
+fn clip_buf(settings: &Settings, count: usize) -> ClipBuf {
+    if !settings.to_clipboard {
+        return ClipBuf::with_capacity(0);
+    }
+    let size = super::output_size(settings, count)
+        .map(|n| n as usize)
+        .unwrap_or_else(|| count.saturating_mul(max_len(settings) + 1));
+    ClipBuf::with_capacity(size)
+}
+
+fn generate_batch_fast(settings: &Settings, count: usize, chars: &Charset) -> Option<ClipBuf> {
+    let mut passwords = clip_buf(settings, count);
+    let mut buf = pool::take(max_len(settings) + 1);
 
-    let mut file: Option<super::SecureBufWriter<std::fs::File>> = None;
+    let mut file = None;
     if !settings.output_file_path.is_empty() {
-        file = Some(super::SecureBufWriter::new(
+        file = Some(super::file_writer(
             OpenOptions::new()
+                .read(settings.mmap)
                 .create(true)
                 .append(true)
                 .open(&settings.output_file_path)
                 .expect("Failed to open output file"),
+            settings,
+            super::output_size(settings, count),
         ));
     }
 
-    let stdout = std::io::stdout();
-    let mut out = super::SecureBufWriter::new(stdout.lock());
+    let mut out = super::stdout_writer(settings);
+    let mut coverage = Coverage::new(settings, count);
+    let mut history = Recorder::new(settings, count);
 
-    for _ in 0..count {
-        generate_from_charset(chars, settings.pass_length, &mut buf);
+    let mut emit = |data: &[u8]| {
+        if let Some(ref mut c) = coverage {
+            c.add(data);
+        }
+        if let Some(ref mut h) = history {
+            h.add(data);
+        }
         if settings.to_clipboard {
-            // Safety: buf contains only ASCII bytes from charset
-            passwords.push_str(unsafe { std::str::from_utf8_unchecked(&buf) });
-            passwords.push('\n');
+            passwords.push(data);
+        } else if let Some(ref mut f) = file {
+            let _ = f.write_all(data);
         } else {
+            let _ = out.write_all(data);
+        }
+    };
+
+    // Large runs are split across threads, written back in order
+    let workers = parallel::workers(count);
+    if workers > 1 {
+        parallel::generate(settings, count, workers, |chunk| {
+            emit(chunk.data());
+            true
+        });
+    } else {
+        for n in 0..count {
+            next_password(settings, chars, n, &mut buf);
             buf.push(b'\n');
-            if let Some(ref mut f) = file {
-                let _ = f.write_all(&buf);
-            } else {
-                let _ = out.write_all(&buf);
-            }
+            emit(&buf);
+            buf.zeroize();
         }
-        buf.zeroize();
     }
 
+    finish_file(file);
+    report_coverage(coverage);
+    finish_history(history);
     if settings.to_clipboard {
         return Some(passwords);
     }
     None
 }
 
-fn generate_batch_slow(settings: &Settings, count: usize) -> Option<String> {
-    let mut passwords = String::with_capacity(count * (settings.pass_length + 1));
+fn generate_batch_slow(settings: &Settings, count: usize) -> Option<ClipBuf> {
+    let mut passwords = clip_buf(settings, count);
 
-    let mut file: Option<super::SecureBufWriter<std::fs::File>> = None;
+    let mut file = None;
     if !settings.output_file_path.is_empty() {
-        file = Some(super::SecureBufWriter::new(
+        file = Some(super::file_writer(
             OpenOptions::new()
+                .read(settings.mmap)
                 .create(true)
                 .append(true)
                 .open(&settings.output_file_path)
                 .expect("Failed to open output file"),
+            settings,
+            super::output_size(settings, count),
         ));
     }
 
-    let stdout = std::io::stdout();
-    let mut out = super::SecureBufWriter::new(stdout.lock());
+    let mut out = super::stdout_writer(settings);
+    let mut buf = pool::take(max_len(settings) + 1);
+    let mut coverage = Coverage::new(settings, count);
+    let mut history = Recorder::new(settings, count);
 
     for _ in 0..count {
-        let mut pass = generate(settings);
-        pass.push('\n');
+        generate(settings, &mut buf);
+        buf.push(b'\n');
+        if let Some(ref mut c) = coverage {
+            c.add(&buf);
+        }
+        if let Some(ref mut h) = history {
+            h.add(&buf);
+        }
         if settings.to_clipboard {
-            passwords.push_str(&pass);
+            passwords.push(&buf);
         } else if let Some(ref mut f) = file {
-            let _ = f.write_all(pass.as_bytes());
+            let _ = f.write_all(&buf);
         } else {
-            let _ = out.write_all(pass.as_bytes());
+            let _ = out.write_all(&buf);
         }
-        pass.zeroize();
+        buf.zeroize();
     }
 
+    finish_file(file);
+    report_coverage(coverage);
+    finish_history(history);
     if settings.to_clipboard {
         return Some(passwords);
     }
     None
 }
 
-/// Generate a single password based on settings.
-pub fn generate(settings: &Settings) -> String {
-    let mut chars = charset::build(settings);
+/// Seal the run into the history, warning when that fails.
+fn finish_history(history: Option<Recorder>) {
+    if let Some(Err(e)) = history.map(Recorder::finish) {
+        crate::cli::prompts::warn(&format!("Warning: history not written: {}", e));
+    }
+}
+
+/// Warn about anything the charset coverage check turned up.
+fn report_coverage(coverage: Option<Coverage>) {
+    for finding in coverage.iter().flat_map(Coverage::findings) {
+        crate::cli::prompts::warn(&format!("Warning: charset check: {}", finding));
+    }
+}
+
+/// Flush the output file and apply the `--fsync` policy.
+fn finish_file(file: Option<super::Synced<Box<dyn Write>>>) {
+    if let Some(Err(e)) = file.map(|f| f.finish()) {
+        crate::cli::prompts::error(&format!("Failed to write output file: {}", e));
+    }
+}
+
+/// Generate passwords to the output file or stdout until a write fails,
+/// typically because the reader closed the pipe (`randpass -n 0 | head`).
+/// Urandom pool (if active) is shut down before returning the error.
+pub fn generate_stream(settings: &Settings) -> std::io::Result<()> {
+    let chars = Charset::build(settings);
+    let mut buf = pool::take(max_len(settings) + 1);
+
+    let mut out: Box<dyn Write> = if settings.output_file_path.is_empty() {
+        super::stdout_writer(settings)
+    } else {
+        Box::new(super::file_writer(
+            OpenOptions::new()
+                .read(settings.mmap)
+                .create(true)
+                .append(true)
+                .open(&settings.output_file_path)?,
+            settings,
+            None,
+        ))
+    };
+
+    let mut result = Ok(());
+    for n in 0.. {
+        next_password(settings, &chars, n, &mut buf);
+        buf.push(b'\n');
+        result = out.write_all(&buf);
+        buf.zeroize();
+        if result.is_err() {
+            break;
+        }
+    }
+
+    crate::rand::shutdown_urandom();
+    result
+}
+
+/// Generate a single password into `buf` (cleared first), rebuilding
+/// and shuffling the density-duplicated pool for it: the seed view's path,
+/// which prints that pool. A `--policy` redraws from the same pool.
+/// Caller zeroizes.
+pub fn generate(settings: &Settings, buf: &mut Vec<u8>) {
+    crate::session::count_password();
+    let mut chars = pool::take(charset::size(settings));
+    charset::build_into(settings, &mut chars);
 
     if settings.view_chars_str {
         println!();
@@ -123,19 +248,184 @@ pub fn generate(settings: &Settings) -> String {
         }
     }
 
-    let bytes: Vec<u8> = (0..settings.pass_length)
-        .map(|_| random_byte(&chars, Rand::get()))
-        .collect();
-    // Safety: charset is all ASCII
-    unsafe { String::from_utf8_unchecked(bytes) }
+    let sample = |buf: &mut Vec<u8>| {
+        buf.clear();
+        buf.extend((0..settings.pass_length).map(|_| random_byte(&chars, Rand::get())));
+        if let Some(start) = settings.start_with {
+            start.fix(buf, || random_byte(&chars, Rand::get()));
+        }
+        if let Some(ref policy) = settings.policy {
+            policy.fix_positions(buf, starts(settings), || random_byte(&chars, Rand::get()));
+        }
+        if let Some(ref pad) = settings.pad {
+            pad.apply(buf);
+        }
+    };
+    match settings.policy {
+        Some(ref policy) => policy::enforce(policy, buf, sample),
+        None => sample(buf),
+    }
+}
+
+/// Bits of entropy in one password under `settings`: the passphrase's or
+/// pattern's, or length times log2 of the character set, with each
+/// position `--start-with` or a position rule constrains counting only
+/// the characters allowed there, plus what `--pad` adds. A policy `trial`
+/// takes off what rejection removes, log2 of the draws per accepted
+/// password.
+pub fn entropy(settings: &Settings, trial: Option<&policy::Trial>) -> f64 {
+    let bits = match (&settings.passphrase, &settings.pattern) {
+        (Some(p), _) => p.entropy(),
+        (None, Some(p)) => p.entropy(),
+        (None, None) => {
+            let fixed = charset::constrained(settings);
+            let free = settings.pass_length - fixed.len();
+            crate::terminal::calculate_entropy(free, charset::size(settings))
+                + fixed
+                    .iter()
+                    .map(|&i| crate::terminal::calculate_entropy(1, charset::size_at(settings, i)))
+                    .sum::<f64>()
+                + settings.pad.as_ref().map_or(0.0, |p| p.entropy())
+        }
+    };
+    bits - trial.map_or(0.0, |t| t.draws_per_password().log2())
+}
+
+/// Fewer bits than this per password get a caution.
+pub const WEAK_BITS: f64 = 60.0;
+
+/// A batch this likely to hold a repeat gets a caution.
+pub const DUPLICATE_WARN: f64 = 0.01;
+
+/// Chance that `count` passwords of `bits` each hold at least one
+/// duplicate, by the birthday bound: 1 - e^(-n(n-1) / 2^(bits+1)).
+pub fn duplicate_chance(bits: f64, count: usize) -> f64 {
+    if count < 2 {
+        return 0.0;
+    }
+    let pairs = count as f64 * (count - 1) as f64 / 2.0;
+    let expected = (pairs.log2() - bits).exp2();
+    -(-expected).exp_m1()
+}
+
+/// Why `settings` make weak passwords, one reason each: under
+/// `WEAK_BITS` of entropy, (for character passwords) every character
+/// from one class, or a batch likely to repeat a password. Empty when
+/// they're fine.
+pub fn weaknesses(settings: &Settings, trial: Option<&policy::Trial>) -> Vec<String> {
+    let mut reasons = Vec::new();
+    let bits = entropy(settings, trial);
+    if bits < WEAK_BITS {
+        reasons.push(format!(
+            "{:.1} bits of entropy per password, under {}",
+            bits, WEAK_BITS
+        ));
+    }
+    let chance = duplicate_chance(bits, settings.number_of_passwords);
+    if chance >= DUPLICATE_WARN {
+        reasons.push(format!(
+            "a {:.0}% chance of duplicates among {} passwords",
+            chance * 100.0,
+            crate::terminal::format_number(settings.number_of_passwords)
+        ));
+    }
+    if settings.passphrase.is_none() && settings.pattern.is_none() {
+        let mut chars = charset::build(settings);
+        let mut present: Vec<&str> = chars
+            .iter()
+            .map(|c| match c {
+                b'a'..=b'z' => "lowercase",
+                b'A'..=b'Z' => "uppercase",
+                b'0'..=b'9' => "digits",
+                _ => "symbols",
+            })
+            .collect();
+        present.sort_unstable();
+        present.dedup();
+        chars.zeroize();
+        if let [only] = present[..] {
+            reasons.push(format!("every character is from one class ({})", only));
+        }
+    }
+    reasons
 }
 
-/// Fast path: generate from pre-built charset (no debug output).
+/// Room a password buffer needs: `pass_length` (or the `--pad` length),
+/// or the longest possible passphrase. Add one for a newline.
+pub fn max_len(settings: &Settings) -> usize {
+    match (&settings.passphrase, &settings.pad) {
+        (Some(p), _) => p.max_len(),
+        (None, Some(pad)) => pad.to.max(settings.pass_length),
+        (None, None) => settings.pass_length,
+    }
+}
+
+/// Produce password number `index` into `buf`: words in passphrase mode,
+/// derived from the chain seed when `--chain` is active, otherwise sampled
+/// from the alias table. Redrawn until it satisfies the `--policy`, if any.
+#[inline]
+pub fn next_password(settings: &Settings, chars: &Charset, index: usize, buf: &mut Vec<u8>) {
+    crate::session::count_password();
+    match settings.policy {
+        Some(ref policy) => policy::enforce(policy, buf, |buf| draw(settings, chars, index, buf)),
+        None => draw(settings, chars, index, buf),
+    }
+}
+
+/// Draw `draws` passwords the way `next_password` would and check them
+/// against the `--policy` without redrawing: see `policy::trial`. None
+/// without a policy.
+pub fn trial_policy(settings: &Settings, draws: usize) -> Option<Result<policy::Trial, String>> {
+    let policy = settings.policy.as_ref()?;
+    let chars = Charset::build(settings);
+    let mut buf = pool::take(max_len(settings) + 1);
+    Some(policy::trial(policy, draws, &mut buf, |buf| {
+        draw(settings, &chars, 0, buf)
+    }))
+}
+
+#[inline(always)]
+fn draw(settings: &Settings, chars: &Charset, index: usize, buf: &mut Vec<u8>) {
+    if let Some(ref passphrase) = settings.passphrase {
+        return passphrase.generate(buf);
+    }
+    if let Some(ref pattern) = settings.pattern {
+        return pattern.generate(buf);
+    }
+    match settings.chain_seed {
+        Some(ref seed) => chain::derive(seed, index as u64, &chars.pool, settings.pass_length, buf),
+        None => chars.generate(settings.pass_length, buf),
+    }
+    if let Some(start) = settings.start_with {
+        start.fix(buf, || chars.table.pick(Rand::get()));
+    }
+    if let Some(ref policy) = settings.policy {
+        policy.fix_positions(buf, starts(settings), || chars.table.pick(Rand::get()));
+    }
+    if let Some(ref pad) = settings.pad {
+        pad.apply(buf);
+    }
+}
+
+/// Whether `c` may go at `index` as far as `--start-with` goes, for
+/// position rules that land on the first character.
+#[inline(always)]
+fn starts(settings: &Settings) -> impl Fn(usize, u8) -> bool + '_ {
+    move |index, c| index > 0 || settings.start_with.is_none_or(|s| s.contains(c))
+}
+
+/// Pool path: generate from a pre-built density-duplicated pool.
 /// Shuffles in place, fills buf with password bytes.
 /// Caller owns the buffer — clear/zeroize between calls.
 #[inline]
 pub fn generate_from_charset(chars: &mut [u8], length: usize, buf: &mut Vec<u8>) {
     shuffle(chars);
+    sample(chars, length, buf);
+}
+
+/// Fill `buf` with `length` characters drawn from `chars`.
+#[inline]
+pub fn sample(chars: &[u8], length: usize, buf: &mut Vec<u8>) {
     buf.clear();
     buf.extend((0..length).map(|_| random_byte(chars, Rand::get())));
 }
@@ -145,8 +435,9 @@ fn random_byte(chars: &[u8], rng: usize) -> u8 {
     chars[rng % chars.len()]
 }
 
+/// Reorder the pool with one RNG draw.
 #[inline]
-fn shuffle(chars: &mut [u8]) {
+pub fn shuffle(chars: &mut [u8]) {
     let rng = Rand::get();
     for i in (1..chars.len()).rev() {
         let j = rng % (i + 1);
@@ -0,0 +1,531 @@
+//! Opt-in encrypted history (`randpass history enable`): generated
+//! passwords kept with the time and an optional `--label`, so one lost
+//! between generation and first use can be found again.
+//!
+//! Runs add entries without asking for anything. Each entry is sealed to
+//! the history's X25519 public key: a fresh ephemeral key meets it, BLAKE3
+//! derives an entry key from the shared secret, and ChaCha20-Poly1305
+//! encrypts `label\npassword` under it. Reading needs the private key,
+//! which is kept encrypted under a key stretched from the passphrase with
+//! Argon2id. The time of each entry stays readable, bound to it as
+//! associated data, so expired entries are dropped without the
+//! passphrase.
+//!
+//! Both files live in `$XDG_DATA_HOME/randpass/history` (else
+//! `~/.local/share/randpass/history`), 0600 in a 0700 directory.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, KeyInit};
+use x25519_dalek::{PublicKey, StaticSecret};
+use zeroize::Zeroize;
+
+use super::pool::{self, PoolBuf};
+use crate::rand::Rand;
+use crate::settings::{Settings, ephemeral};
+
+/// Runs of more passwords than this aren't recorded.
+pub const MAX_RECORDED: usize = 100;
+/// Days an entry is kept when `history enable` names none.
+pub const DEFAULT_EXPIRE_DAYS: u64 = 30;
+/// Shortest passphrase `history enable` accepts.
+pub const MIN_PASSPHRASE: usize = 8;
+
+const MAGIC: &str = "randpass-history 1";
+/// Argon2id cost: 64 MiB, 3 passes, one lane (RFC 9106's second
+/// recommended setting, single-threaded).
+const ARGON2_M_KIB: u32 = 64 * 1024;
+const ARGON2_T: u32 = 3;
+const ARGON2_P: u32 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+/// BLAKE3 context for entry keys.
+const ENTRY_CONTEXT: &str = "randpass history 2026-10 entry key";
+
+pub fn dir() -> PathBuf {
+    let data = std::env::var("XDG_DATA_HOME")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| {
+            let home = std::env::var("HOME").unwrap_or_else(|_| ".".into());
+            format!("{}/.local/share", home)
+        });
+    PathBuf::from(data).join("randpass/history")
+}
+
+fn key_path() -> PathBuf {
+    dir().join("key")
+}
+
+fn entries_path() -> PathBuf {
+    dir().join("entries")
+}
+
+/// Whether `history enable` has been run.
+pub fn enabled() -> bool {
+    key_path().is_file()
+}
+
+/// The history's key file: public key, expiry, and the private key
+/// sealed under the passphrase.
+struct Keys {
+    m_kib: u32,
+    t: u32,
+    p: u32,
+    salt: [u8; SALT_LEN],
+    expire_days: u64,
+    public: [u8; 32],
+    nonce: [u8; NONCE_LEN],
+    sealed: Vec<u8>,
+}
+
+impl Keys {
+    fn load() -> Result<Self, String> {
+        let path = key_path();
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err("history is not enabled (randpass history enable)".into());
+            }
+            Err(e) => return Err(format!("{}: {}", path.display(), e)),
+        };
+        Self::parse(&text).ok_or_else(|| format!("{} is damaged", path.display()))
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        let mut lines = text.lines();
+        if lines.next()? != MAGIC {
+            return None;
+        }
+        let mut keys = Self {
+            m_kib: 0,
+            t: 0,
+            p: 0,
+            salt: [0; SALT_LEN],
+            expire_days: 0,
+            public: [0; 32],
+            nonce: [0; NONCE_LEN],
+            sealed: Vec::new(),
+        };
+        for line in lines {
+            let (key, value) = line.split_once(' ')?;
+            match key {
+                "argon2id" => {
+                    let mut costs = value.split(',').map(|v| v.parse().ok());
+                    keys.m_kib = costs.next()??;
+                    keys.t = costs.next()??;
+                    keys.p = costs.next()??;
+                }
+                "salt" => keys.salt = unhex(value)?.try_into().ok()?,
+                "expire_days" => keys.expire_days = value.parse().ok()?,
+                "public" => keys.public = unhex(value)?.try_into().ok()?,
+                "secret" => {
+                    let (nonce, sealed) = value.split_once(' ')?;
+                    keys.nonce = unhex(nonce)?.try_into().ok()?;
+                    keys.sealed = unhex(sealed)?;
+                }
+                _ => {}
+            }
+        }
+        (keys.m_kib > 0 && !keys.sealed.is_empty()).then_some(keys)
+    }
+
+    fn encode(&self) -> String {
+        format!(
+            "{}\nargon2id {},{},{}\nsalt {}\nexpire_days {}\npublic {}\nsecret {} {}\n",
+            MAGIC,
+            self.m_kib,
+            self.t,
+            self.p,
+            hex(&self.salt),
+            self.expire_days,
+            hex(&self.public),
+            hex(&self.nonce),
+            hex(&self.sealed)
+        )
+    }
+
+    /// What the sealed private key is bound to.
+    fn aad(&self) -> Vec<u8> {
+        [&self.salt[..], &self.public[..]].concat()
+    }
+
+    /// Key for the sealed private key, from `passphrase`.
+    fn stretch(&self, passphrase: &[u8]) -> Result<[u8; 32], String> {
+        let params = Params::new(self.m_kib, self.t, self.p, Some(32))
+            .map_err(|e| format!("argon2id: {}", e))?;
+        let mut key = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase, &self.salt, &mut key)
+            .map_err(|e| format!("argon2id: {}", e))?;
+        Ok(key)
+    }
+
+    /// Seconds an entry is kept; None for forever.
+    fn lifetime(&self) -> Option<u64> {
+        (self.expire_days > 0).then(|| self.expire_days * 86_400)
+    }
+}
+
+/// Days entries are kept; 0 for until purged.
+pub fn expire_days() -> Result<u64, String> {
+    Keys::load().map(|keys| keys.expire_days)
+}
+
+/// Turn the history on with `passphrase`, keeping entries `expire_days`
+/// (0 for until purged).
+pub fn enable(passphrase: &[u8], expire_days: u64) -> Result<(), String> {
+    if ephemeral::enabled() {
+        return Err("ephemeral mode (--no-save / RANDPASS_EPHEMERAL=1), nothing is stored".into());
+    }
+    if enabled() {
+        return Err("history is already enabled (history disable removes it)".into());
+    }
+    let mut secret_bytes = [0u8; 32];
+    fill(&mut secret_bytes);
+    let secret = StaticSecret::from(secret_bytes);
+    secret_bytes.zeroize();
+
+    let mut keys = Keys {
+        m_kib: ARGON2_M_KIB,
+        t: ARGON2_T,
+        p: ARGON2_P,
+        salt: [0; SALT_LEN],
+        expire_days,
+        public: PublicKey::from(&secret).to_bytes(),
+        nonce: [0; NONCE_LEN],
+        sealed: Vec::new(),
+    };
+    fill(&mut keys.salt);
+    fill(&mut keys.nonce);
+    let mut key = keys.stretch(passphrase)?;
+    let sealed = ChaCha20Poly1305::new((&key).into()).encrypt(
+        (&keys.nonce).into(),
+        Payload {
+            msg: secret.as_bytes(),
+            aad: &keys.aad(),
+        },
+    );
+    key.zeroize();
+    keys.sealed = sealed.map_err(|_| "could not seal the history key".to_string())?;
+
+    write_file(&key_path(), keys.encode().as_bytes())
+}
+
+/// Remove the history: entries, then the key.
+pub fn disable() -> Result<(), String> {
+    purge(None)?;
+    remove_file(&key_path())
+}
+
+/// One decrypted entry; the password is zeroized on drop.
+pub struct Entry {
+    pub time: u64,
+    pub label: String,
+    pub password: Vec<u8>,
+}
+
+impl Drop for Entry {
+    fn drop(&mut self) {
+        self.password.zeroize();
+    }
+}
+
+/// Every entry that hasn't expired, oldest first, opened with
+/// `passphrase`.
+pub fn open(passphrase: &[u8]) -> Result<Vec<Entry>, String> {
+    let keys = Keys::load()?;
+    let mut key = keys.stretch(passphrase)?;
+    let opened = ChaCha20Poly1305::new((&key).into()).decrypt(
+        (&keys.nonce).into(),
+        Payload {
+            msg: &keys.sealed,
+            aad: &keys.aad(),
+        },
+    );
+    key.zeroize();
+    let mut secret_bytes: [u8; 32] = opened
+        .map_err(|_| "wrong passphrase".to_string())?
+        .try_into()
+        .map_err(|_| "the history key is damaged".to_string())?;
+    let secret = StaticSecret::from(secret_bytes);
+    secret_bytes.zeroize();
+
+    let mut entries = Vec::new();
+    for (time, sealed) in read_entries(&keys)? {
+        entries.push(unseal(&secret, &keys, time, &sealed)?);
+    }
+    Ok(entries)
+}
+
+/// Remove entry `index` (0-based, as `open` orders them), or all of them.
+pub fn purge(index: Option<usize>) -> Result<(), String> {
+    if ephemeral::enabled() {
+        return Err("ephemeral mode (--no-save / RANDPASS_EPHEMERAL=1), nothing removed".into());
+    }
+    match index {
+        None => {
+            let path = entries_path();
+            wipe(&path);
+            remove_file(&path)
+        }
+        Some(i) => {
+            let keys = Keys::load()?;
+            let mut lines = read_entries(&keys)?;
+            if i >= lines.len() {
+                return Err(format!("no entry {}", i + 1));
+            }
+            lines.remove(i);
+            write_entries(&lines)
+        }
+    }
+}
+
+/// Passwords from one run, gathered as they're written and sealed into
+/// the history at the end. Buffered in locked memory, zeroized on drop.
+pub struct Recorder {
+    label: String,
+    data: PoolBuf,
+}
+
+impl Recorder {
+    /// A recorder for a run of `count` passwords, or None when the
+    /// history is off, the run is too large or nothing may be written.
+    pub fn new(settings: &Settings, count: usize) -> Option<Self> {
+        if count == 0 || ephemeral::enabled() || !enabled() {
+            return None;
+        }
+        if count > MAX_RECORDED {
+            crate::cli::prompts::verbose(&format!(
+                "History: {} passwords, over {}; not recorded",
+                count, MAX_RECORDED
+            ));
+            return None;
+        }
+        Some(Self {
+            label: settings.history_label.clone().unwrap_or_default(),
+            data: pool::take(count * (super::max_len(settings) + 1)),
+        })
+    }
+
+    /// Gather newline-separated passwords; a missing final newline is
+    /// implied.
+    pub fn add(&mut self, data: &[u8]) {
+        self.data.extend_from_slice(data);
+        if !data.ends_with(b"\n") {
+            self.data.push(b'\n');
+        }
+    }
+
+    /// Seal what was gathered into the history. A failure is returned for
+    /// the caller to warn about; the passwords are already out.
+    pub fn finish(self) -> Result<(), String> {
+        let passwords: Vec<&[u8]> = self
+            .data
+            .split(|&b| b == b'\n')
+            .filter(|p| !p.is_empty())
+            .collect();
+        record(&self.label, &passwords)
+    }
+}
+
+/// Add `passwords` under `label`, dropping expired entries on the way.
+pub fn record(label: &str, passwords: &[&[u8]]) -> Result<(), String> {
+    let keys = Keys::load()?;
+    let public = PublicKey::from(keys.public);
+    let now = now();
+    let mut lines = read_entries(&keys)?;
+    for password in passwords {
+        lines.push((now, seal(&public, now, label, password)?));
+    }
+    write_entries(&lines)
+}
+
+/// `ephemeral public || ciphertext` for one entry.
+fn seal(public: &PublicKey, time: u64, label: &str, password: &[u8]) -> Result<Vec<u8>, String> {
+    let mut ephemeral_bytes = [0u8; 32];
+    fill(&mut ephemeral_bytes);
+    let ephemeral = StaticSecret::from(ephemeral_bytes);
+    ephemeral_bytes.zeroize();
+    let ephemeral_public = PublicKey::from(&ephemeral);
+    let mut key = entry_key(
+        ephemeral.diffie_hellman(public).as_bytes(),
+        &ephemeral_public,
+        public,
+    );
+
+    let mut plain = pool::take(label.len() + 1 + password.len());
+    plain.extend_from_slice(label.as_bytes());
+    plain.push(b'\n');
+    plain.extend_from_slice(password);
+    // Each entry key is used once, so a fixed nonce is safe
+    let sealed = ChaCha20Poly1305::new((&key).into()).encrypt(
+        (&[0u8; NONCE_LEN]).into(),
+        Payload {
+            msg: &plain,
+            aad: &time.to_be_bytes(),
+        },
+    );
+    key.zeroize();
+    let sealed = sealed.map_err(|_| "could not seal a history entry".to_string())?;
+    Ok([ephemeral_public.as_bytes(), &sealed[..]].concat())
+}
+
+fn unseal(secret: &StaticSecret, keys: &Keys, time: u64, sealed: &[u8]) -> Result<Entry, String> {
+    let damaged = || "a history entry is damaged".to_string();
+    if sealed.len() < 32 {
+        return Err(damaged());
+    }
+    let (ephemeral_public, ciphertext) = sealed.split_at(32);
+    let ephemeral_public = PublicKey::from(<[u8; 32]>::try_from(ephemeral_public).unwrap());
+    let shared = secret.diffie_hellman(&ephemeral_public);
+    if !shared.was_contributory() {
+        return Err(damaged());
+    }
+    let mut key = entry_key(
+        shared.as_bytes(),
+        &ephemeral_public,
+        &PublicKey::from(keys.public),
+    );
+    let opened = ChaCha20Poly1305::new((&key).into()).decrypt(
+        (&[0u8; NONCE_LEN]).into(),
+        Payload {
+            msg: ciphertext,
+            aad: &time.to_be_bytes(),
+        },
+    );
+    key.zeroize();
+    let mut plain = opened.map_err(|_| damaged())?;
+    let entry = match plain.iter().position(|&b| b == b'\n') {
+        Some(split) => Ok(Entry {
+            time,
+            label: String::from_utf8_lossy(&plain[..split]).into_owned(),
+            password: plain[split + 1..].to_vec(),
+        }),
+        None => Err(damaged()),
+    };
+    plain.zeroize();
+    entry
+}
+
+/// Entry key from the shared secret and both public keys.
+fn entry_key(shared: &[u8; 32], ephemeral: &PublicKey, public: &PublicKey) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new_derive_key(ENTRY_CONTEXT);
+    hasher.update(shared);
+    hasher.update(ephemeral.as_bytes());
+    hasher.update(public.as_bytes());
+    *hasher.finalize().as_bytes()
+}
+
+/// Sealed entries as `(time, sealed)`, oldest first, without the expired
+/// ones.
+fn read_entries(keys: &Keys) -> Result<Vec<(u64, Vec<u8>)>, String> {
+    let path = entries_path();
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("{}: {}", path.display(), e)),
+    };
+    let cutoff = keys.lifetime().map_or(0, |life| now().saturating_sub(life));
+    let mut entries = Vec::new();
+    for line in text.lines() {
+        let entry = line
+            .split_once(' ')
+            .and_then(|(time, sealed)| Some((time.parse::<u64>().ok()?, unhex(sealed)?)))
+            .ok_or_else(|| format!("{} is damaged", path.display()))?;
+        if entry.0 >= cutoff {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+fn write_entries(entries: &[(u64, Vec<u8>)]) -> Result<(), String> {
+    let text: String = entries
+        .iter()
+        .map(|(time, sealed)| format!("{} {}\n", time, hex(sealed)))
+        .collect();
+    write_file(&entries_path(), text.as_bytes())
+}
+
+/// Replace `path` atomically (temp file, sync, rename), 0600 in a 0700
+/// directory.
+fn write_file(path: &std::path::Path, data: &[u8]) -> Result<(), String> {
+    if ephemeral::enabled() {
+        return Err("ephemeral mode (--no-save / RANDPASS_EPHEMERAL=1), nothing is stored".into());
+    }
+    let dir = dir();
+    fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(&dir)
+        .map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let tmp = path.with_extension(format!("tmp.{}", std::process::id()));
+    let result = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(&tmp)
+        .and_then(|mut file| {
+            file.write_all(data)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&tmp, path));
+    result.map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        format!("{}: {}", path.display(), e)
+    })
+}
+
+fn remove_file(path: &std::path::Path) -> Result<(), String> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("{}: {}", path.display(), e))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Overwrite `path` with zeros before it is removed.
+fn wipe(path: &std::path::Path) {
+    if let Ok(len) = fs::metadata(path).map(|m| m.len() as usize)
+        && let Ok(mut file) = OpenOptions::new().write(true).open(path)
+    {
+        let _ = file.write_all(&vec![0u8; len]);
+        let _ = file.sync_all();
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn fill(buf: &mut [u8]) {
+    for chunk in buf.chunks_mut(8) {
+        let word = (Rand::get() as u64).to_le_bytes();
+        chunk.copy_from_slice(&word[..chunk.len()]);
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn unhex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
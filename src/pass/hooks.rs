@@ -0,0 +1,100 @@
+//! Hook commands run around a generation run: `pre_generate` before it,
+//! `post_file` after a run written to an output file, then
+//! `post_generate`. Each is a saved setting run through `sh -c`, with the
+//! run described in `RANDPASS_*` variables, never with the passwords.
+//! Their output goes to stderr so stdout keeps carrying only passwords.
+//!
+//! ```text
+//! randpass config set post_file 'rsync -a "$RANDPASS_FILE" vault:drop/'
+//! ```
+
+use std::process::{Command, Stdio};
+
+use crate::settings::Settings;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Hook {
+    PreGenerate,
+    PostFile,
+    PostGenerate,
+}
+
+impl Hook {
+    pub fn name(self) -> &'static str {
+        match self {
+            Hook::PreGenerate => "pre_generate",
+            Hook::PostFile => "post_file",
+            Hook::PostGenerate => "post_generate",
+        }
+    }
+
+    /// The command configured for this hook; empty for none.
+    fn command(self, settings: &Settings) -> &str {
+        match self {
+            Hook::PreGenerate => &settings.pre_generate,
+            Hook::PostFile if settings.output_file_path.is_empty() => "",
+            Hook::PostFile => &settings.post_file,
+            Hook::PostGenerate => &settings.post_generate,
+        }
+    }
+}
+
+/// The hooks a run under `settings` would call, in order.
+pub fn configured(settings: &Settings) -> Vec<Hook> {
+    [Hook::PreGenerate, Hook::PostFile, Hook::PostGenerate]
+        .into_iter()
+        .filter(|h| !h.command(settings).trim().is_empty())
+        .collect()
+}
+
+/// Run `hook` for a run of `count` passwords (0 for a stream) of `bits`
+/// each, waiting for it. Ok when none is configured. A hook that can't
+/// start or exits non-zero is an Err; whether that stops the run is the
+/// caller's call.
+pub fn run(hook: Hook, settings: &Settings, count: usize, bits: f64) -> Result<(), String> {
+    let command = hook.command(settings);
+    if command.trim().is_empty() {
+        return Ok(());
+    }
+    crate::cli::prompts::verbose(&format!("Hook: {}: {}", hook.name(), command));
+
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(std::io::stderr())
+        .env("RANDPASS_HOOK", hook.name())
+        .env("RANDPASS_COUNT", count.to_string())
+        .env("RANDPASS_ENTROPY", format!("{:.1}", bits))
+        .env("RANDPASS_RNG", super::audit::backend());
+    if let Some(ref p) = settings.passphrase {
+        cmd.env("RANDPASS_WORDS", p.words.to_string());
+    } else {
+        let length = match (&settings.pattern, &settings.pad) {
+            (Some(p), _) => p.width(),
+            (None, Some(pad)) => pad.to.max(settings.pass_length),
+            (None, None) => settings.pass_length,
+        };
+        cmd.env("RANDPASS_LENGTH", length.to_string());
+    }
+    if settings.to_clipboard {
+        cmd.env("RANDPASS_OUTPUT", "clipboard");
+    } else if !settings.output_file_path.is_empty() {
+        let path = std::path::absolute(&settings.output_file_path)
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|_| settings.output_file_path.clone());
+        cmd.env("RANDPASS_OUTPUT", "file")
+            .env("RANDPASS_FILE", path);
+    } else {
+        cmd.env("RANDPASS_OUTPUT", "stdout");
+    }
+
+    let status = cmd
+        .status()
+        .map_err(|e| format!("{} hook: {}", hook.name(), e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} hook failed ({})", hook.name(), status))
+    }
+}
@@ -0,0 +1,96 @@
+//! `--leet`: leetspeak substitutions in passphrase words, for sites that
+//! want symbols in a word-based password. Each substitutable letter is
+//! drawn uniformly from itself and its substitutes, so the substitutions
+//! are random choices and count toward the entropy.
+//!
+//! A word's initial is left alone, so `--caps` still shows on it. Every
+//! substitute belongs to one letter and is never a letter itself, which
+//! keeps the word readable back from its substituted form.
+
+use super::passphrase::uniform;
+use super::words::WordList;
+
+const LIGHT: &str = "a=@,e=3,o=0,s=$";
+pub(super) const HEAVY: &str = "a=@4,b=8,e=3,g=9,i=1!,l=|,o=0,s=$5,t=7+,z=2";
+
+pub const CHOICES: &str = "light, heavy or a map like a=@4,e=3";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Leet {
+    /// Substitutes for each of a-z
+    subs: [Vec<u8>; 26],
+}
+
+impl Leet {
+    /// `light`, `heavy`, or a map of lowercase letters to their
+    /// substitutes: `a=@4,e=3`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let map = match s {
+            "light" => LIGHT,
+            "heavy" => HEAVY,
+            map => map,
+        };
+        let mut subs: [Vec<u8>; 26] = Default::default();
+        for entry in map.split(',') {
+            let (letter, to) = entry.split_once('=')?;
+            let &[letter] = letter.as_bytes() else {
+                return None;
+            };
+            if !letter.is_ascii_lowercase() || to.is_empty() {
+                return None;
+            }
+            for b in to.bytes() {
+                let taken = subs.iter().any(|s| s.contains(&b));
+                if !b.is_ascii_graphic() || b.is_ascii_alphabetic() || taken {
+                    return None;
+                }
+                subs[(letter - b'a') as usize].push(b);
+            }
+        }
+        Some(Self { subs })
+    }
+
+    /// Substitutes for lowercase `letter`; empty if it has none.
+    pub fn substitutes(&self, letter: u8) -> &[u8] {
+        self.choices(letter).unwrap_or_default()
+    }
+
+    fn choices(&self, b: u8) -> Option<&[u8]> {
+        b.is_ascii_lowercase()
+            .then(|| self.subs[(b - b'a') as usize].as_slice())
+            .filter(|s| !s.is_empty())
+    }
+
+    /// Substitute the letters of `word` (one word as written, initial
+    /// included) in place.
+    pub fn apply(&self, word: &mut [u8]) {
+        let initial = std::str::from_utf8(word)
+            .ok()
+            .and_then(|w| w.chars().next())
+            .map_or(0, char::len_utf8);
+        for b in word.iter_mut().skip(initial) {
+            if let Some(subs) = self.choices(*b) {
+                let pick = uniform(subs.len() + 1);
+                if pick > 0 {
+                    *b = subs[pick - 1];
+                }
+            }
+        }
+    }
+
+    /// Bits the substitutions add to `word`.
+    fn bits(&self, word: &str) -> f64 {
+        word.bytes()
+            .skip(word.chars().next().map_or(0, char::len_utf8))
+            .filter_map(|b| self.choices(b))
+            .map(|subs| ((subs.len() + 1) as f64).log2())
+            .sum()
+    }
+
+    /// Mean bits the substitutions add to a word drawn from `list`; a word
+    /// with more substitutable letters carries more.
+    pub fn bits_per_word(&self, list: &WordList) -> f64 {
+        let total: f64 = (0..list.len()).map(|i| self.bits(list.word(i))).sum();
+        total / list.len().max(1) as f64
+    }
+}
@@ -0,0 +1,63 @@
+//! `--memorable`: word, digits and a symbol, word again (`Maple7!harbor`).
+//! Built on passphrase mode: the words come from its list or pseudo-words,
+//! and a random `Joint` replaces the separator between them. The first
+//! word is capitalized, which adds nothing.
+//!
+//! The level trades memorability for entropy: higher levels use more
+//! words and longer joints.
+
+use super::passphrase::{Passphrase, Source, WordSep, uniform};
+
+const DIGITS: &[u8] = b"0123456789";
+
+/// Words and joint digits per level, 1 (easiest to remember) first.
+const LEVELS: [(usize, usize); 4] = [(2, 1), (3, 1), (3, 2), (4, 2)];
+
+pub const DEFAULT_LEVEL: usize = 2;
+
+/// Checked by the parser.
+pub fn valid_level(level: usize) -> bool {
+    (1..=LEVELS.len()).contains(&level)
+}
+
+/// Words at `level`, unless `--words` says otherwise.
+pub fn words(level: usize) -> usize {
+    LEVELS[level - 1].0
+}
+
+/// What goes between two words: random digits, then one random symbol.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Joint {
+    pub digits: usize,
+    /// Unique symbols to draw from
+    pub symbols: Vec<u8>,
+}
+
+impl Joint {
+    pub fn push(&self, buf: &mut Vec<u8>) {
+        for _ in 0..self.digits {
+            buf.push(DIGITS[uniform(DIGITS.len())]);
+        }
+        buf.push(self.symbols[uniform(self.symbols.len())]);
+    }
+
+    /// Bytes one joint takes.
+    pub fn width(&self) -> usize {
+        self.digits + 1
+    }
+
+    pub fn entropy(&self) -> f64 {
+        self.digits as f64 * (DIGITS.len() as f64).log2() + (self.symbols.len() as f64).log2()
+    }
+}
+
+/// A memorable passphrase of `words` words from `source`, with the joint
+/// for `level` drawing symbols from `symbols` (unique, non-empty).
+pub fn passphrase(level: usize, words: usize, source: Source, symbols: Vec<u8>) -> Passphrase {
+    let mut passphrase = Passphrase::new(words, WordSep::None, source);
+    passphrase.joint = Some(Joint {
+        digits: LEVELS[level - 1].1,
+        symbols,
+    });
+    passphrase
+}
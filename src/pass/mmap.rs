@@ -0,0 +1,139 @@
+//! `--mmap`: a file writer that copies output straight into a shared
+//! mapping of the file, so bulk runs make no write syscalls at all.
+//!
+//! The file is sized up front (the expected output, then a window at a
+//! time when that runs out) and mapped through a sliding, mlock'd window.
+//! Flushing, finishing, or dropping the writer early (an interrupted run)
+//! trims the file back to the bytes actually written, and so does the
+//! exit handler when a signal ends the run, so an aborted run never leaves
+//! a zero-filled tail.
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::os::fd::AsRawFd;
+use std::sync::atomic::{AtomicI32, AtomicU64, Ordering};
+
+/// Bytes mapped at once.
+const WINDOW: u64 = 64 * 1024 * 1024;
+
+/// The live writer's file and length written, for `trim_on_exit`
+static LIVE_FD: AtomicI32 = AtomicI32::new(-1);
+static LIVE_LEN: AtomicU64 = AtomicU64::new(0);
+
+/// Trim the live writer's file to what was written. Called from the exit
+/// handlers, where destructors don't run.
+pub fn trim_on_exit() {
+    let fd = LIVE_FD.swap(-1, Ordering::AcqRel);
+    if fd >= 0 {
+        unsafe {
+            libc::ftruncate(fd, LIVE_LEN.load(Ordering::Acquire) as libc::off_t);
+        }
+    }
+}
+
+pub(crate) struct MmapWriter {
+    file: File,
+    /// Mapped window: file offset (page aligned), address, length
+    window: Option<(u64, *mut u8, usize)>,
+    /// File offset of the next byte
+    pos: u64,
+    /// Current file size; `pos..end` is reserved but unwritten
+    end: u64,
+}
+
+impl MmapWriter {
+    /// Take over `file`, writing from its current end with room reserved
+    /// for `expected` bytes.
+    pub fn new(file: File, expected: u64) -> io::Result<Self> {
+        let pos = file.metadata()?.len();
+        let end = pos + expected;
+        LIVE_LEN.store(pos, Ordering::Release);
+        LIVE_FD.store(file.as_raw_fd(), Ordering::Release);
+        file.set_len(end)?;
+        Ok(Self {
+            file,
+            window: None,
+            pos,
+            end,
+        })
+    }
+
+    /// Map the window holding `pos`, growing the file first if it is full.
+    fn map(&mut self) -> io::Result<()> {
+        self.unmap();
+        if self.pos >= self.end {
+            self.end = self.pos + WINDOW;
+            self.file.set_len(self.end)?;
+        }
+        let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as u64;
+        let start = self.pos & !(page - 1);
+        let len = (self.end - start).min(WINDOW) as usize;
+
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                self.file.as_raw_fd(),
+                start as libc::off_t,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        unsafe {
+            libc::mlock(ptr, len);
+        }
+        self.window = Some((start, ptr as *mut u8, len));
+        Ok(())
+    }
+
+    fn unmap(&mut self) {
+        if let Some((_, ptr, len)) = self.window.take() {
+            unsafe {
+                libc::munlock(ptr as *const libc::c_void, len);
+                libc::munmap(ptr as *mut libc::c_void, len);
+            }
+        }
+    }
+}
+
+impl Write for MmapWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let (start, ptr, len) = match self.window {
+            Some((start, ptr, len)) if self.pos < start + len as u64 => (start, ptr, len),
+            _ => {
+                self.map()?;
+                self.window.expect("window was just mapped")
+            }
+        };
+        let offset = (self.pos - start) as usize;
+        let n = data.len().min(len - offset);
+        unsafe {
+            std::ptr::copy_nonoverlapping(data.as_ptr(), ptr.add(offset), n);
+        }
+        self.pos += n as u64;
+        LIVE_LEN.store(self.pos, Ordering::Relaxed);
+        Ok(n)
+    }
+
+    /// Unmap and trim the file to what was written; the next write maps
+    /// (and grows) again. Data reaches storage through the `--fsync`
+    /// policy's sync of the file.
+    fn flush(&mut self) -> io::Result<()> {
+        self.unmap();
+        if self.end != self.pos {
+            self.file.set_len(self.pos)?;
+            self.end = self.pos;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for MmapWriter {
+    fn drop(&mut self) {
+        let _ = self.flush();
+        LIVE_FD.store(-1, Ordering::Release);
+    }
+}
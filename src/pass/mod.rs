@@ -3,13 +3,96 @@
 use std::io::Write;
 use zeroize::Zeroize;
 
+pub mod acronym;
+pub mod audit;
+pub mod chain;
 pub mod charset;
+mod clip;
+mod color;
+pub mod coverage;
+pub mod crypt;
+pub mod fsync;
 mod generate;
+pub mod history;
+pub mod hooks;
+pub mod leet;
+pub mod memorable;
+mod mmap;
 pub mod output;
+pub mod pad;
+mod parallel;
+pub mod passphrase;
+pub mod pattern;
+pub mod policy;
+pub mod pool;
+pub mod pronounceable;
+pub mod pseudo;
+mod ring;
+pub mod strength;
+pub mod username;
+pub mod wordlist;
+pub mod words;
 
+pub use clip::ClipBuf;
+pub(crate) use fsync::Synced;
+pub use generate::entropy;
 pub use generate::generate;
 pub use generate::generate_batch;
 pub use generate::generate_from_charset;
+pub use generate::generate_stream;
+pub use generate::max_len;
+pub use generate::next_password;
+pub use generate::trial_policy;
+pub use generate::weaknesses;
+pub use generate::{sample, shuffle};
+pub(crate) use mmap::MmapWriter;
+pub use mmap::trim_on_exit as trim_mmap_on_exit;
+pub(crate) use ring::RingWriter;
+
+/// Stdout for passwords: `SecureBufWriter`, through `ClassColors` with
+/// `--color-classes` (only set when stdout is a terminal).
+pub(crate) fn stdout_writer(settings: &crate::settings::Settings) -> Box<dyn Write> {
+    let out = SecureBufWriter::new(std::io::stdout().lock());
+    if settings.color_classes {
+        Box::new(color::ClassColors::new(out))
+    } else {
+        Box::new(out)
+    }
+}
+
+/// Writer for a password output file: `MmapWriter` with `--mmap`,
+/// `RingWriter` with `--io-uring`, otherwise `SecureBufWriter`, under the
+/// `--fsync` policy. `expected` bytes are preallocated when the output
+/// size is known. With `--mmap` the file must be open for reading too.
+pub(crate) fn file_writer(
+    file: std::fs::File,
+    settings: &crate::settings::Settings,
+    expected: Option<u64>,
+) -> Synced<Box<dyn Write>> {
+    if let Some(len) = expected {
+        fsync::preallocate(&file, len);
+    }
+    let handle = file.try_clone().expect("Failed to set up output file");
+    let writer: Box<dyn Write> = if settings.mmap {
+        Box::new(
+            MmapWriter::new(file, expected.unwrap_or(0)).expect("Failed to set up output file"),
+        )
+    } else if settings.io_uring {
+        Box::new(RingWriter::new(file).expect("Failed to set up output file"))
+    } else {
+        Box::new(SecureBufWriter::new(file))
+    };
+    Synced::new(writer, handle, settings.fsync)
+}
+
+/// Size of `count` passwords in the output file, when every password has
+/// the same length (not passphrases).
+pub(crate) fn output_size(settings: &crate::settings::Settings, count: usize) -> Option<u64> {
+    if settings.passphrase.is_some() {
+        return None;
+    }
+    Some(count as u64 * (max_len(settings) as u64 + 1))
+}
 
 /// Buffered writer that mlock's its buffer, zeroizes on every flush, and
 /// munlock's + zeroizes on drop. Buffer never reallocates — writes that
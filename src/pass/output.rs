@@ -8,25 +8,39 @@ use std::thread;
 use std::thread::sleep;
 use std::time::{Duration, Instant};
 
-use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use crossterm::event::{Event, KeyCode, KeyModifiers};
 use zeroize::Zeroize;
 
 use crate::settings::Settings;
+use crate::terminal::accessible;
+use crate::terminal::console::{self, out, outln};
+use crate::terminal::notify::{self, Outcome};
 use crate::terminal::{
-    RawModeGuard, box_bottom, box_line, box_top, calculate_entropy, clear, countdown_bar,
-    entropy_source_info, entropy_strength, format_number, print_centered, progress_bar_box,
-    reset_terminal,
+    RESET, RawModeGuard, Throttle, YELLOW, box_bottom, box_line, box_top, clear, countdown_bar,
+    crack_times, entropy_source_info, entropy_strength, format_number, print_centered,
+    progress_bar_box, reset_terminal,
 };
 use crate::tui::gen_file_exists_menu;
 
-use super::{charset, generate, generate_from_charset};
+use super::charset::{self, Charset};
+use super::coverage::Coverage;
+use super::generate::{DUPLICATE_WARN, duplicate_chance};
+use super::history::Recorder;
+use super::{generate, max_len, next_password, parallel, pool};
+
+/// Runs of more passwords than this count down first, so a mistyped
+/// count can be cancelled before the file fills up.
+pub const COUNTDOWN_OVER: usize = 500_000;
+
+/// Longest countdown accepted, in seconds.
+pub const MAX_COUNTDOWN: usize = 3600;
 
 fn non_blocking_read(timeout: Duration) -> Option<Event> {
     let (tx, rx) = mpsc::channel();
 
     thread::spawn(move || {
-        if event::poll(timeout).unwrap_or(false)
-            && let Ok(event) = event::read()
+        if console::poll(timeout).unwrap_or(false)
+            && let Ok(event) = console::read()
         {
             let _ = tx.send(event);
         }
@@ -35,16 +49,17 @@ fn non_blocking_read(timeout: Duration) -> Option<Event> {
     rx.recv().ok()
 }
 
-fn draw_header(entropy: f64, strength: &str, source: &str, chars: usize, settings: &Settings) {
+fn draw_header(entropy: f64, strength: &str, source: &str, pool: &str, settings: &Settings) {
     box_top("Entropy");
     box_line(&format!("{:.1} bits ({})", entropy, strength));
-    box_line(&format!("Source: {} • Charset: {} chars", source, chars));
+    box_line(&format!("Crack time: {}", crack_times(entropy)));
+    box_line(&format!("Source: {} • {}", source, pool));
     box_bottom();
-    println!();
+    outln!();
 
     if settings.number_of_passwords > 500_000 {
         print_centered("[Esc/Ctrl+C] to interrupt");
-        println!();
+        outln!();
     }
 
     if !settings.output_to_terminal {
@@ -52,7 +67,7 @@ fn draw_header(entropy: f64, strength: &str, source: &str, chars: usize, setting
             .map(|p| p.display().to_string())
             .unwrap_or_else(|_| settings.output_file_path.clone());
         print_centered(&format!("Output: {}", full_path));
-        println!();
+        outln!();
     }
 }
 
@@ -60,8 +75,14 @@ fn draw_header(entropy: f64, strength: &str, source: &str, chars: usize, setting
 pub fn with_progress(settings: &Settings) {
     reset_terminal();
 
-    let chars = charset::size(settings);
-    let entropy = calculate_entropy(settings.pass_length, chars);
+    let (entropy, pool) = match (&settings.passphrase, &settings.pattern) {
+        (Some(p), _) => (p.entropy(), p.source.describe()),
+        (None, Some(p)) => (p.entropy(), format!("Pattern: {}", p.source)),
+        (None, None) => (
+            super::entropy(settings, None),
+            format!("Charset: {} chars", charset::size(settings)),
+        ),
+    };
     let strength = entropy_strength(entropy);
     let source = entropy_source_info()
         .split(" (")
@@ -69,18 +90,23 @@ pub fn with_progress(settings: &Settings) {
         .unwrap_or("unknown");
 
     clear();
-    draw_header(entropy, strength, source, chars, settings);
+    draw_header(entropy, strength, source, &pool, settings);
 
-    let mut file = get_file(settings).map(super::SecureBufWriter::new);
+    let expected = super::output_size(settings, settings.number_of_passwords);
+    let mut file = get_file(settings).map(|f| super::file_writer(f, settings, expected));
 
     if file.is_none() && !settings.output_file_path.is_empty() {
         clear();
         return;
     }
 
-    if !settings.output_to_terminal && !settings.output_file_path.is_empty() {
+    // Redrawn over the file-exists prompt, which accessible mode keeps
+    if !settings.output_to_terminal
+        && !settings.output_file_path.is_empty()
+        && !accessible::enabled()
+    {
         clear();
-        draw_header(entropy, strength, source, chars, settings);
+        draw_header(entropy, strength, source, &pool, settings);
     }
 
     let (tx, rx) = mpsc::channel::<KeyCode>();
@@ -88,38 +114,54 @@ pub fn with_progress(settings: &Settings) {
 
     let _raw_guard = RawModeGuard::new().ok();
 
-    thread::spawn(move || {
-        let timeout = Duration::from_millis(1);
-        loop {
-            if let Ok(_) | Err(TryRecvError::Disconnected) = close_rx.try_recv() {
-                break;
-            }
-
-            if let Some(Event::Key(key_event)) = non_blocking_read(timeout) {
-                let is_ctrl_c = key_event.code == KeyCode::Char('c')
-                    && key_event.modifiers.contains(KeyModifiers::CONTROL);
-                if is_ctrl_c || key_event.code == KeyCode::Esc {
-                    let _ = tx.send(KeyCode::Esc);
+    // Only runs long enough to interrupt or count down read keys; keys
+    // typed during a short one are left for the menu
+    if settings.number_of_passwords > COUNTDOWN_OVER {
+        thread::spawn(move || {
+            let timeout = Duration::from_millis(1);
+            loop {
+                if let Ok(_) | Err(TryRecvError::Disconnected) = close_rx.try_recv() {
                     break;
-                } else if key_event.code == KeyCode::Enter {
-                    let _ = tx.send(KeyCode::Enter);
+                }
+
+                if let Some(Event::Key(key_event)) = non_blocking_read(timeout) {
+                    let is_ctrl_c = key_event.code == KeyCode::Char('c')
+                        && key_event.modifiers.contains(KeyModifiers::CONTROL);
+                    if is_ctrl_c || key_event.code == KeyCode::Esc {
+                        let _ = tx.send(KeyCode::Esc);
+                        break;
+                    } else if key_event.code == KeyCode::Enter {
+                        let _ = tx.send(KeyCode::Enter);
+                    }
                 }
             }
-        }
-    });
+        });
+    }
 
-    if !settings.skip_countdown && settings.number_of_passwords > 500_000 {
+    // Nobody can press Enter without a terminal on stdin
+    if settings.countdown() > 0
+        && settings.number_of_passwords > COUNTDOWN_OVER
+        && crate::cli::quiet::is_interactive()
+    {
         use crate::rand::Rand;
 
-        print!("\x1b[?25l");
-        std::io::stdout().flush().expect("Failed to flush stdout");
-
-        println!();
-        println!();
-        println!();
+        // Accessible mode says it once rather than redrawing each second
+        let plain = accessible::enabled();
+        if plain {
+            out!(
+                "Starting in {}s; Enter starts now, Esc cancels\r\n",
+                settings.countdown()
+            );
+        } else {
+            out!("\x1b[?25l");
+            outln!();
+            outln!();
+            outln!();
+        }
+        console::stdout().flush().expect("Failed to flush stdout");
 
         let start = Instant::now();
-        let total_duration = Duration::from_secs(10);
+        let total_duration = Duration::from_secs(settings.countdown() as u64);
 
         let mut spot_pos: i32 = (Rand::get() as i32).abs() % 72;
         let mut direction: i32 = if Rand::get().is_multiple_of(2) { 1 } else { -1 };
@@ -139,11 +181,15 @@ pub fn with_progress(settings: &Settings) {
                 Err(TryRecvError::Empty) => {}
             }
 
+            if plain {
+                sleep(Duration::from_millis(100));
+                continue;
+            }
             let remaining = total_duration.saturating_sub(start.elapsed());
             let secs_left = remaining.as_secs() + 1;
             let text = format!("Starting in {:02}s... [Enter] Start Now", secs_left);
 
-            print!("\x1b[3A");
+            out!("\x1b[3A");
             countdown_bar(spot_pos as usize, &text);
 
             spot_pos += direction;
@@ -158,19 +204,23 @@ pub fn with_progress(settings: &Settings) {
             sleep(Duration::from_millis(100));
         }
 
-        print!("\x1b[3A\x1b[J");
-        std::io::stdout().flush().expect("Failed to flush stdout");
+        if !plain {
+            out!("\x1b[3A\x1b[J");
+        }
+        console::stdout().flush().expect("Failed to flush stdout");
 
         if aborted {
+            // Cancelled at the terminal: nothing to notify anyone of
+            notify::cancel();
             let _ = close_tx.send(());
-            print!("\x1b[?25h");
-            std::io::stdout().flush().expect("Failed to flush stdout");
+            out!("\x1b[?25h");
+            console::stdout().flush().expect("Failed to flush stdout");
             reset_terminal();
-            println!();
+            outln!();
             box_top("Cancelled");
             box_line("Generation aborted by user");
             box_bottom();
-            println!();
+            outln!();
             return;
         }
     }
@@ -181,146 +231,183 @@ pub fn with_progress(settings: &Settings) {
 
     let start_time = Instant::now();
 
-    if !settings.output_to_terminal {
-        print!("\x1b[?25l");
-        std::io::stdout().flush().expect("Failed to flush stdout");
-        println!();
-        println!();
-        println!();
+    // Room for the progress box, which redraws in place
+    if !settings.output_to_terminal && !accessible::enabled() {
+        out!("\x1b[?25l");
+        console::stdout().flush().expect("Failed to flush stdout");
+        outln!();
+        outln!();
+        outln!();
     }
 
     // Fast path: pre-build charset when not viewing seeds
-    let mut base_chars = if !settings.view_chars_str {
-        Some(charset::build(settings))
+    let base_chars = if !settings.view_chars_str
+        || settings.chain_seed.is_some()
+        || settings.passphrase.is_some()
+        || settings.pattern.is_some()
+    {
+        Some(Charset::build(settings))
     } else {
         None
     };
 
-    let mut buf = Vec::with_capacity(settings.pass_length + 1);
-    let render_interval = Duration::from_millis(50);
-    let mut last_render = Instant::now() - render_interval;
-
-    for n in 0..settings.number_of_passwords {
-        if settings.number_of_passwords > 500_000 {
-            let should_interrupt = matches!(
-                rx.try_recv(),
-                Ok(KeyCode::Esc) | Err(TryRecvError::Disconnected)
-            );
-            if should_interrupt {
+    let mut buf = pool::take(max_len(settings) + 1);
+    // Terminal lines: \r, password, \r\n
+    let mut line = pool::take(max_len(settings) + 3);
+    let mut throttle = Throttle::new(settings.number_of_passwords);
+    let mut coverage = Coverage::new(settings, settings.number_of_passwords);
+    let mut history = Recorder::new(settings, settings.number_of_passwords);
+
+    // File-only bulk runs are split across threads, written back in order
+    let workers = parallel::workers(settings.number_of_passwords);
+    if base_chars.is_some() && !settings.output_to_terminal && workers > 1 {
+        let mut done = 0;
+        let mut interrupted = false;
+        parallel::generate(settings, settings.number_of_passwords, workers, |chunk| {
+            if settings.number_of_passwords > 500_000 && interrupt_requested(&rx) {
+                interrupted = true;
+                return false;
+            }
+            if let Some(ref mut f) = file {
+                let _ = f.write_all(chunk.data());
+            }
+            if let Some(ref mut c) = coverage {
+                c.add(chunk.data());
+            }
+            done += chunk.count();
+            if throttle.due(done) {
+                draw_progress(done, settings.number_of_passwords, &throttle);
+            }
+            true
+        });
+        if interrupted {
+            clear();
+            report_interrupted(&close_tx, done, start_time, "");
+            return;
+        }
+    } else {
+        for n in 0..settings.number_of_passwords {
+            if settings.number_of_passwords > 500_000 && interrupt_requested(&rx) {
                 let printed = if !settings.output_to_terminal {
                     clear();
                     "".to_owned()
+                } else if accessible::enabled() {
+                    format!(", printed {}", n)
                 } else if n > 10 {
                     clear_last_n_lines(10);
                     format!(", printed {}", n - 8)
                 } else {
                     "".to_owned()
                 };
-                let _ = close_tx.send(());
-                print!("\x1b[?25h");
-                std::io::stdout().flush().expect("Failed to flush stdout");
-                reset_terminal();
-
-                println!();
-                box_top("Interrupted");
-                box_line(&format!(
-                    "{n} password(s) generated in {}ms{}",
-                    start_time.elapsed().as_millis(),
-                    printed
-                ));
-                box_bottom();
-                println!();
-                crate::rand::shutdown_urandom();
+                report_interrupted(&close_tx, n, start_time, &printed);
                 return;
             }
-        }
 
-        match &mut base_chars {
-            Some(chars) => generate_from_charset(chars, settings.pass_length, &mut buf),
-            None => {
-                let mut pass = generate(settings);
-                buf.clear();
-                buf.extend_from_slice(pass.as_bytes());
-                pass.zeroize();
-            }
-        };
+            match &base_chars {
+                Some(chars) => next_password(settings, chars, n, &mut buf),
+                None => generate(settings, &mut buf),
+            };
 
-        if let Some(ref mut f) = file {
-            buf.push(b'\n');
-            let _ = f.write_all(&buf);
-        }
+            if let Some(ref mut f) = file {
+                buf.push(b'\n');
+                let _ = f.write_all(&buf);
+            }
+            if let Some(ref mut c) = coverage {
+                c.add(&buf);
+            }
+            if let Some(ref mut h) = history {
+                h.add(&buf);
+            }
 
-        if settings.output_to_terminal {
-            // Prepend \r, append \r\n for TUI line output
-            let mut line = Vec::with_capacity(buf.len() + 3);
-            line.push(b'\r');
-            line.extend_from_slice(&buf);
-            line.extend_from_slice(b"\r\n");
-            let stdout = std::io::stdout();
-            let mut out = stdout.lock();
-            let _ = out.write_all(&line);
-            drop(out);
-            line.zeroize();
-        } else {
-            let now = Instant::now();
-            if now.duration_since(last_render) >= render_interval
-                || n + 1 == settings.number_of_passwords
-            {
-                last_render = now;
-                let num = settings.number_of_passwords as f32;
-                let pct = ((n + 1) as f32 / num) * 100.0;
-                let elapsed = start_time.elapsed();
-                let avg = (elapsed.as_millis() as f32) / 1000.0 / (n as f32 + 1.0);
-                let left = num - (n as f32 + 1.0);
-                let eta = avg * left;
-                let stats = format!(
-                    "{} of {} • {:.1}% • ETA: {:.1}s",
-                    format_number(n + 1),
-                    format_number(settings.number_of_passwords),
-                    pct,
-                    eta
-                );
-                print!("\x1b[3A");
-                progress_bar_box(pct, &stats);
-                std::io::stdout().flush().expect("Failed to flush stdout");
+            if settings.output_to_terminal {
+                // Prepend \r, append \r\n for TUI line output
+                line.push(b'\r');
+                line.extend_from_slice(&buf);
+                line.extend_from_slice(b"\r\n");
+                let mut out = console::stdout();
+                let _ = out.write_all(&line);
+                drop(out);
+                line.zeroize();
+            } else if throttle.due(n + 1) {
+                draw_progress(n + 1, settings.number_of_passwords, &throttle);
             }
-        }
 
-        buf.zeroize();
+            buf.zeroize();
+        }
     }
 
+    // Flush and sync before stopping the clock: that's part of the run
+    let synced = file.map(|f| f.finish());
+    let elapsed = start_time.elapsed();
+
     let _ = close_tx.send(());
     drop(_raw_guard);
 
-    print!("\x1b[?25h");
-    std::io::stdout().flush().expect("Failed to flush stdout");
+    out!("\x1b[?25h");
+    console::stdout().flush().expect("Failed to flush stdout");
     reset_terminal();
 
     if !settings.output_to_terminal {
         clear();
     }
 
-    println!();
+    outln!();
     box_top("Complete");
     box_line(&format!(
         "{} password(s) generated in {}ms",
         settings.number_of_passwords,
-        start_time.elapsed().as_millis()
+        elapsed.as_millis()
     ));
+    draw_duplicates(entropy, settings.number_of_passwords);
+    for finding in coverage.iter().flat_map(Coverage::findings) {
+        box_line(&format!("{YELLOW}Charset check: {}{RESET}", finding));
+    }
+    if let Some(Err(e)) = history.map(Recorder::finish) {
+        box_line(&format!("{YELLOW}History not written: {}{RESET}", e));
+    }
     if !settings.output_to_terminal {
         let full_path = std::fs::canonicalize(&settings.output_file_path)
             .map(|p| p.display().to_string())
             .unwrap_or_else(|_| settings.output_file_path.clone());
         box_line(&format!("Output: {}", full_path));
     }
+    match synced {
+        Some(Ok(())) => box_line(&format!("Fsync: {}", settings.fsync)),
+        Some(Err(e)) => box_line(&format!("Write failed: {}", e)),
+        None => {}
+    }
     box_bottom();
-    println!();
+    outln!();
     crate::rand::shutdown_urandom();
 }
 
+/// Batches with less chance of a repeat than this leave it unmentioned.
+const DUPLICATE_SHOWN: f64 = 1e-6;
+
+/// The birthday-bound chance of a repeated password in the batch, when
+/// it is worth a mention; yellow once duplicates are likely.
+fn draw_duplicates(entropy: f64, count: usize) {
+    let chance = duplicate_chance(entropy, count);
+    if chance < DUPLICATE_SHOWN {
+        return;
+    }
+    let percent = if chance >= 1e-4 {
+        format!("{:.2}%", chance * 100.0)
+    } else {
+        "under 0.01%".to_string()
+    };
+    if chance >= DUPLICATE_WARN {
+        box_line(&format!(
+            "{YELLOW}Duplicate chance: {} (birthday bound); use longer passwords{RESET}",
+            percent
+        ));
+    } else {
+        box_line(&format!("Duplicate chance: {} (birthday bound)", percent));
+    }
+}
+
 fn clear_last_n_lines(n: usize) {
-    let stdout = std::io::stdout();
-    let mut handle = stdout.lock();
+    let mut handle = console::stdout();
 
     for _ in 0..n {
         let _ = handle.write_all(b"\x1b[1A");
@@ -344,6 +431,7 @@ fn get_file(settings: &Settings) -> Option<File> {
             }
             Some(
                 OpenOptions::new()
+                    .read(settings.mmap)
                     .create(true)
                     .append(true)
                     .open(&settings.output_file_path)
@@ -354,3 +442,50 @@ fn get_file(settings: &Settings) -> Option<File> {
         None
     }
 }
+
+/// Esc, Ctrl+C, or the key reader gone.
+fn interrupt_requested(rx: &mpsc::Receiver<KeyCode>) -> bool {
+    matches!(
+        rx.try_recv(),
+        Ok(KeyCode::Esc) | Err(TryRecvError::Disconnected)
+    )
+}
+
+/// Redraw the progress box for `done` of `total` passwords.
+fn draw_progress(done: usize, total: usize, throttle: &Throttle) {
+    let pct = (done as f32 / total as f32) * 100.0;
+    let eta = throttle.eta_text(done);
+    let stats = format!(
+        "{} of {} • {:.1}% • {}/s • ETA: {}",
+        format_number(done),
+        format_number(total),
+        pct,
+        format_number(throttle.rate(done) as usize),
+        eta
+    );
+    if !accessible::enabled() {
+        out!("\x1b[3A");
+    }
+    progress_bar_box(pct, &stats);
+    console::stdout().flush().expect("Failed to flush stdout");
+}
+
+/// Restore the terminal and report a run stopped after `n` passwords.
+fn report_interrupted(close_tx: &mpsc::Sender<()>, n: usize, start_time: Instant, printed: &str) {
+    let _ = close_tx.send(());
+    out!("\x1b[?25h");
+    console::stdout().flush().expect("Failed to flush stdout");
+    reset_terminal();
+
+    outln!();
+    box_top("Interrupted");
+    box_line(&format!(
+        "{n} password(s) generated in {}ms{}",
+        start_time.elapsed().as_millis(),
+        printed
+    ));
+    box_bottom();
+    outln!();
+    crate::rand::shutdown_urandom();
+    notify::finish(Outcome::Interrupted);
+}
@@ -0,0 +1,55 @@
+//! `--pad CHAR|random --pad-to LEN`: "haystack" passwords, a short random
+//! core lengthened with one character repeated, like `x7#Qm2........`.
+//! The length defeats guessing that goes by length first, but the
+//! padding is no secret to anyone who knows the scheme, so it isn't
+//! counted as random characters: a fixed character adds nothing, and a
+//! random one adds the bits of one draw, however often it repeats.
+
+use super::passphrase::uniform;
+
+/// Core length when `-l` doesn't set it.
+pub const DEFAULT_CORE: usize = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fill {
+    Char(u8),
+    /// One character drawn per password from these (unique)
+    Random(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pad {
+    pub fill: Fill,
+    /// Length with the padding
+    pub to: usize,
+}
+
+impl Pad {
+    /// Pad the core in `buf` out to `to` bytes.
+    #[inline]
+    pub fn apply(&self, buf: &mut Vec<u8>) {
+        let c = match self.fill {
+            Fill::Char(c) => c,
+            Fill::Random(ref chars) => chars[uniform(chars.len())],
+        };
+        if buf.len() < self.to {
+            buf.resize(self.to, c);
+        }
+    }
+
+    /// Bits the padding adds: the one random draw, if any.
+    pub fn entropy(&self) -> f64 {
+        match self.fill {
+            Fill::Char(_) => 0.0,
+            Fill::Random(ref chars) => (chars.len() as f64).log2(),
+        }
+    }
+
+    /// The fill, for messages.
+    pub fn describe(&self) -> String {
+        match self.fill {
+            Fill::Char(c) => format!("'{}'", c as char),
+            Fill::Random(ref chars) => format!("one of {}", String::from_utf8_lossy(chars)),
+        }
+    }
+}
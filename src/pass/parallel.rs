@@ -0,0 +1,172 @@
+//! Multithreaded bulk generation.
+//!
+//! Workers claim chunks of consecutive password indices, generate each
+//! chunk into its own locked buffer with their own RNG state and charset
+//! copy, and send it to the calling thread. The caller receives chunks in
+//! index order, so output matches the single-threaded layout (and a
+//! `--chain` batch is byte-identical).
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+use zeroize::Zeroize;
+
+use super::charset::Charset;
+use super::{max_len, next_password, pool};
+use crate::rand::{self, Rand};
+use crate::settings::Settings;
+
+/// Runs shorter than this stay single-threaded; spawning costs more than
+/// it saves.
+pub const THRESHOLD: usize = 100_000;
+
+/// Passwords per chunk.
+const CHUNK: usize = 4096;
+
+/// More workers than this only contend on the writer.
+const MAX_WORKERS: usize = 8;
+
+/// One finished chunk: newline-terminated passwords for indices
+/// `first..first + count`. Zeroized and unlocked on drop.
+pub struct Chunk {
+    first: usize,
+    count: usize,
+    data: Vec<u8>,
+}
+
+impl Chunk {
+    fn new(first: usize, count: usize, capacity: usize) -> Self {
+        let data = Vec::with_capacity(capacity);
+        unsafe {
+            libc::mlock(data.as_ptr() as *const libc::c_void, data.capacity());
+        }
+        Self { first, count, data }
+    }
+
+    /// Append one password and its newline. Grows into a fresh locked
+    /// buffer rather than letting Vec reallocate and leave a copy behind.
+    fn push(&mut self, password: &[u8]) {
+        let needed = self.data.len() + password.len() + 1;
+        if needed > self.data.capacity() {
+            let mut grown =
+                Chunk::new(self.first, self.count, needed.max(self.data.capacity() * 2));
+            grown.data.extend_from_slice(&self.data);
+            std::mem::swap(self, &mut grown);
+        }
+        self.data.extend_from_slice(password);
+        self.data.push(b'\n');
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Passwords in this chunk.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl Drop for Chunk {
+    fn drop(&mut self) {
+        let ptr = self.data.as_ptr();
+        let cap = self.data.capacity();
+        self.data.zeroize();
+        unsafe {
+            libc::munlock(ptr as *const libc::c_void, cap);
+        }
+    }
+}
+
+/// Worker threads to use, or 1 when the run is too small or the machine
+/// has a single core.
+pub fn workers(count: usize) -> usize {
+    if count < THRESHOLD {
+        return 1;
+    }
+    thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(MAX_WORKERS)
+}
+
+/// Generate `count` passwords on `workers` threads (fast path only: the
+/// charset is built per worker) and hand each chunk to `sink` in order.
+/// `sink` returns false to stop early. Returns the passwords handed over.
+pub fn generate(
+    settings: &Settings,
+    count: usize,
+    workers: usize,
+    mut sink: impl FnMut(&Chunk) -> bool,
+) -> usize {
+    // Bring up the urandom pool here: its first use may prompt
+    Rand::get();
+
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let (tx, rx) = mpsc::sync_channel::<Chunk>(workers * 2);
+    let mut written = 0;
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let tx = tx.clone();
+            let (next, stop) = (&next, &stop);
+            scope.spawn(move || {
+                worker(settings, count, next, stop, tx);
+                crate::session::flush_thread();
+                rand::zeroize_state();
+            });
+        }
+        drop(tx);
+        // Owned here so returning early disconnects the workers
+        let rx = rx;
+
+        // Chunks arrive out of order; hold the early ones until their turn
+        let mut pending = BTreeMap::new();
+        let mut expected = 0;
+        for chunk in rx.iter() {
+            pending.insert(chunk.first, chunk);
+            while let Some(chunk) = pending.remove(&expected) {
+                expected += chunk.count;
+                if !sink(&chunk) {
+                    stop.store(true, Ordering::Relaxed);
+                    pending.clear();
+                    return;
+                }
+                written += chunk.count;
+            }
+        }
+    });
+
+    written
+}
+
+fn worker(
+    settings: &Settings,
+    count: usize,
+    next: &AtomicUsize,
+    stop: &AtomicBool,
+    tx: mpsc::SyncSender<Chunk>,
+) {
+    let chars = Charset::build(settings);
+    let mut buf = pool::take(max_len(settings));
+    while !stop.load(Ordering::Relaxed) {
+        let first = next.fetch_add(CHUNK, Ordering::Relaxed);
+        if first >= count {
+            break;
+        }
+        let n = CHUNK.min(count - first);
+        let mut chunk = Chunk::new(first, n, n * (max_len(settings) + 1));
+        for index in first..first + n {
+            next_password(settings, &chars, index, &mut buf);
+            chunk.push(&buf);
+            buf.zeroize();
+        }
+        // The receiver is gone once the caller stops
+        if tx.send(chunk).is_err() {
+            break;
+        }
+    }
+}
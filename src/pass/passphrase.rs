@@ -0,0 +1,305 @@
+//! Passphrase mode: words drawn uniformly from a wordlist, optionally
+//! decorated with capitals, digits and symbols for complexity policies.
+
+use zeroize::Zeroize;
+
+use super::leet::Leet;
+use super::memorable::Joint;
+use super::pool;
+use super::pronounceable::Language;
+use super::pseudo::{self, Model};
+use super::wordlist;
+use super::words::WordList;
+use crate::rand::Rand;
+use crate::settings::Settings;
+
+/// Word count when only a list is chosen: 77 bits from a 2048-word list.
+pub const DEFAULT_WORDS: usize = 7;
+
+/// `--word-sep`: what goes between words.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WordSep {
+    Char(char),
+    /// Words run together
+    None,
+    /// Words run together, each capitalized (CorrectHorseBatteryStaple)
+    Camel,
+}
+
+impl Default for WordSep {
+    fn default() -> Self {
+        WordSep::Char('-')
+    }
+}
+
+impl WordSep {
+    /// Parse `none`, `camel`, or a single character.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "none" => Some(WordSep::None),
+            "camel" => Some(WordSep::Camel),
+            _ => {
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Some(WordSep::Char(c)),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for WordSep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WordSep::Char(c) => write!(f, "{}", c),
+            WordSep::None => f.write_str("none"),
+            WordSep::Camel => f.write_str("camel"),
+        }
+    }
+}
+
+const DIGITS: &[u8] = b"0123456789";
+
+/// `--caps`: how word initials are capitalized.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Caps {
+    #[default]
+    None,
+    /// Every word, adds nothing
+    First,
+    /// Each word by coin flip, one bit per word
+    Random,
+}
+
+impl Caps {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "none" => Some(Caps::None),
+            "first" => Some(Caps::First),
+            "random" => Some(Caps::Random),
+            _ => None,
+        }
+    }
+}
+
+/// Where words come from.
+#[derive(Debug, Clone, Copy)]
+pub enum Source {
+    List(&'static WordList),
+    /// `--pseudo-words`: made-up words from the trigram model
+    Pseudo(&'static Model),
+    /// `--pronounceable`: words of this many syllables in a language
+    Syllables(&'static Language, usize),
+}
+
+impl Source {
+    pub fn bits_per_word(&self) -> f64 {
+        match self {
+            Source::List(list) => list.bits_per_word(),
+            Source::Pseudo(model) => model.bits_per_word(),
+            Source::Syllables(lang, n) => lang.bits(*n),
+        }
+    }
+
+    /// Bytes in the longest word this source gives.
+    pub fn longest(&self) -> usize {
+        match self {
+            Source::List(list) => list.longest(),
+            Source::Pseudo(_) => pseudo::MAX_LEN,
+            Source::Syllables(lang, n) => lang.longest(*n),
+        }
+    }
+
+    /// One line for the progress header.
+    pub fn describe(&self) -> String {
+        match self {
+            Source::List(list) => format!("Wordlist: {} ({} words)", list.name(), list.len()),
+            Source::Pseudo(model) => format!(
+                "Pseudo-words: {}-{} letters ({:.1} bits each)",
+                pseudo::MIN_LEN,
+                pseudo::MAX_LEN,
+                model.bits_per_word()
+            ),
+            Source::Syllables(lang, n) => format!(
+                "Pronounceable: {}, {} syllables ({:.1} bits each word)",
+                lang.name,
+                n,
+                lang.bits(*n)
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Passphrase {
+    pub words: usize,
+    pub separator: WordSep,
+    pub source: Source,
+    pub caps: Caps,
+    /// Random digits, each appended to a random word
+    pub digits: usize,
+    /// Random symbols from `symbol_set`, each appended to a random word
+    pub symbols: usize,
+    /// Unique symbols to draw from
+    pub symbol_set: Vec<u8>,
+    /// `--leet` substitutions in each word
+    pub leet: Option<Leet>,
+    /// `--memorable`: random characters in place of the separator
+    pub joint: Option<Joint>,
+}
+
+impl Passphrase {
+    pub fn new(words: usize, separator: WordSep, source: Source) -> Self {
+        Self {
+            words,
+            separator,
+            source,
+            caps: Caps::None,
+            digits: 0,
+            symbols: 0,
+            symbol_set: Vec::new(),
+            leet: None,
+            joint: None,
+        }
+    }
+
+    /// Fill `buf` with one passphrase. Caller zeroizes.
+    pub fn generate(&self, buf: &mut Vec<u8>) {
+        // Word index (LE bytes) then character for each decoration, in
+        // insertion order
+        const EXTRA: usize = size_of::<usize>() + 1;
+        let mut extras = pool::take((self.digits + self.symbols) * EXTRA);
+        for _ in 0..self.digits {
+            extras.extend_from_slice(&uniform(self.words).to_le_bytes());
+            extras.push(DIGITS[uniform(DIGITS.len())]);
+        }
+        for _ in 0..self.symbols {
+            extras.extend_from_slice(&uniform(self.words).to_le_bytes());
+            extras.push(self.symbol_set[uniform(self.symbol_set.len())]);
+        }
+
+        buf.clear();
+        for i in 0..self.words {
+            if i > 0 {
+                match self.joint {
+                    Some(ref joint) => joint.push(buf),
+                    None => push_separator(buf, self.separator),
+                }
+            }
+            let upper = match self.caps {
+                _ if self.separator == WordSep::Camel => true,
+                // Memorable passphrases start with a capital
+                Caps::None => i == 0 && self.joint.is_some(),
+                Caps::First => true,
+                Caps::Random => Rand::get() & 1 == 1,
+            };
+            let start = buf.len();
+            match self.source {
+                Source::List(list) => push_word(buf, list.word(uniform(list.len())), upper),
+                Source::Pseudo(model) => {
+                    let mut word = [0u8; pseudo::MAX_LEN];
+                    let len = model.generate(&mut word);
+                    // Only a-z, always UTF-8
+                    let text = std::str::from_utf8(&word[..len]).unwrap_or_default();
+                    push_word(buf, text, upper);
+                    word.zeroize();
+                }
+                Source::Syllables(lang, n) => {
+                    // Zeroized when dropped
+                    let mut word = pool::take(lang.longest(n));
+                    lang.generate(n, &mut word);
+                    // ASCII only, always UTF-8
+                    push_word(buf, std::str::from_utf8(&word).unwrap_or_default(), upper);
+                }
+            }
+            if let Some(ref leet) = self.leet {
+                leet.apply(&mut buf[start..]);
+            }
+            let on_word = extras
+                .chunks_exact(EXTRA)
+                .filter(|e| e[..EXTRA - 1] == i.to_le_bytes());
+            buf.extend(on_word.map(|e| e[EXTRA - 1]));
+        }
+    }
+
+    /// Upper bound on the bytes `generate` writes: longest words, widest
+    /// separator, and room for an uppercased initial to grow.
+    pub fn max_len(&self) -> usize {
+        let joints = self.joint.as_ref().map_or(0, |j| j.width() * self.words);
+        self.words * (self.source.longest() + 16) + self.digits + self.symbols + joints
+    }
+
+    /// Entropy of one passphrase in bits. Only the choices themselves are
+    /// counted, not which word a digit or symbol landed on, so this is a
+    /// lower bound when decorations are used. `--leet` counts its mean
+    /// over the list; on made-up words it isn't counted.
+    pub fn entropy(&self) -> f64 {
+        let mut bits = self.words as f64 * self.source.bits_per_word();
+        if self.caps == Caps::Random && self.separator != WordSep::Camel {
+            bits += self.words as f64;
+        }
+        bits += self.digits as f64 * (DIGITS.len() as f64).log2();
+        if self.symbols > 0 {
+            bits += self.symbols as f64 * (self.symbol_set.len() as f64).log2();
+        }
+        if let Some(ref joint) = self.joint {
+            bits += self.words.saturating_sub(1) as f64 * joint.entropy();
+        }
+        if let (Some(leet), Source::List(list)) = (&self.leet, self.source) {
+            bits += self.words as f64 * leet.bits_per_word(list);
+        }
+        bits
+    }
+}
+
+/// Passphrase mode as saved in `settings` (the TUI), or None when
+/// `passphrase_words` is 0.
+pub fn from_settings(settings: &Settings) -> Result<Option<Passphrase>, String> {
+    if settings.passphrase_words == 0 {
+        return Ok(None);
+    }
+    let list = wordlist::select(None, None, None, &settings.wordlist)?;
+    Ok(Some(Passphrase::new(
+        settings.passphrase_words,
+        settings.word_sep,
+        Source::List(list),
+    )))
+}
+
+/// Append the separator that goes before a word (nothing for none/camel).
+pub fn push_separator(buf: &mut Vec<u8>, separator: WordSep) {
+    if let WordSep::Char(c) = separator {
+        let mut tmp = [0u8; 4];
+        buf.extend_from_slice(c.encode_utf8(&mut tmp).as_bytes());
+    }
+}
+
+/// Append `word`, with its first letter uppercased when `upper` is set.
+pub fn push_word(buf: &mut Vec<u8>, word: &str, upper: bool) {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) if upper => {
+            let mut tmp = [0u8; 4];
+            for c in first.to_uppercase() {
+                buf.extend_from_slice(c.encode_utf8(&mut tmp).as_bytes());
+            }
+            buf.extend_from_slice(chars.as_str().as_bytes());
+            tmp.zeroize();
+        }
+        _ => buf.extend_from_slice(word.as_bytes()),
+    }
+}
+
+/// Uniform index below `n`, rejecting draws above the largest multiple of
+/// `n` so no word is favoured.
+#[inline]
+pub(super) fn uniform(n: usize) -> usize {
+    let zone = usize::MAX - usize::MAX % n;
+    loop {
+        let v = Rand::get();
+        if v < zone {
+            return v % n;
+        }
+    }
+}
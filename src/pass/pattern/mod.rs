@@ -0,0 +1,151 @@
+//! Template passwords (`--pattern`): each position of the template is
+//! either a literal or a random character from a class, so identifiers
+//! with a fixed shape (MAC addresses, serials, PINs) come out right.
+//!
+//! | Token | Draws from |
+//! |-------|------------|
+//! | `d` | digits |
+//! | `l` / `u` / `L` | lowercase / uppercase / any letter |
+//! | `a` / `A` | lowercase and digits / letters and digits |
+//! | `h` / `H` | lowercase / uppercase hex digits |
+//! | `s` | the `--special` set |
+//! | `*` | letters, digits and the `--special` set |
+//!
+//! `{N}` after a token or literal repeats it N times, `\` makes the next
+//! character literal, and anything else is a literal. Named patterns
+//! (`randpass pattern add`) are kept by `named`.
+
+mod named;
+
+pub use named::{dir, load, names, path};
+
+use super::passphrase::uniform;
+
+const DIGITS: &[u8] = b"0123456789";
+const LOWER: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+const UPPER: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const LOWER_HEX: &[u8] = b"0123456789abcdef";
+const UPPER_HEX: &[u8] = b"0123456789ABCDEF";
+
+/// Longest template accepted once repeats are expanded.
+const MAX_SLOTS: usize = 4096;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Slot {
+    Literal(char),
+    /// Unique characters, one drawn uniformly
+    Class(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    /// The template as given, for messages
+    pub source: String,
+    slots: Vec<Slot>,
+}
+
+impl Pattern {
+    /// Parse a template; `special` is the set `s` and `*` draw from.
+    pub fn parse(template: &str, special: &[u8]) -> Result<Self, String> {
+        let mut special = special.to_vec();
+        special.sort_unstable();
+        special.dedup();
+
+        let mut slots = Vec::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            let slot = match c {
+                '\\' => Slot::Literal(
+                    chars
+                        .next()
+                        .ok_or("pattern ends in '\\' with nothing to escape")?,
+                ),
+                'd' => Slot::Class(DIGITS.to_vec()),
+                'l' => Slot::Class(LOWER.to_vec()),
+                'u' => Slot::Class(UPPER.to_vec()),
+                'L' => Slot::Class([LOWER, UPPER].concat()),
+                'a' => Slot::Class([LOWER, DIGITS].concat()),
+                'A' => Slot::Class([LOWER, UPPER, DIGITS].concat()),
+                'h' => Slot::Class(LOWER_HEX.to_vec()),
+                'H' => Slot::Class(UPPER_HEX.to_vec()),
+                's' | '*' if special.is_empty() => {
+                    return Err(format!("'{}' needs a non-empty --special set", c));
+                }
+                's' => Slot::Class(special.clone()),
+                '*' => {
+                    let mut all = [LOWER, UPPER, DIGITS, &special].concat();
+                    all.sort_unstable();
+                    all.dedup();
+                    Slot::Class(all)
+                }
+                '{' => return Err("'{' must follow a token (\\{ for a literal brace)".into()),
+                c => Slot::Literal(c),
+            };
+
+            let mut count = 1;
+            if chars.peek() == Some(&'{') {
+                chars.next();
+                let digits: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                count = digits
+                    .parse()
+                    .ok()
+                    .filter(|&n: &usize| n > 0)
+                    .ok_or_else(|| format!("invalid repeat {{{}}} in pattern", digits))?;
+            }
+            if slots.len() + count > MAX_SLOTS {
+                return Err(format!("pattern is longer than {} characters", MAX_SLOTS));
+            }
+            slots.extend(std::iter::repeat_n(slot, count));
+        }
+
+        if !slots.iter().any(|s| matches!(s, Slot::Class(_))) {
+            return Err(format!("pattern '{}' has nothing random in it", template));
+        }
+        Ok(Self {
+            source: template.to_string(),
+            slots,
+        })
+    }
+
+    /// A pattern built from slots directly, for modes that derive one
+    /// (`--acronym`). `source` describes it in messages.
+    pub(super) fn from_slots(source: String, slots: Vec<Slot>) -> Self {
+        Self { source, slots }
+    }
+
+    /// Fill `buf` (cleared first) with one password. Caller zeroizes.
+    pub fn generate(&self, buf: &mut Vec<u8>) {
+        buf.clear();
+        for slot in &self.slots {
+            match slot {
+                Slot::Literal(c) => {
+                    let mut tmp = [0u8; 4];
+                    buf.extend_from_slice(c.encode_utf8(&mut tmp).as_bytes());
+                }
+                Slot::Class(chars) => buf.push(chars[uniform(chars.len())]),
+            }
+        }
+    }
+
+    /// Bytes in every password: each one is the same length.
+    pub fn width(&self) -> usize {
+        self.slots
+            .iter()
+            .map(|s| match s {
+                Slot::Literal(c) => c.len_utf8(),
+                Slot::Class(_) => 1,
+            })
+            .sum()
+    }
+
+    /// Bits in one password: log2 of each class; literals add nothing.
+    pub fn entropy(&self) -> f64 {
+        self.slots
+            .iter()
+            .map(|s| match s {
+                Slot::Literal(_) => 0.0,
+                Slot::Class(chars) => (chars.len() as f64).log2(),
+            })
+            .sum()
+    }
+}
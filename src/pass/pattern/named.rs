@@ -0,0 +1,42 @@
+//! Named patterns saved with `randpass pattern add`, one `NAME.pat` file
+//! per pattern next to the settings file, so a team can share a format
+//! and `--pattern-name NAME` finds it.
+
+use std::path::PathBuf;
+
+/// `~/.config/randpass/patterns`.
+pub fn dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".into());
+    PathBuf::from(home).join(".config/randpass/patterns")
+}
+
+pub fn path(name: &str) -> PathBuf {
+    dir().join(format!("{}.pat", name))
+}
+
+/// Names of saved patterns, sorted.
+pub fn names() -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|e| {
+            let name = e.file_name().into_string().ok()?;
+            name.strip_suffix(".pat").map(String::from)
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+/// The template saved as `name`.
+pub fn load(name: &str) -> Result<String, String> {
+    match std::fs::read_to_string(path(name)) {
+        Ok(data) => Ok(data.trim_end_matches(['\r', '\n']).to_string()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(format!(
+            "no pattern named '{}' (see `randpass pattern list`)",
+            name
+        )),
+        Err(e) => Err(format!("{}: {}", path(name).display(), e)),
+    }
+}
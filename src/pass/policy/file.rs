@@ -0,0 +1,526 @@
+//! Policy files: a subset of TOML.
+//!
+//! ```toml
+//! min_length = 14
+//! max_length = 64
+//! forbidden_chars = "lI1O0"
+//! forbidden_substrings = ["password", "acme"]
+//! forbidden_words = ["acme", "winter"]
+//! must_match = ['^[A-Za-z]']
+//! must_not_match = ['(?i)qwerty']
+//!
+//! [require]
+//! lowercase = 1
+//! uppercase = 1
+//! digits = 2
+//! special = 1
+//!
+//! [positions]
+//! digits = "last"
+//! special = ["!first", "!last"]
+//! ```
+//!
+//! `allowed_chars = "..."` limits passwords to the characters listed.
+//! `forbidden_words` are substrings too, but matched ignoring case and
+//! leetspeak (`Acm3` contains `acme`). `[positions]` puts a class at
+//! `first`, `last` or a 1-based index, or keeps it out with `!`.
+//!
+//! Supported: `#` comments, `[table]` headers, `key = value` with
+//! integers, basic (`"..."`, with escapes) and literal (`'...'`) strings,
+//! and arrays of those, which may span lines. Unknown keys are
+//! errors, so a typo can't silently drop a rule.
+
+use regex_lite::Regex;
+
+use super::{Class, Policy, Position, SPECS};
+
+enum Value {
+    Int(i64),
+    Str(String),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    fn kind(&self) -> &'static str {
+        match self {
+            Value::Int(_) => "an integer",
+            Value::Str(_) => "a string",
+            Value::Array(_) => "an array",
+        }
+    }
+}
+
+/// Read and parse the policy file at `path`, or the installed policy
+/// named `path` (see `installed::resolve`).
+pub fn load(path: &str) -> Result<Policy, String> {
+    let file = super::installed::resolve(path);
+    let text = std::fs::read_to_string(&file).map_err(|e| format!("{}: {}", path, e))?;
+    parse(&text, path)
+}
+
+/// `policy` in the file format, keys in a fixed order, so any policy
+/// (presets included) can be written out, edited and loaded again.
+pub fn render(policy: &Policy) -> String {
+    let mut out = String::new();
+    let mut line = |key: &str, value: String| out.push_str(&format!("{} = {}\n", key, value));
+    let list = |items: &mut dyn Iterator<Item = &str>| {
+        let items: Vec<String> = items.map(quote).collect();
+        format!("[{}]", items.join(", "))
+    };
+
+    if let Some(min) = policy.min_length {
+        line("min_length", min.to_string());
+    }
+    if let Some(max) = policy.max_length {
+        line("max_length", max.to_string());
+    }
+    if let Some(ref allowed) = policy.allowed_chars {
+        line("allowed_chars", quote(allowed));
+    }
+    if !policy.forbidden_chars.is_empty() {
+        line("forbidden_chars", quote(&policy.forbidden_chars));
+    }
+    if !policy.forbidden_substrings.is_empty() {
+        line(
+            "forbidden_substrings",
+            list(&mut policy.forbidden_substrings.iter().map(String::as_str)),
+        );
+    }
+    if !policy.forbidden_words.is_empty() {
+        line(
+            "forbidden_words",
+            list(&mut policy.forbidden_words.iter().map(String::as_str)),
+        );
+    }
+    if !policy.must_match.is_empty() {
+        line(
+            "must_match",
+            list(&mut policy.must_match.iter().map(Regex::as_str)),
+        );
+    }
+    if !policy.must_not_match.is_empty() {
+        line(
+            "must_not_match",
+            list(&mut policy.must_not_match.iter().map(Regex::as_str)),
+        );
+    }
+    if !policy.require.is_empty() {
+        out.push_str("\n[require]\n");
+        for &(class, count) in &policy.require {
+            out.push_str(&format!("{} = {}\n", class.key(), count));
+        }
+    }
+    if !policy.positions.is_empty() {
+        out.push_str("\n[positions]\n");
+        for class in Class::ALL {
+            let specs: Vec<String> = policy
+                .positions
+                .iter()
+                .filter(|p| p.class == class)
+                .map(Position::spec)
+                .collect();
+            if !specs.is_empty() {
+                out.push_str(&format!(
+                    "{} = {}\n",
+                    class.key(),
+                    list(&mut specs.iter().map(String::as_str))
+                ));
+            }
+        }
+    }
+    out
+}
+
+/// `s` as a basic string.
+fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Parse policy `text`; `source` names it in errors (`corp.toml:3: ...`).
+pub fn parse(text: &str, source: &str) -> Result<Policy, String> {
+    let mut policy = Policy {
+        source: source.to_string(),
+        ..Default::default()
+    };
+    let mut table = String::new();
+    let mut lines = text.lines().enumerate();
+
+    while let Some((n, line)) = lines.next() {
+        let at = |e: String| format!("{}:{}: {}", source, n + 1, e);
+        let mut p = Parser::new(line);
+        match p.peek() {
+            None | Some(b'#') => continue,
+            Some(b'[') => {
+                p.i += 1;
+                table = p.key().map_err(at)?;
+                p.expect(b']').map_err(at)?;
+                if table != "require" && table != "positions" {
+                    return Err(at(format!("unknown table [{}]", table)));
+                }
+                p.end().map_err(at)?;
+                continue;
+            }
+            Some(_) => {}
+        }
+
+        // Arrays may continue on the following lines
+        let mut entry = line.to_string();
+        let (key, value) = loop {
+            let mut p = Parser::new(&entry);
+            let key = p.key().map_err(at)?;
+            p.expect(b'=').map_err(at)?;
+            match p.value() {
+                Ok(value) => {
+                    p.end().map_err(at)?;
+                    break (key, value);
+                }
+                Err(Unfinished::Open) => match lines.next() {
+                    Some((_, more)) => {
+                        entry.push('\n');
+                        entry.push_str(more);
+                    }
+                    None => return Err(at("unterminated array".to_string())),
+                },
+                Err(Unfinished::Error(e)) => return Err(at(e)),
+            }
+        };
+        apply(&mut policy, &table, &key, value).map_err(at)?;
+    }
+
+    if let (Some(min), Some(max)) = (policy.min_length, policy.max_length)
+        && min > max
+    {
+        return Err(format!(
+            "{}: min_length {} is above max_length {}",
+            source, min, max
+        ));
+    }
+    Ok(policy)
+}
+
+/// Set one key of `policy`.
+fn apply(policy: &mut Policy, table: &str, key: &str, value: Value) -> Result<(), String> {
+    if table == "positions" {
+        let class = class(key)?;
+        let mut positions = Vec::new();
+        for spec in strings(key, value)? {
+            positions.extend(
+                Position::parse(class, &spec)
+                    .ok_or_else(|| format!("bad position {:?} for {} ({})", spec, key, SPECS))?,
+            );
+        }
+        policy.positions.retain(|p| p.class != class);
+        policy.positions.extend(positions);
+        return Ok(());
+    }
+    if table == "require" {
+        let class = class(key)?;
+        let count = count(key, value)?;
+        policy.require.retain(|&(c, _)| c != class);
+        policy.require.push((class, count));
+        return Ok(());
+    }
+
+    match key {
+        "min_length" => policy.min_length = Some(count(key, value)?).filter(|&n| n > 0),
+        "max_length" => match count(key, value)? {
+            0 => return Err("max_length must be at least 1".to_string()),
+            n => policy.max_length = Some(n),
+        },
+        "allowed_chars" => match string(key, value)? {
+            s if s.is_empty() => return Err("allowed_chars can't be empty".to_string()),
+            s => policy.allowed_chars = Some(s),
+        },
+        "forbidden_chars" => policy.forbidden_chars = string(key, value)?,
+        "forbidden_substrings" => {
+            policy.forbidden_substrings = strings(key, value)?;
+            if policy.forbidden_substrings.iter().any(String::is_empty) {
+                return Err("forbidden_substrings can't hold an empty string".to_string());
+            }
+        }
+        "forbidden_words" => {
+            let words = strings(key, value)?;
+            if words.iter().any(String::is_empty) {
+                return Err("forbidden_words can't hold an empty string".to_string());
+            }
+            policy.forbidden_words = words.iter().map(|w| w.to_lowercase()).collect();
+        }
+        "must_match" => policy.must_match = patterns(key, value)?,
+        "must_not_match" => policy.must_not_match = patterns(key, value)?,
+        _ => return Err(format!("unknown key {}", key)),
+    }
+    Ok(())
+}
+
+/// The class a `[require]` or `[positions]` key names.
+fn class(key: &str) -> Result<Class, String> {
+    Class::ALL
+        .into_iter()
+        .find(|c| c.key() == key)
+        .ok_or_else(|| {
+            format!(
+                "unknown class {} (lowercase, uppercase, digits or special)",
+                key
+            )
+        })
+}
+
+fn count(key: &str, value: Value) -> Result<usize, String> {
+    match value {
+        Value::Int(n) if n >= 0 => Ok(n as usize),
+        Value::Int(_) => Err(format!("{} can't be negative", key)),
+        other => Err(format!("{} must be an integer, not {}", key, other.kind())),
+    }
+}
+
+fn string(key: &str, value: Value) -> Result<String, String> {
+    match value {
+        Value::Str(s) => Ok(s),
+        other => Err(format!("{} must be a string, not {}", key, other.kind())),
+    }
+}
+
+/// An array of strings; a single string is taken as a one-element array.
+fn strings(key: &str, value: Value) -> Result<Vec<String>, String> {
+    match value {
+        Value::Str(s) => Ok(vec![s]),
+        Value::Array(items) => items
+            .into_iter()
+            .map(|item| match item {
+                Value::Str(s) => Ok(s),
+                other => Err(format!("{} must hold strings, not {}", key, other.kind())),
+            })
+            .collect(),
+        other => Err(format!(
+            "{} must be an array of strings, not {}",
+            key,
+            other.kind()
+        )),
+    }
+}
+
+fn patterns(key: &str, value: Value) -> Result<Vec<Regex>, String> {
+    compile(key, &strings(key, value)?)
+}
+
+/// Compile each pattern once, up front; `key` names them in errors.
+pub fn compile(key: &str, patterns: &[String]) -> Result<Vec<Regex>, String> {
+    patterns
+        .iter()
+        .map(|s| Regex::new(s).map_err(|e| format!("{}: bad pattern /{}/: {}", key, s, e)))
+        .collect()
+}
+
+/// Why a value didn't parse: plain errors, or an array still open at the
+/// end of the text (the caller appends the next line and tries again).
+enum Unfinished {
+    Open,
+    Error(String),
+}
+
+impl From<String> for Unfinished {
+    fn from(e: String) -> Self {
+        Unfinished::Error(e)
+    }
+}
+
+struct Parser<'a> {
+    s: &'a [u8],
+    i: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Parser {
+            s: text.as_bytes(),
+            i: 0,
+        }
+    }
+
+    /// Next significant byte, not consumed. Skips whitespace and newlines;
+    /// a comment runs to the end of its line.
+    fn peek(&mut self) -> Option<u8> {
+        loop {
+            while self.i < self.s.len() && self.s[self.i].is_ascii_whitespace() {
+                self.i += 1;
+            }
+            if self.s.get(self.i) == Some(&b'#') {
+                match self.s[self.i..].iter().position(|&b| b == b'\n') {
+                    Some(end) => self.i += end,
+                    None => return Some(b'#'),
+                }
+            } else {
+                return self.s.get(self.i).copied();
+            }
+        }
+    }
+
+    fn expect(&mut self, want: u8) -> Result<(), String> {
+        match self.peek() {
+            Some(b) if b == want => {
+                self.i += 1;
+                Ok(())
+            }
+            Some(b) => Err(format!(
+                "expected '{}', found '{}'",
+                want as char, b as char
+            )),
+            None => Err(format!("expected '{}'", want as char)),
+        }
+    }
+
+    /// Nothing but a comment may follow.
+    fn end(&mut self) -> Result<(), String> {
+        match self.peek() {
+            None | Some(b'#') => Ok(()),
+            Some(b) => Err(format!("unexpected '{}' after value", b as char)),
+        }
+    }
+
+    /// A bare (`[A-Za-z0-9_-]+`) or quoted key.
+    fn key(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some(b'"') => self.basic_string(),
+            Some(b'\'') => self.literal_string(),
+            _ => {
+                let start = self.i;
+                while self.i < self.s.len()
+                    && (self.s[self.i].is_ascii_alphanumeric() || b"_-".contains(&self.s[self.i]))
+                {
+                    self.i += 1;
+                }
+                if self.i == start {
+                    return Err("expected a key".to_string());
+                }
+                Ok(String::from_utf8_lossy(&self.s[start..self.i]).into_owned())
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, Unfinished> {
+        match self.peek() {
+            Some(b'"') => Ok(Value::Str(self.basic_string()?)),
+            Some(b'\'') => Ok(Value::Str(self.literal_string()?)),
+            Some(b'[') => {
+                self.i += 1;
+                let mut items = Vec::new();
+                loop {
+                    match self.peek() {
+                        None | Some(b'#') => return Err(Unfinished::Open),
+                        Some(b']') => {
+                            self.i += 1;
+                            return Ok(Value::Array(items));
+                        }
+                        Some(_) => {}
+                    }
+                    items.push(self.value()?);
+                    match self.peek() {
+                        None | Some(b'#') => return Err(Unfinished::Open),
+                        Some(b',') => self.i += 1,
+                        Some(b']') => {}
+                        Some(b) => {
+                            return Err(
+                                format!("expected ',' or ']', found '{}'", b as char).into()
+                            );
+                        }
+                    }
+                }
+            }
+            Some(b'+' | b'-' | b'0'..=b'9') => {
+                let start = self.i;
+                self.i += 1;
+                while self.i < self.s.len()
+                    && (self.s[self.i].is_ascii_digit() || self.s[self.i] == b'_')
+                {
+                    self.i += 1;
+                }
+                let digits: String = String::from_utf8_lossy(&self.s[start..self.i])
+                    .chars()
+                    .filter(|&c| c != '_')
+                    .collect();
+                digits
+                    .parse()
+                    .map(Value::Int)
+                    .map_err(|_| Unfinished::Error(format!("invalid integer {}", digits)))
+            }
+            _ => Err("expected a value".to_string().into()),
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, String> {
+        self.i += 1;
+        let start = self.i;
+        match self.s[start..]
+            .iter()
+            .position(|&b| b == b'\'' || b == b'\n')
+        {
+            Some(len) if self.s[start + len] == b'\'' => {
+                self.i = start + len + 1;
+                Ok(String::from_utf8_lossy(&self.s[start..start + len]).into_owned())
+            }
+            _ => Err("unterminated string".to_string()),
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, String> {
+        self.i += 1;
+        let mut out = Vec::new();
+        loop {
+            let Some(&b) = self.s.get(self.i) else {
+                return Err("unterminated string".to_string());
+            };
+            self.i += 1;
+            match b {
+                b'"' => return Ok(String::from_utf8_lossy(&out).into_owned()),
+                b'\n' => return Err("unterminated string".to_string()),
+                b'\\' => {
+                    let Some(&e) = self.s.get(self.i) else {
+                        return Err("unterminated string".to_string());
+                    };
+                    self.i += 1;
+                    let c = match e {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'b' => '\u{8}',
+                        b't' => '\t',
+                        b'n' => '\n',
+                        b'f' => '\u{c}',
+                        b'r' => '\r',
+                        b'u' => self.unicode(4)?,
+                        b'U' => self.unicode(8)?,
+                        _ => return Err(format!("bad escape \\{}", e as char)),
+                    };
+                    let mut utf8 = [0u8; 4];
+                    out.extend_from_slice(c.encode_utf8(&mut utf8).as_bytes());
+                }
+                b => out.push(b),
+            }
+        }
+    }
+
+    /// The character after `\u` or `\U`, written as `digits` hex digits.
+    fn unicode(&mut self, digits: usize) -> Result<char, String> {
+        let code = self
+            .s
+            .get(self.i..self.i + digits)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u32::from_str_radix(h, 16).ok())
+            .and_then(char::from_u32)
+            .ok_or("bad unicode escape")?;
+        self.i += digits;
+        Ok(code)
+    }
+}
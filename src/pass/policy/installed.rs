@@ -0,0 +1,43 @@
+//! Policies installed with `randpass policy import`, kept next to the
+//! settings file so `--policy NAME` finds them by name.
+
+use std::path::{Path, PathBuf};
+
+/// `~/.config/randpass/policies`.
+pub fn installed_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".into());
+    PathBuf::from(home).join(".config/randpass/policies")
+}
+
+pub fn installed_path(name: &str) -> PathBuf {
+    installed_dir().join(format!("{}.toml", name))
+}
+
+/// Names of installed policies, sorted.
+pub fn installed() -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(installed_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|e| {
+            let name = e.file_name().into_string().ok()?;
+            name.strip_suffix(".toml").map(String::from)
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+/// The file `--policy ARG` means: ARG itself if it exists or looks like a
+/// path, else the installed policy of that name, if there is one.
+pub fn resolve(arg: &str) -> PathBuf {
+    if Path::new(arg).exists() || arg.contains('/') {
+        return PathBuf::from(arg);
+    }
+    let installed = installed_path(arg);
+    if installed.exists() {
+        installed
+    } else {
+        PathBuf::from(arg)
+    }
+}
@@ -0,0 +1,455 @@
+//! Password policies: declarative constraints loaded from a file
+//! (`--policy FILE`, format in `file`) that every generated password must
+//! satisfy.
+//!
+//! What can be enforced up front is: forbidden characters are taken out
+//! of the character set, lengths, required classes and positions are
+//! checked against the settings before anything is generated, and
+//! character passwords are sampled to fit the positions (see
+//! `position`). The rest (counts,
+//! substrings, patterns, passphrase lengths) is enforced by rejection:
+//! a password that breaks a rule is zeroized and drawn again, which keeps
+//! the accepted passwords uniform over the ones the policy allows.
+
+mod file;
+mod installed;
+mod position;
+pub mod preset;
+mod retry;
+mod screen;
+
+pub use file::{compile, load, parse, render};
+pub use installed::{installed, installed_dir, installed_path, resolve};
+pub use position::{At, Position, SPECS};
+pub use retry::{MAX_ATTEMPTS, Stats, TRIAL_DRAWS, Trial, enforce, percent, stats, trial};
+pub use screen::{read_words, reads_as};
+
+use regex_lite::Regex;
+
+use super::charset;
+use crate::settings::Settings;
+
+/// Character classes a policy can require.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Class {
+    Lowercase,
+    Uppercase,
+    Digits,
+    /// Anything that isn't an ASCII letter or digit
+    Special,
+}
+
+impl Class {
+    pub const ALL: [Class; 4] = [
+        Class::Lowercase,
+        Class::Uppercase,
+        Class::Digits,
+        Class::Special,
+    ];
+
+    /// Key in the policy file's `[require]` table.
+    pub fn key(self) -> &'static str {
+        match self {
+            Class::Lowercase => "lowercase",
+            Class::Uppercase => "uppercase",
+            Class::Digits => "digits",
+            Class::Special => "special",
+        }
+    }
+
+    /// What one character of the class is called in messages.
+    pub fn noun(self) -> &'static str {
+        match self {
+            Class::Lowercase => "lowercase letter",
+            Class::Uppercase => "uppercase letter",
+            Class::Digits => "digit",
+            Class::Special => "special character",
+        }
+    }
+
+    pub fn contains(self, c: char) -> bool {
+        match self {
+            Class::Lowercase => c.is_ascii_lowercase(),
+            Class::Uppercase => c.is_ascii_uppercase(),
+            Class::Digits => c.is_ascii_digit(),
+            Class::Special => !c.is_ascii_alphanumeric(),
+        }
+    }
+}
+
+/// A rule a password can break. Indexes point into the policy's lists.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rule {
+    MinLength,
+    MaxLength,
+    Require(Class),
+    /// A forbidden character, or one outside `allowed_chars`
+    Chars,
+    Substring(usize),
+    /// A `forbidden_words` entry, matched ignoring case and leetspeak
+    Word(usize),
+    MustMatch(usize),
+    MustNotMatch(usize),
+    Position(usize),
+}
+
+impl Rule {
+    /// Policy keys, one per kind of rule, in `kind` order.
+    pub const KINDS: [&'static str; 12] = [
+        "min_length",
+        "max_length",
+        "require.lowercase",
+        "require.uppercase",
+        "require.digits",
+        "require.special",
+        "allowed/forbidden_chars",
+        "forbidden_substrings",
+        "forbidden_words",
+        "must_match",
+        "must_not_match",
+        "positions",
+    ];
+
+    /// Index into `KINDS`.
+    pub fn kind(self) -> usize {
+        match self {
+            Rule::MinLength => 0,
+            Rule::MaxLength => 1,
+            Rule::Require(class) => 2 + class as usize,
+            Rule::Chars => 6,
+            Rule::Substring(_) => 7,
+            Rule::Word(_) => 8,
+            Rule::MustMatch(_) => 9,
+            Rule::MustNotMatch(_) => 10,
+            Rule::Position(_) => 11,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    /// Where the policy came from, for messages
+    pub source: String,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    /// Minimum count per class; classes not listed aren't required
+    pub require: Vec<(Class, usize)>,
+    /// When set, the only characters a password may contain
+    pub allowed_chars: Option<String>,
+    pub forbidden_chars: String,
+    pub forbidden_substrings: Vec<String>,
+    /// Lowercased; see `screen`
+    pub forbidden_words: Vec<String>,
+    pub must_match: Vec<Regex>,
+    pub must_not_match: Vec<Regex>,
+    /// Classes required or ruled out at given positions
+    pub positions: Vec<Position>,
+}
+
+impl Policy {
+    /// True if `c` may appear in a password.
+    pub fn allows(&self, c: char) -> bool {
+        !self.forbidden_chars.contains(c)
+            && self.allowed_chars.as_ref().is_none_or(|a| a.contains(c))
+    }
+
+    /// True if `c` may go at `index` of a password `len` characters long,
+    /// as far as the position rules go.
+    pub fn allows_at(&self, index: usize, len: usize, c: char) -> bool {
+        self.positions
+            .iter()
+            .all(|p| p.index(len) != Some(index) || p.allows(c))
+    }
+
+    /// Redraw each character of `password` a position rule lands on with
+    /// `pick` until it fits, and `also(index, c)` accepts it (`--start-with`
+    /// on the first): the pool's weights restricted to what's allowed
+    /// there, the other characters untouched. The caller checks (`prepare`)
+    /// that every such position has a character to take.
+    #[inline]
+    pub fn fix_positions(
+        &self,
+        password: &mut [u8],
+        also: impl Fn(usize, u8) -> bool,
+        mut pick: impl FnMut() -> u8,
+    ) {
+        let len = password.len();
+        for rule in &self.positions {
+            if let Some(i) = rule.index(len) {
+                while !(also(i, password[i]) && self.allows_at(i, len, password[i] as char)) {
+                    password[i] = pick();
+                }
+            }
+        }
+    }
+
+    /// Check a password, returning the first rule it breaks.
+    pub fn check(&self, password: &str) -> Result<(), Rule> {
+        let mut first = None;
+        self.scan(password, |rule| {
+            first = Some(rule);
+            false
+        });
+        first.map_or(Ok(()), Err)
+    }
+
+    /// Every rule a password breaks, in policy order.
+    pub fn violations(&self, password: &str) -> Vec<Rule> {
+        let mut broken = Vec::new();
+        self.scan(password, |rule| {
+            broken.push(rule);
+            true
+        });
+        broken
+    }
+
+    /// Pass each rule `password` breaks to `broken`, until it returns false.
+    fn scan(&self, password: &str, mut broken: impl FnMut(Rule) -> bool) {
+        let length = password.chars().count();
+        let rules = [
+            (
+                self.min_length.is_some_and(|min| length < min),
+                Rule::MinLength,
+            ),
+            (
+                self.max_length.is_some_and(|max| length > max),
+                Rule::MaxLength,
+            ),
+        ]
+        .into_iter()
+        .chain(self.require.iter().map(|&(class, count)| {
+            let short = password.chars().filter(|&c| class.contains(c)).count() < count;
+            (short, Rule::Require(class))
+        }))
+        .chain(std::iter::once_with(|| {
+            (password.chars().any(|c| !self.allows(c)), Rule::Chars)
+        }))
+        .chain(
+            self.forbidden_substrings
+                .iter()
+                .enumerate()
+                .map(|(i, s)| (password.contains(s.as_str()), Rule::Substring(i))),
+        )
+        .chain(
+            self.forbidden_words
+                .iter()
+                .enumerate()
+                .map(|(i, w)| (screen::contains(password, w), Rule::Word(i))),
+        )
+        .chain(
+            self.must_match
+                .iter()
+                .enumerate()
+                .map(|(i, re)| (!re.is_match(password), Rule::MustMatch(i))),
+        )
+        .chain(
+            self.must_not_match
+                .iter()
+                .enumerate()
+                .map(|(i, re)| (re.is_match(password), Rule::MustNotMatch(i))),
+        )
+        .chain(self.positions.iter().enumerate().map(move |(i, p)| {
+            let c = p.index(length).and_then(|at| password.chars().nth(at));
+            (c.is_some_and(|c| !p.allows(c)), Rule::Position(i))
+        }));
+        // Lazy, so nothing past the rule that stops the scan is evaluated
+        rules.filter(|&(hit, _)| hit).all(|(_, rule)| broken(rule));
+    }
+
+    /// What breaking `rule` means, for messages.
+    pub fn describe(&self, rule: Rule) -> String {
+        match rule {
+            Rule::MinLength => format!("shorter than min_length {}", self.min_length.unwrap_or(0)),
+            Rule::MaxLength => format!("longer than max_length {}", self.max_length.unwrap_or(0)),
+            Rule::Require(class) => {
+                let count = self
+                    .require
+                    .iter()
+                    .find(|&&(c, _)| c == class)
+                    .map_or(0, |&(_, n)| n);
+                match count {
+                    1 => format!("no {}", class.noun()),
+                    _ => format!("fewer than {} {}s", count, class.noun()),
+                }
+            }
+            Rule::Chars => "contains a character the policy doesn't allow".to_string(),
+            Rule::Substring(i) => format!(
+                "contains forbidden substring {:?}",
+                self.forbidden_substrings[i]
+            ),
+            Rule::Word(i) => format!(
+                "contains forbidden word {:?} (ignoring case and leetspeak)",
+                self.forbidden_words[i]
+            ),
+            Rule::MustMatch(i) => {
+                format!("doesn't match must_match /{}/", self.must_match[i].as_str())
+            }
+            Rule::MustNotMatch(i) => {
+                format!(
+                    "matches must_not_match /{}/",
+                    self.must_not_match[i].as_str()
+                )
+            }
+            Rule::Position(i) => self.positions[i].describe(),
+        }
+    }
+
+    /// Fit `settings` to the policy before generating: an unset length is
+    /// moved into the policy's range, an explicit one must already be in
+    /// it. Errors name the first conflict that no password could get past.
+    pub fn prepare(&self, settings: &mut Settings, explicit_length: bool) -> Result<(), String> {
+        if settings.chain_seed.is_some() {
+            return Err("--chain derives fixed passwords and can't redraw them".to_string());
+        }
+        // Passphrase lengths vary per draw and patterns fix every class;
+        // those are left to rejection
+        if settings.passphrase.is_some() || settings.pattern.is_some() {
+            return Ok(());
+        }
+
+        let min = self.min_length.unwrap_or(1);
+        let max = self.max_length.unwrap_or(usize::MAX);
+        if !(min..=max).contains(&settings.pass_length) {
+            if explicit_length {
+                return Err(format!(
+                    "length {} is outside the policy's range ({})",
+                    settings.pass_length,
+                    self.length_range()
+                ));
+            }
+            settings.pass_length = settings.pass_length.clamp(min, max);
+        }
+
+        let required: usize = self.require.iter().map(|&(_, n)| n).sum();
+        if required > settings.pass_length {
+            return Err(format!(
+                "it requires {} classed characters but passwords are {} long",
+                required, settings.pass_length
+            ));
+        }
+        // Forbidden characters are already out of the pool
+        let pool = charset::build(settings);
+        if pool.is_empty() {
+            return Err("it forbids every character in the character set".to_string());
+        }
+        for &(class, count) in &self.require {
+            if count > 0 && !pool.iter().any(|&c| class.contains(c as char)) {
+                return Err(format!(
+                    "it requires {} characters, which the character set doesn't include",
+                    class.key()
+                ));
+            }
+        }
+        let len = settings.pass_length;
+        for rule in &self.positions {
+            let Some(i) = rule.index(len) else {
+                return Err(format!(
+                    "{} = {} is past the end of {}-character passwords",
+                    rule.class.key(),
+                    rule.spec(),
+                    len
+                ));
+            };
+            if !pool.iter().any(|&c| self.allows_at(i, len, c as char)) {
+                return Err(format!(
+                    "no character in the set fits position {} ({} = {})",
+                    i + 1,
+                    rule.class.key(),
+                    rule.spec()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Saved settings (by `Settings` field name) that keep a password run
+    /// from meeting the policy, with why; empty when none do. Unlike
+    /// `prepare`, nothing is moved, so an interactive user can see and fix
+    /// each one. Passphrases are left to rejection.
+    pub fn conflicts(&self, settings: &Settings) -> Vec<(&'static str, String)> {
+        let mut conflicts = Vec::new();
+        if settings.passphrase.is_some() || settings.passphrase_words > 0 {
+            return conflicts;
+        }
+
+        let min = self.min_length.unwrap_or(1);
+        let max = self.max_length.unwrap_or(usize::MAX);
+        let required: usize = self.require.iter().map(|&(_, n)| n).sum();
+        if !(min..=max).contains(&settings.pass_length) {
+            conflicts.push(("pass_length", format!("policy: {}", self.length_range())));
+        } else if required > settings.pass_length {
+            conflicts.push((
+                "pass_length",
+                format!("policy requires {} classed characters", required),
+            ));
+        }
+
+        let pool = charset::build(settings);
+        if pool.is_empty() {
+            let densities = [
+                ("special_char_density", settings.special_char_density),
+                ("numeric_char_density", settings.numeric_char_density),
+                ("lowercase_char_density", settings.lowercase_char_density),
+                ("uppercase_char_density", settings.uppercase_char_density),
+            ];
+            for (field, density) in densities {
+                if density > 0 {
+                    conflicts.push((field, "policy allows none of these characters".to_string()));
+                }
+            }
+        }
+        for &(class, count) in &self.require {
+            if count == 0 || pool.iter().any(|&c| class.contains(c as char)) {
+                continue;
+            }
+            conflicts.push((
+                class_field(class, settings),
+                format!("policy requires a {}", class.noun()),
+            ));
+        }
+        let len = settings.pass_length;
+        for rule in &self.positions {
+            match rule.index(len) {
+                None => conflicts.push((
+                    "pass_length",
+                    format!("policy sets position {}", rule.spec()),
+                )),
+                Some(i) if !pool.iter().any(|&c| self.allows_at(i, len, c as char)) => {
+                    conflicts.push((
+                        class_field(rule.class, settings),
+                        format!(
+                            "nothing fits position {} ({} = {})",
+                            i + 1,
+                            rule.class.key(),
+                            rule.spec()
+                        ),
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+        conflicts
+    }
+
+    /// `min_length`..`max_length` as text.
+    pub fn length_range(&self) -> String {
+        match (self.min_length, self.max_length) {
+            (Some(min), Some(max)) if min == max => format!("exactly {} characters", min),
+            (Some(min), Some(max)) => format!("{} to {} characters", min, max),
+            (Some(min), None) => format!("minimum of {} characters", min),
+            (None, Some(max)) => format!("maximum of {} characters", max),
+            (None, None) => "any length".to_string(),
+        }
+    }
+}
+
+/// The setting that puts `class` in the character set.
+fn class_field(class: Class, settings: &Settings) -> &'static str {
+    match class {
+        Class::Lowercase => "lowercase_char_density",
+        Class::Uppercase => "uppercase_char_density",
+        Class::Digits => "numeric_char_density",
+        Class::Special if settings.special_char_density == 0 => "special_char_density",
+        Class::Special => "special_chars",
+    }
+}
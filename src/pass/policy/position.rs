@@ -0,0 +1,109 @@
+//! Position rules: a class a character must (or must not) be in at one
+//! place in the password, like legacy systems that want a digit last or
+//! no symbol first (`--digit-at end --special-at !first`, or a
+//! `[positions]` table in a policy file).
+//!
+//! Character passwords meet them by sampling: a constrained character is
+//! redrawn until it fits, which keeps the pool's weights among the
+//! characters allowed there and leaves the other positions alone. Every
+//! other mode (passphrases, patterns) meets them by rejection.
+
+use super::Class;
+
+/// Where a rule applies.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum At {
+    First,
+    Last,
+    /// 1-based, from the start
+    Index(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub class: Class,
+    pub at: At,
+    /// `!`: the character must not be in the class
+    pub not: bool,
+}
+
+/// Accepted position specs, for messages.
+pub const SPECS: &str = "first, last, N, or !first, !last, !N (comma-separated)";
+
+impl Position {
+    /// `spec` is a comma-separated list of `first` (or `start`), `last`
+    /// (or `end`) and 1-based indexes, each with an optional `!`.
+    pub fn parse(class: Class, spec: &str) -> Option<Vec<Self>> {
+        spec.split(',')
+            .map(|item| {
+                let (not, at) = match item.trim().strip_prefix('!') {
+                    Some(at) => (true, at),
+                    None => (false, item.trim()),
+                };
+                let at = match at {
+                    "first" | "start" => At::First,
+                    "last" | "end" => At::Last,
+                    n => At::Index(n.parse().ok().filter(|&n| n > 0)?),
+                };
+                Some(Self { class, at, not })
+            })
+            .collect()
+    }
+
+    /// Index (0-based) the rule lands on in a password of `len`
+    /// characters; None when the password is too short to have it.
+    pub fn index(&self, len: usize) -> Option<usize> {
+        match self.at {
+            At::First => (len > 0).then_some(0),
+            At::Last => len.checked_sub(1),
+            At::Index(n) => (n <= len).then_some(n - 1),
+        }
+    }
+
+    pub fn allows(&self, c: char) -> bool {
+        self.class.contains(c) != self.not
+    }
+
+    /// The spec as written: `!first`, `last`, `3`.
+    pub fn spec(&self) -> String {
+        let at = match self.at {
+            At::First => "first".to_string(),
+            At::Last => "last".to_string(),
+            At::Index(n) => n.to_string(),
+        };
+        if self.not { format!("!{}", at) } else { at }
+    }
+
+    /// The rule in words: `digit last`, `no special character first`.
+    pub fn summary(&self) -> String {
+        match self.not {
+            true => format!("no {} {}", self.class.noun(), self.place()),
+            false => format!("{} {}", self.class.noun(), self.place()),
+        }
+    }
+
+    /// What breaking the rule means, for messages.
+    pub fn describe(&self) -> String {
+        match self.not {
+            true => format!("{} {}", class_phrase(self.class), self.place()),
+            false => format!("no {} {}", self.class.noun(), self.place()),
+        }
+    }
+
+    fn place(&self) -> String {
+        match self.at {
+            At::First => "first".to_string(),
+            At::Last => "last".to_string(),
+            At::Index(n) => format!("at position {}", n),
+        }
+    }
+}
+
+/// "a digit", "an uppercase letter".
+fn class_phrase(class: Class) -> String {
+    let noun = class.noun();
+    match noun.starts_with(['a', 'e', 'i', 'o', 'u']) {
+        true => format!("an {}", noun),
+        false => format!("a {}", noun),
+    }
+}
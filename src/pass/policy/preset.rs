@@ -0,0 +1,87 @@
+//! Built-in policies for systems with well-known password rules
+//! (`--preset NAME`), written in the policy file format.
+
+use super::{Policy, parse};
+
+/// Name, one-line summary, and policy text of each preset.
+pub const PRESETS: &[(&str, &str, &str)] = &[
+    (
+        "aws-iam",
+        "AWS IAM user: 8-128 characters, IAM's symbol set, every class",
+        AWS_IAM,
+    ),
+    (
+        "active-directory",
+        "Active Directory with complexity on: 14-127 characters, every class",
+        ACTIVE_DIRECTORY,
+    ),
+    (
+        "mysql",
+        "MySQL validate_password MEDIUM: 8-32 characters, every class, no quotes",
+        MYSQL,
+    ),
+    (
+        "pin4",
+        "Four-digit PIN, no repeated digits or straight runs",
+        PIN4,
+    ),
+];
+
+const AWS_IAM: &str = r#"
+# IAM accepts letters, digits and these symbols. Requiring every class
+# meets any account password policy that asks for a character type.
+min_length = 8
+max_length = 128
+allowed_chars = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789!@#$%^&*()_+-=[]{}|'"
+
+[require]
+lowercase = 1
+uppercase = 1
+digits = 1
+special = 1
+"#;
+
+const ACTIVE_DIRECTORY: &str = r#"
+# Complexity asks for three of the four classes; all four always
+# qualifies. 14 is the minimum in Microsoft's security baselines, 127
+# the longest the Windows sign-in screen takes.
+min_length = 14
+max_length = 127
+
+[require]
+lowercase = 1
+uppercase = 1
+digits = 1
+special = 1
+"#;
+
+const MYSQL: &str = r#"
+# 32 is the longest a replication password can be. Quotes, backslash
+# and backtick are left out so the password needs no escaping in SQL or
+# option files.
+min_length = 8
+max_length = 32
+forbidden_chars = "'\"`\\"
+
+[require]
+lowercase = 1
+uppercase = 1
+digits = 1
+special = 1
+"#;
+
+const PIN4: &str = r#"
+min_length = 4
+max_length = 4
+allowed_chars = "0123456789"
+must_not_match = [
+    '^(?:0000|1111|2222|3333|4444|5555|6666|7777|8888|9999)$',
+    '^(?:0123|1234|2345|3456|4567|5678|6789|9876|8765|7654|6543|5432|4321|3210)$',
+]
+"#;
+
+/// The preset called `name`.
+pub fn get(name: &str) -> Option<Policy> {
+    let &(name, _, text) = PRESETS.iter().find(|(n, _, _)| *n == name)?;
+    Some(parse(text, name).expect("built-in presets parse"))
+}
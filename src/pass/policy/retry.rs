@@ -0,0 +1,181 @@
+//! Drawing until a password satisfies the policy. Every draw is counted
+//! (for `--verbose`), and a trial run before generating catches policies
+//! that no draw can meet, or that would take too long to meet, so those
+//! fail up front instead of spinning.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use zeroize::Zeroize;
+
+use super::{Policy, Rule};
+use crate::cli::prompts;
+
+/// Draws per password before the policy is reported as unsatisfiable,
+/// unless a trial showed more are needed.
+pub const MAX_ATTEMPTS: usize = 10_000;
+
+/// Candidates a trial draws before generating.
+pub const TRIAL_DRAWS: usize = 2_000;
+
+/// Ceiling on the draws per password a trial may allow.
+const MAX_LIMIT: usize = 10_000_000;
+
+/// Draws per password allowed for this run; see `trial`.
+static LIMIT: AtomicUsize = AtomicUsize::new(MAX_ATTEMPTS);
+
+static PASSWORDS: AtomicU64 = AtomicU64::new(0);
+static DRAWS: AtomicU64 = AtomicU64::new(0);
+static MOST_DRAWS: AtomicU64 = AtomicU64::new(0);
+static REJECTED: [AtomicU64; Rule::KINDS.len()] = [const { AtomicU64::new(0) }; Rule::KINDS.len()];
+
+/// Totals over every password accepted so far this run.
+pub struct Stats {
+    pub passwords: u64,
+    pub draws: u64,
+    /// Most draws any one password took
+    pub most_draws: u64,
+    /// Rejections per kind of rule (`Rule::KINDS`), most frequent first;
+    /// kinds that never fired are left out
+    pub rejected: Vec<(&'static str, u64)>,
+}
+
+pub fn stats() -> Stats {
+    let mut rejected: Vec<_> = Rule::KINDS
+        .iter()
+        .zip(&REJECTED)
+        .map(|(&kind, n)| (kind, n.load(Ordering::Relaxed)))
+        .filter(|&(_, n)| n > 0)
+        .collect();
+    rejected.sort_by_key(|&(_, n)| std::cmp::Reverse(n));
+    Stats {
+        passwords: PASSWORDS.load(Ordering::Relaxed),
+        draws: DRAWS.load(Ordering::Relaxed),
+        most_draws: MOST_DRAWS.load(Ordering::Relaxed),
+        rejected,
+    }
+}
+
+/// Run `produce` until its password satisfies `policy`. Rejected draws
+/// are zeroized before the next one. Gives up (and exits) after the
+/// run's draw limit, naming the rule most draws broke.
+#[inline]
+pub fn enforce(policy: &Policy, buf: &mut Vec<u8>, mut produce: impl FnMut(&mut Vec<u8>)) {
+    let limit = LIMIT.load(Ordering::Relaxed);
+    let mut rejected = [0u64; Rule::KINDS.len()];
+    // One broken rule per kind, to describe when giving up
+    let mut example = [None; Rule::KINDS.len()];
+
+    for draw in 1..=limit {
+        produce(buf);
+        match policy.check(std::str::from_utf8(buf).unwrap_or_default()) {
+            Ok(()) => {
+                record(draw as u64, &rejected);
+                return;
+            }
+            Err(rule) => {
+                rejected[rule.kind()] += 1;
+                example[rule.kind()] = Some(rule);
+                buf.zeroize();
+            }
+        }
+    }
+
+    record(limit as u64, &rejected);
+    let kind = (0..rejected.len())
+        .max_by_key(|&k| rejected[k])
+        .unwrap_or(0);
+    let reason = example[kind].map_or_else(String::new, |rule| {
+        format!(
+            "; most rejected: {} ({})",
+            policy.describe(rule),
+            percent(rejected[kind], limit as u64)
+        )
+    });
+    prompts::fail(&format!(
+        "Error: policy {}: no password out of {} draws satisfied it{}",
+        policy.source, limit, reason
+    ));
+}
+
+/// Add one accepted (or abandoned) password's counts to the run totals.
+fn record(draws: u64, rejected: &[u64; Rule::KINDS.len()]) {
+    PASSWORDS.fetch_add(1, Ordering::Relaxed);
+    DRAWS.fetch_add(draws, Ordering::Relaxed);
+    MOST_DRAWS.fetch_max(draws, Ordering::Relaxed);
+    for (total, &n) in REJECTED.iter().zip(rejected) {
+        if n > 0 {
+            total.fetch_add(n, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Outcome of `trial`.
+pub struct Trial {
+    pub draws: usize,
+    pub accepted: usize,
+    /// The rule broken most often and how many draws broke it
+    pub most_broken: Option<(Rule, usize)>,
+}
+
+impl Trial {
+    /// Mean draws per accepted password, going by the trial.
+    pub fn draws_per_password(&self) -> f64 {
+        self.draws as f64 / self.accepted.max(1) as f64
+    }
+}
+
+/// Check `draws` candidates from `produce` against `policy` without
+/// redrawing, and size the run's per-password draw limit from the
+/// acceptance rate: generous enough that a policy the trial could meet
+/// practically never hits it. Errors when no candidate passed, naming
+/// the rule that stopped most of them.
+pub fn trial(
+    policy: &Policy,
+    draws: usize,
+    buf: &mut Vec<u8>,
+    mut produce: impl FnMut(&mut Vec<u8>),
+) -> Result<Trial, String> {
+    let mut accepted = 0;
+    let mut broken: Vec<(Rule, usize)> = Vec::new();
+    for _ in 0..draws {
+        produce(buf);
+        match policy.check(std::str::from_utf8(buf).unwrap_or_default()) {
+            Ok(()) => accepted += 1,
+            Err(rule) => match broken.iter_mut().find(|(r, _)| *r == rule) {
+                Some((_, n)) => *n += 1,
+                None => broken.push((rule, 1)),
+            },
+        }
+        buf.zeroize();
+    }
+    let most_broken = broken.into_iter().max_by_key(|&(_, n)| n);
+
+    if accepted == 0 {
+        let reason = most_broken.map_or_else(String::new, |(rule, n)| {
+            format!(
+                "; most rejected: {} ({})",
+                policy.describe(rule),
+                percent(n as u64, draws as u64)
+            )
+        });
+        return Err(format!(
+            "none of {} trial passwords satisfied it, so it can't be met by redrawing{}",
+            draws, reason
+        ));
+    }
+
+    let trial = Trial {
+        draws,
+        accepted,
+        most_broken,
+    };
+    // e^-50 odds of one password running out of draws
+    let limit = (trial.draws_per_password() * 50.0) as usize;
+    LIMIT.store(limit.clamp(MAX_ATTEMPTS, MAX_LIMIT), Ordering::Relaxed);
+    Ok(trial)
+}
+
+/// `n` of `total` as a percentage.
+pub fn percent(n: u64, total: u64) -> String {
+    format!("{:.1}%", n as f64 * 100.0 / total.max(1) as f64)
+}
@@ -0,0 +1,47 @@
+//! Forbidden words (`forbidden_words`, `--forbid FILE`): substrings matched
+//! ignoring case and common leetspeak, so `P@ssw0rd` and `ACME` count as
+//! `password` and `acme`. Words are kept lowercased.
+
+/// Letters a leetspeak character can stand for.
+fn leet(c: char) -> &'static str {
+    match c {
+        '0' => "o",
+        '1' => "il",
+        '!' | '|' => "il",
+        '2' => "z",
+        '3' => "e",
+        '4' | '@' => "a",
+        '5' | '$' => "s",
+        '6' | '9' => "g",
+        '7' | '+' => "t",
+        '8' => "b",
+        _ => "",
+    }
+}
+
+/// True if password character `p` can be read as word character `w`.
+pub fn reads_as(p: char, w: char) -> bool {
+    p == w || p.to_lowercase().eq([w]) || leet(p).contains(w)
+}
+
+/// True if `password` contains `word` (lowercase), ignoring case and
+/// leetspeak.
+pub fn contains(password: &str, word: &str) -> bool {
+    password.char_indices().any(|(i, _)| {
+        let mut rest = password[i..].chars();
+        word.chars()
+            .all(|w| rest.next().is_some_and(|p| reads_as(p, w)))
+    })
+}
+
+/// Words from a `--forbid` file: one per line, lowercased; blank lines
+/// and `#` comments are skipped.
+pub fn read_words(path: &str) -> Result<Vec<String>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    Ok(text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(str::to_lowercase)
+        .collect())
+}
@@ -0,0 +1,161 @@
+//! Zeroizing buffer pool for the generation loops.
+//!
+//! Password, pool and line buffers are taken from here once per run and
+//! reused for every password, so the hot loops never touch the allocator.
+//! A returned buffer is zeroized over its whole capacity and kept, still
+//! mlock'd, for the next run (or the next worker thread).
+
+use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
+
+use zeroize::Zeroize;
+
+/// Buffers kept for reuse; more are freed on return.
+const MAX_POOLED: usize = 16;
+
+static FREE: Mutex<Vec<Vec<u8>>> = Mutex::new(Vec::new());
+
+/// A pooled byte buffer. Derefs to `Vec<u8>`; writes must stay within the
+/// capacity it was taken with, or the Vec reallocates and leaves a copy
+/// behind.
+pub struct PoolBuf {
+    buf: Vec<u8>,
+}
+
+/// Take an empty buffer with room for at least `capacity` bytes.
+pub fn take(capacity: usize) -> PoolBuf {
+    let reused = FREE.lock().ok().and_then(|mut free| {
+        let i = free.iter().position(|b| b.capacity() >= capacity)?;
+        Some(free.swap_remove(i))
+    });
+    let buf = reused.unwrap_or_else(|| {
+        let buf = Vec::with_capacity(capacity);
+        let locked =
+            unsafe { libc::mlock(buf.as_ptr() as *const libc::c_void, buf.capacity()) } == 0;
+        crate::cli::prompts::debug(&format!(
+            "Pool: new {}-byte buffer{}",
+            buf.capacity(),
+            if locked {
+                ", mlocked"
+            } else {
+                ", mlock failed"
+            }
+        ));
+        buf
+    });
+    PoolBuf { buf }
+}
+
+impl Deref for PoolBuf {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.buf
+    }
+}
+
+impl DerefMut for PoolBuf {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.buf
+    }
+}
+
+impl Drop for PoolBuf {
+    fn drop(&mut self) {
+        let mut buf = std::mem::take(&mut self.buf);
+        buf.zeroize();
+        if let Ok(mut free) = FREE.lock()
+            && free.len() < MAX_POOLED
+        {
+            free.push(buf);
+            return;
+        }
+        unsafe {
+            libc::munlock(buf.as_ptr() as *const libc::c_void, buf.capacity());
+        }
+    }
+}
+
+/// The generation loop takes its buffers from the pool once per run, so a
+/// run's allocations don't grow with its length. Counted with a global
+/// allocator, on the test's thread only.
+#[cfg(test)]
+mod tests {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::pass::generate_batch;
+    use crate::settings::{Settings, ephemeral};
+
+    struct Counting;
+
+    static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+    thread_local! {
+        static COUNTING: Cell<bool> = const { Cell::new(false) };
+    }
+
+    fn count() {
+        if COUNTING.try_with(Cell::get).unwrap_or(false) {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    unsafe impl GlobalAlloc for Counting {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            count();
+            unsafe { System.alloc(layout) }
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            unsafe { System.dealloc(ptr, layout) }
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            count();
+            unsafe { System.realloc(ptr, layout, new_size) }
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: Counting = Counting;
+
+    /// Allocations made generating `n` passwords into `path`.
+    fn allocations(path: &str, n: usize) -> usize {
+        let settings = Settings {
+            pass_length: 32,
+            number_of_passwords: n,
+            output_to_terminal: false,
+            output_file_path: path.to_string(),
+            ..Default::default()
+        };
+        let _ = std::fs::remove_file(path);
+        ALLOCATIONS.store(0, Ordering::Relaxed);
+        COUNTING.set(true);
+        generate_batch(&settings, n);
+        COUNTING.set(false);
+        ALLOCATIONS.load(Ordering::Relaxed)
+    }
+
+    #[test]
+    fn allocations_do_not_grow_with_the_count() {
+        // No files outside the scratch directory
+        ephemeral::set(true);
+        let dir = std::env::temp_dir().join(format!("randpass-alloc-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("out.txt");
+        let path = path.to_str().unwrap();
+
+        // The first run fills the pool and any lazy state
+        allocations(path, 1000);
+        let small = allocations(path, 1000);
+        let large = allocations(path, 50_000);
+        assert_eq!(small, large, "-n 1000: {}, -n 50000: {}", small, large);
+        assert_eq!(
+            std::fs::read_to_string(path).unwrap().lines().count(),
+            50_000
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,137 @@
+//! `--pronounceable`: words built from one language's syllables, for
+//! `--lang en`, `de` or `ja` (Hepburn romaji).
+//!
+//! A syllable is an onset drawn uniformly with a nucleus, skipping the
+//! pairs the language doesn't use; the last syllable may close with a
+//! coda. Onsets are consonants only (or empty in romaji) and every
+//! nucleus is a vowel run (a single vowel in romaji), so a word splits
+//! back into its syllables one way only. Different draws never give the
+//! same word, and the entropy is exactly the count of choices.
+
+use super::passphrase::uniform;
+
+#[derive(Debug)]
+pub struct Language {
+    pub code: &'static str,
+    pub name: &'static str,
+    onsets: &'static [&'static str],
+    nuclei: &'static [&'static str],
+    /// Onset + nucleus pairs left out
+    except: &'static [&'static str],
+    /// Closings for the last syllable; none is also a choice
+    codas: &'static [&'static str],
+}
+
+pub const LANGUAGES: [Language; 3] = [
+    Language {
+        code: "en",
+        name: "English",
+        onsets: &[
+            "b", "bl", "br", "c", "ch", "cl", "cr", "d", "dr", "f", "fl", "fr", "g", "gl", "gr",
+            "h", "j", "k", "l", "m", "n", "p", "pl", "pr", "r", "s", "sc", "sh", "sk", "sl", "sm",
+            "sn", "sp", "st", "str", "sw", "t", "th", "tr", "v", "w", "y", "z",
+        ],
+        nuclei: &["a", "e", "i", "o", "u", "ai", "ea", "ee", "oa", "oo", "ou"],
+        except: &[],
+        codas: &[
+            "b", "ck", "d", "ft", "g", "l", "ll", "m", "n", "nd", "ng", "nk", "nt", "p", "r", "rd",
+            "rn", "rt", "s", "sh", "sk", "st", "t", "th", "x",
+        ],
+    },
+    Language {
+        code: "de",
+        name: "German",
+        onsets: &[
+            "b", "bl", "br", "d", "dr", "f", "fl", "fr", "g", "gl", "gr", "h", "j", "k", "kl",
+            "kn", "kr", "l", "m", "n", "p", "pf", "pl", "pr", "r", "s", "sch", "schl", "schm",
+            "schn", "schr", "schw", "sp", "st", "str", "t", "tr", "w", "z", "zw",
+        ],
+        nuclei: &["a", "e", "i", "o", "u", "au", "ei", "eu", "ie"],
+        except: &[],
+        codas: &[
+            "ch", "cht", "ck", "f", "ft", "g", "k", "l", "ld", "lt", "m", "n", "nd", "ng", "nk",
+            "nn", "ns", "nt", "r", "rn", "rt", "s", "ss", "st", "t", "tz",
+        ],
+    },
+    Language {
+        code: "ja",
+        name: "Japanese (romaji)",
+        onsets: &[
+            "", "k", "s", "sh", "t", "ch", "ts", "n", "h", "f", "m", "y", "r", "w", "g", "z", "j",
+            "d", "b", "p", "ky", "ny", "hy", "my", "ry", "gy", "by", "py",
+        ],
+        nuclei: &["a", "i", "u", "e", "o"],
+        except: &[
+            "si", "ti", "tu", "hu", "zi", "di", "du", "yi", "ye", "wi", "wu", "we", "wo", "she",
+            "che", "je", "tsa", "tsi", "tse", "tso", "fa", "fi", "fe", "fo", "kyi", "kye", "nyi",
+            "nye", "hyi", "hye", "myi", "mye", "ryi", "rye", "gyi", "gye", "byi", "bye", "pyi",
+            "pye",
+        ],
+        codas: &["n"],
+    },
+];
+
+/// Languages by code, for messages.
+pub const CODES: &str = "en, de or ja";
+
+/// Bits a word gets when `--pronounceable` names no syllable count.
+const DEFAULT_BITS: f64 = 60.0;
+
+pub fn get(code: &str) -> Option<&'static Language> {
+    LANGUAGES.iter().find(|l| l.code == code)
+}
+
+impl Language {
+    /// Distinct syllables.
+    fn syllables(&self) -> usize {
+        self.onsets.len() * self.nuclei.len() - self.except.len()
+    }
+
+    pub fn bits_per_syllable(&self) -> f64 {
+        (self.syllables() as f64).log2()
+    }
+
+    fn coda_bits(&self) -> f64 {
+        ((self.codas.len() + 1) as f64).log2()
+    }
+
+    /// Entropy of one word of `syllables` syllables.
+    pub fn bits(&self, syllables: usize) -> f64 {
+        syllables as f64 * self.bits_per_syllable() + self.coda_bits()
+    }
+
+    /// Fewest syllables for a word of at least 60 bits.
+    pub fn default_syllables(&self) -> usize {
+        ((DEFAULT_BITS - self.coda_bits()) / self.bits_per_syllable()).ceil() as usize
+    }
+
+    /// Bytes in the longest word of `syllables` syllables.
+    pub fn longest(&self, syllables: usize) -> usize {
+        let max = |list: &[&str]| list.iter().map(|s| s.len()).max().unwrap_or(0);
+        syllables * (max(self.onsets) + max(self.nuclei)) + max(self.codas)
+    }
+
+    /// Append one word of `syllables` syllables to `buf`.
+    pub fn generate(&self, syllables: usize, buf: &mut Vec<u8>) {
+        for _ in 0..syllables {
+            loop {
+                let onset = self.onsets[uniform(self.onsets.len())];
+                let nucleus = self.nuclei[uniform(self.nuclei.len())];
+                let left_out = self.except.iter().any(|e| {
+                    e.len() == onset.len() + nucleus.len()
+                        && e.starts_with(onset)
+                        && e.ends_with(nucleus)
+                });
+                if !left_out {
+                    buf.extend_from_slice(onset.as_bytes());
+                    buf.extend_from_slice(nucleus.as_bytes());
+                    break;
+                }
+            }
+        }
+        let coda = uniform(self.codas.len() + 1);
+        if coda > 0 {
+            buf.extend_from_slice(self.codas[coda - 1].as_bytes());
+        }
+    }
+}
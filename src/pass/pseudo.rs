@@ -0,0 +1,162 @@
+//! Pronounceable pseudo-words from a letter trigram model.
+//!
+//! The model is every three-letter window (with word start and end as
+//! extra symbols) seen in the embedded English list. A pseudo-word is a
+//! string of 5-8 letters whose every window was seen, drawn uniformly
+//! from all such strings, so its entropy is exactly log2 of how many there
+//! are. Words from the training list are redrawn and not counted.
+//!
+//! The training list is the 2048-word BIP39 English list, not a
+//! dictionary: common words missing from it (`house`, `bottle`) can still
+//! come out, so a pseudo-word is only guaranteed not to be a list word.
+
+use std::sync::OnceLock;
+
+use zeroize::Zeroize;
+
+use super::passphrase::uniform;
+use super::words;
+
+pub const MIN_LEN: usize = 5;
+pub const MAX_LEN: usize = 8;
+
+/// Letters a-z, then the word boundary.
+const SYMBOLS: usize = 27;
+const EDGE: usize = 26;
+const END: u32 = 1 << EDGE;
+/// State = previous two symbols.
+const STATES: usize = SYMBOLS * SYMBOLS;
+const START: usize = EDGE * SYMBOLS + EDGE;
+
+pub struct Model {
+    /// Allowed next symbols for each state: bit c for letter c, `END` to stop
+    next: [u32; STATES],
+    /// `ways[n][state]`: strings of exactly n more letters that can end
+    ways: Vec<[usize; STATES]>,
+    /// Training words of MIN_LEN..=MAX_LEN letters, packed, sorted
+    excluded: Vec<u64>,
+    /// Strings of every allowed length, training words included
+    total: usize,
+}
+
+static MODEL: OnceLock<Model> = OnceLock::new();
+
+/// The model trained on the default embedded list, built on first use.
+pub fn model() -> &'static Model {
+    MODEL.get_or_init(|| {
+        let list = words::get(words::DEFAULT).expect("default list is embedded");
+        Model::train((0..list.len()).map(|i| list.word(i)))
+    })
+}
+
+impl Model {
+    fn train<'a>(training: impl Iterator<Item = &'a str>) -> Self {
+        let mut next = [0u32; STATES];
+        let mut excluded = Vec::new();
+        for word in training {
+            // Only plain a-z words say anything about these strings
+            if !word.bytes().all(|b| b.is_ascii_lowercase()) {
+                continue;
+            }
+            let (mut a, mut b) = (EDGE, EDGE);
+            for c in word.bytes().map(|b| (b - b'a') as usize) {
+                next[a * SYMBOLS + b] |= 1 << c;
+                (a, b) = (b, c);
+            }
+            next[a * SYMBOLS + b] |= END;
+            if (MIN_LEN..=MAX_LEN).contains(&word.len()) {
+                excluded.push(pack(word.as_bytes()));
+            }
+        }
+        excluded.sort_unstable();
+        excluded.dedup();
+
+        let mut ways = vec![[0usize; STATES]; MAX_LEN + 1];
+        for (state, allowed) in next.iter().enumerate() {
+            ways[0][state] = usize::from(allowed & END != 0);
+        }
+        for n in 1..=MAX_LEN {
+            for state in 0..STATES {
+                let b = state % SYMBOLS;
+                ways[n][state] = letters(next[state])
+                    .map(|c| ways[n - 1][b * SYMBOLS + c])
+                    .sum();
+            }
+        }
+        let total = (MIN_LEN..=MAX_LEN).map(|n| ways[n][START]).sum();
+
+        Self {
+            next,
+            ways,
+            excluded,
+            total,
+        }
+    }
+
+    /// Pseudo-words the model can produce, real words left out.
+    pub fn count(&self) -> usize {
+        self.total - self.excluded.len()
+    }
+
+    /// Entropy of one pseudo-word: each is equally likely.
+    pub fn bits_per_word(&self) -> f64 {
+        (self.count() as f64).log2()
+    }
+
+    /// Write one pseudo-word into `out` and return its length.
+    pub fn generate(&self, out: &mut [u8; MAX_LEN]) -> usize {
+        loop {
+            let len = self.draw(uniform(self.total), out);
+            let mut packed = pack(&out[..len]);
+            let real = self.excluded.binary_search(&packed).is_ok();
+            packed.zeroize();
+            if !real {
+                return len;
+            }
+        }
+    }
+
+    /// Decode `index` (below `total`) into its string: strings are
+    /// numbered by length, then letter by letter.
+    fn draw(&self, mut index: usize, out: &mut [u8; MAX_LEN]) -> usize {
+        let mut len = MIN_LEN;
+        while index >= self.ways[len][START] {
+            index -= self.ways[len][START];
+            len += 1;
+        }
+        let mut state = START;
+        for (i, slot) in out.iter_mut().enumerate().take(len) {
+            let remaining = len - i - 1;
+            let b = state % SYMBOLS;
+            for c in letters(self.next[state]) {
+                let ways = self.ways[remaining][b * SYMBOLS + c];
+                if index < ways {
+                    *slot = b'a' + c as u8;
+                    state = b * SYMBOLS + c;
+                    break;
+                }
+                index -= ways;
+            }
+        }
+        len
+    }
+}
+
+impl std::fmt::Debug for Model {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Model")
+            .field("count", &self.count())
+            .finish()
+    }
+}
+
+/// Letters set in a `next` mask.
+fn letters(allowed: u32) -> impl Iterator<Item = usize> {
+    (0..EDGE).filter(move |c| allowed & (1 << c) != 0)
+}
+
+/// Five bits per letter; fits MAX_LEN letters and keeps lengths apart.
+fn pack(word: &[u8]) -> u64 {
+    word.iter()
+        .fold(0, |acc, &b| (acc << 5) | u64::from(b - b'a' + 1))
+}
@@ -0,0 +1,419 @@
+//! `--io-uring`: a file writer that overlaps disk writes with generation.
+//!
+//! Output is staged in a few page-aligned, mlock'd buffers. On Linux 5.6+
+//! a full buffer is handed to io_uring and the next one is filled while
+//! the kernel writes it. Where io_uring is missing (older kernels, seccomp
+//! filters, other platforms) full buffers are gathered and written with a
+//! single `pwritev`. Buffers are zeroized as soon as the kernel is done
+//! with them.
+//!
+//! The file may be open with O_DIRECT (`--direct`). Full buffers keep its
+//! alignment; a partly filled one whose length breaks it is written after
+//! O_DIRECT is cleared, as the kernel would refuse it.
+
+use std::fs::File;
+use std::io::{self, Seek, Write};
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::FileExt;
+
+use zeroize::Zeroize;
+
+/// Bytes per buffer; a multiple of the O_DIRECT alignment.
+const BUF_SIZE: usize = 256 * 1024;
+/// Buffers in the ring: one filling, the rest in flight.
+const DEPTH: usize = 4;
+const ALIGN: usize = 4096;
+
+/// One staging buffer and where its bytes go in the file.
+struct Buffer {
+    ptr: *mut u8,
+    len: usize,
+    offset: u64,
+}
+
+impl Buffer {
+    fn new() -> Self {
+        let layout = Self::layout();
+        let ptr = unsafe { std::alloc::alloc_zeroed(layout) };
+        if ptr.is_null() {
+            std::alloc::handle_alloc_error(layout);
+        }
+        unsafe {
+            libc::mlock(ptr as *const libc::c_void, BUF_SIZE);
+        }
+        Self {
+            ptr,
+            len: 0,
+            offset: 0,
+        }
+    }
+
+    fn layout() -> std::alloc::Layout {
+        std::alloc::Layout::from_size_align(BUF_SIZE, ALIGN).expect("invalid layout constants")
+    }
+
+    /// Copy as much of `data` as fits; returns the bytes taken.
+    fn fill(&mut self, data: &[u8]) -> usize {
+        let n = data.len().min(BUF_SIZE - self.len);
+        unsafe {
+            std::ptr::copy_nonoverlapping(data.as_ptr(), self.ptr.add(self.len), n);
+        }
+        self.len += n;
+        n
+    }
+
+    fn filled(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    fn clear(&mut self) {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }.zeroize();
+        self.len = 0;
+    }
+}
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        self.len = BUF_SIZE;
+        self.clear();
+        unsafe {
+            libc::munlock(self.ptr as *const libc::c_void, BUF_SIZE);
+            std::alloc::dealloc(self.ptr, Self::layout());
+        }
+    }
+}
+
+enum Backend {
+    #[cfg(target_os = "linux")]
+    Uring {
+        ring: Box<io_uring::IoUring>,
+        in_flight: [bool; DEPTH],
+    },
+    /// Full buffers `0..current` wait for one pwritev
+    Writev,
+}
+
+pub(crate) struct RingWriter {
+    file: File,
+    bufs: Vec<Buffer>,
+    /// Buffer being filled
+    current: usize,
+    /// File offset the next filled buffer is written at
+    offset: u64,
+    backend: Backend,
+    /// A failure after `write` had taken the bytes, reported by the next
+    /// call
+    error: Option<io::Error>,
+}
+
+impl RingWriter {
+    /// Take over `file`, writing from its current end (O_APPEND is
+    /// cleared: writes carry explicit offsets and may complete out of
+    /// order).
+    pub fn new(mut file: File) -> io::Result<Self> {
+        let fd = file.as_raw_fd();
+        let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+        let offset = if flags >= 0 && flags & libc::O_APPEND != 0 {
+            unsafe { libc::fcntl(fd, libc::F_SETFL, flags & !libc::O_APPEND) };
+            file.metadata()?.len()
+        } else {
+            file.stream_position()?
+        };
+
+        let mut bufs: Vec<Buffer> = (0..DEPTH).map(|_| Buffer::new()).collect();
+        bufs[0].offset = offset;
+        Ok(Self {
+            file,
+            bufs,
+            current: 0,
+            offset,
+            backend: Self::backend(),
+            error: None,
+        })
+    }
+
+    #[cfg(target_os = "linux")]
+    fn backend() -> Backend {
+        use io_uring::{IoUring, Probe, opcode};
+
+        let ring = IoUring::new(DEPTH as u32).and_then(|ring| {
+            let mut probe = Probe::new();
+            ring.submitter().register_probe(&mut probe)?;
+            if probe.is_supported(opcode::Write::CODE) {
+                Ok(ring)
+            } else {
+                Err(io::Error::other("IORING_OP_WRITE not supported"))
+            }
+        });
+        match ring {
+            Ok(ring) => Backend::Uring {
+                ring: Box::new(ring),
+                in_flight: [false; DEPTH],
+            },
+            Err(e) => {
+                crate::cli::prompts::warn(&format!("io_uring unavailable ({}), using writev", e));
+                Backend::Writev
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn backend() -> Backend {
+        Backend::Writev
+    }
+
+    /// Clear O_DIRECT before the current buffer goes out if its length
+    /// isn't aligned. It stays cleared: every offset after it is
+    /// unaligned too.
+    fn unaligned_tail(&self) {
+        #[cfg(target_os = "linux")]
+        if !self.bufs[self.current].len.is_multiple_of(ALIGN) {
+            let fd = self.file.as_raw_fd();
+            let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+            if flags >= 0 && flags & libc::O_DIRECT != 0 {
+                unsafe { libc::fcntl(fd, libc::F_SETFL, flags & !libc::O_DIRECT) };
+            }
+        }
+    }
+
+    /// The current buffer is full (or being flushed): pass it on.
+    fn rotate(&mut self) -> io::Result<()> {
+        match self.backend {
+            #[cfg(target_os = "linux")]
+            Backend::Uring { .. } => {
+                self.submit(self.current)?;
+                self.current = (self.current + 1) % DEPTH;
+                self.wait_for(self.current)?;
+            }
+            Backend::Writev => {
+                self.current += 1;
+                if self.current == DEPTH {
+                    self.current = 0;
+                    let written = self.write_gathered(DEPTH);
+                    self.bufs[0].offset = self.offset;
+                    written?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Queue buffer `i` on the ring.
+    #[cfg(target_os = "linux")]
+    fn submit(&mut self, i: usize) -> io::Result<()> {
+        use io_uring::{opcode, types};
+
+        let buf = &mut self.bufs[i];
+        buf.offset = self.offset;
+        self.offset += buf.len as u64;
+        let Backend::Uring { ring, in_flight } = &mut self.backend else {
+            unreachable!("submit needs the io_uring backend");
+        };
+        let entry = opcode::Write::new(types::Fd(self.file.as_raw_fd()), buf.ptr, buf.len as u32)
+            .offset(buf.offset)
+            .build()
+            .user_data(i as u64);
+        // At most DEPTH entries are ever outstanding, so the queue has room
+        unsafe { ring.submission().push(&entry) }.map_err(io::Error::other)?;
+        in_flight[i] = true;
+        ring.submit()?;
+        Ok(())
+    }
+
+    /// Block until buffer `i` is written, reaping other completions too.
+    #[cfg(target_os = "linux")]
+    fn wait_for(&mut self, i: usize) -> io::Result<()> {
+        loop {
+            let Backend::Uring { ring, in_flight } = &mut self.backend else {
+                return Ok(());
+            };
+            if !in_flight[i] {
+                return Ok(());
+            }
+            match ring.submit_and_wait(1) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                result => result?,
+            };
+            let mut done = [(0usize, 0i32); DEPTH];
+            let mut count = 0;
+            for cqe in ring.completion() {
+                done[count] = (cqe.user_data() as usize, cqe.result());
+                count += 1;
+            }
+            for &(j, _) in &done[..count] {
+                in_flight[j] = false;
+            }
+            for &(j, result) in &done[..count] {
+                self.complete(j, result)?;
+            }
+        }
+    }
+
+    /// Finish buffer `i` after the kernel wrote `result` bytes of it (or
+    /// failed with -errno). Short writes are finished synchronously.
+    #[cfg(target_os = "linux")]
+    fn complete(&mut self, i: usize, result: i32) -> io::Result<()> {
+        let buf = &mut self.bufs[i];
+        let outcome = if result < 0 {
+            Err(io::Error::from_raw_os_error(-result))
+        } else {
+            let written = result as usize;
+            let rest = &buf.filled()[written..];
+            self.file.write_all_at(rest, buf.offset + written as u64)
+        };
+        buf.clear();
+        outcome
+    }
+
+    /// Write buffers `0..n` with one pwritev (finishing short writes one
+    /// buffer at a time), then clear them.
+    fn write_gathered(&mut self, n: usize) -> io::Result<()> {
+        let iov: Vec<libc::iovec> = self.bufs[..n]
+            .iter()
+            .map(|b| libc::iovec {
+                iov_base: b.ptr as *mut libc::c_void,
+                iov_len: b.len,
+            })
+            .collect();
+        let total: usize = self.bufs[..n].iter().map(|b| b.len).sum();
+        let start = self.bufs[0].offset;
+
+        let result = loop {
+            let r = unsafe {
+                libc::pwritev(
+                    self.file.as_raw_fd(),
+                    iov.as_ptr(),
+                    n as libc::c_int,
+                    start as libc::off_t,
+                )
+            };
+            if r >= 0 {
+                break Ok(r as usize);
+            }
+            let e = io::Error::last_os_error();
+            if e.kind() != io::ErrorKind::Interrupted {
+                break Err(e);
+            }
+        };
+
+        let outcome = result.and_then(|mut skip| {
+            let mut at = start;
+            for buf in &self.bufs[..n] {
+                let take = skip.min(buf.len);
+                skip -= take;
+                if take < buf.len {
+                    self.file
+                        .write_all_at(&buf.filled()[take..], at + take as u64)?;
+                }
+                at += buf.len as u64;
+            }
+            Ok(())
+        });
+        for buf in &mut self.bufs[..n] {
+            buf.clear();
+        }
+        self.offset = start + total as u64;
+        outcome
+    }
+
+    /// Write out the partly filled buffer and wait for everything in
+    /// flight.
+    fn flush_buffers(&mut self) -> io::Result<()> {
+        self.unaligned_tail();
+        match self.backend {
+            #[cfg(target_os = "linux")]
+            Backend::Uring { .. } => {
+                let mut result = Ok(());
+                if self.bufs[self.current].len > 0 {
+                    result = self.rotate();
+                }
+                for i in 0..DEPTH {
+                    let waited = self.wait_for(i);
+                    result = result.and(waited);
+                }
+                result
+            }
+            Backend::Writev => {
+                let n = self.current + usize::from(self.bufs[self.current].len > 0);
+                if n > 0 {
+                    self.write_gathered(n)?;
+                }
+                self.current = 0;
+                self.bufs[0].offset = self.offset;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Write for RingWriter {
+    /// Takes the bytes before passing a full buffer on, so a failure to
+    /// write it is returned by the next call rather than this one.
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        let n = self.bufs[self.current].fill(data);
+        if self.bufs[self.current].len == BUF_SIZE
+            && let Err(e) = self.rotate()
+        {
+            self.error = Some(e);
+        }
+        Ok(n)
+    }
+
+    /// Write out the partly filled buffer and wait for everything in
+    /// flight; a failure `write` held back comes first.
+    fn flush(&mut self) -> io::Result<()> {
+        let result = self.flush_buffers();
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => result,
+        }
+    }
+}
+
+impl Drop for RingWriter {
+    fn drop(&mut self) {
+        let _ = self.flush();
+        // The kernel may still be reading a buffer if waiting failed; leak
+        // them rather than free memory it could write from
+        #[cfg(target_os = "linux")]
+        if let Backend::Uring { in_flight, .. } = &self.backend
+            && in_flight.iter().any(|&f| f)
+        {
+            std::mem::forget(std::mem::take(&mut self.bufs));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    use super::{BUF_SIZE, RingWriter};
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn unaligned_tail_is_written_under_o_direct() {
+        let path = std::env::temp_dir().join(format!("randpass-ring-tail-{}", std::process::id()));
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .custom_flags(libc::O_DIRECT)
+            .open(&path)
+            .unwrap();
+        let data: Vec<u8> = (0..BUF_SIZE + 5000).map(|i| (i % 251) as u8).collect();
+
+        let mut writer = RingWriter::new(file).unwrap();
+        writer.write_all(&data).unwrap();
+        writer.flush().unwrap();
+        drop(writer);
+
+        assert_eq!(std::fs::read(&path).unwrap(), data);
+        let _ = std::fs::remove_file(path);
+    }
+}
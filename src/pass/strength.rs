@@ -0,0 +1,247 @@
+//! Entropy estimates for a string someone chose, rather than one randpass
+//! drew: what `randpass entropy` reports.
+//!
+//! Three figures, each an upper bound in its own way:
+//!
+//! - Shannon: length times the entropy of the string's own character
+//!   frequencies, so repeats cost it bits and nothing else does
+//! - Charset: length times log2 of every class the string touches, which
+//!   is what a brute force over those classes faces
+//! - Pattern: the string split into the cheapest guesses an attacker
+//!   would try first (wordlist words read through case and leetspeak,
+//!   runs of one character, sequences, keyboard rows, years), with the
+//!   rest counted per character as in the charset figure
+//!
+//! Only the pattern figure says much about a human-chosen password.
+
+use super::policy::reads_as;
+use super::words;
+
+const LOWER: f64 = 26.0;
+const UPPER: f64 = 26.0;
+const DIGITS: f64 = 10.0;
+/// Printable ASCII punctuation and space
+const SYMBOLS: f64 = 33.0;
+/// Anything outside ASCII: a rough size for a brute force over it
+const OTHER: f64 = 100.0;
+
+/// Shortest run that counts as a repeat, sequence or keyboard pattern.
+const MIN_RUN: usize = 3;
+/// Shortest wordlist word looked for.
+const MIN_WORD: usize = 4;
+
+const KEYBOARD_ROWS: [&str; 4] = [
+    "`1234567890-=",
+    "qwertyuiop[]\\",
+    "asdfghjkl;'",
+    "zxcvbnm,./",
+];
+/// Keys a keyboard run can start from
+const KEYS: f64 = 47.0;
+
+/// One pattern found in the string.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// What kind: "word", "repeat", "sequence", "keyboard" or "year"
+    pub kind: &'static str,
+    /// Index of its first character
+    pub start: usize,
+    /// Characters covered
+    pub len: usize,
+    pub bits: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Estimate {
+    pub len: usize,
+    /// Classes present: lowercase, uppercase, digits, symbols, other
+    pub classes: Vec<&'static str>,
+    pub shannon: f64,
+    pub charset: f64,
+    pub pattern: f64,
+    pub findings: Vec<Finding>,
+}
+
+fn class(c: char) -> (&'static str, f64) {
+    match c {
+        'a'..='z' => ("lowercase", LOWER),
+        'A'..='Z' => ("uppercase", UPPER),
+        '0'..='9' => ("digits", DIGITS),
+        c if c.is_ascii() => ("symbols", SYMBOLS),
+        _ => ("other", OTHER),
+    }
+}
+
+/// Estimate the entropy of `s` three ways.
+pub fn estimate(s: &str) -> Estimate {
+    let chars: Vec<char> = s.chars().collect();
+    let len = chars.len();
+
+    let mut classes: Vec<(&'static str, f64)> = chars.iter().map(|&c| class(c)).collect();
+    classes.sort_by(|a, b| a.0.cmp(b.0));
+    classes.dedup_by(|a, b| a.0 == b.0);
+    let pool: f64 = classes.iter().map(|c| c.1).sum();
+    let charset = if len == 0 {
+        0.0
+    } else {
+        len as f64 * pool.log2()
+    };
+
+    let mut sorted = chars.clone();
+    sorted.sort_unstable();
+    let per_char: f64 = sorted
+        .chunk_by(|a, b| a == b)
+        .map(|run| {
+            let p = run.len() as f64 / len as f64;
+            -p * p.log2()
+        })
+        .sum();
+    // abs: a single repeated character sums to -0
+    let shannon = (len as f64 * per_char).abs();
+
+    let (pattern, findings) = patterns(&chars, pool);
+    Estimate {
+        len,
+        classes: classes.iter().map(|c| c.0).collect(),
+        shannon,
+        charset,
+        pattern: pattern.min(charset),
+        findings,
+    }
+}
+
+/// Walk the string taking the longest pattern at each position (one
+/// character from `pool` when none fits) and add up their bits.
+fn patterns(chars: &[char], pool: f64) -> (f64, Vec<Finding>) {
+    let mut total = 0.0;
+    let mut findings = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let rest = &chars[i..];
+        let found = [
+            word(rest),
+            repeat(rest),
+            sequence(rest),
+            keyboard(rest),
+            year(rest),
+        ]
+        .into_iter()
+        .flatten()
+        .max_by(|a, b| a.len.cmp(&b.len).then(b.bits.total_cmp(&a.bits)));
+        match found {
+            Some(f) => {
+                total += f.bits;
+                i += f.len;
+                findings.push(Finding {
+                    start: i - f.len,
+                    ..f
+                });
+            }
+            None => {
+                total += pool.log2();
+                i += 1;
+            }
+        }
+    }
+    (total, findings)
+}
+
+/// The longest word of the default list at the start of `s`, read
+/// through case and leetspeak. Each capital and each substitution is
+/// one more bit.
+fn word(s: &[char]) -> Option<Finding> {
+    let list = words::get(words::DEFAULT)?;
+    let mut best: Option<Finding> = None;
+    for w in (0..list.len()).map(|i| list.word(i)) {
+        let n = w.chars().count();
+        if n < MIN_WORD || n > s.len() || best.as_ref().is_some_and(|b| b.len >= n) {
+            continue;
+        }
+        if !s.iter().zip(w.chars()).all(|(&p, w)| reads_as(p, w)) {
+            continue;
+        }
+        let changed = s.iter().zip(w.chars()).filter(|&(&p, w)| p != w).count();
+        best = Some(Finding {
+            kind: "word",
+            start: 0,
+            len: n,
+            bits: (list.len() as f64).log2() + changed as f64,
+        });
+    }
+    best
+}
+
+/// One character repeated: `aaaa`.
+fn repeat(s: &[char]) -> Option<Finding> {
+    let n = s.iter().take_while(|&&c| c == s[0]).count();
+    (n >= MIN_RUN).then(|| Finding {
+        kind: "repeat",
+        start: 0,
+        len: n,
+        bits: class(s[0]).1.log2() + (n as f64).log2(),
+    })
+}
+
+/// Letters or digits stepping up or down by one: `abcd`, `9876`.
+fn sequence(s: &[char]) -> Option<Finding> {
+    if s.len() < MIN_RUN || !s[0].is_ascii_alphanumeric() {
+        return None;
+    }
+    let step = s[1] as i32 - s[0] as i32;
+    if step.abs() != 1 {
+        return None;
+    }
+    let n = 1 + s
+        .windows(2)
+        .take_while(|w| w[1] as i32 - w[0] as i32 == step && class(w[1]).0 == class(s[0]).0)
+        .count();
+    (n >= MIN_RUN).then(|| Finding {
+        kind: "sequence",
+        start: 0,
+        len: n,
+        bits: class(s[0]).1.log2() + 1.0 + (n as f64).log2(),
+    })
+}
+
+/// Neighbouring keys along one row of a US keyboard: `qwerty`, `lkjh`.
+fn keyboard(s: &[char]) -> Option<Finding> {
+    let lower: Vec<char> = s.iter().map(|c| c.to_ascii_lowercase()).collect();
+    KEYBOARD_ROWS
+        .iter()
+        .filter_map(|row| {
+            let row: Vec<char> = row.chars().collect();
+            let at = |c: char| row.iter().position(|&k| k == c);
+            let start = at(lower[0])? as i32;
+            let second = at(*lower.get(1)?)? as i32;
+            let step = second - start;
+            if step.abs() != 1 {
+                return None;
+            }
+            let n = 1 + lower
+                .windows(2)
+                .take_while(|w| matches!((at(w[0]), at(w[1])), (Some(a), Some(b)) if b as i32 - a as i32 == step))
+                .count();
+            (n >= MIN_RUN).then_some(n)
+        })
+        .max()
+        .map(|n| Finding {
+            kind: "keyboard",
+            start: 0,
+        len: n,
+            bits: KEYS.log2() + 1.0 + (n as f64).log2(),
+        })
+}
+
+/// A year from 1900 to 2099.
+fn year(s: &[char]) -> Option<Finding> {
+    let digits: String = s.iter().take(4).collect();
+    let is_year = digits.len() == 4
+        && digits.chars().all(|c| c.is_ascii_digit())
+        && (digits.starts_with("19") || digits.starts_with("20"));
+    is_year.then(|| Finding {
+        kind: "year",
+        start: 0,
+        len: 4,
+        bits: 200f64.log2(),
+    })
+}
@@ -0,0 +1,130 @@
+//! Random usernames for account provisioning: nothing in them points at
+//! the person, unlike first.last. Each style draws uniformly from its own
+//! space, and names over the length limit are redrawn.
+
+use super::passphrase::uniform;
+use super::pseudo;
+
+/// `--max-len` default for `alnum`, which always fills it.
+pub const ALNUM_LEN: usize = 10;
+
+/// Digits after the words of `adjective-noun` and `pronounceable`.
+const SUFFIX_DIGITS: usize = 2;
+
+const ALNUM: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+
+const ADJECTIVES: [&str; 128] = [
+    "able", "agile", "amber", "ample", "azure", "basic", "blue", "bold", "brave", "brief",
+    "bright", "brisk", "broad", "calm", "candid", "chief", "civil", "clean", "clear", "clever",
+    "close", "cool", "coral", "cosmic", "crisp", "curly", "daring", "deep", "dense", "direct",
+    "dry", "eager", "early", "easy", "even", "exact", "fair", "fancy", "fast", "fine", "firm",
+    "first", "fluent", "formal", "frank", "free", "fresh", "gentle", "giant", "glad", "golden",
+    "grand", "green", "happy", "hardy", "hazel", "honest", "humble", "ideal", "indigo", "ivory",
+    "jolly", "keen", "kind", "large", "lasting", "level", "light", "lively", "loyal", "lucid",
+    "lucky", "lunar", "major", "mellow", "merry", "mighty", "mild", "modern", "modest", "narrow",
+    "neat", "nimble", "noble", "olive", "open", "patient", "plain", "polar", "polite", "prime",
+    "proud", "quick", "quiet", "rapid", "rare", "ready", "regal", "rich", "robust", "rosy",
+    "royal", "rustic", "safe", "sandy", "scarlet", "sharp", "silent", "silver", "simple", "sleek",
+    "smart", "smooth", "solar", "solid", "sonic", "steady", "still", "sturdy", "sunny", "swift",
+    "tidy", "true", "upbeat", "vivid", "warm", "wise", "witty",
+];
+
+const NOUNS: [&str; 128] = [
+    "acorn", "anchor", "apple", "arrow", "aspen", "badger", "banjo", "bay", "beacon", "bear",
+    "birch", "bison", "breeze", "brook", "cactus", "canyon", "cedar", "cliff", "cloud", "comet",
+    "condor", "coral", "cove", "crane", "creek", "dawn", "delta", "dingo", "dolphin", "dune",
+    "eagle", "ember", "falcon", "fern", "ferret", "finch", "fjord", "flint", "forest", "fox",
+    "gazelle", "geyser", "glacier", "glade", "grove", "harbor", "hawk", "hazel", "heron", "hill",
+    "ibis", "island", "jaguar", "jasper", "kayak", "kestrel", "kiwi", "koala", "lagoon", "lake",
+    "lantern", "lark", "lemur", "lily", "lotus", "lynx", "mango", "maple", "marble", "marsh",
+    "meadow", "mesa", "meteor", "moose", "moss", "nebula", "newt", "oak", "oasis", "ocean",
+    "orbit", "orca", "otter", "owl", "panda", "parrot", "peak", "pebble", "pepper", "pine",
+    "planet", "plover", "pond", "prairie", "puffin", "quail", "quartz", "rabbit", "raven", "reef",
+    "ridge", "river", "robin", "rocket", "salmon", "sequoia", "shore", "sparrow", "spruce", "star",
+    "stone", "summit", "swan", "thistle", "thunder", "tiger", "trail", "tulip", "tundra", "turtle",
+    "vale", "valley", "walrus", "willow", "wolf", "wren", "yak", "zebra",
+];
+
+/// `--style` of `randpass username`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    /// `quiet_otter42`: an adjective, a noun and two digits
+    AdjectiveNoun,
+    /// A pseudo-word (see `pseudo`) and two digits
+    Pronounceable,
+    /// Lowercase letters and digits, starting with a letter
+    Alnum,
+}
+
+impl Style {
+    pub const NAMES: &str = "adjective-noun, pronounceable or alnum";
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "adjective-noun" => Some(Style::AdjectiveNoun),
+            "pronounceable" => Some(Style::Pronounceable),
+            "alnum" => Some(Style::Alnum),
+            _ => None,
+        }
+    }
+
+    /// Shortest name the style can produce.
+    pub fn min_len(self) -> usize {
+        match self {
+            Style::AdjectiveNoun => {
+                let shortest = |list: &[&str]| list.iter().map(|w| w.len()).min().unwrap_or(0);
+                shortest(&ADJECTIVES) + 1 + shortest(&NOUNS) + SUFFIX_DIGITS
+            }
+            Style::Pronounceable => pseudo::MIN_LEN + SUFFIX_DIGITS,
+            Style::Alnum => 1,
+        }
+    }
+
+    /// Bits of one name with no length limit (for `alnum`, at `len`).
+    pub fn bits(self, len: usize) -> f64 {
+        let suffix = (10f64).powi(SUFFIX_DIGITS as i32).log2();
+        match self {
+            Style::AdjectiveNoun => ((ADJECTIVES.len() * NOUNS.len()) as f64).log2() + suffix,
+            Style::Pronounceable => pseudo::model().bits_per_word() + suffix,
+            Style::Alnum => 26f64.log2() + (len.saturating_sub(1) as f64) * 36f64.log2(),
+        }
+    }
+}
+
+/// One username in `style`, at most `max_len` characters (exactly
+/// `max_len` for `alnum`). Caller checks `max_len >= style.min_len()`.
+pub fn generate(style: Style, max_len: usize) -> String {
+    loop {
+        let name = draw(style, max_len);
+        if name.len() <= max_len {
+            return name;
+        }
+    }
+}
+
+fn draw(style: Style, len: usize) -> String {
+    let mut name = String::new();
+    match style {
+        Style::AdjectiveNoun => {
+            name.push_str(ADJECTIVES[uniform(ADJECTIVES.len())]);
+            name.push('_');
+            name.push_str(NOUNS[uniform(NOUNS.len())]);
+        }
+        Style::Pronounceable => {
+            let mut word = [0u8; pseudo::MAX_LEN];
+            let n = pseudo::model().generate(&mut word);
+            name.extend(word[..n].iter().map(|&b| b as char));
+        }
+        Style::Alnum => {
+            name.push((b'a' + uniform(26) as u8) as char);
+            for _ in 1..len {
+                name.push(ALNUM[uniform(ALNUM.len())] as char);
+            }
+            return name;
+        }
+    }
+    for _ in 0..SUFFIX_DIGITS {
+        name.push((b'0' + uniform(10) as u8) as char);
+    }
+    name
+}
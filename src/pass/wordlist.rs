@@ -0,0 +1,381 @@
+//! User wordlists (`--wordlist`, the `wordlist` setting, and lists
+//! installed with `randpass wordlist add`).
+//!
+//! One word per line. Diceware files (`11111<TAB>word`) work as-is: a
+//! leading roll number is dropped. Blank lines and repeated words are
+//! skipped, so bits per word reflect the unique words actually drawn.
+//!
+//! Installed lists live in the cache directory as `NAME.txt` next to a
+//! `NAME.txt.sha256` recorded at install time (`sha256sum -c` format);
+//! the digest is checked every time the list is used.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+use super::words::{self, WordList};
+use crate::cli::prompts;
+
+/// Size of the short diceware lists (6^4); smaller lists get a warning.
+pub const MIN_WORDS: usize = 1296;
+
+/// Words shorter than this are flagged by `wordlist check`.
+pub const SHORT_WORD: usize = 3;
+
+/// What `randpass wordlist check` finds in a list, by index into `words`.
+pub struct Overlap {
+    /// Unique words in file order
+    pub words: Vec<String>,
+    /// Repeats dropped
+    pub duplicates: usize,
+    /// Pairs one insertion, deletion or substitution apart
+    pub near: Vec<(usize, usize)>,
+    /// Groups of words linked by near pairs; a word in none is its own
+    pub groups: usize,
+    /// (word, longer word it appears in)
+    pub embedded: Vec<(usize, usize)>,
+    /// Words that begin some longer word
+    pub prefixes: usize,
+    /// Words under `SHORT_WORD` characters
+    pub short: Vec<usize>,
+}
+
+impl Overlap {
+    /// Bits per word as listed.
+    pub fn bits(&self) -> f64 {
+        (self.words.len() as f64).log2()
+    }
+
+    /// Bits per word when near-duplicates can't be told apart: each group
+    /// counts once.
+    pub fn distinct_bits(&self) -> f64 {
+        (self.groups as f64).log2()
+    }
+
+    /// Bits per word joined without a separator: a word that begins
+    /// another can run into the next one, so only the rest are safe.
+    pub fn joined_bits(&self) -> f64 {
+        ((self.words.len() - self.prefixes) as f64).log2()
+    }
+
+    /// Bits of the shortest word guessed letter by letter over the
+    /// characters the list uses. Under the list's bits, short words make
+    /// a phrase cheaper to brute-force than to draw.
+    pub fn shortest_bits(&self) -> f64 {
+        let alphabet: HashSet<char> = self.words.iter().flat_map(|w| w.chars()).collect();
+        let shortest = self
+            .words
+            .iter()
+            .map(|w| w.chars().count())
+            .min()
+            .unwrap_or(0);
+        shortest as f64 * (alphabet.len() as f64).log2()
+    }
+}
+
+/// Screen list contents for what weakens it: repeats, words one edit
+/// apart, words inside other words, and very short words.
+pub fn overlap(data: &[u8]) -> Result<Overlap, String> {
+    let text = std::str::from_utf8(data).map_err(|_| "not valid UTF-8".to_string())?;
+    let (words, duplicates) = parse(text)?;
+    if words.len() < 2 {
+        return Err(format!("{} unique word(s), need at least 2", words.len()));
+    }
+    let index: HashMap<&str, usize> = words.iter().enumerate().map(|(i, w)| (*w, i)).collect();
+
+    // Words one edit apart share the word itself or one of its deletions
+    let mut by_key: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, word) in words.iter().enumerate() {
+        let chars: Vec<char> = word.chars().collect();
+        by_key.entry(word.to_string()).or_default().push(i);
+        for skip in 0..chars.len() {
+            let key: String = chars
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != skip)
+                .map(|(_, c)| c)
+                .collect();
+            by_key.entry(key).or_default().push(i);
+        }
+    }
+    let mut near: Vec<(usize, usize)> = by_key
+        .values()
+        .flat_map(|ids| {
+            ids.iter()
+                .flat_map(move |&a| ids.iter().map(move |&b| (a, b)))
+                .filter(|&(a, b)| a < b && one_edit(words[a], words[b]))
+        })
+        .collect();
+    near.sort_unstable();
+    near.dedup();
+
+    let mut parent: Vec<usize> = (0..words.len()).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    for &(a, b) in &near {
+        let (ra, rb) = (root(&mut parent, a), root(&mut parent, b));
+        parent[ra] = rb;
+    }
+    let groups = (0..words.len())
+        .filter(|&i| root(&mut parent, i) == i)
+        .count();
+
+    let mut embedded = Vec::new();
+    let mut prefixes = HashSet::new();
+    for (outer, word) in words.iter().enumerate() {
+        let bounds: Vec<usize> = word
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain([word.len()])
+            .collect();
+        let mut inside = HashSet::new();
+        for (a, &start) in bounds.iter().enumerate() {
+            for &end in &bounds[a + 1..] {
+                if end - start == word.len() {
+                    continue;
+                }
+                if let Some(&inner) = index.get(&word[start..end])
+                    && inside.insert(inner)
+                {
+                    embedded.push((inner, outer));
+                    if start == 0 {
+                        prefixes.insert(inner);
+                    }
+                }
+            }
+        }
+    }
+    embedded.sort_unstable();
+
+    let short = (0..words.len())
+        .filter(|&i| words[i].chars().count() < SHORT_WORD)
+        .collect();
+
+    Ok(Overlap {
+        words: words.iter().map(|w| w.to_string()).collect(),
+        duplicates,
+        near,
+        groups,
+        embedded,
+        prefixes: prefixes.len(),
+        short,
+    })
+}
+
+/// True if `a` and `b` differ by exactly one insertion, deletion or
+/// substitution.
+fn one_edit(a: &str, b: &str) -> bool {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if long.len() - short.len() > 1 {
+        return false;
+    }
+    let head = short.iter().zip(&long).take_while(|(x, y)| x == y).count();
+    if short.len() == long.len() {
+        head < short.len() && short[head + 1..] == long[head + 1..]
+    } else {
+        short[head..] == long[head + 1..]
+    }
+}
+
+/// The list passphrase mode draws from: `file` (--wordlist), else `name`
+/// (--list) or the list for `lang` (--lang), else the `saved` wordlist
+/// setting, else the embedded default.
+pub fn select(
+    name: Option<&str>,
+    lang: Option<&str>,
+    file: Option<&str>,
+    saved: &str,
+) -> Result<&'static WordList, String> {
+    if [name, lang, file].iter().flatten().count() > 1 {
+        return Err("use only one of --list, --lang and --wordlist".to_string());
+    }
+    let lang_name = lang.map(lang_list).transpose()?;
+    let name = lang_name.as_deref().or(name);
+    let file = file.or((name.is_none() && !saved.is_empty()).then_some(saved));
+    if let Some(path) = file {
+        return load(path).map_err(|e| format!("wordlist {}: {}", path, e));
+    }
+    let name = name.unwrap_or(words::DEFAULT);
+    if let Some(list) = words::get(name) {
+        return Ok(list);
+    }
+    if installed_path(name).exists() {
+        return load_installed(name).map_err(|e| format!("wordlist {}: {}", name, e));
+    }
+    Err(format!(
+        "unknown wordlist '{}' (see `randpass wordlist list`)",
+        name
+    ))
+}
+
+/// The list for `--lang code`: the embedded one, else one installed as
+/// `lang-CODE`, which `wordlist add` records a SHA-256 for and every use
+/// checks.
+fn lang_list(code: &str) -> Result<String, String> {
+    if let Some(name) = words::for_lang(code) {
+        return Ok(name.to_string());
+    }
+    if !(2..=3).contains(&code.len()) || !code.bytes().all(|b| b.is_ascii_alphabetic()) {
+        return Err(format!(
+            "--lang takes a language code like de, not '{}'",
+            code
+        ));
+    }
+    let name = format!("lang-{}", code.to_ascii_lowercase());
+    if installed_path(&name).exists() {
+        return Ok(name);
+    }
+    Err(format!(
+        "no wordlist for language '{}' (embedded: {}); install one with `randpass wordlist add {} <PATH|URL> --sha256 HEX`",
+        code,
+        words::langs(),
+        name
+    ))
+}
+
+/// Read and check the list at `path`. The list is kept for the rest of
+/// the run, like the embedded ones.
+pub fn load(path: &str) -> Result<&'static WordList, String> {
+    let mut data = std::fs::read(path).map_err(|e| e.to_string())?;
+    let result = check(path, &data);
+    data.zeroize();
+    Ok(Box::leak(Box::new(result?)))
+}
+
+/// Load an installed list after checking it against its recorded digest.
+pub fn load_installed(name: &str) -> Result<&'static WordList, String> {
+    let mut data = std::fs::read(installed_path(name)).map_err(|e| e.to_string())?;
+    let result = verify_digest(name, &data).and_then(|()| check(name, &data));
+    data.zeroize();
+    Ok(Box::leak(Box::new(result?)))
+}
+
+/// Compare `data` with the digest recorded when `name` was installed.
+pub fn verify_digest(name: &str, data: &[u8]) -> Result<(), String> {
+    let recorded = std::fs::read_to_string(digest_path(name))
+        .map_err(|e| format!("no recorded digest ({})", e))?;
+    let recorded = recorded.split_whitespace().next().unwrap_or_default();
+    let actual = sha256_hex(data);
+    if !recorded.eq_ignore_ascii_case(&actual) {
+        return Err(format!(
+            "SHA-256 mismatch, list changed since install (recorded {}, now {})",
+            recorded, actual
+        ));
+    }
+    Ok(())
+}
+
+/// Lowercase hex SHA-256 of `data`.
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// `$XDG_CACHE_HOME/randpass/wordlists`, else `~/.cache/randpass/wordlists`.
+pub fn installed_dir() -> PathBuf {
+    let cache = std::env::var("XDG_CACHE_HOME")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| {
+            let home = std::env::var("HOME").unwrap_or_else(|_| ".".into());
+            format!("{}/.cache", home)
+        });
+    PathBuf::from(cache).join("randpass/wordlists")
+}
+
+pub fn installed_path(name: &str) -> PathBuf {
+    installed_dir().join(format!("{}.txt", name))
+}
+
+pub fn digest_path(name: &str) -> PathBuf {
+    installed_dir().join(format!("{}.txt.sha256", name))
+}
+
+/// Names of installed lists, sorted.
+pub fn installed() -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(installed_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|e| {
+            let file = e.file_name().into_string().ok()?;
+            file.strip_suffix(".txt").map(str::to_string)
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+/// Parse and check list contents, warning about anything that weakens it.
+pub fn check(name: &str, data: &[u8]) -> Result<WordList, String> {
+    let text = std::str::from_utf8(data).map_err(|_| "not valid UTF-8".to_string())?;
+    let (words, duplicates) = parse(text)?;
+    let list = WordList::new(name.to_string(), &words);
+
+    if list.len() < 2 {
+        return Err(format!("{} unique word(s), need at least 2", list.len()));
+    }
+    if duplicates > 0 {
+        prompts::warn(&format!(
+            "Wordlist: skipped {} duplicate(s), {} unique words",
+            duplicates,
+            list.len()
+        ));
+    }
+    let decomposed = (0..list.len())
+        .filter(|&i| is_decomposed(list.word(i)))
+        .count();
+    if decomposed > 0 {
+        prompts::warn(&format!(
+            "Wordlist: {} word(s) use combining accents (NFD), which may not match what a keyboard types (NFC)",
+            decomposed
+        ));
+    }
+    if list.len() < MIN_WORDS {
+        prompts::warn(&format!(
+            "Wordlist: only {} words ({:.1} bits/word), add words to reach {}+",
+            list.len(),
+            list.bits_per_word(),
+            MIN_WORDS
+        ));
+    }
+    Ok(list)
+}
+
+/// True if `word` contains a combining diacritical mark (U+0300-U+036F),
+/// i.e. an accent stored as a separate code point.
+fn is_decomposed(word: &str) -> bool {
+    word.chars().any(|c| ('\u{300}'..='\u{36f}').contains(&c))
+}
+
+/// Unique words in file order, and how many repeats were dropped.
+fn parse(text: &str) -> Result<(Vec<&str>, usize), String> {
+    let mut seen = HashSet::new();
+    let mut words = Vec::new();
+    let mut duplicates = 0;
+
+    for (n, line) in text.lines().enumerate() {
+        let word = match line.split_whitespace().collect::<Vec<_>>()[..] {
+            [] => continue,
+            [word] => word,
+            [roll, word] if roll.bytes().all(|b| b.is_ascii_digit()) => word,
+            _ => return Err(format!("line {}: expected one word", n + 1)),
+        };
+        if seen.insert(word) {
+            words.push(word);
+        } else {
+            duplicates += 1;
+        }
+    }
+    Ok((words, duplicates))
+}
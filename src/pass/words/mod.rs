@@ -0,0 +1,255 @@
+//! Wordlists for passphrase mode.
+//!
+//! Lists are embedded zlib-compressed and inflated on first use into a
+//! single mlock'd buffer, one word per line. Embedded lists live for the
+//! whole run; nothing is decompressed unless passphrase mode asks for it.
+
+use std::sync::OnceLock;
+
+use zeroize::Zeroize;
+
+/// List used when `--list` is not given.
+pub const DEFAULT: &str = "bip39-english";
+
+struct Embedded {
+    name: &'static str,
+    description: &'static str,
+    /// ISO 639-1 code for `--lang`
+    lang: &'static str,
+    /// Word count, so listing needs no inflating
+    words: usize,
+    /// Inflated size in bytes, so the locked buffer is allocated once
+    size: usize,
+    data: &'static [u8],
+}
+
+/// The BIP39 lists (2048 words each, unique in their first four letters).
+/// Accented words are stored NFC (precomposed), as keyboards type them.
+/// `scripts/embed_wordlist.sh` compresses a list and prints its entry.
+const EMBEDDED: &[Embedded] = &[
+    Embedded {
+        name: "bip39-english",
+        description: "BIP39 English",
+        lang: "en",
+        words: 2048,
+        size: 13116,
+        data: include_bytes!("data/bip39-english.z"),
+    },
+    Embedded {
+        name: "bip39-spanish",
+        description: "BIP39 Spanish",
+        lang: "es",
+        words: 2048,
+        size: 13659,
+        data: include_bytes!("data/bip39-spanish.z"),
+    },
+    Embedded {
+        name: "bip39-french",
+        description: "BIP39 French",
+        lang: "fr",
+        words: 2048,
+        size: 16389,
+        data: include_bytes!("data/bip39-french.z"),
+    },
+    Embedded {
+        name: "bip39-italian",
+        description: "BIP39 Italian",
+        lang: "it",
+        words: 2048,
+        size: 16033,
+        data: include_bytes!("data/bip39-italian.z"),
+    },
+    Embedded {
+        name: "bip39-portuguese",
+        description: "BIP39 Portuguese",
+        lang: "pt",
+        words: 2048,
+        size: 15671,
+        data: include_bytes!("data/bip39-portuguese.z"),
+    },
+    Embedded {
+        name: "bip39-czech",
+        description: "BIP39 Czech",
+        lang: "cs",
+        words: 2048,
+        size: 14945,
+        data: include_bytes!("data/bip39-czech.z"),
+    },
+];
+
+static LOADED: [OnceLock<WordList>; EMBEDDED.len()] = [const { OnceLock::new() }; EMBEDDED.len()];
+
+/// Metadata of an embedded list, without inflating it.
+pub struct Info {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub lang: &'static str,
+    pub words: usize,
+}
+
+impl Info {
+    pub fn bits_per_word(&self) -> f64 {
+        (self.words as f64).log2()
+    }
+}
+
+/// The embedded lists, in display order.
+pub fn available() -> impl Iterator<Item = Info> {
+    EMBEDDED.iter().map(|e| Info {
+        name: e.name,
+        description: e.description,
+        lang: e.lang,
+        words: e.words,
+    })
+}
+
+/// Metadata of the embedded list `name`.
+pub fn get_info(name: &str) -> Option<Info> {
+    available().find(|info| info.name == name)
+}
+
+/// Name of the embedded list for language `code` (`--lang`).
+pub fn for_lang(code: &str) -> Option<&'static str> {
+    EMBEDDED
+        .iter()
+        .find(|e| e.lang.eq_ignore_ascii_case(code))
+        .map(|e| e.name)
+}
+
+/// Language codes with an embedded list, for error messages.
+pub fn langs() -> String {
+    let codes: Vec<&str> = EMBEDDED.iter().map(|e| e.lang).collect();
+    codes.join(", ")
+}
+
+/// Embedded list by name, inflated on first use.
+pub fn get(name: &str) -> Option<&'static WordList> {
+    let i = EMBEDDED.iter().position(|e| e.name == name)?;
+    Some(LOADED[i].get_or_init(|| inflate(&EMBEDDED[i])))
+}
+
+fn inflate(embedded: &Embedded) -> WordList {
+    let mut text = vec![0u8; embedded.size];
+    lock(text.as_ptr(), text.len());
+    let inflated = miniz_oxide::inflate::decompress_slice_iter_to_slice(
+        &mut text,
+        std::iter::once(embedded.data),
+        true,
+        false,
+    );
+    match inflated {
+        Ok(n) if n == embedded.size => {}
+        _ => panic!("embedded wordlist {} is corrupt", embedded.name),
+    }
+    let text = String::from_utf8(text).expect("embedded wordlists are UTF-8");
+    let list = WordList::from_locked(embedded.name.to_string(), text);
+    assert_eq!(
+        list.len(),
+        embedded.words,
+        "embedded wordlist {}",
+        embedded.name
+    );
+    list
+}
+
+fn lock(ptr: *const u8, len: usize) {
+    unsafe {
+        libc::mlock(ptr as *const libc::c_void, len);
+    }
+}
+
+/// Words stored back to back in one locked buffer. Zeroized and unlocked
+/// on drop.
+pub struct WordList {
+    name: String,
+    text: String,
+    /// Byte range of each word in `text`
+    spans: Vec<(u32, u32)>,
+    /// Bytes in the longest word
+    longest: usize,
+}
+
+impl WordList {
+    /// Copy `words` into a new locked buffer.
+    pub fn new(name: String, words: &[&str]) -> Self {
+        let size = words.iter().map(|w| w.len() + 1).sum();
+        let mut text = String::with_capacity(size);
+        lock(text.as_ptr(), size);
+        for word in words {
+            text.push_str(word);
+            text.push('\n');
+        }
+        Self::from_locked(name, text)
+    }
+
+    /// Index a buffer that is already locked; one word per line, blank
+    /// lines skipped.
+    fn from_locked(name: String, text: String) -> Self {
+        let mut spans = Vec::new();
+        let mut longest = 0;
+        let mut start = 0;
+        for line in text.split('\n') {
+            let end = start + line.len();
+            if !line.is_empty() {
+                spans.push((start as u32, end as u32));
+                longest = longest.max(line.len());
+            }
+            start = end + 1;
+        }
+        Self {
+            name,
+            text,
+            spans,
+            longest,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Word number `index`; panics when out of range.
+    #[inline]
+    pub fn word(&self, index: usize) -> &str {
+        let (start, end) = self.spans[index];
+        &self.text[start as usize..end as usize]
+    }
+
+    /// Bytes in the longest word.
+    pub fn longest(&self) -> usize {
+        self.longest
+    }
+
+    /// Entropy of one uniformly chosen word.
+    pub fn bits_per_word(&self) -> f64 {
+        (self.len() as f64).log2()
+    }
+}
+
+impl std::fmt::Debug for WordList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WordList")
+            .field("name", &self.name)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl Drop for WordList {
+    fn drop(&mut self) {
+        let ptr = self.text.as_ptr();
+        let len = self.text.len();
+        self.text.zeroize();
+        unsafe {
+            libc::munlock(ptr as *const libc::c_void, len);
+        }
+    }
+}
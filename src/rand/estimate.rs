@@ -0,0 +1,173 @@
+//! Min-entropy estimators from NIST SP 800-90B section 6.3, for raw
+//! (unconditioned) samples of 8 bits: the most common value, collision,
+//! Markov and lag prediction estimates. Each gives a lower confidence
+//! bound at 99%, in bits per sample. The two binary estimators run on the
+//! samples' bits, and their result is scaled to a sample.
+//!
+//! This is the non-IID track cut down to four estimators; a full
+//! assessment also runs the compression, t-tuple, LRS and other
+//! predictor estimates, and the IID permutation tests.
+
+/// z for the upper bound of a 99% confidence interval
+const Z: f64 = 2.576;
+/// Bits per sample
+const SAMPLE_BITS: f64 = 8.0;
+/// Lags the lag predictor tries
+const LAGS: usize = 128;
+
+/// Bits of `samples`, most significant first.
+fn bits(samples: &[u8]) -> impl Iterator<Item = u8> + '_ {
+    samples
+        .iter()
+        .flat_map(|&s| (0..8).rev().map(move |i| (s >> i) & 1))
+}
+
+/// Upper 99% bound on a probability measured as `p` over `n` trials.
+fn upper(p: f64, n: usize) -> f64 {
+    (p + Z * (p * (1.0 - p) / (n as f64 - 1.0)).sqrt()).min(1.0)
+}
+
+/// 6.3.1: the most common value's share, bounded above.
+pub fn most_common(samples: &[u8]) -> f64 {
+    let mut counts = [0usize; 256];
+    for &s in samples {
+        counts[s as usize] += 1;
+    }
+    let max = counts.iter().copied().max().unwrap_or(0);
+    -upper(max as f64 / samples.len() as f64, samples.len()).log2()
+}
+
+/// 6.3.2 on the bit string: the mean time to the first repeated bit.
+/// With bits it is always 2 or 3, and the mean is 2 + 2p(1 - p), which
+/// solves for the likelier bit's probability p directly.
+pub fn collision(samples: &[u8]) -> f64 {
+    let bits: Vec<u8> = bits(samples).collect();
+    let mut times = Vec::new();
+    let mut i = 0;
+    while i + 2 < bits.len() {
+        let t = if bits[i] == bits[i + 1] { 2 } else { 3 };
+        times.push(t as f64);
+        i += t;
+    }
+    let v = times.len() as f64;
+    let mean = times.iter().sum::<f64>() / v;
+    let var = times.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / (v - 1.0);
+    let bound = (mean - Z * var.sqrt() / v.sqrt()).max(2.0);
+
+    let p = if bound >= 2.5 {
+        0.5
+    } else {
+        (1.0 + (1.0 - 2.0 * (bound - 2.0)).sqrt()) / 2.0
+    };
+    (-p.log2() * SAMPLE_BITS).min(SAMPLE_BITS)
+}
+
+/// 6.3.3 on the bit string: the likeliest 128-bit path through a
+/// first-order Markov model of the bits.
+pub fn markov(samples: &[u8]) -> f64 {
+    let mut ones = 0usize;
+    let mut pairs = [[0usize; 2]; 2];
+    let mut prev = None;
+    let mut len = 0;
+    for b in bits(samples) {
+        ones += b as usize;
+        if let Some(p) = prev {
+            pairs[p as usize][b as usize] += 1;
+        }
+        prev = Some(b);
+        len += 1;
+    }
+
+    let ratio = |a: usize, b: usize| {
+        if a + b == 0 {
+            0.0
+        } else {
+            a as f64 / (a + b) as f64
+        }
+    };
+    let p1 = ones as f64 / len as f64;
+    let p0 = 1.0 - p1;
+    let p00 = ratio(pairs[0][0], pairs[0][1]);
+    let p01 = 1.0 - p00;
+    let p10 = ratio(pairs[1][0], pairs[1][1]);
+    let p11 = 1.0 - p10;
+
+    // log2 of each path's probability; log2(0) is -inf and never the max
+    let l = f64::log2;
+    let paths = [
+        l(p0) + 127.0 * l(p00),
+        l(p0) + 64.0 * l(p01) + 63.0 * l(p10),
+        l(p0) + l(p01) + 126.0 * l(p11),
+        l(p1) + l(p10) + 126.0 * l(p00),
+        l(p1) + 64.0 * l(p10) + 63.0 * l(p01),
+        l(p1) + 127.0 * l(p11),
+    ];
+    let max = paths.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    (-max / 128.0).min(1.0) * SAMPLE_BITS
+}
+
+/// 6.3.8: predict each sample as the one `d` samples back, for the lag
+/// `d` that has been right most often so far. Both the overall success
+/// rate and the longest run of successes bound the probability.
+pub fn lag(samples: &[u8]) -> f64 {
+    let mut scores = [0usize; LAGS];
+    let mut winner = 0;
+    let mut correct = 0usize;
+    let mut run = 0usize;
+    let mut longest = 0usize;
+
+    for i in 1..samples.len() {
+        if i > winner && samples[i - 1 - winner] == samples[i] {
+            correct += 1;
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+        for d in 0..LAGS.min(i) {
+            if samples[i - 1 - d] == samples[i] {
+                scores[d] += 1;
+                if scores[d] >= scores[winner] {
+                    winner = d;
+                }
+            }
+        }
+    }
+
+    let n = samples.len() - 1;
+    let global = if correct == 0 {
+        1.0 - 0.01f64.powf(1.0 / n as f64)
+    } else {
+        upper(correct as f64 / n as f64, n)
+    };
+    let local = local_bound(longest + 1, n);
+    -global.max(local).max(1.0 / 256.0).log2()
+}
+
+/// The success probability p at which a longest run of `r` successes in
+/// `n` predictions is the 99th percentile (6.3.7 step 9), by bisection.
+fn local_bound(r: usize, n: usize) -> f64 {
+    let r = r as f64;
+    // Log of the chance of no run of r successes at probability p
+    let no_run = |p: f64| {
+        let q = 1.0 - p;
+        let mut x: f64 = 1.0;
+        for _ in 0..10 {
+            x = 1.0 + q * p.powf(r) * x.powf(r + 1.0);
+        }
+        (1.0 - p * x).ln() - ((r + 1.0 - r * x) * q).ln() - (n as f64 + 1.0) * x.ln()
+    };
+
+    let target = 0.99f64.ln();
+    let (mut lo, mut hi) = (0.0, 1.0 - 1e-12);
+    for _ in 0..64 {
+        let mid = (lo + hi) / 2.0;
+        // Falls as p rises; NaN (past the root) counts as below
+        if no_run(mid) > target {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
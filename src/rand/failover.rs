@@ -0,0 +1,227 @@
+//! Entropy source failover: an ordered list of sources (`--rng
+//! hwrng,urandom,counter`, saved as `rng`) where the first one that starts
+//! is used and the rest stand behind it. A source that fails its health
+//! test, or stops delivering mid-run, hands over to the next one that
+//! starts, and the switch is reported on stderr and in the audit log's
+//! `rng=` field. With nothing left to fail over to, the run stops.
+//!
+//! A lone `urandom` stands on the cycle counter, as it always has when its
+//! pool can't be had; a lone `hwrng` stands on nothing.
+
+use std::sync::Mutex;
+
+use super::{hwrng, urand};
+use crate::cli::prompts;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// The CPU cycle counter (`rdtsc` on x86_64)
+    Counter,
+    Urandom,
+    Hwrng,
+}
+
+impl Source {
+    /// Every source, in declaration order: `source as usize` indexes it.
+    pub const ALL: [Source; 3] = [Source::Counter, Source::Urandom, Source::Hwrng];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "counter" | "rdtsc" => Some(Source::Counter),
+            "urandom" | "urand" => Some(Source::Urandom),
+            "hwrng" => Some(Source::Hwrng),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Source::Counter => "counter",
+            Source::Urandom => "urandom",
+            Source::Hwrng => "hwrng",
+        }
+    }
+}
+
+/// Parse a comma-separated source list, in failover order.
+pub fn parse(list: &str) -> Result<Vec<Source>, String> {
+    let mut sources = Vec::new();
+    for name in list.split(',').map(str::trim) {
+        let source = Source::parse(name).ok_or_else(|| format!("unknown RNG source '{}'", name))?;
+        if sources.contains(&source) {
+            return Err(format!("{} is listed twice", name));
+        }
+        sources.push(source);
+    }
+    Ok(sources)
+}
+
+/// `hwrng,urandom,counter`
+pub fn describe(sources: &[Source]) -> String {
+    sources
+        .iter()
+        .map(|s| s.name())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Sources still standing behind the one in use, next first.
+static FALLBACKS: Mutex<Vec<Source>> = Mutex::new(Vec::new());
+/// Sources this process failed over from, in order.
+static FAILED: Mutex<Vec<Source>> = Mutex::new(Vec::new());
+/// Device and read size for an hwrng further down the list.
+static HWRNG: Mutex<(String, usize)> = Mutex::new((String::new(), 0));
+
+/// The source draws go to now.
+pub fn current() -> Source {
+    if urand::is_requested() {
+        Source::Urandom
+    } else if hwrng::is_requested() {
+        Source::Hwrng
+    } else {
+        Source::Counter
+    }
+}
+
+/// Sources failed over from so far, for the audit log: `hwrng>`, or
+/// empty when the first choice held.
+pub fn trail() -> String {
+    FAILED
+        .lock()
+        .map(|failed| failed.iter().map(|s| format!("{}>", s.name())).collect())
+        .unwrap_or_default()
+}
+
+/// Replace whatever stands behind the source in use.
+pub(super) fn set_fallbacks(sources: Vec<Source>) {
+    if let Ok(mut fallbacks) = FALLBACKS.lock() {
+        *fallbacks = sources;
+    }
+}
+
+/// Select the first source in `sources` that starts, with the rest behind
+/// it. `device` and `read_size` are for hwrng wherever it is listed. Err
+/// when none starts.
+pub fn select(sources: &[Source], device: &str, read_size: usize) -> Result<(), String> {
+    if let Ok(mut hw) = HWRNG.lock() {
+        *hw = (device.to_string(), read_size);
+    }
+    let mut sources = sources.to_vec();
+    if sources == [Source::Urandom] {
+        sources.push(Source::Counter);
+    }
+    let Some(&first) = sources.first() else {
+        return Ok(());
+    };
+    set_fallbacks(sources[1..].to_vec());
+    let from = current();
+    if from == first {
+        return Ok(());
+    }
+    match take_over(first) {
+        Ok(()) => {
+            release(from);
+            Ok(())
+        }
+        Err(e) if sources.len() > 1 => advance_from(from, first, &e),
+        Err(e) => Err(e),
+    }
+}
+
+/// Move off `from`, which failed for `reason`, to the next source that
+/// starts. Ok without a switch when another thread already moved off it.
+/// Err when nothing is left.
+pub(super) fn advance(from: Source, reason: &str) -> Result<(), String> {
+    advance_from(from, from, reason)
+}
+
+/// `failed` is the source that gave `reason`; `from` is the one selected,
+/// which differs while `select` is still looking for a first source.
+fn advance_from(from: Source, failed: Source, reason: &str) -> Result<(), String> {
+    let Ok(mut fallbacks) = FALLBACKS.lock() else {
+        return Err(format!("{}: {}", failed.name(), reason));
+    };
+    if current() != from {
+        return Ok(());
+    }
+    let (mut failed, mut reason) = (failed, reason.to_string());
+    while !fallbacks.is_empty() {
+        let next = fallbacks.remove(0);
+        prompts::rng_failover(failed.name(), &reason, next.name());
+        if let Ok(mut trail) = FAILED.lock() {
+            trail.push(failed);
+        }
+        match take_over(next) {
+            Ok(()) => {
+                if from != next {
+                    release(from);
+                }
+                return Ok(());
+            }
+            Err(e) => (failed, reason) = (next, e),
+        }
+    }
+    Err(format!(
+        "{}: {}; no source left to fail over to",
+        failed.name(),
+        reason
+    ))
+}
+
+/// The hot path's failover: draw from the next source, or stop the run.
+#[cold]
+#[inline(never)]
+pub(super) fn fail_over(from: Source, reason: &str, hint: usize) -> u64 {
+    if let Err(e) = advance(from, reason) {
+        prompts::error(&format!("Error: RNG: {}; output stopped", e));
+        std::process::exit(1)
+    }
+    super::entropy(hint)
+}
+
+/// Start `source` and route draws to it. Pools are filled here, under the
+/// failover lock, so generation threads don't race to fill them.
+fn take_over(source: Source) -> Result<(), String> {
+    match source {
+        Source::Counter => {
+            if current() != Source::Counter {
+                crate::session::count_switch();
+            }
+            Ok(())
+        }
+        Source::Urandom => urand::take_over(),
+        Source::Hwrng => {
+            let (device, read_size) = HWRNG
+                .lock()
+                .map(|hw| hw.clone())
+                .map_err(|_| "device settings unavailable".to_string())?;
+            hwrng::enable(&device, read_size)
+        }
+    }
+}
+
+/// Stop routing draws to `source`. Its pool stays until the run's
+/// shutdown: threads may still be reading it.
+fn release(source: Source) {
+    match source {
+        Source::Counter => {}
+        Source::Urandom => urand::deselect(),
+        Source::Hwrng => hwrng::deselect(),
+    }
+}
+
+/// The health test on the source in use, failing over down the list
+/// while it fails. The first failure is returned when none passes.
+pub fn health_check() -> Result<(), String> {
+    let mut first = None;
+    loop {
+        let Err(reason) = super::health::check() else {
+            return Ok(());
+        };
+        let advanced = advance(current(), &reason);
+        let first = first.get_or_insert(reason);
+        if advanced.is_err() {
+            return Err(first.clone());
+        }
+    }
+}
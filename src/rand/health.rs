@@ -0,0 +1,82 @@
+//! Startup health test for byte output.
+//!
+//! Loosely modeled on the NIST SP 800-90B continuous tests: the raw source
+//! must not be stuck, and a block of generator output must not be visibly
+//! biased. This only catches catastrophic failures (a counter that stopped,
+//! an empty pool), it is not a statistical test suite.
+
+use super::{Rand, entropy};
+
+const SAMPLES: usize = 1024;
+
+/// A counter that advances less than this across the sample run is stuck.
+const MIN_DISTINCT: usize = 16;
+
+/// Monobit bound: 6 standard deviations over `SAMPLES * 64` bits.
+const MAX_MONOBIT_DEVIATION: usize = 768;
+
+/// Byte-frequency chi-square bound (255 degrees of freedom, mean 255,
+/// standard deviation ~22.6); ~10 deviations above the mean.
+const MAX_CHI_SQUARE: f64 = 480.0;
+
+/// Run the health test on the selected backend. Returns a description of
+/// the failure, if any.
+pub fn check() -> Result<(), String> {
+    check_source()?;
+    check_output()
+}
+
+/// Raw source: enough distinct readings to be advancing at all.
+fn check_source() -> Result<(), String> {
+    let mut raw: Vec<u64> = (0..SAMPLES).map(entropy).collect();
+    raw.sort_unstable();
+    raw.dedup();
+    let distinct = raw.len();
+    raw.fill(0);
+
+    if distinct < MIN_DISTINCT {
+        return Err(format!(
+            "raw entropy source looks stuck ({} distinct values in {} reads)",
+            distinct, SAMPLES
+        ));
+    }
+    Ok(())
+}
+
+/// Generator output: bit balance and byte distribution.
+fn check_output() -> Result<(), String> {
+    let mut ones = 0usize;
+    let mut counts = [0usize; 256];
+    for _ in 0..SAMPLES {
+        let word = Rand::get() as u64;
+        ones += word.count_ones() as usize;
+        for b in word.to_le_bytes() {
+            counts[b as usize] += 1;
+        }
+    }
+
+    let half = SAMPLES * 64 / 2;
+    if ones.abs_diff(half) > MAX_MONOBIT_DEVIATION {
+        return Err(format!(
+            "output bits are biased ({} ones in {} bits)",
+            ones,
+            SAMPLES * 64
+        ));
+    }
+
+    let expected = (SAMPLES * 8) as f64 / 256.0;
+    let chi_square: f64 = counts
+        .iter()
+        .map(|&c| {
+            let d = c as f64 - expected;
+            d * d / expected
+        })
+        .sum();
+    if chi_square > MAX_CHI_SQUARE {
+        return Err(format!(
+            "output bytes are unevenly distributed (chi-square {:.0})",
+            chi_square
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,414 @@
+//! Hardware TRNG pool (`--rng hwrng`): entropy read from a character
+//! device such as `/dev/hwrng` or a serial TRNG on `/dev/ttyACM0`, in
+//! reads of up to `hwrng_read_size` bytes. The device fills a 64 KiB pool
+//! that the generator draws from like the urandom pool: sequentially
+//! within a lap, from a state-scrambled offset across laps. A background
+//! thread keeps overwriting it as fast as the device delivers.
+//!
+//! Every byte read passes two of the SP 800-90B continuous health tests,
+//! sized for at least one bit of min-entropy per byte: the repetition
+//! count test (no value 21 times running) and the adaptive proportion
+//! test (no value 410 times in a 512-byte window). A failure while
+//! filling the pool refuses the backend; one later fails over to the next
+//! source listed (`rand::failover`), or stops the run.
+
+use std::fs::File;
+use std::io::Read;
+use std::os::fd::AsRawFd;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread::{self, JoinHandle};
+use zeroize::Zeroize;
+
+use crate::cli::prompts;
+
+const POOL_SIZE: usize = 64 * 1024;
+const POOL_MASK: usize = POOL_SIZE - 1;
+
+pub const DEFAULT_DEVICE: &str = "/dev/hwrng";
+pub const DEFAULT_READ_SIZE: usize = 4096;
+/// Reads are capped at the pool size.
+pub const MAX_READ_SIZE: usize = POOL_SIZE;
+
+/// Repetition count cutoff: 1 + 20 / H for H = 1 bit per byte, a false
+/// alarm rate of 2^-20.
+const REPETITION_CUTOFF: usize = 21;
+/// Adaptive proportion window and cutoff for H = 1 (SP 800-90B table 2).
+const PROPORTION_WINDOW: usize = 512;
+const PROPORTION_CUTOFF: usize = 410;
+
+/// How long the first fill waits for each read before giving up.
+const FILL_TIMEOUT_MS: i32 = 5000;
+/// How often the refresh thread looks up from a silent device to check
+/// for shutdown.
+const REFRESH_POLL_MS: i32 = 100;
+
+static mut POOL: *mut u8 = std::ptr::null_mut();
+static READ_POS: AtomicUsize = AtomicUsize::new(0);
+static LAP_OFFSET: AtomicUsize = AtomicUsize::new(0);
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static SHUTDOWN: AtomicBool = AtomicBool::new(false);
+/// Set by the refresh thread when the device fails a health test or
+/// stops reading; the next draw stops the run.
+static FAILED: AtomicBool = AtomicBool::new(false);
+static FAILURE: Mutex<String> = Mutex::new(String::new());
+static DEVICE: Mutex<(String, usize)> = Mutex::new((String::new(), DEFAULT_READ_SIZE));
+static REFRESH: Mutex<Option<JoinHandle<()>>> = Mutex::new(None);
+/// Held while filling, so generation threads refilling after a shutdown
+/// don't each build a pool.
+static INIT: Mutex<()> = Mutex::new(());
+
+// =============================================================================
+// Public API
+// =============================================================================
+
+pub fn is_requested() -> bool {
+    REQUESTED.load(Ordering::Relaxed)
+}
+
+/// Whether `device` exists, for offering the backend at all.
+pub fn is_available(device: &str) -> bool {
+    std::path::Path::new(device).exists()
+}
+
+/// The device in use, or last used.
+pub fn device() -> String {
+    DEVICE.lock().map(|d| d.0.clone()).unwrap_or_default()
+}
+
+/// Select `device`, read `read_size` bytes at a time, as the entropy
+/// source. The pool is filled and checked here, so a missing, silent or
+/// failing device is reported before anything is generated.
+pub fn enable(device: &str, read_size: usize) -> Result<(), String> {
+    if read_size == 0 || read_size > MAX_READ_SIZE {
+        return Err(format!(
+            "hwrng read size must be 1 to {} bytes, got {}",
+            MAX_READ_SIZE, read_size
+        ));
+    }
+    if REQUESTED.load(Ordering::Acquire) {
+        shutdown();
+    }
+    if let Ok(mut d) = DEVICE.lock() {
+        *d = (device.to_string(), read_size);
+    }
+    init()?;
+    if !REQUESTED.swap(true, Ordering::AcqRel) {
+        crate::session::count_switch();
+    }
+    Ok(())
+}
+
+pub fn disable() {
+    if REQUESTED.swap(false, Ordering::AcqRel) {
+        crate::session::count_switch();
+    }
+    shutdown()
+}
+
+/// Deselect without counting a switch: another pooled source is taking
+/// over, and that one counts it.
+pub(super) fn release() {
+    REQUESTED.store(false, Ordering::Release);
+    shutdown()
+}
+
+/// Stop routing draws here, leaving the pool to the run's shutdown:
+/// threads may still be reading it.
+pub(super) fn deselect() {
+    REQUESTED.store(false, Ordering::Release);
+}
+
+/// Returns a random u64 from the pool. `hint` (RNG state) scrambles the
+/// read position across laps. Refills the pool on first use after a
+/// shutdown.
+#[inline(always)]
+pub fn rand(hint: usize) -> u64 {
+    if !ACTIVE.load(Ordering::Relaxed) {
+        if !REQUESTED.load(Ordering::Relaxed) {
+            return 0;
+        }
+        if let Err(e) = init() {
+            return super::failover::fail_over(super::failover::Source::Hwrng, &e, hint);
+        }
+    }
+    if FAILED.load(Ordering::Relaxed) {
+        let reason = FAILURE.lock().map(|f| f.clone()).unwrap_or_default();
+        return super::failover::fail_over(super::failover::Source::Hwrng, &reason, hint);
+    }
+
+    let p = READ_POS.fetch_add(8, Ordering::Relaxed);
+    if p & POOL_MASK < 8 {
+        LAP_OFFSET.store(hint & POOL_MASK & !7, Ordering::Relaxed);
+    }
+    let pos = p.wrapping_add(LAP_OFFSET.load(Ordering::Relaxed)) & POOL_MASK & !7;
+
+    crate::session::count_raw(super::failover::Source::Hwrng);
+    unsafe { std::ptr::read_unaligned(POOL.add(pos) as *const u64) }
+}
+
+/// Emergency zero for signal handlers - minimal, async-signal-safe.
+///
+/// # Safety
+///
+/// Only for exit paths: the pool is wiped in place while other threads
+/// may still be reading it.
+#[inline(never)]
+pub unsafe fn emergency_zero() {
+    unsafe {
+        let ptr = POOL;
+        if !ptr.is_null() {
+            let ptr64 = ptr as *mut u64;
+            for i in 0..POOL_SIZE / 8 {
+                std::ptr::write_volatile(ptr64.add(i), 0u64);
+            }
+        }
+    }
+}
+
+// =============================================================================
+// Health tests
+// =============================================================================
+
+/// The continuous health tests, fed every byte the device delivers.
+struct Health {
+    last: Option<u8>,
+    run: usize,
+    window_value: u8,
+    window_seen: usize,
+    window_matches: usize,
+}
+
+impl Health {
+    fn new() -> Self {
+        Self {
+            last: None,
+            run: 0,
+            window_value: 0,
+            window_seen: 0,
+            window_matches: 0,
+        }
+    }
+
+    fn feed(&mut self, bytes: &[u8]) -> Result<(), String> {
+        for &b in bytes {
+            if self.last == Some(b) {
+                self.run += 1;
+                if self.run >= REPETITION_CUTOFF {
+                    return Err(format!(
+                        "repetition count test failed (0x{:02x} {} times running)",
+                        b, self.run
+                    ));
+                }
+            } else {
+                self.last = Some(b);
+                self.run = 1;
+            }
+
+            // Each window counts the value it starts with
+            if self.window_seen == 0 {
+                self.window_value = b;
+                self.window_matches = 0;
+            }
+            if b == self.window_value {
+                self.window_matches += 1;
+                if self.window_matches >= PROPORTION_CUTOFF {
+                    return Err(format!(
+                        "adaptive proportion test failed (0x{:02x} {} times in {} bytes)",
+                        b, self.window_matches, PROPORTION_WINDOW
+                    ));
+                }
+            }
+            self.window_seen = (self.window_seen + 1) % PROPORTION_WINDOW;
+        }
+        Ok(())
+    }
+}
+
+// =============================================================================
+// Pool management
+// =============================================================================
+
+/// Open the device, in raw mode when it is a serial line: a TRNG's bytes
+/// must not be line-buffered, echoed or translated.
+fn open(device: &str) -> Result<File, String> {
+    let file = File::open(device).map_err(|e| format!("{}: {}", device, e))?;
+    let fd = file.as_raw_fd();
+    unsafe {
+        if libc::isatty(fd) == 1 {
+            let mut termios: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(fd, &mut termios) == 0 {
+                libc::cfmakeraw(&mut termios);
+                libc::tcsetattr(fd, libc::TCSANOW, &termios);
+            }
+        }
+    }
+    Ok(file)
+}
+
+/// One read of up to `buf.len()` bytes, waiting at most `timeout_ms`.
+/// Ok(0) when the device had nothing in time.
+fn read_some(file: &mut File, buf: &mut [u8], timeout_ms: i32) -> std::io::Result<usize> {
+    let mut pfd = libc::pollfd {
+        fd: file.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    match unsafe { libc::poll(&mut pfd, 1, timeout_ms) } {
+        n if n < 0 => {
+            let e = std::io::Error::last_os_error();
+            if e.kind() == std::io::ErrorKind::Interrupted {
+                Ok(0)
+            } else {
+                Err(e)
+            }
+        }
+        0 => Ok(0),
+        _ => match file.read(buf)? {
+            0 => Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "device returned end of file",
+            )),
+            n => Ok(n),
+        },
+    }
+}
+
+/// Allocate and mlock the pool, fill it from the device through the
+/// health tests, and start the refresh thread.
+#[cold]
+#[inline(never)]
+fn init() -> Result<(), String> {
+    let _filling = INIT.lock();
+    if ACTIVE.load(Ordering::Acquire) {
+        return Ok(());
+    }
+    let (device, read_size) = DEVICE
+        .lock()
+        .map(|d| d.clone())
+        .map_err(|_| "device settings unavailable".to_string())?;
+    let mut file = open(&device)?;
+
+    let layout =
+        std::alloc::Layout::from_size_align(POOL_SIZE, 4096).expect("invalid layout constants");
+    let pool_ptr = unsafe { std::alloc::alloc_zeroed(layout) };
+    if pool_ptr.is_null() {
+        std::alloc::handle_alloc_error(layout);
+    }
+    // Best effort: the pool is refreshed continuously, unlike a key
+    unsafe { libc::mlock(pool_ptr as *const libc::c_void, POOL_SIZE) };
+    let pool = unsafe { std::slice::from_raw_parts_mut(pool_ptr, POOL_SIZE) };
+
+    let mut health = Health::new();
+    let mut filled = 0;
+    let fill = loop {
+        if filled == POOL_SIZE {
+            break Ok(());
+        }
+        let end = (filled + read_size).min(POOL_SIZE);
+        match read_some(&mut file, &mut pool[filled..end], FILL_TIMEOUT_MS) {
+            Ok(0) => {
+                break Err(format!(
+                    "{}: no data in {}s ({} of {} bytes read)",
+                    device,
+                    FILL_TIMEOUT_MS / 1000,
+                    filled,
+                    POOL_SIZE
+                ));
+            }
+            Ok(n) => {
+                if let Err(e) = health.feed(&pool[filled..filled + n]) {
+                    break Err(format!("{}: {}", device, e));
+                }
+                filled += n;
+            }
+            Err(e) => break Err(format!("{}: {}", device, e)),
+        }
+    };
+    if let Err(e) = fill {
+        pool.zeroize();
+        unsafe {
+            libc::munlock(pool_ptr as *const libc::c_void, POOL_SIZE);
+            std::alloc::dealloc(pool_ptr, layout);
+        }
+        return Err(e);
+    }
+    prompts::verbose(&format!(
+        "RNG: hwrng pool filled from {} ({} bytes a read), health tests passed",
+        device, read_size
+    ));
+
+    unsafe { POOL = pool_ptr };
+    crate::session::count_pool_read(super::failover::Source::Hwrng, POOL_SIZE);
+    READ_POS.store(0, Ordering::Release);
+    FAILED.store(false, Ordering::Release);
+    SHUTDOWN.store(false, Ordering::Release);
+    ACTIVE.store(true, Ordering::Release);
+
+    let handle = thread::spawn(move || refresh(file, read_size, health));
+    if let Ok(mut slot) = REFRESH.lock() {
+        *slot = Some(handle);
+    }
+    Ok(())
+}
+
+/// Overwrite the pool a read at a time, through the same health tests,
+/// until shutdown or a failure.
+fn refresh(mut file: File, read_size: usize, mut health: Health) {
+    let mut block = vec![0u8; read_size];
+    let mut write_pos = 0usize;
+    while !SHUTDOWN.load(Ordering::Relaxed) {
+        let n = match read_some(&mut file, &mut block, REFRESH_POLL_MS) {
+            Ok(n) => n,
+            Err(e) => {
+                fail(format!("{} stopped reading: {}", device(), e));
+                break;
+            }
+        };
+        if let Err(e) = health.feed(&block[..n]) {
+            fail(format!("{}: {}", device(), e));
+            break;
+        }
+        // shutdown joins this thread before freeing the pool
+        let pool = unsafe { std::slice::from_raw_parts_mut(POOL, POOL_SIZE) };
+        for &b in &block[..n] {
+            pool[write_pos] = b;
+            write_pos = (write_pos + 1) & POOL_MASK;
+        }
+        crate::session::count_pool_read(super::failover::Source::Hwrng, n);
+    }
+    block.zeroize();
+}
+
+fn fail(reason: String) {
+    if let Ok(mut failure) = FAILURE.lock() {
+        *failure = reason;
+    }
+    FAILED.store(true, Ordering::Release);
+}
+
+/// Stop the refresh thread, zeroize and deallocate the pool. Keeps the
+/// selection: the next draw refills the pool from the device.
+pub fn shutdown() {
+    if !ACTIVE.load(Ordering::Acquire) {
+        return;
+    }
+    SHUTDOWN.store(true, Ordering::Release);
+    if let Some(handle) = REFRESH.lock().ok().and_then(|mut slot| slot.take()) {
+        let _ = handle.join();
+    }
+
+    unsafe {
+        let ptr = POOL;
+        if !ptr.is_null() {
+            POOL = std::ptr::null_mut();
+            std::slice::from_raw_parts_mut(ptr, POOL_SIZE).zeroize();
+            libc::munlock(ptr as *const libc::c_void, POOL_SIZE);
+            let layout = std::alloc::Layout::from_size_align(POOL_SIZE, 4096)
+                .expect("invalid layout constants");
+            std::alloc::dealloc(ptr, layout);
+        }
+    }
+    ACTIVE.store(false, Ordering::Release);
+}
@@ -1,26 +1,70 @@
 //! Random number generation with hardware entropy.
 
+pub mod estimate;
+pub mod failover;
+mod health;
 mod hw;
+pub mod hwrng;
+pub mod prefetch;
 mod primes;
 pub mod urand;
 
-use core::cell::UnsafeCell;
-use std::sync::LazyLock;
+use core::cell::Cell;
+use std::sync::Once;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use primes::PRIMES;
 
-// Re-export urandom control
-pub use urand::{
-    disable as disable_urandom, enable as enable_urandom, shutdown as shutdown_urandom,
-};
+pub use health::check as health_check;
+pub use hw::{entropy as hardware_entropy, source_name as hardware_source};
+pub use prefetch::{start as start_prefetch, stop as stop_prefetch};
+
+/// Request the urandom pool, with the cycle counter behind it. Returns
+/// false if /dev/urandom is not available.
+pub fn enable_urandom() -> bool {
+    failover::set_fallbacks(vec![failover::Source::Counter]);
+    urand::enable()
+}
+
+pub fn disable_urandom() {
+    failover::set_fallbacks(Vec::new());
+    urand::disable()
+}
 
 pub fn is_urandom_enabled() -> bool {
     urand::is_requested()
 }
 
+/// Select the hardware TRNG at `device` (`hwrng`), in place of the urandom
+/// pool or the cycle counter, with nothing behind it.
+pub fn enable_hwrng(device: &str, read_size: usize) -> Result<(), String> {
+    hwrng::enable(device, read_size)?;
+    failover::set_fallbacks(Vec::new());
+    urand::release();
+    Ok(())
+}
+
+pub fn disable_hwrng() {
+    failover::set_fallbacks(Vec::new());
+    hwrng::disable()
+}
+
+pub fn is_hwrng_enabled() -> bool {
+    hwrng::is_requested()
+}
+
+/// Zeroize and release the pooled sources (urandom, hwrng) after a run.
+/// The selection stays; the next draw refills them.
+pub fn shutdown_urandom() {
+    urand::shutdown();
+    hwrng::shutdown();
+}
+
 pub fn entropy_source() -> &'static str {
     if urand::is_requested() {
         "/dev/urandom"
+    } else if hwrng::is_requested() {
+        "hwrng"
     } else {
         hw::source_name()
     }
@@ -34,7 +78,10 @@ pub fn entropy_source() -> &'static str {
 fn entropy(hint: usize) -> u64 {
     if urand::is_requested() {
         urand::rand(hint)
+    } else if hwrng::is_requested() {
+        hwrng::rand(hint)
     } else {
+        crate::session::count_raw(failover::Source::Counter);
         hw::entropy()
     }
 }
@@ -43,38 +90,107 @@ fn entropy(hint: usize) -> u64 {
 // RNG
 // =============================================================================
 
-static RAND: LazyLock<Rand> = LazyLock::new(Rand::new);
+thread_local! {
+    // Each thread has its own state, seeded on its first draw (0 = unseeded),
+    // so generation threads never share or race on it.
+    static STATE: Cell<usize> = const { Cell::new(0) };
+}
+
+pub const DEFAULT_WARMUP: usize = 64;
+pub const MAX_WARMUP: usize = 1 << 20;
 
-pub struct Rand(UnsafeCell<usize>);
-unsafe impl Sync for Rand {}
+/// Outputs a freshly seeded state discards before its first draw is used
+/// (`warmup_discard`).
+static WARMUP: AtomicUsize = AtomicUsize::new(DEFAULT_WARMUP);
+static AT_FORK: Once = Once::new();
+
+/// Set the warm-up discard count for states seeded from here on.
+pub fn set_warmup(outputs: usize) {
+    WARMUP.store(outputs.min(MAX_WARMUP), Ordering::Relaxed);
+}
+
+pub fn warmup() -> usize {
+    WARMUP.load(Ordering::Relaxed)
+}
+
+/// fork() child: the one thread left holds a copy of the parent's state.
+/// Unseed it, so the child seeds and warms up its own.
+extern "C" fn unseed_after_fork() {
+    let _ = STATE.try_with(|cell| cell.set(0));
+}
+
+pub struct Rand;
 
 impl Rand {
-    #[inline]
-    pub fn new() -> Self {
-        Rand(UnsafeCell::new(entropy(0) as usize))
+    #[inline(always)]
+    pub fn get() -> usize {
+        crate::session::count_word();
+        if let Some(word) = prefetch::pop() {
+            return word;
+        }
+        Self::draw()
     }
 
+    /// Draw on this thread's own state, bypassing the prefetch ring.
     #[inline(always)]
-    pub fn get() -> usize {
-        let state = unsafe { *RAND.0.get() };
+    fn draw() -> usize {
+        STATE.with(|cell| {
+            let mut state = cell.get();
+            if state == 0 {
+                state = Self::seed();
+            }
+            let (out, new_state) = Self::step(state);
+            cell.set(new_state);
+            out
+        })
+    }
+
+    /// Seed a state from the entropy source, then step it through the
+    /// warm-up: the first outputs after a seed come from a cycle counter
+    /// that has barely moved, so they are thrown away.
+    #[cold]
+    #[inline(never)]
+    fn seed() -> usize {
+        AT_FORK.call_once(|| unsafe {
+            libc::pthread_atfork(None, None, Some(unseed_after_fork));
+        });
+        let mut state = entropy(0) as usize;
+        let discard = warmup();
+        for _ in 0..discard {
+            state = Self::step(state).1;
+        }
+        let thread = std::thread::current();
+        crate::cli::prompts::debug(&format!(
+            "RNG: thread {} seeded, {} warm-up outputs discarded",
+            thread
+                .name()
+                .map_or_else(|| format!("{:?}", thread.id()), str::to_string),
+            discard
+        ));
+        state
+    }
+
+    #[inline(always)]
+    fn step(state: usize) -> (usize, usize) {
         let ent = entropy(state) as usize;
 
         // Mix entropy into prime selection
         let mixed = state ^ ent;
-        let idx = (mixed ^ (mixed >> 32)) as usize % PRIMES.len();
+        let idx = (mixed ^ (mixed >> 32)) % PRIMES.len();
 
         // State transition: rotate, multiply by prime, XOR entropy
         let new_state = state.rotate_left(17).wrapping_mul(PRIMES[idx]) ^ ent;
-        unsafe { *RAND.0.get() = new_state };
 
         // SplitMix64 output finalizer
         let mut z = new_state;
         z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9_usize);
         z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb_usize);
-        z ^ (z >> 31)
+        (z ^ (z >> 31), new_state)
     }
 }
 
+/// Zero the calling thread's RNG state. Generation threads call this
+/// before they exit; the main thread's is zeroed by the exit handlers.
 pub fn zeroize_state() {
-    unsafe { std::ptr::write_volatile(RAND.0.get(), 0) }
+    let _ = STATE.try_with(|cell| unsafe { std::ptr::write_volatile(cell.as_ptr(), 0) });
 }
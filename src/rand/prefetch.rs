@@ -0,0 +1,213 @@
+//! Entropy prefetch: a background producer keeps a bounded ring of
+//! conditioned RNG words ahead of the generating thread, so `Rand::get` on
+//! that thread is a ring pop instead of an entropy read and mix.
+//!
+//! Single producer, single consumer: only the thread that called `start`
+//! is served from the ring. Other threads (parallel workers, the producer
+//! itself) draw directly, and so does the consumer when the ring runs dry,
+//! so the pipeline never blocks generation. The ring is mlock'd, each word
+//! is zeroed as it is popped, and the rest is wiped on stop or exit.
+
+use std::cell::Cell;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use super::Rand;
+
+/// How long `wipe` waits for the producer to see the stop, in 1 ms naps.
+/// A parked producer wakes within one nap; this bounds the wait if it
+/// is the thread that crashed.
+const STOP_WAIT_MS: usize = 50;
+
+/// Ring size in words (a power of two).
+pub const CAPACITY: usize = 4096;
+const MASK: usize = CAPACITY - 1;
+
+/// Fill at or below which a parked producer is woken.
+const LOW_WATER: usize = CAPACITY / 2;
+
+static RING: [AtomicUsize; CAPACITY] = [const { AtomicUsize::new(0) }; CAPACITY];
+/// Next word to pop; only the consumer moves it
+static HEAD: AtomicUsize = AtomicUsize::new(0);
+/// Next slot to fill; only the producer moves it
+static TAIL: AtomicUsize = AtomicUsize::new(0);
+static RUNNING: AtomicBool = AtomicBool::new(false);
+static PARKED: AtomicBool = AtomicBool::new(false);
+/// Set while the producer may still write the ring; it clears this on exit
+static PRODUCING: AtomicBool = AtomicBool::new(false);
+static PRODUCER: Mutex<Option<JoinHandle<()>>> = Mutex::new(None);
+
+// Consumer-side counters; single writer, so plain load/store
+static POPS: AtomicU64 = AtomicU64::new(0);
+static UNDERRUNS: AtomicU64 = AtomicU64::new(0);
+static FILL_SUM: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    static CONSUMER: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Ring fill levels seen by the consumer since `start`.
+#[derive(Debug, Clone, Copy)]
+pub struct Stats {
+    pub capacity: usize,
+    /// Draws made while the pipeline ran
+    pub pops: u64,
+    /// Draws that found the ring empty and fell back to a direct draw
+    pub underruns: u64,
+    /// Average fill seen per draw, as a fraction of `capacity`
+    pub mean_fill: f64,
+}
+
+pub fn is_running() -> bool {
+    RUNNING.load(Ordering::Relaxed)
+}
+
+/// Start the producer and serve the calling thread from the ring.
+/// Returns false if a pipeline is already running.
+pub fn start() -> bool {
+    if RUNNING.swap(true, Ordering::AcqRel) {
+        return false;
+    }
+    // First draw here, not on the producer: bringing up the urandom pool
+    // may prompt
+    Rand::draw();
+
+    HEAD.store(0, Ordering::Relaxed);
+    TAIL.store(0, Ordering::Relaxed);
+    POPS.store(0, Ordering::Relaxed);
+    UNDERRUNS.store(0, Ordering::Relaxed);
+    FILL_SUM.store(0, Ordering::Relaxed);
+    unsafe {
+        libc::mlock(
+            RING.as_ptr() as *const libc::c_void,
+            std::mem::size_of_val(&RING),
+        );
+    }
+
+    PRODUCING.store(true, Ordering::Release);
+    let handle = thread::spawn(produce);
+    if let Ok(mut producer) = PRODUCER.lock() {
+        *producer = Some(handle);
+    }
+    CONSUMER.with(|c| c.set(true));
+    true
+}
+
+/// Stop the producer, wipe the ring, and go back to direct draws.
+pub fn stop() {
+    if !RUNNING.swap(false, Ordering::AcqRel) {
+        return;
+    }
+    CONSUMER.with(|c| c.set(false));
+    let handle = PRODUCER.lock().ok().and_then(|mut p| p.take());
+    if let Some(handle) = handle {
+        handle.thread().unpark();
+        let _ = handle.join();
+    }
+    wipe();
+    unsafe {
+        libc::munlock(
+            RING.as_ptr() as *const libc::c_void,
+            std::mem::size_of_val(&RING),
+        );
+    }
+}
+
+/// Stop producing and zero the ring without joining. For the exit and
+/// crash handlers: takes no locks and doesn't allocate. Waits for the
+/// producer to leave its loop first, so no word lands after the zeroing.
+/// The indices stay put; `start` resets them.
+pub fn wipe() {
+    RUNNING.store(false, Ordering::Release);
+    let nap = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 1_000_000,
+    };
+    for _ in 0..STOP_WAIT_MS {
+        if !PRODUCING.load(Ordering::Acquire) {
+            break;
+        }
+        // nanosleep is async-signal-safe
+        unsafe { libc::nanosleep(&nap, std::ptr::null_mut()) };
+    }
+    for slot in &RING {
+        slot.store(0, Ordering::Relaxed);
+    }
+}
+
+pub fn stats() -> Stats {
+    let pops = POPS.load(Ordering::Relaxed);
+    let mean_fill = if pops > 0 {
+        FILL_SUM.load(Ordering::Relaxed) as f64 / pops as f64 / CAPACITY as f64
+    } else {
+        0.0
+    };
+    Stats {
+        capacity: CAPACITY,
+        pops,
+        underruns: UNDERRUNS.load(Ordering::Relaxed),
+        mean_fill,
+    }
+}
+
+/// The next prefetched word, or None if this thread isn't the consumer or
+/// the ring is empty.
+#[inline(always)]
+pub(super) fn pop() -> Option<usize> {
+    if !CONSUMER.with(Cell::get) {
+        return None;
+    }
+    let head = HEAD.load(Ordering::Relaxed);
+    let fill = TAIL.load(Ordering::Acquire).wrapping_sub(head);
+    bump(&POPS, 1);
+    bump(&FILL_SUM, fill as u64);
+
+    if fill <= LOW_WATER && PARKED.load(Ordering::Relaxed) && PARKED.swap(false, Ordering::AcqRel) {
+        wake();
+    }
+    if fill == 0 {
+        bump(&UNDERRUNS, 1);
+        return None;
+    }
+
+    let slot = &RING[head & MASK];
+    let word = slot.load(Ordering::Relaxed);
+    slot.store(0, Ordering::Relaxed);
+    HEAD.store(head.wrapping_add(1), Ordering::Release);
+    Some(word)
+}
+
+#[inline(always)]
+fn bump(counter: &AtomicU64, n: u64) {
+    counter.store(counter.load(Ordering::Relaxed) + n, Ordering::Relaxed);
+}
+
+#[cold]
+fn wake() {
+    if let Ok(producer) = PRODUCER.lock()
+        && let Some(handle) = producer.as_ref()
+    {
+        handle.thread().unpark();
+    }
+}
+
+/// Producer loop: fill the ring, park while it is full.
+fn produce() {
+    while RUNNING.load(Ordering::Acquire) {
+        let tail = TAIL.load(Ordering::Relaxed);
+        if tail.wrapping_sub(HEAD.load(Ordering::Acquire)) >= CAPACITY {
+            PARKED.store(true, Ordering::Release);
+            // The timeout covers a wakeup lost between the check and park
+            thread::park_timeout(Duration::from_millis(1));
+            PARKED.store(false, Ordering::Release);
+            continue;
+        }
+        RING[tail & MASK].store(Rand::draw(), Ordering::Relaxed);
+        TAIL.store(tail.wrapping_add(1), Ordering::Release);
+    }
+    PRODUCING.store(false, Ordering::Release);
+    crate::session::flush_thread();
+    super::zeroize_state();
+}
@@ -25,6 +25,9 @@ static ACTIVE: AtomicBool = AtomicBool::new(false);
 static DECLINED: AtomicBool = AtomicBool::new(false);
 static LAP_OFFSET: AtomicUsize = AtomicUsize::new(0);
 static SHUTDOWN: AtomicBool = AtomicBool::new(false);
+/// Set by the refresh thread when /dev/urandom stops reading; the next
+/// draw fails over.
+static FAILED: AtomicBool = AtomicBool::new(false);
 
 // =============================================================================
 // Public API
@@ -48,24 +51,67 @@ pub fn enable() -> bool {
     if !is_available() {
         return false;
     }
-    REQUESTED.store(true, Ordering::Release);
+    super::hwrng::release();
+    if !REQUESTED.swap(true, Ordering::AcqRel) {
+        crate::session::count_switch();
+    }
     true
 }
 
 pub fn disable() {
+    if REQUESTED.swap(false, Ordering::AcqRel) {
+        crate::session::count_switch();
+    }
+    shutdown()
+}
+
+/// Deselect without counting a switch: another pooled source is taking
+/// over, and that one counts it.
+pub(super) fn release() {
     REQUESTED.store(false, Ordering::Release);
     shutdown()
 }
 
+/// Select the pool in place of a source that failed mid-run. Filled
+/// here rather than on the next draw, so generation threads don't race
+/// to fill it.
+pub(super) fn take_over() -> Result<(), String> {
+    if !is_available() {
+        return Err("/dev/urandom not available".to_string());
+    }
+    init()?;
+    if !REQUESTED.swap(true, Ordering::AcqRel) {
+        crate::session::count_switch();
+    }
+    Ok(())
+}
+
+/// Stop routing draws here, leaving the pool to the run's shutdown:
+/// threads may still be reading it.
+pub(super) fn deselect() {
+    REQUESTED.store(false, Ordering::Release);
+}
+
 /// Returns a random u64 from the pool. `hint` (RNG state) scrambles the
 /// read position so the access pattern is unpredictable.
 /// On first call, allocates pool, fills from /dev/urandom, starts refresh thread.
+/// A pool that can't be had, or stops refreshing, fails over.
 #[inline(always)]
 pub fn rand(hint: usize) -> u64 {
-    if !ACTIVE.load(Ordering::Relaxed)
-        && (!REQUESTED.load(Ordering::Relaxed) || DECLINED.load(Ordering::Relaxed) || !init())
-    {
-        return 0;
+    if !ACTIVE.load(Ordering::Relaxed) {
+        if !REQUESTED.load(Ordering::Relaxed) {
+            return 0;
+        }
+        if let Err(e) = init() {
+            return super::failover::fail_over(super::failover::Source::Urandom, &e, hint);
+        }
+    }
+    if FAILED.load(Ordering::Relaxed) {
+        return super::failover::fail_over(
+            super::failover::Source::Urandom,
+            "/dev/urandom stopped reading",
+            hint,
+        );
     }
 
     let p = READ_POS.fetch_add(8, Ordering::Relaxed);
@@ -78,10 +124,16 @@ pub fn rand(hint: usize) -> u64 {
 
     let pos = p.wrapping_add(LAP_OFFSET.load(Ordering::Relaxed)) & POOL_MASK & !7;
 
+    crate::session::count_raw(super::failover::Source::Urandom);
     unsafe { std::ptr::read_unaligned(POOL.add(pos) as *const u64) }
 }
 
 /// Emergency zero for signal handlers - minimal, async-signal-safe.
+///
+/// # Safety
+///
+/// Only for exit paths: the pool is wiped in place while other threads
+/// may still be reading it.
 #[inline(never)]
 pub unsafe fn emergency_zero() {
     unsafe {
@@ -103,13 +155,14 @@ pub unsafe fn emergency_zero() {
 /// Allocate pool, fill from /dev/urandom, mlock, and start refresh thread.
 #[cold]
 #[inline(never)]
-fn init() -> bool {
+fn init() -> Result<(), String> {
     if ACTIVE.load(Ordering::Acquire) {
-        return true;
+        return Ok(());
     }
     if DECLINED.load(Ordering::Acquire) {
-        return false;
+        return Err("pool declined after mlock failed".to_string());
     }
+    let mut file = File::open("/dev/urandom").map_err(|e| format!("/dev/urandom: {}", e))?;
 
     let layout =
         std::alloc::Layout::from_size_align(POOL_SIZE, 4096).expect("invalid layout constants");
@@ -127,27 +180,29 @@ fn init() -> bool {
         if !prompts::mlock_continue_prompt() {
             unsafe { std::alloc::dealloc(pool_ptr, layout) };
             DECLINED.store(true, Ordering::Release);
-            return false;
+            return Err("pool declined after mlock failed".to_string());
         }
     }
 
-    let mut file = File::open("/dev/urandom").expect("urand: failed to open /dev/urandom");
-    unsafe {
-        file.read_exact(std::slice::from_raw_parts_mut(pool_ptr, POOL_SIZE))
-            .expect("urand: failed to read from /dev/urandom");
-        POOL = pool_ptr;
+    let pool = unsafe { std::slice::from_raw_parts_mut(pool_ptr, POOL_SIZE) };
+    if let Err(e) = file.read_exact(pool) {
+        pool.zeroize();
+        unsafe {
+            libc::munlock(pool_ptr as *const libc::c_void, POOL_SIZE);
+            std::alloc::dealloc(pool_ptr, layout);
+        }
+        return Err(format!("/dev/urandom: {}", e));
     }
+    unsafe { POOL = pool_ptr };
+    crate::session::count_pool_read(super::failover::Source::Urandom, POOL_SIZE);
 
     READ_POS.store(0, Ordering::Release);
+    FAILED.store(false, Ordering::Release);
     SHUTDOWN.store(false, Ordering::Release);
     ACTIVE.store(true, Ordering::Release);
 
     // Start background refresh thread
-    thread::spawn(|| {
-        let mut file = match File::open("/dev/urandom") {
-            Ok(f) => f,
-            Err(_) => return,
-        };
+    thread::spawn(move || {
         let mut write_pos = 0usize;
 
         while !SHUTDOWN.load(Ordering::Relaxed) {
@@ -157,14 +212,18 @@ fn init() -> bool {
                     break;
                 }
                 let slice = std::slice::from_raw_parts_mut(ptr.add(write_pos), CHUNK_SIZE);
-                let _ = file.read_exact(slice);
+                if file.read_exact(slice).is_err() {
+                    FAILED.store(true, Ordering::Release);
+                    break;
+                }
             }
+            crate::session::count_pool_read(super::failover::Source::Urandom, CHUNK_SIZE);
             write_pos = (write_pos + CHUNK_SIZE) & POOL_MASK;
             thread::sleep(Duration::from_millis(100));
         }
     });
 
-    true
+    Ok(())
 }
 
 /// Kill refresh thread, zeroize and deallocate pool. Preserves the user's
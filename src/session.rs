@@ -0,0 +1,218 @@
+//! Per-session counters: passwords generated, random bytes drawn,
+//! entropy source switches, raw entropy per backend, and time spent. Kept
+//! in memory only, shown by the menu's stats screen and, with
+//! `--session-stats`, on stderr at exit. A signal's exit prints it from
+//! the exit watcher thread (`exits::watch_signals`), never from the
+//! handler.
+//!
+//! Passwords and draws are counted on the hot path, so each thread keeps
+//! its own counts; generation threads fold theirs in with `flush_thread`
+//! before they end.
+
+use std::cell::Cell;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Instant;
+
+use crate::rand::failover::Source;
+use crate::terminal::format_number;
+
+static STARTED: OnceLock<Instant> = OnceLock::new();
+static PASSWORDS: AtomicU64 = AtomicU64::new(0);
+static WORDS: AtomicU64 = AtomicU64::new(0);
+static SWITCHES: AtomicU64 = AtomicU64::new(0);
+static REPORT: AtomicBool = AtomicBool::new(false);
+/// Raw 64-bit entropy reads per backend, indexed by `Source`
+static RAW_READS: [AtomicU64; 3] = [const { AtomicU64::new(0) }; 3];
+/// Bytes the pooled backends read from their device into the pool
+static POOL_BYTES: [AtomicU64; 3] = [const { AtomicU64::new(0) }; 3];
+
+thread_local! {
+    static LOCAL_PASSWORDS: Cell<u64> = const { Cell::new(0) };
+    static LOCAL_WORDS: Cell<u64> = const { Cell::new(0) };
+    static LOCAL_RAW: [Cell<u64>; 3] = const { [Cell::new(0), Cell::new(0), Cell::new(0)] };
+}
+
+/// Start the session clock and turn on the exit report for
+/// `--session-stats`, which may sit anywhere on the command line.
+pub fn init(args: &[String]) {
+    STARTED.get_or_init(Instant::now);
+    if args.iter().any(|a| a == "--session-stats") {
+        REPORT.store(true, Ordering::SeqCst);
+        crate::exits::watch_signals();
+    }
+}
+
+/// Whether the summary is still to be printed. Async-signal-safe.
+pub fn reporting() -> bool {
+    REPORT.load(Ordering::SeqCst)
+}
+
+#[inline(always)]
+pub fn count_password() {
+    LOCAL_PASSWORDS.with(|c| c.set(c.get() + 1));
+}
+
+/// One 8-byte word handed out by the RNG.
+#[inline(always)]
+pub fn count_word() {
+    LOCAL_WORDS.with(|c| c.set(c.get() + 1));
+}
+
+pub fn count_switch() {
+    SWITCHES.fetch_add(1, Ordering::Relaxed);
+}
+
+/// One raw 64-bit read from `source`, before mixing.
+#[inline(always)]
+pub fn count_raw(source: Source) {
+    LOCAL_RAW.with(|c| {
+        let c = &c[source as usize];
+        c.set(c.get() + 1)
+    });
+}
+
+/// `bytes` read from `source`'s device into its pool. Called by the pool
+/// fill and refresh, off the hot path.
+pub fn count_pool_read(source: Source, bytes: usize) {
+    POOL_BYTES[source as usize].fetch_add(bytes as u64, Ordering::Relaxed);
+}
+
+/// Fold this thread's counts into the session's. Generation threads call
+/// this before they exit.
+pub fn flush_thread() {
+    let _ = LOCAL_PASSWORDS.try_with(|c| PASSWORDS.fetch_add(c.replace(0), Ordering::Relaxed));
+    let _ = LOCAL_WORDS.try_with(|c| WORDS.fetch_add(c.replace(0), Ordering::Relaxed));
+    let _ = LOCAL_RAW.try_with(|cells| {
+        for (c, total) in cells.iter().zip(&RAW_READS) {
+            total.fetch_add(c.replace(0), Ordering::Relaxed);
+        }
+    });
+}
+
+/// Raw entropy one backend contributed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RawUse {
+    /// 64-bit reads drawn from the backend
+    pub reads: u64,
+    /// Bytes read from the device into the pool (pooled backends)
+    pub pool_bytes: u64,
+}
+
+impl RawUse {
+    pub fn bits_drawn(&self) -> u64 {
+        self.reads.saturating_mul(64)
+    }
+
+    pub fn bits_read(&self) -> u64 {
+        self.pool_bytes.saturating_mul(8)
+    }
+
+    /// Bits drawn per bit read into the pool: above 1, pool bytes were
+    /// handed out more than once before the refresh overwrote them.
+    pub fn reuse(&self) -> Option<f64> {
+        (self.pool_bytes > 0).then(|| self.bits_drawn() as f64 / self.bits_read() as f64)
+    }
+}
+
+/// Raw entropy per backend so far, indexed by `Source`. Counts from
+/// threads still running are left out.
+pub fn raw_use() -> [RawUse; 3] {
+    flush_thread();
+    std::array::from_fn(|i| RawUse {
+        reads: RAW_READS[i].load(Ordering::Relaxed),
+        pool_bytes: POOL_BYTES[i].load(Ordering::Relaxed),
+    })
+}
+
+/// Raw entropy used since `start`, a `raw_use` snapshot: one run's share.
+pub fn raw_use_since(start: &[RawUse; 3]) -> [RawUse; 3] {
+    let now = raw_use();
+    std::array::from_fn(|i| RawUse {
+        reads: now[i].reads - start[i].reads,
+        pool_bytes: now[i].pool_bytes - start[i].pool_bytes,
+    })
+}
+
+/// `12,800 bits (200 reads)`, with the pool's side for pooled backends.
+fn describe_raw(usage: &RawUse) -> String {
+    let mut text = format!(
+        "{} bits ({} reads)",
+        format_number(usage.bits_drawn() as usize),
+        format_number(usage.reads as usize)
+    );
+    if let Some(reuse) = usage.reuse() {
+        text.push_str(&format!(
+            ", {} bits read into the pool, {:.2} drawn per bit read",
+            format_number(usage.bits_read() as usize),
+            reuse
+        ));
+    }
+    text
+}
+
+/// The session so far as label and value rows, for the stats screen and
+/// the exit report. Counts from threads still running are left out.
+pub fn rows() -> Vec<(&'static str, String)> {
+    flush_thread();
+    let wall = STARTED.get().map_or(0.0, |t| t.elapsed().as_secs_f64());
+    let words = WORDS.load(Ordering::Relaxed);
+    let mut rows = vec![
+        ("Time", format!("{:.1}s (CPU {:.1}s)", wall, cpu_secs())),
+        (
+            "Passwords generated",
+            format_number(PASSWORDS.load(Ordering::Relaxed) as usize),
+        ),
+        (
+            "Random bytes drawn",
+            format!(
+                "{} ({} words)",
+                format_number(words.saturating_mul(8) as usize),
+                format_number(words as usize)
+            ),
+        ),
+        ("Entropy source", crate::rand::entropy_source().to_string()),
+        (
+            "Source switches",
+            SWITCHES.load(Ordering::Relaxed).to_string(),
+        ),
+    ];
+    for (source, usage) in Source::ALL.iter().zip(raw_use()) {
+        if usage.reads > 0 || usage.pool_bytes > 0 {
+            let label = match source {
+                Source::Counter => "Raw entropy, counter",
+                Source::Urandom => "Raw entropy, urandom",
+                Source::Hwrng => "Raw entropy, hwrng",
+            };
+            rows.push((label, describe_raw(&usage)));
+        }
+    }
+    rows
+}
+
+/// User plus system CPU time of the process.
+fn cpu_secs() -> f64 {
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) } != 0 {
+        return 0.0;
+    }
+    let secs = |t: libc::timeval| t.tv_sec as f64 + t.tv_usec as f64 / 1e6;
+    secs(usage.ru_utime) + secs(usage.ru_stime)
+}
+
+/// Print the session on stderr when `--session-stats` asked, once: when
+/// `main` returns, from atexit after `process::exit`, or from the exit
+/// watcher after a signal. An exit from the signal handler comes after
+/// the watcher printed it, so this returns on the atomic there.
+pub fn report() {
+    if !REPORT.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let rows = rows();
+    let width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+    let mut report = String::from("Session:\n");
+    for (label, value) in rows {
+        report.push_str(&format!("  {:<width$}  {}\n", label, value));
+    }
+    eprint!("{}", report);
+}
@@ -0,0 +1,36 @@
+//! Global ephemeral mode: the run must not touch `~/.config/randpass`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Global ephemeral flag - settings are read if present, never written
+static EPHEMERAL: AtomicBool = AtomicBool::new(false);
+
+/// Environment variable that enables ephemeral mode when set to `1`
+pub const ENV_VAR: &str = "RANDPASS_EPHEMERAL";
+
+/// Enable ephemeral mode (no config directory or file is created or changed)
+pub fn set(ephemeral: bool) {
+    EPHEMERAL.store(ephemeral, Ordering::SeqCst);
+}
+
+/// Check if ephemeral mode is enabled
+pub fn enabled() -> bool {
+    EPHEMERAL.load(Ordering::Relaxed)
+}
+
+/// Enable ephemeral mode from `--no-save` or `RANDPASS_EPHEMERAL=1`. Runs
+/// before anything loads settings.
+pub fn init(args: &[String]) {
+    let from_env = std::env::var(ENV_VAR).is_ok_and(|v| v == "1");
+    if from_env || args.iter().any(|a| a == "--no-save") {
+        set(true);
+    }
+}
+
+/// Error returned by writes attempted in ephemeral mode
+pub fn write_refused() -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::PermissionDenied,
+        "ephemeral mode (--no-save / RANDPASS_EPHEMERAL=1), settings not written",
+    )
+}
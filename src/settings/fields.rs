@@ -0,0 +1,190 @@
+//! Named access to persisted settings fields.
+//!
+//! The settings file stores each field as `key=value`; this registry is the
+//! single list of those keys and how each one is formatted and parsed.
+
+use super::Settings;
+use crate::pass::passphrase::WordSep;
+
+/// Persisted fields, in file order.
+pub const FIELDS: &[&str] = &[
+    "pass_length",
+    "number_of_passwords",
+    "skip_countdown",
+    "countdown_secs",
+    "view_chars_str",
+    "special_chars",
+    "randomize_seed_chars",
+    "special_char_density",
+    "numeric_char_density",
+    "lowercase_char_density",
+    "uppercase_char_density",
+    "output_file_path",
+    "output_to_terminal",
+    "cli_command",
+    "wordlist",
+    "passphrase_words",
+    "word_sep",
+    "policy_file",
+    "min_entropy_bits",
+    "audit_log",
+    "pre_generate",
+    "post_generate",
+    "post_file",
+    "rng",
+    "hwrng_device",
+    "hwrng_read_size",
+    "warmup_discard",
+];
+
+/// Fields that command-line runs take from the settings file without
+/// `--saved`; the others start from the CLI defaults.
+pub const CLI_FIELDS: &[&str] = &[
+    "cli_command",
+    "wordlist",
+    "word_sep",
+    "policy_file",
+    "min_entropy_bits",
+    "audit_log",
+    "pre_generate",
+    "post_generate",
+    "post_file",
+    "rng",
+    "hwrng_device",
+    "hwrng_read_size",
+    "warmup_discard",
+];
+
+/// How a field's value is typed, for structured output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Number,
+    Bool,
+    Text,
+}
+
+pub fn field_kind(key: &str) -> Option<FieldKind> {
+    match key {
+        "skip_countdown" | "view_chars_str" | "output_to_terminal" | "audit_log" => {
+            Some(FieldKind::Bool)
+        }
+        "special_chars" | "output_file_path" | "cli_command" | "wordlist" | "word_sep"
+        | "policy_file" | "pre_generate" | "post_generate" | "post_file" | "rng"
+        | "hwrng_device" => Some(FieldKind::Text),
+        k if FIELDS.contains(&k) => Some(FieldKind::Number),
+        _ => None,
+    }
+}
+
+fn parse_number(key: &str, value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} expects a number, got '{}'", key, value))
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} expects true or false, got '{}'", key, value))
+}
+
+impl Settings {
+    /// CLI defaults, with the `CLI_FIELDS` taken from `self`.
+    pub fn for_cli(&self) -> Settings {
+        let mut settings = Settings {
+            number_of_passwords: 1, // CLI default, not interactive default (19)
+            countdown_secs: 0,      // Only with --countdown
+            ..Default::default()
+        };
+        for key in CLI_FIELDS {
+            if let Some(value) = self.get_field(key) {
+                // Stored forms parse back; a failure keeps the default
+                let _ = settings.set_field(key, &value);
+            }
+        }
+        settings
+    }
+
+    /// Value of a persisted field as stored in the settings file.
+    pub fn get_field(&self, key: &str) -> Option<String> {
+        let value = match key {
+            "pass_length" => self.pass_length.to_string(),
+            "number_of_passwords" => self.number_of_passwords.to_string(),
+            "skip_countdown" => self.skip_countdown.to_string(),
+            "countdown_secs" => self.countdown_secs.to_string(),
+            "view_chars_str" => self.view_chars_str.to_string(),
+            "special_chars" => String::from_utf8_lossy(&self.special_chars).into_owned(),
+            "randomize_seed_chars" => self.randomize_seed_chars.to_string(),
+            "special_char_density" => self.special_char_density.to_string(),
+            "numeric_char_density" => self.numeric_char_density.to_string(),
+            "lowercase_char_density" => self.lowercase_char_density.to_string(),
+            "uppercase_char_density" => self.uppercase_char_density.to_string(),
+            "output_file_path" => self.output_file_path.clone(),
+            "output_to_terminal" => self.output_to_terminal.to_string(),
+            "cli_command" => self.cli_command.clone(),
+            "wordlist" => self.wordlist.clone(),
+            "passphrase_words" => self.passphrase_words.to_string(),
+            "word_sep" => self.word_sep.to_string(),
+            "policy_file" => self.policy_file.clone(),
+            "min_entropy_bits" => self.min_entropy_bits.to_string(),
+            "audit_log" => self.audit_log.to_string(),
+            "pre_generate" => self.pre_generate.clone(),
+            "post_generate" => self.post_generate.clone(),
+            "post_file" => self.post_file.clone(),
+            "rng" => self.rng.clone(),
+            "hwrng_device" => self.hwrng_device.clone(),
+            "hwrng_read_size" => self.hwrng_read_size.to_string(),
+            "warmup_discard" => self.warmup_discard.to_string(),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Set a persisted field from its stored form. Leaves the field
+    /// unchanged on error.
+    pub fn set_field(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "pass_length" => self.pass_length = parse_number(key, value)?,
+            "number_of_passwords" => self.number_of_passwords = parse_number(key, value)?,
+            "skip_countdown" => self.skip_countdown = parse_bool(key, value)?,
+            "countdown_secs" => self.countdown_secs = parse_number(key, value)?,
+            "view_chars_str" => self.view_chars_str = parse_bool(key, value)?,
+            "special_chars" => self.special_chars = value.bytes().collect(),
+            "randomize_seed_chars" => self.randomize_seed_chars = parse_number(key, value)?,
+            "special_char_density" => self.special_char_density = parse_number(key, value)?,
+            "numeric_char_density" => self.numeric_char_density = parse_number(key, value)?,
+            "lowercase_char_density" => self.lowercase_char_density = parse_number(key, value)?,
+            "uppercase_char_density" => self.uppercase_char_density = parse_number(key, value)?,
+            "output_file_path" => self.output_file_path = value.to_string(),
+            "output_to_terminal" => self.output_to_terminal = parse_bool(key, value)?,
+            "cli_command" => self.cli_command = value.to_string(),
+            "wordlist" => self.wordlist = value.to_string(),
+            "passphrase_words" => self.passphrase_words = parse_number(key, value)?,
+            "word_sep" => {
+                self.word_sep = WordSep::parse(value).ok_or_else(|| {
+                    format!(
+                        "{} expects one character, none or camel, got '{}'",
+                        key, value
+                    )
+                })?
+            }
+            "policy_file" => self.policy_file = value.to_string(),
+            "min_entropy_bits" => self.min_entropy_bits = parse_number(key, value)?,
+            "audit_log" => self.audit_log = parse_bool(key, value)?,
+            "pre_generate" => self.pre_generate = value.to_string(),
+            "post_generate" => self.post_generate = value.to_string(),
+            "post_file" => self.post_file = value.to_string(),
+            "rng" => {
+                if !value.is_empty() {
+                    crate::rand::failover::parse(value).map_err(|e| format!("{}: {}", key, e))?;
+                }
+                self.rng = value.to_string()
+            }
+            "hwrng_device" => self.hwrng_device = value.to_string(),
+            "hwrng_read_size" => self.hwrng_read_size = parse_number(key, value)?,
+            "warmup_discard" => self.warmup_discard = parse_number(key, value)?,
+            _ => return Err(format!("Unknown setting: {}", key)),
+        }
+        Ok(())
+    }
+}
@@ -1,107 +1,460 @@
 //! Settings file persistence.
+//!
+//! Since version 2 the file is a `version=N` header followed by one
+//! `key=value` line per field in `fields::FIELDS`. Since version 3 values
+//! are backslash-escaped (`\\`, `\n`, `\r`, `\t`, `\xHH`), so any string
+//! round-trips. Older files are upgraded through `MIGRATIONS` on load and
+//! rewritten in the current format.
+//!
+//! Every read and write happens under an advisory lock. A change to one
+//! field goes through `update`, which holds the lock from reading the
+//! file to writing it back, so two processes changing different fields
+//! both keep their change.
 
 use std::env;
 use std::fs::OpenOptions;
-use std::io::{BufRead, BufReader, Write};
+use std::io::Write;
+use std::os::fd::AsRawFd;
 use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use super::Settings;
+use super::ephemeral;
+use super::fields::FIELDS;
+use crate::cli::prompts;
 
+/// Current file format version.
+pub const VERSION: u32 = 3;
+
+/// Decoded but not yet applied file contents, in file order.
+type Fields = Vec<(String, String)>;
+
+/// `MIGRATIONS[n - 1]` upgrades a version `n` file to version `n + 1`.
+/// Append a step whenever the format or a field's meaning changes.
+const MIGRATIONS: &[fn(Fields) -> Fields] = &[v1_to_v2, v2_to_v3];
+
+/// Field values as this process last read or wrote them; `save_changes`
+/// writes only what differs from these.
+static LAST_SEEN: Mutex<Option<Fields>> = Mutex::new(None);
+
+/// Write the settings file atomically: concurrent savers are serialized by
+/// an advisory lock, and each writes a temp file that is renamed over the
+/// old one, so readers never see a partial file.
 pub fn save(settings: &Settings) -> std::io::Result<()> {
-    let mut file = OpenOptions::new()
+    if ephemeral::enabled() {
+        return Err(ephemeral::write_refused());
+    }
+    let path = get_path();
+    let _lock = lock(&path)?;
+    refuse_newer(&path)?;
+    write(settings, &path)
+}
+
+/// Read the file, apply `change` and write the result back, holding the
+/// lock throughout. Returns the settings as written.
+pub fn update(
+    change: impl FnOnce(&mut Settings) -> Result<(), String>,
+) -> Result<Settings, String> {
+    if ephemeral::enabled() {
+        return Err(ephemeral::write_refused().to_string());
+    }
+    let path = get_path();
+    create_parent(&path).map_err(|e| format!("Settings: {}", e))?;
+    let _lock = lock(&path).map_err(|e| format!("Settings: {}", e))?;
+
+    refuse_newer(&path).map_err(|e| format!("Settings: {}", e))?;
+    let mut settings = Settings::default();
+    read(&mut settings, &path).map_err(|e| format!("Settings: {}", e))?;
+    change(&mut settings)?;
+    write(&settings, &path).map_err(|e| format!("Failed to save settings: {}", e))?;
+    Ok(settings)
+}
+
+/// Save the fields of `settings` changed since this process last read or
+/// wrote the file, keeping what another process saved in the meantime to
+/// the rest. Returns the settings as written.
+pub fn save_changes(settings: &Settings) -> Result<Settings, String> {
+    let seen = LAST_SEEN.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    update(|file| {
+        for key in FIELDS {
+            let value = settings.get_field(key).unwrap_or_default();
+            let unchanged = seen
+                .as_ref()
+                .and_then(|seen| seen.iter().find(|(k, _)| k == key))
+                .is_some_and(|(_, v)| *v == value);
+            if !unchanged {
+                file.set_field(key, &value)?;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Err when the file at `path` is a newer version than this binary
+/// writes: saving would drop every key it doesn't know. The caller holds
+/// the lock.
+fn refuse_newer(path: &str) -> std::io::Result<()> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    match decode(&text) {
+        Some((version, _)) if version > VERSION => Err(std::io::Error::other(format!(
+            "{} is version {}, written by a newer randpass (this one writes {}); not saving over it",
+            path, version, VERSION
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Write `settings` to `path` through a temp file and rename. The caller
+/// holds the lock.
+fn write(settings: &Settings, path: &str) -> std::io::Result<()> {
+    let tmp_path = format!("{}.tmp.{}", path, std::process::id());
+    let result = (|| {
+        let mut tmp = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&tmp_path)?;
+        // Keep whatever permissions the user gave the old file
+        if let Ok(meta) = std::fs::metadata(path) {
+            tmp.set_permissions(meta.permissions())?;
+        }
+        tmp.write_all(encode(settings).as_bytes())?;
+        tmp.sync_all()?;
+        std::fs::rename(&tmp_path, path)
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    } else {
+        seen(settings);
+    }
+    result
+}
+
+/// Remember `settings` as what the file holds.
+fn seen(settings: &Settings) {
+    let fields = FIELDS
+        .iter()
+        .map(|key| (key.to_string(), settings.get_field(key).unwrap_or_default()))
+        .collect();
+    *LAST_SEEN.lock().unwrap_or_else(|e| e.into_inner()) = Some(fields);
+}
+
+/// Exclusive advisory lock on `<path>.lock`, released when the file drops.
+/// A separate lock file is used because the rename replaces the settings
+/// file's inode.
+fn lock(path: &str) -> std::io::Result<std::fs::File> {
+    let file = OpenOptions::new()
         .write(true)
         .create(true)
-        .truncate(true)
-        .open(get_path())?;
+        .truncate(false)
+        .open(format!("{}.lock", path))?;
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(file)
+}
 
-    let special_chars_str = settings
-        .special_chars
-        .iter()
-        .map(|&b| match b {
-            b',' => "|,".to_string(),
-            b'|' => "||".to_string(),
-            _ => (b as char).to_string(),
-        })
-        .collect::<Vec<String>>()
-        .join("");
-
-    let data = format!(
-        "{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
-        settings.pass_length,
-        settings.number_of_passwords,
-        settings.skip_countdown,
-        settings.view_chars_str,
-        special_chars_str,
-        settings.randomize_seed_chars,
-        settings.special_char_density,
-        settings.numeric_char_density,
-        settings.lowercase_char_density,
-        settings.uppercase_char_density,
-        settings.output_file_path,
-        settings.output_to_terminal,
-        settings.cli_command
-    );
-
-    file.write_all(data.as_bytes())?;
-    Ok(())
+fn create_parent(path: &str) -> std::io::Result<()> {
+    match Path::new(path).parent() {
+        Some(parent) if !parent.exists() => std::fs::create_dir_all(parent),
+        _ => Ok(()),
+    }
 }
 
 pub fn load(settings: &mut Settings) -> std::io::Result<()> {
     let path = get_path();
-    if !Path::new(&path).exists()
-        && let Some(parent) = Path::new(&path).parent()
-        && let Err(e) = std::fs::create_dir_all(parent)
-    {
+    if ephemeral::enabled() {
+        return load_read_only(settings, &path);
+    }
+    if let Err(e) = create_parent(&path) {
         eprintln!("Failed to create directory for settings file: {}", e);
         return Ok(());
     }
+    let _lock = lock(&path)?;
+    if read(settings, &path)? {
+        write(settings, &path)?;
+    }
+    Ok(())
+}
 
-    let file = OpenOptions::new()
-        .read(true)
-        .create(true)
-        .truncate(false)
-        .write(true)
-        .open(&path)?;
+/// Apply the file at `path` to `settings`. Returns true when it should be
+/// rewritten: missing, empty, unreadable (a copy is kept) or an older
+/// version. A newer file is left alone for the newer binary.
+fn read(settings: &mut Settings, path: &str) -> std::io::Result<bool> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
 
-    let mut reader = BufReader::new(file);
-    let mut line = String::new();
-    reader.read_line(&mut line)?;
+    if text.is_empty() {
+        return Ok(true);
+    }
 
-    if line.is_empty() {
-        save(settings)?;
-    } else {
-        let parts = split_escaped(line.trim(), ',');
-
-        if parts.len() == 13 {
-            settings.pass_length = parts[0].parse().unwrap_or(settings.pass_length);
-            settings.number_of_passwords = parts[1].parse().unwrap_or(settings.number_of_passwords);
-            settings.skip_countdown = parts[2].parse().unwrap_or(settings.skip_countdown);
-            settings.view_chars_str = parts[3].parse().unwrap_or(settings.view_chars_str);
-            settings.special_chars = parts[4].bytes().collect();
-            settings.randomize_seed_chars =
-                parts[5].parse().unwrap_or(settings.randomize_seed_chars);
-            settings.special_char_density =
-                parts[6].parse().unwrap_or(settings.special_char_density);
-            settings.numeric_char_density =
-                parts[7].parse().unwrap_or(settings.numeric_char_density);
-            settings.lowercase_char_density =
-                parts[8].parse().unwrap_or(settings.lowercase_char_density);
-            settings.uppercase_char_density =
-                parts[9].parse().unwrap_or(settings.uppercase_char_density);
-            settings.output_file_path = parts[10].to_string();
-            settings.output_to_terminal = parts[11].parse().unwrap_or(settings.output_to_terminal);
-            settings.cli_command = parts[12].parse().unwrap_or(settings.cli_command.clone());
-        } else {
-            save(settings)?;
-            load(settings)?;
+    let Some((version, fields)) = decode(&text) else {
+        // Unreadable: keep a copy rather than silently losing it
+        let backup = format!("{}.bak", path);
+        std::fs::write(&backup, &text)?;
+        eprintln!(
+            "Settings file unreadable, reset to defaults (old copy: {})",
+            backup
+        );
+        return Ok(true);
+    };
+
+    apply(settings, &upgrade(version, fields));
+    seen(settings);
+    Ok(version < VERSION)
+}
+
+/// Ephemeral load: apply an existing file (any known version) without
+/// creating, migrating, or backing it up. Missing or unreadable files
+/// leave the defaults in place.
+fn load_read_only(settings: &mut Settings, path: &str) -> std::io::Result<()> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    if let Some((version, fields)) = decode(&text) {
+        apply(settings, &upgrade(version, fields));
+    }
+    Ok(())
+}
+
+/// Run the migrations from `version` up to the current version.
+fn upgrade(version: u32, mut fields: Fields) -> Fields {
+    if version < VERSION {
+        for migrate in &MIGRATIONS[(version - 1) as usize..] {
+            fields = migrate(fields);
         }
     }
+    fields
+}
 
-    Ok(())
+/// Current-format file contents for `settings`.
+fn encode(settings: &Settings) -> String {
+    let mut data = format!("version={}\n", VERSION);
+    for key in FIELDS {
+        let value = settings.get_field(key).unwrap_or_default();
+        data.push_str(&format!("{}={}\n", key, escape(&value)));
+    }
+    data
+}
+
+/// Split a settings file into its version and fields. Returns None when
+/// the contents match no known version.
+fn decode(text: &str) -> Option<(u32, Fields)> {
+    let mut lines = text.lines();
+    let first = lines.next()?;
+
+    let Some(version) = first.strip_prefix("version=") else {
+        return decode_v1(first);
+    };
+    let version: u32 = version.trim().parse().ok().filter(|&v| v >= 2)?;
+
+    // Version 2 stored values raw
+    let fields = lines
+        .filter_map(|line| line.split_once('='))
+        .map(|(k, v)| {
+            let v = if version >= 3 {
+                unescape(v)
+            } else {
+                v.to_string()
+            };
+            (k.to_string(), v)
+        })
+        .collect();
+    Some((version, fields))
+}
+
+/// Escape a value so it fits on one line and reads back unchanged.
+fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_ascii_control() => out.push_str(&format!("\\x{:02x}", c as u8)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Inverse of `escape`. Malformed escapes are kept literally.
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => out.push('\\'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('t') => out.push('\t'),
+            Some('x') => {
+                let hex: String = chars.clone().take(2).collect();
+                match u8::from_str_radix(&hex, 16) {
+                    Ok(b) if hex.len() == 2 && b.is_ascii() => {
+                        out.push(b as char);
+                        chars.nth(1);
+                    }
+                    _ => out.push_str("\\x"),
+                }
+            }
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Copy known fields into `settings`, then validate the result. Unknown
+/// keys (from a newer version) are skipped; unparsable or out-of-range
+/// values keep the default and are reported once per run.
+fn apply(settings: &mut Settings, fields: &Fields) {
+    let issues = apply_fields(settings, fields);
+    report(&issues);
+}
+
+/// `apply` without reporting. Returns one message per substituted field.
+fn apply_fields(settings: &mut Settings, fields: &Fields) -> Vec<String> {
+    let mut issues = Vec::new();
+    for (key, value) in fields {
+        if FIELDS.contains(&key.as_str())
+            && let Err(e) = settings.set_field(key, value)
+        {
+            let current = settings.get_field(key).unwrap_or_default();
+            issues.push(format!("{}, using {}", e, current));
+        }
+    }
+    issues.extend(settings.validate().iter().map(ToString::to_string));
+    issues
+}
+
+/// State of the settings file on disk, as `load` would see it.
+pub enum FileStatus {
+    Missing,
+    Unreadable,
+    Loaded { version: u32, issues: Vec<String> },
+}
+
+/// Inspect the settings file without creating, migrating, or reporting.
+pub fn inspect() -> std::io::Result<FileStatus> {
+    let text = match std::fs::read_to_string(get_path()) {
+        Ok(text) if text.is_empty() => return Ok(FileStatus::Missing),
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(FileStatus::Missing),
+        Err(e) => return Err(e),
+    };
+    let Some((version, fields)) = decode(&text) else {
+        return Ok(FileStatus::Unreadable);
+    };
+    let mut settings = Settings::default();
+    let issues = apply_fields(&mut settings, &upgrade(version, fields));
+    Ok(FileStatus::Loaded { version, issues })
+}
+
+/// Warn about settings problems, once per process (settings are loaded
+/// several times per run).
+fn report(issues: &[String]) {
+    static REPORTED: AtomicBool = AtomicBool::new(false);
+    if issues.is_empty() || REPORTED.swap(true, Ordering::Relaxed) {
+        return;
+    }
+    for issue in issues {
+        prompts::warn(&format!("Settings: {}", issue));
+    }
+    prompts::warn(&format!(
+        "Fix with `randpass config set` or edit {}",
+        get_path()
+    ));
+}
+
+// =============================================================================
+// Version 1: one line of 13 comma-separated positional fields, `|` escapes
+// =============================================================================
+
+/// Field order of a version 1 line.
+const V1_ORDER: [&str; 13] = [
+    "pass_length",
+    "number_of_passwords",
+    "skip_countdown",
+    "view_chars_str",
+    "special_chars",
+    "randomize_seed_chars",
+    "special_char_density",
+    "numeric_char_density",
+    "lowercase_char_density",
+    "uppercase_char_density",
+    "output_file_path",
+    "output_to_terminal",
+    "cli_command",
+];
+
+/// Version 1 fields keyed by position ("0" to "12").
+fn decode_v1(line: &str) -> Option<(u32, Fields)> {
+    let mut parts = split_escaped(line.trim(), ',');
+    if parts.len() > V1_ORDER.len() {
+        parts = rejoin_v1(parts)?;
+    }
+    if parts.len() != V1_ORDER.len() {
+        return None;
+    }
+    let fields = parts
+        .into_iter()
+        .enumerate()
+        .map(|(i, v)| (i.to_string(), v))
+        .collect();
+    Some((1, fields))
+}
+
+/// Version 1 never escaped commas in `output_file_path` or `cli_command`,
+/// so such lines split into too many parts. Both surround the
+/// `output_to_terminal` bool, which puts the path before the first
+/// `true`/`false` after position 10 and the command after it.
+fn rejoin_v1(parts: Vec<String>) -> Option<Vec<String>> {
+    let flag = (11..parts.len()).find(|&i| parts[i] == "true" || parts[i] == "false")?;
+    let mut fixed = parts[..10].to_vec();
+    fixed.push(parts[10..flag].join(","));
+    fixed.push(parts[flag].clone());
+    fixed.push(parts[flag + 1..].join(","));
+    Some(fixed)
+}
+
+/// Version 2 replaced positions with names.
+fn v1_to_v2(fields: Fields) -> Fields {
+    fields
+        .into_iter()
+        .filter_map(|(pos, value)| {
+            let name = V1_ORDER.get(pos.parse::<usize>().ok()?)?;
+            Some((name.to_string(), value))
+        })
+        .collect()
+}
+
+/// Version 3 escapes values; fields are already decoded, so nothing changes.
+fn v2_to_v3(fields: Fields) -> Fields {
+    fields
 }
 
 #[inline]
-fn get_path() -> String {
+pub fn get_path() -> String {
     let home = env::var("HOME").unwrap_or_else(|_| ".".into());
     format!("{}/.config/randpass/settings", home)
 }
@@ -135,3 +488,174 @@ fn split_escaped(s: &str, delimiter: char) -> Vec<String> {
 
     parts
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `text` decoded, upgraded and applied as `load` would, with the
+    /// version it was written in.
+    fn parse(text: &str) -> (u32, Settings) {
+        let (version, fields) = decode(text).expect("undecodable fixture");
+        let mut settings = Settings::default();
+        apply(&mut settings, &upgrade(version, fields));
+        (version, settings)
+    }
+
+    /// Load `text`, save it in the current format and load that again.
+    /// Returns the first load after checking the second matches it field
+    /// for field.
+    fn round_trip(name: &str, text: &str, version: u32) -> Settings {
+        let (found, loaded) = parse(text);
+        assert_eq!(found, version, "{}", name);
+
+        let saved = encode(&loaded);
+        assert!(saved.starts_with(&format!("version={}\n", VERSION)));
+        let (found, reloaded) = parse(&saved);
+        assert_eq!(found, VERSION, "{}", name);
+        for key in FIELDS {
+            assert_eq!(
+                loaded.get_field(key),
+                reloaded.get_field(key),
+                "{}: {}",
+                name,
+                key
+            );
+        }
+        loaded
+    }
+
+    #[test]
+    fn v1_round_trips() {
+        let s = round_trip(
+            "v1",
+            "20,7,true,false,!@#$%|,,5,1,2,3,4,out/pw.txt,false,-l 20 --hex\n",
+            1,
+        );
+        assert_eq!(s.pass_length, 20);
+        assert_eq!(s.number_of_passwords, 7);
+        assert!(s.skip_countdown);
+        assert!(!s.view_chars_str);
+        assert_eq!(s.special_chars, b"!@#$%,");
+        assert_eq!(s.randomize_seed_chars, 5);
+        assert_eq!(
+            (
+                s.special_char_density,
+                s.numeric_char_density,
+                s.lowercase_char_density,
+                s.uppercase_char_density
+            ),
+            (1, 2, 3, 4)
+        );
+        assert_eq!(s.output_file_path, "out/pw.txt");
+        assert!(!s.output_to_terminal);
+        assert_eq!(s.cli_command, "-l 20 --hex");
+    }
+
+    #[test]
+    fn v1_with_unescaped_commas_round_trips() {
+        let s = round_trip(
+            "v1-commas",
+            "20,7,true,false,!@#,5,1,2,3,4,my,pw.txt,false,--special ,; -l 9\n",
+            1,
+        );
+        assert_eq!(s.special_chars, b"!@#");
+        assert_eq!(s.output_file_path, "my,pw.txt");
+        assert!(!s.output_to_terminal);
+        assert_eq!(s.cli_command, "--special ,; -l 9");
+    }
+
+    #[test]
+    fn v2_round_trips() {
+        // Version 2 stored values raw: a backslash is just a backslash
+        let s = round_trip(
+            "v2",
+            "version=2\npass_length=12\nspecial_chars=\\x-=\ncli_command=--pattern a=b\n",
+            2,
+        );
+        assert_eq!(s.pass_length, 12);
+        assert_eq!(s.special_chars, b"\\x-=");
+        assert_eq!(s.cli_command, "--pattern a=b");
+    }
+
+    #[test]
+    fn v3_round_trips() {
+        let s = round_trip(
+            "v3",
+            "version=3\npass_length=30\ncli_command=-l 5\\n--hex\noutput_file_path=C:\\\\pw\\t.txt\nspecial_chars=äé€,\n",
+            3,
+        );
+        assert_eq!(s.pass_length, 30);
+        assert_eq!(s.cli_command, "-l 5\n--hex");
+        assert_eq!(s.output_file_path, "C:\\pw\t.txt");
+        assert_eq!(s.special_chars, "äé€,".as_bytes());
+    }
+
+    /// Characters the format has to get right, and some that need nothing.
+    const TRICKY: &[char] = &[
+        '\\', '=', '\n', '\r', '\t', ',', '|', '\x01', '\x7f', 'x', '0', ' ', 'é', '€', '😀',
+    ];
+
+    /// `count` seeded strings of up to 12 characters from `TRICKY`.
+    fn samples(count: usize) -> Vec<String> {
+        let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as usize
+        };
+        (0..count)
+            .map(|_| {
+                let len = next() % 13;
+                (0..len).map(|_| TRICKY[next() % TRICKY.len()]).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn escape_round_trips() {
+        for value in samples(5000) {
+            let escaped = escape(&value);
+            assert!(!escaped.contains(['\n', '\r']), "{:?}", escaped);
+            assert_eq!(unescape(&escaped), value, "{:?}", escaped);
+        }
+    }
+
+    #[test]
+    fn save_load_round_trips() {
+        for (n, value) in samples(300).into_iter().enumerate() {
+            let mut settings = Settings::default();
+            for key in ["cli_command", "output_file_path"] {
+                settings.set_field(key, &value).unwrap();
+            }
+            // An empty set falls back to the default on load
+            if !value.is_empty() {
+                settings.set_field("special_chars", &value).unwrap();
+            }
+
+            let (_, loaded) = parse(&encode(&settings));
+            for key in FIELDS {
+                assert_eq!(
+                    settings.get_field(key),
+                    loaded.get_field(key),
+                    "#{} {}",
+                    n,
+                    key
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_keys_and_bad_values_keep_the_default() {
+        let (_, s) = parse("version=2\nfuture_key=1\npass_length=abc\n");
+        assert_eq!(s.pass_length, Settings::default().pass_length);
+    }
+
+    #[test]
+    fn unreadable_text_does_not_decode() {
+        assert!(decode("1,2,3\n").is_none());
+        assert!(decode("version=x\n").is_none());
+    }
+}
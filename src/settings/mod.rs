@@ -1,12 +1,21 @@
 //! Password generation settings.
 
+pub mod ephemeral;
+mod fields;
 mod file;
+mod validate;
+
+pub use fields::{CLI_FIELDS, FIELDS, FieldKind, field_kind};
+pub use file::{FileStatus, VERSION, get_path as file_path, inspect as inspect_file, update};
+pub use validate::Issue;
 
 #[derive(Debug, Clone)]
 pub struct Settings {
     pub pass_length: usize,
     pub number_of_passwords: usize,
     pub skip_countdown: bool,
+    /// Seconds of countdown before a large interactive run; 0 for none
+    pub countdown_secs: usize,
     pub view_chars_str: bool,
     pub special_chars: Vec<u8>,
     pub randomize_seed_chars: usize,
@@ -18,6 +27,55 @@ pub struct Settings {
     pub output_to_terminal: bool,
     pub cli_command: String,
     pub to_clipboard: bool,
+    /// Passphrase wordlist file; empty uses the embedded default
+    pub wordlist: String,
+    /// Words per passphrase; 0 generates character passwords
+    pub passphrase_words: usize,
+    pub word_sep: crate::pass::passphrase::WordSep,
+    /// Policy file or installed policy name applied when neither flags
+    /// nor a profile pick one; empty for none
+    pub policy_file: String,
+    /// Refuse passwords with fewer bits of entropy than this; 0 for no floor
+    pub min_entropy_bits: usize,
+    /// Append a line per generation run to the audit log (`pass::audit`)
+    pub audit_log: bool,
+    /// Hook commands around a run (`pass::hooks`); empty for none
+    pub pre_generate: String,
+    pub post_generate: String,
+    pub post_file: String,
+    /// Entropy sources in failover order, comma-separated
+    /// (`rand::failover`); empty for the cycle counter
+    pub rng: String,
+    /// Character device of the hardware TRNG (`--rng hwrng`)
+    pub hwrng_device: String,
+    /// Bytes asked of the TRNG per read
+    pub hwrng_read_size: usize,
+    /// RNG outputs discarded after each generator seeding
+    pub warmup_discard: usize,
+    /// `--chain`: derive passwords from this seed instead of sampling (not persisted)
+    pub chain_seed: Option<[u8; crate::pass::chain::SEED_LEN]>,
+    /// Passphrase mode: words instead of characters (not persisted)
+    pub passphrase: Option<crate::pass::passphrase::Passphrase>,
+    /// `--policy`: rules every password must satisfy (not persisted)
+    pub policy: Option<crate::pass::policy::Policy>,
+    /// `--start-with`: class of the first character (not persisted)
+    pub start_with: Option<crate::pass::charset::StartWith>,
+    /// `--pattern`: template every password follows (not persisted)
+    pub pattern: Option<crate::pass::pattern::Pattern>,
+    /// `--pad`: padding after a `pass_length` core (not persisted)
+    pub pad: Option<crate::pass::pad::Pad>,
+    /// `--color-classes` with stdout a terminal: color digits, uppercase
+    /// and symbols (not persisted)
+    pub color_classes: bool,
+    /// `--io-uring`: write the output file through `RingWriter` (not persisted)
+    pub io_uring: bool,
+    /// `--mmap`: write output files through `MmapWriter` (not persisted)
+    pub mmap: bool,
+    /// `--fsync`: when the output file is synced (not persisted)
+    pub fsync: crate::pass::fsync::Fsync,
+    /// `--label`: what history entries from this run are filed under (not
+    /// persisted)
+    pub history_label: Option<String>,
 }
 
 impl Settings {
@@ -31,6 +89,33 @@ impl Settings {
         file::save(self)
     }
 
+    /// Save only the fields changed since the file was last read or
+    /// written here, leaving other processes' changes in place. Returns
+    /// the settings as saved.
+    pub fn save_changes(&self) -> Result<Settings, String> {
+        file::save_changes(self)
+    }
+
+    /// Fix out-of-range fields with defaults; one issue per fix.
+    pub fn validate(&mut self) -> Vec<Issue> {
+        validate::validate(self)
+    }
+
+    /// Err when the output file is set and can't be written; checked
+    /// before a run writes, not on load.
+    pub fn check_output(&self) -> Result<(), String> {
+        validate::check_output(self)
+    }
+
+    /// Seconds to count down before a large run: none when skipped.
+    pub fn countdown(&self) -> usize {
+        if self.skip_countdown {
+            0
+        } else {
+            self.countdown_secs
+        }
+    }
+
     pub fn has_saved_command() -> bool {
         Self::load_from_file()
             .map(|s| !s.cli_command.is_empty())
@@ -44,6 +129,7 @@ impl Default for Settings {
             pass_length: 74,
             number_of_passwords: 19,
             skip_countdown: false,
+            countdown_secs: 10,
             view_chars_str: false,
             special_chars: vec![b'!', b'@', b'#', b'$', b'%', b'^', b'&', b'*'],
             randomize_seed_chars: 5,
@@ -55,6 +141,30 @@ impl Default for Settings {
             output_to_terminal: true,
             cli_command: String::new(),
             to_clipboard: false,
+            wordlist: String::new(),
+            passphrase_words: 0,
+            word_sep: Default::default(),
+            policy_file: String::new(),
+            min_entropy_bits: 0,
+            audit_log: false,
+            pre_generate: String::new(),
+            post_generate: String::new(),
+            post_file: String::new(),
+            rng: String::new(),
+            hwrng_device: crate::rand::hwrng::DEFAULT_DEVICE.to_string(),
+            hwrng_read_size: crate::rand::hwrng::DEFAULT_READ_SIZE,
+            warmup_discard: crate::rand::DEFAULT_WARMUP,
+            chain_seed: None,
+            passphrase: None,
+            policy: None,
+            start_with: None,
+            pattern: None,
+            pad: None,
+            color_classes: false,
+            io_uring: false,
+            mmap: false,
+            fsync: Default::default(),
+            history_label: None,
         }
     }
 }
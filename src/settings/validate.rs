@@ -0,0 +1,155 @@
+//! Range checks for loaded settings.
+//!
+//! A file can parse cleanly and still be unusable (a zero length, every
+//! character class off). Each problem is fixed in memory with a default
+//! and reported by field name; the file itself is only rewritten when the
+//! user saves. The output path is left as saved and checked by
+//! `check_output` when a run is about to write there, since a relative
+//! path depends on where randpass runs.
+
+use std::ffi::CString;
+use std::path::Path;
+
+use super::Settings;
+use crate::pass::charset;
+
+/// One invalid field: what was wrong, and what replaced it.
+pub struct Issue {
+    pub problem: String,
+    pub substitute: String,
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}, {}", self.problem, self.substitute)
+    }
+}
+
+/// Check `settings`, substituting defaults for invalid fields. Returns one
+/// issue per substitution.
+pub fn validate(settings: &mut Settings) -> Vec<Issue> {
+    let defaults = Settings::default();
+    let mut issues = Vec::new();
+
+    if settings.pass_length == 0 {
+        settings.pass_length = defaults.pass_length;
+        issues.push(Issue {
+            problem: "pass_length is 0 (must be at least 1)".to_string(),
+            substitute: format!("using {}", defaults.pass_length),
+        });
+    }
+
+    if settings.countdown_secs > crate::pass::output::MAX_COUNTDOWN {
+        issues.push(Issue {
+            problem: format!(
+                "countdown_secs is {} (at most {})",
+                settings.countdown_secs,
+                crate::pass::output::MAX_COUNTDOWN
+            ),
+            substitute: format!("using {}", defaults.countdown_secs),
+        });
+        settings.countdown_secs = defaults.countdown_secs;
+    }
+
+    if !(1..=crate::rand::hwrng::MAX_READ_SIZE).contains(&settings.hwrng_read_size) {
+        issues.push(Issue {
+            problem: format!(
+                "hwrng_read_size is {} (1 to {})",
+                settings.hwrng_read_size,
+                crate::rand::hwrng::MAX_READ_SIZE
+            ),
+            substitute: format!("using {}", defaults.hwrng_read_size),
+        });
+        settings.hwrng_read_size = defaults.hwrng_read_size;
+    }
+
+    if settings.warmup_discard > crate::rand::MAX_WARMUP {
+        issues.push(Issue {
+            problem: format!(
+                "warmup_discard is {} (at most {})",
+                settings.warmup_discard,
+                crate::rand::MAX_WARMUP
+            ),
+            substitute: format!("using {}", defaults.warmup_discard),
+        });
+        settings.warmup_discard = defaults.warmup_discard;
+    }
+
+    if charset::size(settings) == 0 {
+        settings.special_char_density = defaults.special_char_density;
+        settings.numeric_char_density = defaults.numeric_char_density;
+        settings.lowercase_char_density = defaults.lowercase_char_density;
+        settings.uppercase_char_density = defaults.uppercase_char_density;
+        if settings.special_chars.is_empty() {
+            settings.special_chars = defaults.special_chars.clone();
+        }
+        issues.push(Issue {
+            problem: "no character class enabled (all densities 0 or special_chars empty)"
+                .to_string(),
+            substitute: "using default densities".to_string(),
+        });
+    }
+
+    if !settings.wordlist.is_empty() && !Path::new(&settings.wordlist).is_file() {
+        issues.push(Issue {
+            problem: format!("wordlist '{}' is not a file", settings.wordlist),
+            substitute: format!("using {}", crate::pass::words::DEFAULT),
+        });
+        settings.wordlist.clear();
+    }
+
+    if !settings.policy_file.is_empty()
+        && !crate::pass::policy::resolve(&settings.policy_file).is_file()
+    {
+        issues.push(Issue {
+            problem: format!(
+                "policy_file '{}' is not a file or installed policy",
+                settings.policy_file
+            ),
+            substitute: "using no policy".to_string(),
+        });
+        settings.policy_file.clear();
+    }
+
+    issues
+}
+
+/// Err when `output_file_path` is set and can't be written.
+pub fn check_output(settings: &Settings) -> Result<(), String> {
+    let path = &settings.output_file_path;
+    if path.is_empty() || is_writable_target(path) {
+        return Ok(());
+    }
+    Err(format!(
+        "output file '{}' is not writable; nothing generated",
+        path
+    ))
+}
+
+/// True if `path` can be written: an existing file with write access, or
+/// a new file whose nearest existing ancestor directory is writable
+/// (missing directories are created on output).
+fn is_writable_target(path: &str) -> bool {
+    let path = Path::new(path);
+    if path.exists() {
+        return !path.is_dir() && can_write(path);
+    }
+    let mut dir = path.parent();
+    while let Some(d) = dir {
+        let d = if d.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            d
+        };
+        if d.exists() {
+            return d.is_dir() && can_write(d);
+        }
+        dir = d.parent();
+    }
+    false
+}
+
+fn can_write(path: &Path) -> bool {
+    CString::new(path.as_os_str().as_encoded_bytes())
+        .is_ok_and(|c| unsafe { libc::access(c.as_ptr(), libc::W_OK) } == 0)
+}
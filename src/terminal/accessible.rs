@@ -0,0 +1,29 @@
+//! Global accessible mode (`--accessible`): plain sequential output for
+//! screen readers and braille terminals. Boxes become plain lines, the
+//! screen is never cleared, progress is announced a line at a time
+//! instead of redrawn in place, and input is read as ordinary lines
+//! without cursor movement.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ACCESSIBLE: AtomicBool = AtomicBool::new(false);
+
+/// Environment variable that enables accessible mode when set to `1`
+pub const ENV_VAR: &str = "RANDPASS_ACCESSIBLE";
+
+pub fn set(accessible: bool) {
+    ACCESSIBLE.store(accessible, Ordering::SeqCst);
+}
+
+pub fn enabled() -> bool {
+    ACCESSIBLE.load(Ordering::Relaxed)
+}
+
+/// Enable accessible mode from `--accessible` or `RANDPASS_ACCESSIBLE=1`.
+/// Runs before anything is drawn.
+pub fn init(args: &[String]) {
+    let from_env = std::env::var(ENV_VAR).is_ok_and(|v| v == "1");
+    if from_env || args.iter().any(|a| a == "--accessible") {
+        set(true);
+    }
+}
@@ -0,0 +1,350 @@
+//! The terminal as the interactive code sees it: key events in, text out.
+//!
+//! Normally that is crossterm's event reader and the real stdout and
+//! stderr. A `Script` swaps in a queue of events and a buffer that keeps
+//! everything written, so menus, prompts, the countdown and the progress
+//! view can be driven without a terminal, as the tests in `tests/` and
+//! `randpass --script` do. Interactive code reads keys with `read`/`poll`,
+//! prints with `out!` and `outln!`, and takes writers from
+//! `stdout`/`stderr`.
+//!
+//! A script may also expect text: the key after an expectation is held
+//! back until the output since the previous one shows it.
+//!
+//! Captured output keeps passwords in memory unzeroized, as the terminal
+//! it stands in for would show them.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::thread::sleep;
+use std::time::Duration;
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+
+/// Terminal size a script reports unless told otherwise.
+pub const SCRIPT_SIZE: (u16, u16) = (80, 30);
+
+/// Set while a script stands in for the terminal; checked before the lock
+/// so real output pays one atomic load.
+static SCRIPTED: AtomicBool = AtomicBool::new(false);
+/// Set for `--script`: output goes on to stdout as well, and a script
+/// that can't go on stops the run rather than panicking.
+static HEADLESS: AtomicBool = AtomicBool::new(false);
+
+/// One step of a script.
+#[derive(Debug, Clone)]
+pub enum Step {
+    Key(Event),
+    /// Text the output must show before the next key is read
+    Expect(String),
+}
+
+struct Scripted {
+    steps: VecDeque<Step>,
+    output: Vec<u8>,
+    /// Where the next expectation starts looking in `output`
+    seen: usize,
+    /// Expectations not met yet; headless output is only kept while some
+    /// are left
+    expects: usize,
+    size: (u16, u16),
+}
+
+static SCRIPT: Mutex<Option<Scripted>> = Mutex::new(None);
+/// Held by the live script: one at a time, as there is one terminal.
+static EXCLUSIVE: Mutex<()> = Mutex::new(());
+
+fn script() -> MutexGuard<'static, Option<Scripted>> {
+    SCRIPT.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// True while a script stands in for the terminal.
+pub fn scripted() -> bool {
+    SCRIPTED.load(Ordering::Relaxed)
+}
+
+impl Scripted {
+    /// Meet the expectations at the front of the script from the output
+    /// so far. Err with the first one the output doesn't show.
+    fn settle(&mut self) -> Result<(), String> {
+        while let Some(Step::Expect(text)) = self.steps.front() {
+            let at = self.output[self.seen..]
+                .windows(text.len().max(1))
+                .position(|w| w == text.as_bytes())
+                .ok_or_else(|| format!("expected \"{}\", which was not shown", text))?;
+            self.seen += at + text.len();
+            self.steps.pop_front();
+            self.expects -= 1;
+        }
+        Ok(())
+    }
+}
+
+/// A script that can't go on: the test fails, or a headless run stops.
+fn stop(reason: &str) -> ! {
+    if !HEADLESS.load(Ordering::Relaxed) {
+        panic!("{}", reason);
+    }
+    let _ = io::stdout().flush();
+    crate::cli::prompts::error(&format!("Error: --script: {}", reason));
+    std::process::exit(1)
+}
+
+/// The script's next key, once the expectations before it are met.
+fn next_event() -> Result<Event, String> {
+    let mut guard = script();
+    let Some(s) = guard.as_mut() else {
+        return Err("no script".to_string());
+    };
+    s.settle()?;
+    match s.steps.pop_front() {
+        Some(Step::Key(event)) => Ok(event),
+        _ => Err("the script ran out of keys while randpass waited for one".to_string()),
+    }
+}
+
+/// Wait for the next event. A script that has run out, or expects text
+/// that wasn't shown, panics rather than leave a menu waiting forever.
+pub fn read() -> io::Result<Event> {
+    if !scripted() {
+        return event::read();
+    }
+    match next_event() {
+        Ok(event) => Ok(event),
+        Err(reason) => stop(&reason),
+    }
+}
+
+/// Whether an event is ready within `timeout`. An empty script waits out
+/// the timeout, like a terminal nobody types at, and so does one whose
+/// next key waits on text not shown yet.
+pub fn poll(timeout: Duration) -> io::Result<bool> {
+    if !scripted() {
+        return event::poll(timeout);
+    }
+    let ready = script()
+        .as_mut()
+        .is_some_and(|s| s.settle().is_ok() && matches!(s.steps.front(), Some(Step::Key(_))));
+    if !ready {
+        sleep(timeout);
+    }
+    Ok(ready)
+}
+
+/// Read one line of cooked input into `line`, as stdin's `read_line`
+/// would. A script types it from its keys up to Enter, echoing it as the
+/// terminal would; Ctrl+D on an empty line is end of input.
+pub fn read_line(line: &mut String) -> io::Result<usize> {
+    if !scripted() {
+        return io::stdin().read_line(line);
+    }
+    let start = line.len();
+    loop {
+        let key = match next_event() {
+            Ok(Event::Key(key)) => key,
+            Ok(_) => continue,
+            Err(reason) => stop(&reason),
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Enter => {
+                line.push('\n');
+                break;
+            }
+            KeyCode::Char('d') if ctrl && line.len() == start => return Ok(0),
+            KeyCode::Char(c) if !ctrl => line.push(c),
+            KeyCode::Backspace if line.len() > start => {
+                line.pop();
+            }
+            _ => {}
+        }
+    }
+    stdout().write_all(&line.as_bytes()[start..])?;
+    Ok(line.len() - start)
+}
+
+/// Terminal size in columns and rows.
+pub fn size() -> io::Result<(u16, u16)> {
+    if !scripted() {
+        return crossterm::terminal::size();
+    }
+    Ok(script().as_ref().map_or(SCRIPT_SIZE, |s| s.size))
+}
+
+/// `print!` through the console; backs `out!` and `outln!`. Panics when
+/// stdout can't be written, as `print!` does.
+pub fn print(args: fmt::Arguments) {
+    stdout().write_fmt(args).expect("failed printing to stdout");
+}
+
+/// `print!` to the console's stdout.
+macro_rules! out {
+    ($($arg:tt)*) => {
+        $crate::terminal::console::print(format_args!($($arg)*))
+    };
+}
+
+/// `println!` to the console's stdout.
+macro_rules! outln {
+    () => {
+        $crate::terminal::console::print(format_args!("\n"))
+    };
+    ($($arg:tt)*) => {
+        $crate::terminal::console::print(format_args!("{}\n", format_args!($($arg)*)))
+    };
+}
+
+pub(crate) use {out, outln};
+
+/// A locked stdout or stderr, or the script's buffer in their place.
+pub enum Stream {
+    Stdout(io::StdoutLock<'static>),
+    Stderr(io::StderrLock<'static>),
+    Captured,
+}
+
+pub fn stdout() -> Stream {
+    if scripted() {
+        Stream::Captured
+    } else {
+        Stream::Stdout(io::stdout().lock())
+    }
+}
+
+pub fn stderr() -> Stream {
+    if scripted() {
+        Stream::Captured
+    } else {
+        Stream::Stderr(io::stderr().lock())
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Stdout(out) => out.write(buf),
+            Stream::Stderr(err) => err.write(buf),
+            Stream::Captured => {
+                let headless = HEADLESS.load(Ordering::Relaxed);
+                if let Some(s) = script().as_mut()
+                    && (!headless || s.expects > 0)
+                {
+                    s.output.extend_from_slice(buf);
+                }
+                if headless {
+                    io::stdout().write_all(buf)?;
+                }
+                Ok(buf.len())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Stdout(out) => out.flush(),
+            Stream::Stderr(err) => err.flush(),
+            Stream::Captured if HEADLESS.load(Ordering::Relaxed) => io::stdout().flush(),
+            Stream::Captured => Ok(()),
+        }
+    }
+}
+
+/// A scripted terminal: the keys it will be sent, and what has been
+/// written to it. Stands in from `new` until dropped; a second script
+/// waits for the first.
+pub struct Script {
+    _exclusive: MutexGuard<'static, ()>,
+}
+
+impl Script {
+    pub fn new(events: impl IntoIterator<Item = Event>) -> Self {
+        Self::steps(events.into_iter().map(Step::Key))
+    }
+
+    /// A script of keys and expectations.
+    pub fn steps(steps: impl IntoIterator<Item = Step>) -> Self {
+        let exclusive = EXCLUSIVE.lock().unwrap_or_else(PoisonError::into_inner);
+        let steps: VecDeque<Step> = steps.into_iter().collect();
+        *script() = Some(Scripted {
+            expects: steps
+                .iter()
+                .filter(|s| matches!(s, Step::Expect(_)))
+                .count(),
+            steps,
+            output: Vec::new(),
+            seen: 0,
+            size: SCRIPT_SIZE,
+        });
+        SCRIPTED.store(true, Ordering::SeqCst);
+        Self {
+            _exclusive: exclusive,
+        }
+    }
+
+    /// Pass output on to stdout as it is written, keeping only what
+    /// expectations still need, and stop the process with an error where
+    /// the script can't go on. For `--script`.
+    pub fn headless(self) -> Self {
+        HEADLESS.store(true, Ordering::SeqCst);
+        self
+    }
+
+    /// Report `cols` by `rows` as the terminal size.
+    pub fn with_size(self, cols: u16, rows: u16) -> Self {
+        if let Some(s) = script().as_mut() {
+            s.size = (cols, rows);
+        }
+        self
+    }
+
+    /// Keys not read yet.
+    pub fn pending(&self) -> usize {
+        script().as_ref().map_or(0, |s| {
+            s.steps.iter().filter(|s| matches!(s, Step::Key(_))).count()
+        })
+    }
+
+    /// Everything written so far, stdout and stderr interleaved as on
+    /// screen. Headless, only what expectations still need is kept.
+    pub fn output(&self) -> String {
+        script()
+            .as_ref()
+            .map(|s| String::from_utf8_lossy(&s.output).into_owned())
+            .unwrap_or_default()
+    }
+
+    /// Meet the expectations left after the last key read. Err with the
+    /// first one the output doesn't show.
+    pub fn finish(&self) -> Result<(), String> {
+        script().as_mut().map_or(Ok(()), Scripted::settle)
+    }
+}
+
+impl Drop for Script {
+    fn drop(&mut self) {
+        SCRIPTED.store(false, Ordering::SeqCst);
+        HEADLESS.store(false, Ordering::SeqCst);
+        *script() = None;
+    }
+}
+
+/// A key press.
+pub fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+/// A key pressed with Ctrl held.
+pub fn ctrl(c: char) -> Event {
+    Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL))
+}
+
+/// `text` typed, then Enter.
+pub fn line(text: &str) -> Vec<Event> {
+    text.chars()
+        .map(|c| key(KeyCode::Char(c)))
+        .chain([key(KeyCode::Enter)])
+        .collect()
+}
@@ -1,9 +1,17 @@
 //! Shared terminal utilities.
 //!
-//! Box drawing, progress bars, raw mode management, and ANSI helpers.
+//! Box drawing, progress bars and their redraw throttle, raw mode
+//! management, ANSI helpers, the accessible mode that turns them into
+//! plain lines, desktop notifications at the end of long runs, and the
+//! console that interactive code reads keys from and prints to.
 
+pub mod accessible;
+pub mod console;
+pub mod notify;
 mod output;
+mod progress;
 mod raw_mode;
 
 pub use output::*;
+pub use progress::Throttle;
 pub use raw_mode::*;
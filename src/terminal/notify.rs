@@ -0,0 +1,408 @@
+//! `--notify`: a desktop notification when a long run ends, so it can be
+//! left in the background. The notification goes over the session D-Bus
+//! to `org.freedesktop.Notifications`, spoken directly on the bus socket.
+//! Without a bus or a notification server the terminal bell rings
+//! instead.
+//!
+//! A run is watched from `start` to `finish`. One still watched when the
+//! process exits was cut short. After an error `on_exit` announces it from
+//! the atexit cleanup. After a signal the handler only rings the bell, and
+//! `signaled` sends the notification from the exit watcher thread
+//! (`exits::watch_signals`), since none of this is async-signal-safe.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// How long the bus gets to answer before the bell rings instead.
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+const METHOD_CALL: u8 = 1;
+const METHOD_RETURN: u8 = 2;
+const ERROR: u8 = 3;
+/// Header field holding the serial a reply answers.
+const REPLY_SERIAL: u8 = 5;
+/// Serial of the Notify call; Hello goes first as 1.
+const NOTIFY_SERIAL: u32 = 2;
+
+struct Run {
+    what: String,
+    start: Instant,
+}
+
+static RUN: Mutex<Option<Run>> = Mutex::new(None);
+/// Whether `RUN` holds a run, readable from a signal handler.
+static WATCHING: AtomicBool = AtomicBool::new(false);
+
+/// How a watched run ended.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    Finished,
+    /// Stopped from the keyboard (Esc or Ctrl+C in the progress view)
+    Interrupted,
+    /// Killed by a signal or ended by an error
+    Stopped,
+}
+
+/// Watch a run described by `what` ("1,000,000 password(s) to out.txt").
+pub fn start(what: String) {
+    crate::exits::watch_signals();
+    if let Ok(mut run) = RUN.lock() {
+        *run = Some(Run {
+            what,
+            start: Instant::now(),
+        });
+        WATCHING.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Whether a run is watched. Async-signal-safe.
+pub fn watching() -> bool {
+    WATCHING.load(Ordering::SeqCst)
+}
+
+/// The watched run, no longer watched.
+fn take() -> Option<Run> {
+    let run = RUN.lock().ok().and_then(|mut run| run.take());
+    WATCHING.store(false, Ordering::SeqCst);
+    run
+}
+
+/// Stop watching without a word: the user ended the run at the terminal
+/// before it began.
+pub fn cancel() {
+    take();
+}
+
+/// Announce the watched run's end, if a run is watched.
+pub fn finish(outcome: Outcome) {
+    if let Some(run) = take() {
+        announce(&run, outcome, true);
+    }
+}
+
+/// atexit: a run still watched never got to `finish`. The exit from a
+/// signal handler comes after `signaled` took the run, so this returns on
+/// the atomic check there.
+pub fn on_exit() {
+    if !watching() {
+        return;
+    }
+    if let Some(run) = take() {
+        announce(&run, Outcome::Stopped, true);
+    }
+}
+
+/// A signal stopped the run: the handler has rung the bell, so only the
+/// notification is left.
+pub fn signaled() {
+    if let Some(run) = take() {
+        announce(&run, Outcome::Stopped, false);
+    }
+}
+
+/// BEL on the controlling terminal, else stderr, with only
+/// async-signal-safe calls.
+pub fn signal_bell() {
+    unsafe {
+        let tty = libc::open(
+            c"/dev/tty".as_ptr(),
+            libc::O_WRONLY | libc::O_NOCTTY | libc::O_CLOEXEC,
+        );
+        let fd = if tty >= 0 { tty } else { 2 };
+        libc::write(fd, b"\x07".as_ptr() as *const libc::c_void, 1);
+        if tty >= 0 {
+            libc::close(tty);
+        }
+    }
+}
+
+/// Post the notification for `run`, ringing the bell if it can't be
+/// sent and `bell` allows.
+fn announce(run: &Run, outcome: Outcome, bell: bool) {
+    let secs = run.start.elapsed().as_secs_f64();
+    let (summary, body) = match outcome {
+        Outcome::Finished => (
+            "randpass: run finished",
+            format!("{} in {:.1}s", run.what, secs),
+        ),
+        Outcome::Interrupted => (
+            "randpass: run interrupted",
+            format!("{}, interrupted after {:.1}s", run.what, secs),
+        ),
+        Outcome::Stopped => (
+            "randpass: run stopped",
+            format!("{}, stopped after {:.1}s", run.what, secs),
+        ),
+    };
+    match send(summary, &body) {
+        Ok(()) => {}
+        Err(e) if bell => {
+            crate::cli::prompts::verbose(&format!("Notify: {}; ringing the bell", e));
+            ring();
+        }
+        Err(e) => crate::cli::prompts::verbose(&format!("Notify: {}", e)),
+    }
+}
+
+/// BEL on the controlling terminal, which is still there when stderr is
+/// redirected; stderr when there is none.
+fn ring() {
+    match std::fs::OpenOptions::new().write(true).open("/dev/tty") {
+        Ok(mut tty) => {
+            let _ = tty.write_all(b"\x07");
+        }
+        Err(_) => {
+            let _ = std::io::stderr().write_all(b"\x07");
+        }
+    }
+}
+
+/// Post the notification and wait for the server to take it.
+fn send(summary: &str, body: &str) -> std::io::Result<()> {
+    let stream = connect()?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut bus = BufReader::new(stream);
+    authenticate(&mut bus)?;
+
+    let hello = call(
+        1,
+        "org.freedesktop.DBus",
+        "/org/freedesktop/DBus",
+        "Hello",
+        "",
+        &[],
+    );
+    let mut args = Marshal::default();
+    args.str("randpass");
+    args.u32(0); // replaces_id: a new notification
+    args.str(""); // app_icon
+    args.str(summary);
+    args.str(body);
+    args.u32(0); // actions: empty as
+    args.u32(0); // hints: empty a{sv}, padded to its entries
+    args.pad(8);
+    args.u32(u32::MAX); // expire_timeout -1: the server's default
+    let notify = call(
+        NOTIFY_SERIAL,
+        "org.freedesktop.Notifications",
+        "/org/freedesktop/Notifications",
+        "Notify",
+        "susssasa{sv}i",
+        &args.0,
+    );
+    let out = bus.get_mut();
+    out.write_all(&hello)?;
+    out.write_all(&notify)?;
+
+    // Hello's reply and NameAcquired come first; skip to ours
+    loop {
+        let (kind, reply_to) = read_message(&mut bus)?;
+        match (kind, reply_to) {
+            (METHOD_RETURN, Some(NOTIFY_SERIAL)) => return Ok(()),
+            (ERROR, Some(NOTIFY_SERIAL)) => {
+                return Err(std::io::Error::other("no notification server"));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The session bus socket from `DBUS_SESSION_BUS_ADDRESS`, else
+/// `$XDG_RUNTIME_DIR/bus`. Addresses are `;`-separated `unix:path=...` or
+/// `unix:abstract=...`, tried in order.
+fn connect() -> std::io::Result<UnixStream> {
+    let address = std::env::var("DBUS_SESSION_BUS_ADDRESS")
+        .or_else(|_| std::env::var("XDG_RUNTIME_DIR").map(|dir| format!("unix:path={}/bus", dir)));
+    let Ok(address) = address else {
+        return Err(std::io::Error::other("no session bus"));
+    };
+    let mut last = std::io::Error::other("no unix session bus address");
+    for entry in address.split(';') {
+        let Some(params) = entry.strip_prefix("unix:") else {
+            continue;
+        };
+        for param in params.split(',') {
+            let connected = match param.split_once('=') {
+                Some(("path", path)) => UnixStream::connect(unescape(path)),
+                #[cfg(target_os = "linux")]
+                Some(("abstract", name)) => {
+                    use std::os::linux::net::SocketAddrExt;
+                    std::os::unix::net::SocketAddr::from_abstract_name(unescape(name))
+                        .and_then(|addr| UnixStream::connect_addr(&addr))
+                }
+                _ => continue,
+            };
+            match connected {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last = e,
+            }
+        }
+    }
+    Err(last)
+}
+
+/// D-Bus addresses escape bytes as `%XX`.
+fn unescape(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| value.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(b) => {
+                out.push(b);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// SASL EXTERNAL: the bus knows our uid from the socket, we name it.
+fn authenticate(bus: &mut BufReader<UnixStream>) -> std::io::Result<()> {
+    let uid = unsafe { libc::getuid() }.to_string();
+    let hex: String = uid.bytes().map(|b| format!("{:02x}", b)).collect();
+    bus.get_mut()
+        .write_all(format!("\0AUTH EXTERNAL {}\r\n", hex).as_bytes())?;
+    let mut line = String::new();
+    bus.read_line(&mut line)?;
+    if !line.starts_with("OK ") {
+        return Err(std::io::Error::other(format!(
+            "bus refused authentication: {}",
+            line.trim()
+        )));
+    }
+    bus.get_mut().write_all(b"BEGIN\r\n")
+}
+
+/// Marshalled values, little-endian, aligned from the start of the buffer
+/// (header fields and body both start 8-aligned in a message).
+#[derive(Default)]
+struct Marshal(Vec<u8>);
+
+impl Marshal {
+    fn pad(&mut self, align: usize) {
+        while !self.0.len().is_multiple_of(align) {
+            self.0.push(0);
+        }
+    }
+
+    fn byte(&mut self, b: u8) {
+        self.0.push(b);
+    }
+
+    fn u32(&mut self, n: u32) {
+        self.pad(4);
+        self.0.extend_from_slice(&n.to_le_bytes());
+    }
+
+    /// A string or object path.
+    fn str(&mut self, s: &str) {
+        self.u32(s.len() as u32);
+        self.0.extend_from_slice(s.as_bytes());
+        self.0.push(0);
+    }
+
+    fn signature(&mut self, s: &str) {
+        self.0.push(s.len() as u8);
+        self.0.extend_from_slice(s.as_bytes());
+        self.0.push(0);
+    }
+}
+
+/// A method call on `path` of interface `dest` (each service here names
+/// its interface after itself), with `body` marshalled per `signature`.
+fn call(
+    serial: u32,
+    dest: &str,
+    path: &str,
+    member: &str,
+    signature: &str,
+    body: &[u8],
+) -> Vec<u8> {
+    let mut fields = Marshal::default();
+    for (code, kind, value) in [
+        (1, "o", path),
+        (2, "s", dest),
+        (3, "s", member),
+        (6, "s", dest),
+    ] {
+        fields.pad(8);
+        fields.byte(code);
+        fields.signature(kind);
+        fields.str(value);
+    }
+    if !signature.is_empty() {
+        fields.pad(8);
+        fields.byte(8);
+        fields.signature("g");
+        fields.signature(signature);
+    }
+
+    let mut message = Marshal(vec![b'l', METHOD_CALL, 0, 1]);
+    message.u32(body.len() as u32);
+    message.u32(serial);
+    message.u32(fields.0.len() as u32);
+    message.0.extend_from_slice(&fields.0);
+    message.pad(8);
+    message.0.extend_from_slice(body);
+    message.0
+}
+
+/// Read one message: its type and the serial it replies to, if any.
+fn read_message(bus: &mut BufReader<UnixStream>) -> std::io::Result<(u8, Option<u32>)> {
+    let malformed = || std::io::Error::other("malformed message from the bus");
+    let mut fixed = [0u8; 16];
+    bus.read_exact(&mut fixed)?;
+    let word = |b: &[u8]| {
+        let b = [b[0], b[1], b[2], b[3]];
+        match fixed[0] {
+            b'B' => u32::from_be_bytes(b),
+            _ => u32::from_le_bytes(b),
+        }
+    };
+    let body_len = word(&fixed[4..8]) as usize;
+    let fields_len = word(&fixed[12..16]) as usize;
+    let padded = (fields_len + 7) & !7;
+    let mut rest = vec![0u8; padded + body_len];
+    bus.read_exact(&mut rest)?;
+
+    let fields = &rest[..fields_len];
+    let mut reply_to = None;
+    let mut i = 0;
+    while i < fields_len {
+        i = (i + 7) & !7;
+        let code = *fields.get(i).ok_or_else(malformed)?;
+        let sig_len = *fields.get(i + 1).ok_or_else(malformed)? as usize;
+        let kind = *fields.get(i + 2).ok_or_else(malformed)?;
+        i += 3 + sig_len;
+        match kind {
+            b'u' | b's' | b'o' => {
+                i = (i + 3) & !3;
+                let n = word(fields.get(i..i + 4).ok_or_else(malformed)?);
+                i += 4;
+                if kind == b'u' {
+                    if code == REPLY_SERIAL {
+                        reply_to = Some(n);
+                    }
+                } else {
+                    i += n as usize + 1;
+                }
+            }
+            b'g' => i += *fields.get(i).ok_or_else(malformed)? as usize + 2,
+            _ => return Err(malformed()),
+        }
+    }
+    Ok((fixed[1], reply_to))
+}
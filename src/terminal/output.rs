@@ -3,7 +3,10 @@
 //! Box drawing, progress bars, number formatting, ANSI helpers.
 
 use crossterm::terminal::disable_raw_mode;
-use std::io::{self, Write};
+use std::io::Write;
+
+use super::accessible;
+use super::console::{self, out, outln};
 
 // ============================================================================
 // ANSI Color/Style Constants
@@ -12,26 +15,57 @@ use std::io::{self, Write};
 pub const RESET: &str = "\x1b[0m";
 pub const UNDERLINE: &str = "\x1b[4m";
 pub const RED: &str = "\x1b[38;5;9m";
+pub const YELLOW: &str = "\x1b[33m";
 
 // ============================================================================
 // Terminal Control
 // ============================================================================
 
-/// Clear screen and move cursor to top-left.
+/// Clear screen and move cursor to top-left. Accessible mode keeps what
+/// was read on screen.
 pub fn clear() {
-    print!("\x1b[2J\x1b[3J\x1b[H");
+    if accessible::enabled() {
+        return;
+    }
+    out!("\x1b[2J\x1b[3J\x1b[H");
     flush();
 }
 
+/// Fewest rows the boxed layout is drawn for; shorter terminals get the
+/// compact one, which leaves out borders and spacer lines.
+pub const COMPACT_ROWS: u16 = 24;
+
+/// Terminal size, if there is a terminal to ask.
+fn term_size() -> Option<(usize, usize)> {
+    console::size()
+        .ok()
+        .filter(|&(cols, rows)| cols > 0 && rows > 0)
+        .map(|(cols, rows)| (cols as usize, rows as usize))
+}
+
+/// True when the terminal is narrower than a box or too short for the
+/// menus, or in accessible mode: boxes are drawn as plain lines that wrap
+/// and scroll cleanly.
+pub fn compact() -> bool {
+    accessible::enabled()
+        || term_size().is_some_and(|(cols, rows)| cols < BOX_WIDTH || rows < COMPACT_ROWS as usize)
+}
+
+/// Width for the progress and countdown bars: the box width, or the
+/// terminal's if that is narrower, so a redraw never wraps.
+fn bar_width() -> usize {
+    term_size().map_or(BOX_WIDTH, |(cols, _)| cols.clamp(4, BOX_WIDTH))
+}
+
 /// Flush stdout.
 pub fn flush() {
-    let _ = io::stdout().flush();
+    let _ = console::stdout().flush();
 }
 
 /// Reset terminal to sane state (fixes staggered text issues).
 pub fn reset_terminal() {
     let _ = disable_raw_mode();
-    print!("\x1b[0m");
+    out!("\x1b[0m");
     flush();
 }
 
@@ -41,12 +75,16 @@ pub fn reset_terminal() {
 
 /// Print error message in red.
 pub fn print_error(msg: &str) {
-    println!("{RED}{msg}{RESET}");
+    outln!("{RED}{msg}{RESET}");
 }
 
 /// Print a horizontal rule (box style).
 pub fn print_rule() {
-    println!("├{}┤", "─".repeat(BOX_WIDTH - 2));
+    if compact() {
+        outln!();
+        return;
+    }
+    outln!("├{}┤", "─".repeat(BOX_WIDTH - 2));
 }
 
 // ============================================================================
@@ -65,6 +103,22 @@ pub fn format_number(num: usize) -> String {
     result
 }
 
+/// Format a byte count with binary units: 512 B, 1.5 KiB, 3.2 GiB.
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
 // ============================================================================
 // Box Drawing (74 char width)
 // ============================================================================
@@ -73,30 +127,50 @@ pub const BOX_WIDTH: usize = 74;
 
 /// Print box top with optional title: ┌─ Title ───────────────────────────┐
 pub fn box_top(title: &str) {
-    if title.is_empty() {
-        println!("┌{}┐", "─".repeat(BOX_WIDTH - 2));
+    if compact() {
+        if accessible::enabled() {
+            if !title.is_empty() {
+                outln!("{title}:");
+            }
+        } else if !title.is_empty() {
+            outln!("{UNDERLINE}{title}{RESET}");
+        }
+    } else if title.is_empty() {
+        outln!("┌{}┐", "─".repeat(BOX_WIDTH - 2));
     } else {
         let title_part = format!("─ {} ", title);
         let remaining = BOX_WIDTH - 2 - title_part.chars().count();
-        println!("┌{}{}┐", title_part, "─".repeat(remaining));
+        outln!("┌{}{}┐", title_part, "─".repeat(remaining));
     }
 }
 
 /// Print box content line: │ content                                        │
 pub fn box_line(content: &str) {
+    if compact() {
+        if !content.trim().is_empty() {
+            outln!("{}", content.trim_end());
+        }
+        return;
+    }
     let inner_width = BOX_WIDTH - 4;
     let display_len = console_width(content);
 
     if display_len <= inner_width {
         let padding = inner_width - display_len;
-        println!("│ {}{} │", content, " ".repeat(padding));
+        outln!("│ {}{} │", content, " ".repeat(padding));
     } else {
-        println!("│ {} │", content);
+        outln!("│ {} │", content);
     }
 }
 
 /// Print centered box content line: │          content          │
 pub fn box_line_center(content: &str) {
+    if compact() {
+        if !content.trim().is_empty() {
+            outln!("{}", content.trim());
+        }
+        return;
+    }
     let inner_width = BOX_WIDTH - 4;
     let display_len = console_width(content);
 
@@ -104,20 +178,24 @@ pub fn box_line_center(content: &str) {
         let total_padding = inner_width - display_len;
         let left_pad = total_padding / 2;
         let right_pad = total_padding - left_pad;
-        println!(
+        outln!(
             "│ {}{}{} │",
             " ".repeat(left_pad),
             content,
             " ".repeat(right_pad)
         );
     } else {
-        println!("│ {} │", content);
+        outln!("│ {} │", content);
     }
 }
 
 /// Print box bottom: └───────────────────────────────────────────────────────┘
 pub fn box_bottom() {
-    println!("└{}┘", "─".repeat(BOX_WIDTH - 2));
+    if compact() {
+        outln!();
+        return;
+    }
+    outln!("└{}┘", "─".repeat(BOX_WIDTH - 2));
 }
 
 /// Print a help option with flag and description, auto-wrapping if needed.
@@ -126,6 +204,12 @@ pub fn box_opt(flag: &str, desc: &str) {
     let flag_col = 27;
     let desc_col = inner_width - flag_col;
 
+    if compact() {
+        outln!("{}", flag.trim_end());
+        outln!("      {}", desc);
+        return;
+    }
+
     let flag_padded = if flag.len() < flag_col {
         format!("{}{}", flag, " ".repeat(flag_col - flag.len()))
     } else {
@@ -153,16 +237,16 @@ pub fn box_opt(flag: &str, desc: &str) {
 
     if let Some(first) = lines.first() {
         let padding = desc_col.saturating_sub(first.len());
-        println!("│ {}{}{} │", flag_padded, first, " ".repeat(padding));
+        outln!("│ {}{}{} │", flag_padded, first, " ".repeat(padding));
     } else {
         let padding = desc_col;
-        println!("│ {}{} │", flag_padded, " ".repeat(padding));
+        outln!("│ {}{} │", flag_padded, " ".repeat(padding));
     }
 
     let indent = " ".repeat(flag_col);
     for line in lines.iter().skip(1) {
         let padding = desc_col.saturating_sub(line.len());
-        println!("│ {}{}{} │", indent, line, " ".repeat(padding));
+        outln!("│ {}{}{} │", indent, line, " ".repeat(padding));
     }
 }
 
@@ -186,23 +270,41 @@ fn console_width(s: &str) -> usize {
 
 /// Print centered text within box width.
 pub fn print_centered(text: &str) {
+    if compact() {
+        out!("{}\r\n", text);
+        let _ = console::stdout().flush();
+        return;
+    }
     let padding = BOX_WIDTH.saturating_sub(text.len()) / 2;
-    print!(
+    out!(
         "{}{}{}\r\n",
         " ".repeat(padding),
         text,
         " ".repeat(BOX_WIDTH - padding - text.len())
     );
-    let _ = std::io::stdout().flush();
+    let _ = console::stdout().flush();
 }
 
 // ============================================================================
 // Progress Bar
 // ============================================================================
 
-/// Render a progress bar inside a box with centered text (3 lines).
+/// Render a progress bar inside a box with centered text (3 lines). The
+/// box narrows to fit the terminal and the text is cut to the box.
 pub fn progress_bar_box(percent: f32, stats: &str) {
-    let inner_width = BOX_WIDTH - 2;
+    progress_bar_box_to(&mut console::stdout(), percent, stats);
+}
+
+/// Render the progress box to any writer (stderr for `--bytes` runs whose
+/// stdout carries data). Accessible mode writes `stats` as a line of its
+/// own instead.
+pub fn progress_bar_box_to<W: Write>(out: &mut W, percent: f32, stats: &str) {
+    if accessible::enabled() {
+        let _ = write!(out, "\rProgress: {}\r\n", stats.replace(" • ", ", "));
+        let _ = out.flush();
+        return;
+    }
+    let inner_width = bar_width() - 2;
     let filled = if percent >= 100.0 {
         inner_width
     } else {
@@ -226,54 +328,54 @@ pub fn progress_bar_box(percent: f32, stats: &str) {
 
     // Top border
     if filled > 0 {
-        print!("\r▗");
-        print!("{}", "▄".repeat(filled));
+        let _ = write!(out, "\r▗");
+        let _ = write!(out, "{}", "▄".repeat(filled));
     } else {
-        print!("\r┌");
+        let _ = write!(out, "\r┌");
     }
     if filled < inner_width {
-        print!("{}", "─".repeat(inner_width - filled));
-        print!("┐\r\n");
+        let _ = write!(out, "{}", "─".repeat(inner_width - filled));
+        let _ = write!(out, "┐\r\n");
     } else {
-        print!("▖\r\n");
+        let _ = write!(out, "▖\r\n");
     }
 
     // Middle
     if filled > 0 {
-        print!("\r▐");
+        let _ = write!(out, "\r▐");
         let filled_str: String = content[..filled].iter().collect();
-        print!("\x1b[7m{}\x1b[0m", filled_str);
+        let _ = write!(out, "\x1b[7m{}\x1b[0m", filled_str);
     } else {
-        print!("\r│");
+        let _ = write!(out, "\r│");
     }
     if filled < inner_width {
         let unfilled_str: String = content[filled..].iter().collect();
-        print!("{}", unfilled_str);
-        print!("│\r\n");
+        let _ = write!(out, "{}", unfilled_str);
+        let _ = write!(out, "│\r\n");
     } else {
-        print!("▌\r\n");
+        let _ = write!(out, "▌\r\n");
     }
 
     // Bottom border
     if filled > 0 {
-        print!("\r▝");
-        print!("{}", "▀".repeat(filled));
+        let _ = write!(out, "\r▝");
+        let _ = write!(out, "{}", "▀".repeat(filled));
     } else {
-        print!("\r└");
+        let _ = write!(out, "\r└");
     }
     if filled < inner_width {
-        print!("{}", "─".repeat(inner_width - filled));
-        print!("┘\r\n");
+        let _ = write!(out, "{}", "─".repeat(inner_width - filled));
+        let _ = write!(out, "┘\r\n");
     } else {
-        print!("▘\r\n");
+        let _ = write!(out, "▘\r\n");
     }
 
-    let _ = std::io::stdout().flush();
+    let _ = out.flush();
 }
 
 /// Render a countdown bar with bouncing grey spot and centered text (3 lines).
 pub fn countdown_bar(spot_pos: usize, text: &str) {
-    let inner_width = BOX_WIDTH - 2;
+    let inner_width = bar_width() - 2;
 
     let text_chars: Vec<char> = text.chars().collect();
     let text_len = text_chars.len();
@@ -290,23 +392,24 @@ pub fn countdown_bar(spot_pos: usize, text: &str) {
         }
     }
 
-    let spot = spot_pos % inner_width;
+    // The spot bounces across the full box width; scale it to this one
+    let spot = spot_pos.min(BOX_WIDTH - 3) * (inner_width - 1) / (BOX_WIDTH - 3);
 
-    print!("\r┌{}┐\r\n", "─".repeat(inner_width));
+    out!("\r┌{}┐\r\n", "─".repeat(inner_width));
 
-    print!("\r│");
+    out!("\r│");
     for (i, ch) in content.iter().enumerate() {
         if i == spot {
-            print!("\x1b[90m█\x1b[0m");
+            out!("\x1b[90m█\x1b[0m");
         } else {
-            print!("{}", ch);
+            out!("{}", ch);
         }
     }
-    print!("│\r\n");
+    out!("│\r\n");
 
-    print!("\r└{}┘\r\n", "─".repeat(inner_width));
+    out!("\r└{}┘\r\n", "─".repeat(inner_width));
 
-    let _ = std::io::stdout().flush();
+    let _ = console::stdout().flush();
 }
 
 // ============================================================================
@@ -331,11 +434,65 @@ pub fn entropy_strength(bits: f64) -> &'static str {
     }
 }
 
+/// Attacker models for crack-time estimates, with guesses per second:
+/// a login form that throttles to 100 guesses an hour, an offline attack
+/// on bcrypt hashes, and an offline attack on a fast hash (MD5, NTLM)
+/// with a rig of GPUs.
+pub const ATTACKERS: [(&str, f64); 3] =
+    [("online", 100.0 / 3600.0), ("bcrypt", 1e4), ("GPU", 1e11)];
+
+/// Average time to guess a password of `bits` at `rate` guesses per
+/// second, which is half the space: "3 hours", "10^21 years".
+pub fn crack_time(bits: f64, rate: f64) -> String {
+    const UNITS: [(&str, f64); 5] = [
+        ("second", 1.0),
+        ("minute", 60.0),
+        ("hour", 3600.0),
+        ("day", 86400.0),
+        ("year", 31_557_600.0),
+    ];
+    // In log10 so very long passwords don't overflow
+    let log_secs = (bits - 1.0).max(0.0) * 2f64.log10() - rate.log10();
+    if log_secs < 0.0 {
+        return "instantly".to_string();
+    }
+    let log_years = log_secs - UNITS[4].1.log10();
+    if log_years >= 6.0 {
+        return format!("10^{} years", log_years.floor());
+    }
+    let secs = 10f64.powf(log_secs);
+    let (unit, size) = UNITS
+        .iter()
+        .rev()
+        .find(|(_, size)| secs >= *size)
+        .copied()
+        .unwrap_or(UNITS[0]);
+    let n = (secs / size) as usize;
+    format!(
+        "{} {}{}",
+        format_number(n),
+        unit,
+        if n == 1 { "" } else { "s" }
+    )
+}
+
+/// Crack times for every attacker model, on one line.
+pub fn crack_times(bits: f64) -> String {
+    ATTACKERS
+        .iter()
+        .map(|(name, rate)| format!("{} {}", name, crack_time(bits, *rate)))
+        .collect::<Vec<_>>()
+        .join(" • ")
+}
+
 /// Get info about the entropy source.
 pub fn entropy_source_info() -> &'static str {
     if crate::rand::is_urandom_enabled() {
         return "/dev/urandom (32MB pool) - High quality";
     }
+    if crate::rand::is_hwrng_enabled() {
+        return "hwrng (hardware TRNG, health-tested) - High quality";
+    }
 
     #[cfg(target_arch = "x86_64")]
     {
@@ -0,0 +1,137 @@
+//! Redraw throttling and ETA smoothing for progress boxes.
+
+use std::time::{Duration, Instant};
+
+use super::accessible;
+
+/// Longest stride between clock reads, in items.
+const MAX_STRIDE: usize = 1 << 16;
+
+/// Time constant of the moving average, in seconds: a sample's weight
+/// is `1 - e^(-dt/TAU)` for the `dt` it covers, so a long stall counts
+/// for its whole length rather than as one frame among many.
+const TAU: f64 = 3.0;
+
+/// Decides when a progress box for `total` items redraws: at most once a
+/// frame (30 fps), and always on the last item. The clock is only read
+/// every `stride` items, with the stride adapted so reads land a few times
+/// a frame however fast items complete. In accessible mode each redraw is
+/// a line read aloud, so it comes once a tenth of the run instead.
+pub struct Throttle {
+    total: usize,
+    start: Instant,
+    /// When the last checkpoint ran, and at which item
+    checked: (Instant, usize),
+    /// When the box was last drawn, and at which item
+    drawn: (Instant, usize),
+    /// Next item to read the clock at
+    next_check: usize,
+    stride: usize,
+    /// Smoothed items per second; None until the first frame
+    rate: Option<f64>,
+    /// Tenths of the run already announced, in accessible mode
+    announced: usize,
+}
+
+impl Throttle {
+    pub const FRAME: Duration = Duration::from_nanos(1_000_000_000 / 30);
+
+    /// Clock reads aimed for per frame.
+    const CHECKS_PER_FRAME: u32 = 4;
+
+    pub fn new(total: usize) -> Self {
+        let now = Instant::now();
+        Self {
+            total,
+            start: now,
+            checked: (now, 0),
+            // Draw on the first checkpoint
+            drawn: (now - Self::FRAME, 0),
+            next_check: 1,
+            stride: 1,
+            rate: None,
+            announced: 0,
+        }
+    }
+
+    /// True when the box should be redrawn now that `done` items are
+    /// complete.
+    pub fn due(&mut self, done: usize) -> bool {
+        if done < self.next_check && done < self.total {
+            return false;
+        }
+        let now = Instant::now();
+
+        // Aim the next read a fraction of a frame ahead at the current pace
+        let (at, items) = self.checked;
+        let elapsed = now.duration_since(at);
+        let target = Self::FRAME / Self::CHECKS_PER_FRAME;
+        if elapsed > Duration::ZERO && done > items {
+            let scaled = (done - items) as f64 * target.as_secs_f64() / elapsed.as_secs_f64();
+            self.stride = (scaled as usize).clamp(1, MAX_STRIDE);
+        }
+        self.checked = (now, done);
+        self.next_check = done + self.stride;
+
+        let (at, items) = self.drawn;
+        let since = now.duration_since(at);
+        if since < Self::FRAME && done < self.total {
+            return false;
+        }
+        if accessible::enabled() {
+            let tenth = (done as u128 * 10 / self.total.max(1) as u128) as usize;
+            if tenth <= self.announced && done < self.total {
+                return false;
+            }
+            self.announced = tenth;
+        }
+        if done > items && at > self.start {
+            let dt = since.as_secs_f64();
+            let sample = (done - items) as f64 / dt;
+            let weight = 1.0 - (-dt / TAU).exp();
+            self.rate = Some(match self.rate {
+                Some(rate) => rate + weight * (sample - rate),
+                None => sample,
+            });
+        }
+        self.drawn = (now, done);
+        true
+    }
+
+    /// Seconds left at the smoothed rate; None until a full frame has
+    /// been measured.
+    pub fn eta(&self, done: usize) -> Option<f64> {
+        let rate = self.rate.filter(|&r| r > 0.0)?;
+        Some(self.total.saturating_sub(done) as f64 / rate)
+    }
+
+    /// `eta` formatted for a progress line: "1.5s", "12m 05s", "2h 40m",
+    /// or "--" while unknown.
+    pub fn eta_text(&self, done: usize) -> String {
+        let Some(secs) = self.eta(done) else {
+            return "--".to_string();
+        };
+        let whole = secs.round() as u64;
+        match whole {
+            _ if secs < 60.0 => format!("{:.1}s", secs),
+            ..3600 => format!("{}m {:02}s", whole / 60, whole % 60),
+            _ => format!("{}h {:02}m", whole / 3600, whole % 3600 / 60),
+        }
+    }
+
+    /// Smoothed items per second for a live throughput figure; the
+    /// average since the start until a frame has been measured.
+    pub fn rate(&self, done: usize) -> f64 {
+        self.rate.unwrap_or_else(|| self.average(done))
+    }
+
+    /// Items per second since the start.
+    pub fn average(&self, done: usize) -> f64 {
+        let elapsed = self.start.elapsed().as_secs_f64();
+        if elapsed > 0.0 {
+            done as f64 / elapsed
+        } else {
+            0.0
+        }
+    }
+}
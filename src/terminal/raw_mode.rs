@@ -10,7 +10,11 @@ pub struct RawModeGuard {
 
 impl RawModeGuard {
     /// Enable raw mode, returning a guard that will disable it on drop.
+    /// A script has no terminal mode to change.
     pub fn new() -> io::Result<Self> {
+        if super::console::scripted() {
+            return Ok(Self { was_enabled: false });
+        }
         enable_raw_mode()?;
         Ok(Self { was_enabled: true })
     }
@@ -1,6 +1,7 @@
-use crossterm::event::{Event, KeyCode, KeyModifiers, read};
+use crossterm::event::{Event, KeyCode, KeyModifiers};
 
-use crate::terminal::{RawModeGuard, flush, format_number, reset_terminal};
+use crate::terminal::console::{self, out, outln, read};
+use crate::terminal::{RawModeGuard, accessible, flush, format_number, reset_terminal};
 
 /// Map a 1-based cursor position in raw digits to a 1-based position in the
 /// comma-formatted display string.
@@ -35,8 +36,43 @@ fn format_digits(s: &str) -> String {
     }
 }
 
+/// Accessible mode's input: one line read with the terminal's own line
+/// editing, nothing redrawn. The current value is said in brackets and an
+/// empty line keeps it; end of input (Ctrl+D) cancels.
+fn plain_input(prompt: &str, initial_value: &str) -> Option<String> {
+    if initial_value.is_empty() {
+        out!("{}: ", prompt);
+    } else {
+        out!("{} [{}]: ", prompt, initial_value);
+    }
+    flush();
+    let mut line = String::new();
+    match console::read_line(&mut line) {
+        Ok(0) | Err(_) => {
+            outln!();
+            None
+        }
+        Ok(_) => {
+            let line = line.trim_end_matches(['\r', '\n']);
+            Some(if line.is_empty() { initial_value } else { line }.to_string())
+        }
+    }
+}
+
 /// Get numeric input with live comma formatting and cursor movement
 pub fn get_numeric_input(prompt: &str, initial_value: usize) -> Option<usize> {
+    if accessible::enabled() {
+        let initial = format_digits(&initial_value.to_string());
+        let input = plain_input(prompt, &initial)?;
+        let digits: String = input.chars().filter(|&c| c != ',').collect();
+        return match digits.trim() {
+            "" => Some(0),
+            d => d.parse().ok().or_else(|| {
+                outln!("Not a number: {}", input.trim());
+                None
+            }),
+        };
+    }
     let mut digits = if initial_value > 0 {
         initial_value.to_string()
     } else {
@@ -51,7 +87,7 @@ pub fn get_numeric_input(prompt: &str, initial_value: usize) -> Option<usize> {
     };
 
     let formatted = format_digits(&digits);
-    print!("{}: {}", prompt, formatted);
+    out!("{}: {}", prompt, formatted);
     flush();
 
     let mut last_display_len = formatted.len();
@@ -62,7 +98,7 @@ pub fn get_numeric_input(prompt: &str, initial_value: usize) -> Option<usize> {
                 match key_event.code {
                     KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
                         reset_terminal();
-                        println!();
+                        outln!();
                         std::process::exit(0);
                     }
                     KeyCode::Char('q') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
@@ -80,26 +116,18 @@ pub fn get_numeric_input(prompt: &str, initial_value: usize) -> Option<usize> {
                     KeyCode::Enter => {
                         break;
                     }
-                    KeyCode::Backspace => {
-                        if cursor_pos > 1 {
-                            cursor_pos -= 1;
-                            digits.remove(cursor_pos - 1);
-                        }
+                    KeyCode::Backspace if cursor_pos > 1 => {
+                        cursor_pos -= 1;
+                        digits.remove(cursor_pos - 1);
                     }
-                    KeyCode::Delete => {
-                        if cursor_pos <= digits.len() {
-                            digits.remove(cursor_pos - 1);
-                        }
+                    KeyCode::Delete if cursor_pos <= digits.len() => {
+                        digits.remove(cursor_pos - 1);
                     }
-                    KeyCode::Left => {
-                        if cursor_pos > 1 {
-                            cursor_pos -= 1;
-                        }
+                    KeyCode::Left if cursor_pos > 1 => {
+                        cursor_pos -= 1;
                     }
-                    KeyCode::Right => {
-                        if cursor_pos < digits.len() + 1 {
-                            cursor_pos += 1;
-                        }
+                    KeyCode::Right if cursor_pos < digits.len() + 1 => {
+                        cursor_pos += 1;
                     }
                     KeyCode::Home => {
                         cursor_pos = 1;
@@ -116,14 +144,14 @@ pub fn get_numeric_input(prompt: &str, initial_value: usize) -> Option<usize> {
 
                 // Redraw with formatting
                 let formatted = format_digits(&digits);
-                print!("\r{}: {}", prompt, " ".repeat(last_display_len + 1));
-                print!("\r{}: {}", prompt, formatted);
+                out!("\r{}: {}", prompt, " ".repeat(last_display_len + 1));
+                out!("\r{}: {}", prompt, formatted);
                 flush();
                 last_display_len = formatted.len();
 
                 // Position cursor within formatted display
                 let display_col = digit_cursor_to_display(&digits, cursor_pos);
-                print!("\x1b[{}G", prompt.len() + 2 + display_col);
+                out!("\x1b[{}G", prompt.len() + 2 + display_col);
                 flush();
             }
             Err(_) => break,
@@ -132,7 +160,7 @@ pub fn get_numeric_input(prompt: &str, initial_value: usize) -> Option<usize> {
     }
 
     drop(_guard);
-    println!();
+    outln!();
 
     if cancelled {
         None
@@ -144,6 +172,9 @@ pub fn get_numeric_input(prompt: &str, initial_value: usize) -> Option<usize> {
 }
 
 pub fn get_editable_input(prompt: &str, initial_value: &str) -> Option<String> {
+    if accessible::enabled() {
+        return plain_input(prompt, initial_value);
+    }
     let mut input = initial_value.to_string();
     let mut cursor_pos = input.len() + 1;
     let mut input_len = cursor_pos;
@@ -155,7 +186,7 @@ pub fn get_editable_input(prompt: &str, initial_value: &str) -> Option<String> {
         Err(_) => return Some(input), // Can't enable raw mode, return default
     };
 
-    print!("{}: {}", prompt, input);
+    out!("{}: {}", prompt, input);
     flush();
 
     loop {
@@ -165,7 +196,7 @@ pub fn get_editable_input(prompt: &str, initial_value: &str) -> Option<String> {
                     KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
                         // Reset terminal BEFORE exit since process::exit doesn't run destructors
                         reset_terminal();
-                        println!();
+                        outln!();
                         std::process::exit(0);
                     }
                     KeyCode::Char('q') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
@@ -183,27 +214,19 @@ pub fn get_editable_input(prompt: &str, initial_value: &str) -> Option<String> {
                     KeyCode::Enter => {
                         break;
                     }
-                    KeyCode::Backspace => {
-                        if cursor_pos > 1 {
-                            cursor_pos -= 1;
-                            input_len -= 1;
-                            input.remove(cursor_pos - 1);
-                        }
+                    KeyCode::Backspace if cursor_pos > 1 => {
+                        cursor_pos -= 1;
+                        input_len -= 1;
+                        input.remove(cursor_pos - 1);
                     }
-                    KeyCode::Delete => {
-                        if cursor_pos < input.len() + 1 {
-                            input.remove(cursor_pos - 1);
-                        }
+                    KeyCode::Delete if cursor_pos < input.len() + 1 => {
+                        input.remove(cursor_pos - 1);
                     }
-                    KeyCode::Left => {
-                        if cursor_pos > 1 {
-                            cursor_pos -= 1;
-                        }
+                    KeyCode::Left if cursor_pos > 1 => {
+                        cursor_pos -= 1;
                     }
-                    KeyCode::Right => {
-                        if cursor_pos < input.len() + 1 {
-                            cursor_pos += 1;
-                        }
+                    KeyCode::Right if cursor_pos < input.len() + 1 => {
+                        cursor_pos += 1;
                     }
                     KeyCode::Char(c) => {
                         input.insert(cursor_pos - 1, c);
@@ -214,12 +237,12 @@ pub fn get_editable_input(prompt: &str, initial_value: &str) -> Option<String> {
                 }
 
                 // Redraw the input line
-                print!("\r{}: {}", prompt, " ".repeat(input_len + 1));
-                print!("\r{}: {}", prompt, input);
+                out!("\r{}: {}", prompt, " ".repeat(input_len + 1));
+                out!("\r{}: {}", prompt, input);
                 flush();
 
                 // Move cursor to correct position
-                print!("\x1b[{}G", prompt.len() + 2 + cursor_pos);
+                out!("\x1b[{}G", prompt.len() + 2 + cursor_pos);
                 flush();
             }
             Err(_) => {
@@ -231,6 +254,6 @@ pub fn get_editable_input(prompt: &str, initial_value: &str) -> Option<String> {
 
     // Explicitly drop guard to disable raw mode BEFORE println
     drop(_guard);
-    println!();
+    outln!();
     if cancelled { None } else { Some(input) }
 }
@@ -2,10 +2,12 @@
 
 mod input;
 mod options;
+mod picker;
 mod text;
 
 pub use input::*;
 pub use options::*;
+pub use picker::pick;
 pub use text::*;
 
 /// Run TUI interactive mode.
@@ -4,18 +4,36 @@ use std::{
     process::exit,
 };
 
-use crate::pass::output::with_progress as output_passwords;
+use crate::pass;
+use crate::pass::hooks::Hook;
+use crate::pass::output::with_progress;
+use crate::pass::passphrase::{self, WordSep};
+use crate::pass::policy::{self, preset};
 use crate::settings::Settings;
-use crate::terminal::{clear, reset_terminal};
+use crate::terminal::console::{self, out, outln};
+use crate::terminal::{clear, print_error, reset_terminal};
 
 use super::{
     enter_prompt, get_editable_input, get_numeric_input, print_file_exists, print_help,
-    print_main_menu, print_settings_menu,
+    print_main_menu, print_session_stats, print_settings_menu,
 };
 
 pub fn gen_file_exists_menu(settings: &Settings) -> Option<File> {
     use std::io::Write;
 
+    // No one to ask: append, as CLI file output does
+    if !crate::cli::quiet::is_interactive() {
+        crate::cli::prompts::warn(&format!(
+            "{} exists and stdin is not a terminal; appending",
+            settings.output_file_path
+        ));
+        return OpenOptions::new()
+            .read(settings.mmap)
+            .append(true)
+            .open(&settings.output_file_path)
+            .ok();
+    }
+
     print_file_exists(&settings.output_file_path);
 
     loop {
@@ -25,6 +43,7 @@ pub fn gen_file_exists_menu(settings: &Settings) -> Option<File> {
         if choice == "o" {
             return Some(
                 OpenOptions::new()
+                    .read(settings.mmap)
                     .create(true)
                     .write(true)
                     .truncate(true)
@@ -34,6 +53,7 @@ pub fn gen_file_exists_menu(settings: &Settings) -> Option<File> {
         } else if choice == "a" {
             return Some(
                 OpenOptions::new()
+                    .read(settings.mmap)
                     .create(true)
                     .append(true)
                     .open(&settings.output_file_path)
@@ -41,12 +61,52 @@ pub fn gen_file_exists_menu(settings: &Settings) -> Option<File> {
             );
         } else {
             // Move up 2 lines (to blank line), clear it, print error, move down, clear prompt line
-            print!(
-                "\x1b[2A\x1b[2K\x1b[31mInvalid choice. Please enter 'a' or 'o'.\x1b[0m\n\x1b[2K"
-            );
-            let _ = std::io::stdout().flush();
+            out!("\x1b[2A\x1b[2K\x1b[31mInvalid choice. Please enter 'a' or 'o'.\x1b[0m\n\x1b[2K");
+            let _ = console::stdout().flush();
+        }
+    }
+}
+
+/// Generate with the passphrase settings applied. Fails when the saved
+/// wordlist can't be used.
+fn output_passwords(settings: &Settings) -> Result<(), String> {
+    let mut settings = settings.clone();
+    settings.passphrase = passphrase::from_settings(&settings)?;
+    let mut trial = None;
+    if let Some(policy) = settings.policy.clone() {
+        if let Some((field, reason)) = policy.conflicts(&settings).into_iter().next() {
+            return Err(format!(
+                "Policy {}: {} ({}); fix the settings marked in red first",
+                policy.source, field, reason
+            ));
+        }
+        // The length shown in the menu is the user's; don't move it
+        trial = policy
+            .prepare(&mut settings, true)
+            .and_then(|_| pass::trial_policy(&settings, policy::TRIAL_DRAWS).transpose())
+            .map_err(|e| format!("Policy {}: {}", policy.source, e))?;
+    }
+    let bits = pass::entropy(&settings, trial.as_ref());
+    if bits < settings.min_entropy_bits as f64 {
+        return Err(format!(
+            "Passwords would carry {:.1} bits of entropy, under the {}-bit floor (min_entropy_bits); make them longer or lower it with randpass config set min_entropy_bits",
+            bits, settings.min_entropy_bits
+        ));
+    }
+    settings.check_output()?;
+    let count = settings.number_of_passwords;
+    pass::hooks::run(Hook::PreGenerate, &settings, count, bits)
+        .map_err(|e| format!("{}; nothing generated", e))?;
+    with_progress(&settings);
+    if let Err(e) = pass::audit::record(&settings, count, bits) {
+        print_error(&format!("Audit log not written: {}", e));
+    }
+    for hook in [Hook::PostFile, Hook::PostGenerate] {
+        if let Err(e) = pass::hooks::run(hook, &settings, count, bits) {
+            print_error(&e);
         }
     }
+    Ok(())
 }
 
 pub fn gen_main_menu() {
@@ -56,15 +116,25 @@ pub fn gen_main_menu() {
     let mut settings = match Settings::load_from_file() {
         Ok(s) => s,
         Err(e) => {
-            println!("Error loading settings: {}", e);
+            outln!("Error loading settings: {}", e);
             Settings::default()
         }
     };
+    crate::rand::set_warmup(settings.warmup_discard);
+    // The saved default policy; conflicts show in the settings menu
+    if !settings.policy_file.is_empty() {
+        match policy::load(&settings.policy_file) {
+            Ok(policy) => settings.policy = Some(policy),
+            Err(e) => print_error(&format!("Error loading policy: {}", e)),
+        }
+    }
 
     if settings.number_of_passwords > 100 {
         update_settings(&mut settings);
-    } else if settings.output_file_path.is_empty() {
-        output_passwords(&settings);
+    } else if settings.output_file_path.is_empty()
+        && let Err(e) = output_passwords(&settings)
+    {
+        print_error(&e);
     }
     let mut print_invalid = false;
 
@@ -82,7 +152,9 @@ pub fn gen_main_menu() {
         match input.trim() {
             "" => {
                 clear();
-                output_passwords(&settings);
+                if let Err(e) = output_passwords(&settings) {
+                    print_error(&e);
+                }
                 reset_terminal(); // Ensure clean state after password generation
             }
             "1" => {
@@ -95,6 +167,10 @@ pub fn gen_main_menu() {
                 print_help();
             }
             "4" => {
+                clear();
+                print_session_stats();
+            }
+            "5" => {
                 clear();
                 break;
             }
@@ -106,8 +182,31 @@ pub fn gen_main_menu() {
     }
 }
 
+/// Most changes `u` can step back through.
+const MAX_UNDO: usize = 100;
+
+/// Settings as they were on entering the menu, and before each change
+/// since, for `u) undo` and `x) discard`.
+struct Edits {
+    original: Settings,
+    history: Vec<Settings>,
+}
+
+/// Whether anything the menu edits differs: the saved fields or the
+/// session's policy.
+fn changed(a: &Settings, b: &Settings) -> bool {
+    crate::settings::FIELDS
+        .iter()
+        .any(|key| a.get_field(key) != b.get_field(key))
+        || a.policy.as_ref().map(|p| &p.source) != b.policy.as_ref().map(|p| &p.source)
+}
+
 pub fn update_settings(settings: &mut Settings) {
     let (mut print_error, mut last_option, mut error_txt) = (0, String::new(), String::new());
+    let mut edits = Edits {
+        original: settings.clone(),
+        history: Vec::new(),
+    };
 
     loop {
         print_settings_menu(settings, print_error, &error_txt);
@@ -125,23 +224,32 @@ pub fn update_settings(settings: &mut Settings) {
             trim_choice
         } else {
             print_error = 0;
-            println!("{}: {}", enter_prompt(), last_option);
+            outln!("{}: {}", enter_prompt(), last_option);
             last_option.clone()
         };
 
         let choice = choice.as_str();
+        let before = settings.clone();
 
-        match choice.parse::<i32>() {
-            Ok(num) => {
-                if let Break = menu_options(num, &mut print_error, &mut error_txt, settings) {
-                    break;
-                }
-            }
-            Err(_) => {
-                if let Break = command_options(choice, &mut print_error, &mut error_txt, settings) {
-                    break;
-                }
+        let action = match choice.parse::<i32>() {
+            Ok(num) => menu_options(num, &mut print_error, &mut error_txt, settings),
+            Err(_) => command_options(
+                choice,
+                &mut print_error,
+                &mut error_txt,
+                settings,
+                &mut edits,
+            ),
+        };
+        // Undo itself steps back, it isn't a change to record
+        if !choice.contains('u') && changed(&before, settings) {
+            if edits.history.len() == MAX_UNDO {
+                edits.history.remove(0);
             }
+            edits.history.push(before);
+        }
+        if let Break = action {
+            break;
         }
     }
 }
@@ -276,17 +384,27 @@ fn menu_options(
             settings.output_file_path = path.trim().to_string();
         }
         11 => {
-            // skip countdown
-            let new_bool = match get_editable_input("Enter 't' or 'f'", "") {
+            // countdown
+            let new_secs = match get_editable_input("Enter seconds (0 to skip)", "") {
                 Some(s) => s,
                 None => return Continue,
             };
 
-            match new_bool.trim() {
-                "" => return Continue,
-                "t" => settings.skip_countdown = true,
-                "f" => settings.skip_countdown = false,
-                _ => *print_error = 2,
+            if new_secs.trim().is_empty() {
+                return Continue;
+            }
+            match new_secs.trim().parse::<usize>() {
+                Ok(secs) if secs <= crate::pass::output::MAX_COUNTDOWN => {
+                    settings.countdown_secs = secs;
+                    settings.skip_countdown = false;
+                }
+                _ => {
+                    *print_error = 999;
+                    *error_txt = format!(
+                        "Invalid input, please enter seconds from 0 to {}...",
+                        crate::pass::output::MAX_COUNTDOWN
+                    );
+                }
             }
         }
         12 => {
@@ -298,14 +416,92 @@ fn menu_options(
             settings.cli_command = new_command;
         }
         13 => {
-            // entropy source toggle
-            if crate::rand::is_urandom_enabled() {
-                crate::rand::disable_urandom();
-            } else if !crate::rand::enable_urandom() {
+            // entropy source: counter -> urandom -> hwrng (when there is
+            // one) -> counter
+            use crate::rand;
+            if rand::is_urandom_enabled() {
+                if rand::hwrng::is_available(&settings.hwrng_device) {
+                    if let Err(e) =
+                        rand::enable_hwrng(&settings.hwrng_device, settings.hwrng_read_size)
+                    {
+                        rand::disable_urandom();
+                        *print_error = 999;
+                        *error_txt = format!("hwrng: {}", e);
+                    }
+                } else {
+                    rand::disable_urandom();
+                }
+            } else if rand::is_hwrng_enabled() {
+                rand::disable_hwrng();
+            } else if !rand::enable_urandom() {
                 *print_error = 999;
                 *error_txt = "/dev/urandom not available on this system".to_string();
             }
         }
+        14 => {
+            // passphrase words (0 = passwords)
+            if let Some(n) = get_numeric_input("Words per passphrase", settings.passphrase_words) {
+                settings.passphrase_words = n;
+            }
+        }
+        15 => {
+            // word separator
+            let current = settings.word_sep.to_string();
+            let new_sep = match get_editable_input("Enter one character, none or camel", &current) {
+                Some(s) => s,
+                None => return Continue,
+            };
+            match WordSep::parse(new_sep.trim_end_matches(['\r', '\n'])) {
+                Some(sep) => settings.word_sep = sep,
+                None => {
+                    *print_error = 999;
+                    *error_txt = "Enter one character, 'none' or 'camel'".to_string();
+                }
+            }
+        }
+        16 => {
+            // wordlist file
+            let new_path = match get_editable_input(
+                "Enter wordlist file path (empty for the embedded list)",
+                &settings.wordlist,
+            ) {
+                Some(s) => s,
+                None => return Continue,
+            };
+            let path = new_path.trim();
+            if path.is_empty() || Path::new(path).is_file() {
+                settings.wordlist = path.to_string();
+            } else {
+                *print_error = 999;
+                *error_txt = format!("{} is not a file", path);
+            }
+        }
+        17 => {
+            // policy: a preset, policy file or installed policy
+            let current = settings
+                .policy
+                .as_ref()
+                .map_or("none", |p| p.source.as_str());
+            let name = match get_editable_input("Enter a preset, policy file or none", current) {
+                Some(s) => s,
+                None => return Continue,
+            };
+            match name.trim() {
+                "" | "none" => settings.policy = None,
+                name => match preset::get(name).map_or_else(|| policy::load(name), Ok) {
+                    Ok(policy) => {
+                        settings.policy = Some(policy.clone());
+                        // Pull the length into the policy's range; what
+                        // still conflicts is marked in the menu
+                        let _ = policy.prepare(settings, false);
+                    }
+                    Err(e) => {
+                        *print_error = 999;
+                        *error_txt = format!("Policy {}: {}", name, e);
+                    }
+                },
+            }
+        }
         _ => {
             clear();
             *print_error = 998;
@@ -319,6 +515,7 @@ fn command_options(
     print_error: &mut i32,
     error_txt: &mut String,
     settings: &mut Settings,
+    edits: &mut Edits,
 ) -> LoopAction {
     // println!("{:?}", choice);
     if choice.is_empty() {
@@ -329,7 +526,11 @@ fn command_options(
         } else {
             // generate passwords
             clear();
-            output_passwords(settings);
+            if let Err(e) = output_passwords(settings) {
+                *print_error = 999;
+                *error_txt = e;
+                return Continue;
+            }
             return Break;
         }
     }
@@ -341,7 +542,7 @@ fn command_options(
     }
 
     match choice.chars().next() {
-        Some('s') | Some('e') | Some('r') | Some('f') | Some('d') => {}
+        Some('s') | Some('e') | Some('r') | Some('f') | Some('d') | Some('u') | Some('x') => {}
         _ => {
             *print_error = 0;
             *error_txt = "Invalid selection".to_string();
@@ -352,26 +553,39 @@ fn command_options(
     for ch in choice.chars() {
         match ch {
             's' => {
-                // save settings
-                if let Err(e) = settings.save_to_file() {
-                    *print_error = 1;
-                    *error_txt = format!("Error saving settings: {}", e);
-                } else {
-                    *print_error = 0;
+                // save settings, keeping fields changed elsewhere since
+                match settings.save_changes() {
+                    Ok(saved) => {
+                        *print_error = 0;
+                        for key in crate::settings::FIELDS {
+                            if let Some(value) = saved.get_field(key) {
+                                let _ = settings.set_field(key, &value);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        *print_error = 1;
+                        *error_txt = format!("Error saving settings: {}", e);
+                    }
                 }
             }
             'e' => {}
             'r' => {
                 // load default settings
                 *print_error = 0;
+                // The policy is this session's, not a setting
+                let policy = settings.policy.take();
                 *settings = Settings::default();
+                settings.policy = policy;
             }
             'f' => {
                 // load from file
                 match Settings::load_from_file() {
                     Ok(s) => {
                         *print_error = 0;
+                        let policy = settings.policy.take();
                         *settings = s;
+                        settings.policy = policy;
                     }
                     Err(e) => {
                         *print_error = 1;
@@ -379,6 +593,24 @@ fn command_options(
                     }
                 }
             }
+            'u' => {
+                // undo last change
+                match edits.history.pop() {
+                    Some(previous) => {
+                        *print_error = 0;
+                        *settings = previous;
+                    }
+                    None => {
+                        *print_error = 999;
+                        *error_txt = "Nothing to undo".to_string();
+                    }
+                }
+            }
+            'x' => {
+                // discard all changes since entering the menu
+                *print_error = 0;
+                *settings = edits.original.clone();
+            }
             'd' => {
                 clear();
                 if Path::new(&settings.output_file_path).exists() {
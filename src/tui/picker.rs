@@ -0,0 +1,245 @@
+//! Arrow-key list with a fuzzy filter, for `--pick-from`. It is drawn on
+//! stderr so stdout carries only the choice, and erased when done so the
+//! rejected candidates don't stay on screen. Accessible mode lists the
+//! candidates numbered instead and reads a number.
+
+use std::io::Write;
+
+use crossterm::event::{Event, KeyCode, KeyModifiers};
+use zeroize::Zeroize;
+
+use crate::pass::pool;
+use crate::terminal::console::{self, read};
+use crate::terminal::{RawModeGuard, accessible};
+
+/// Candidates shown at once; the list scrolls past this.
+const VISIBLE: usize = 10;
+
+/// Where the user is in the list.
+struct State<'a> {
+    candidates: &'a [&'a [u8]],
+    /// Indices of the candidates the filter keeps, best first
+    shown: Vec<usize>,
+    filter: Vec<u8>,
+    /// Row of `shown` under the cursor, and the first row on screen
+    selected: usize,
+    top: usize,
+    visible: usize,
+    width: usize,
+}
+
+/// Let the user choose one of `candidates`: arrows move, typing filters,
+/// Enter picks and Esc or Ctrl+C cancels. Returns the index picked.
+pub fn pick(candidates: &[&[u8]]) -> Option<usize> {
+    if accessible::enabled() {
+        return pick_numbered(candidates);
+    }
+    let _guard = RawModeGuard::new().ok()?;
+    let (cols, rows) = console::size().unwrap_or((80, 24));
+    let mut state = State {
+        candidates,
+        shown: matches(candidates, &[]),
+        filter: Vec::new(),
+        selected: 0,
+        top: 0,
+        visible: VISIBLE
+            .min(candidates.len())
+            .min((rows as usize).saturating_sub(2))
+            .max(1),
+        width: (cols as usize).saturating_sub(3).max(8),
+    };
+
+    // A frame holds passwords, so it is built in a locked buffer and
+    // written at once; 4 bytes a column covers any UTF-8 character
+    let mut frame = pool::take((state.visible + 1) * (state.width * 4 + 32));
+    let mut err = console::stderr();
+    let _ = write!(err, "\x1b[?25l");
+
+    let mut redraw = false;
+    let choice = loop {
+        state.draw(&mut frame, redraw);
+        let _ = err.write_all(&frame);
+        let _ = err.flush();
+        frame.zeroize();
+        redraw = true;
+
+        let key = match read() {
+            Ok(Event::Key(key)) => key,
+            Ok(_) => continue,
+            Err(_) => break None,
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => break None,
+            KeyCode::Char('c') if ctrl => break None,
+            KeyCode::Enter => {
+                if let Some(&i) = state.shown.get(state.selected) {
+                    break Some(i);
+                }
+            }
+            KeyCode::Up => state.step(-1),
+            KeyCode::Down => state.step(1),
+            KeyCode::Char('p') if ctrl => state.step(-1),
+            KeyCode::Char('n') if ctrl => state.step(1),
+            KeyCode::PageUp => state.step(-(state.visible as isize)),
+            KeyCode::PageDown => state.step(state.visible as isize),
+            KeyCode::Home => state.step(isize::MIN),
+            KeyCode::End => state.step(isize::MAX),
+            KeyCode::Backspace => {
+                state.filter.pop();
+                state.refilter();
+            }
+            KeyCode::Char('u') if ctrl => {
+                state.filter.zeroize();
+                state.refilter();
+            }
+            KeyCode::Char(c) if !ctrl && (c.is_ascii_graphic() || c == ' ') => {
+                state.filter.push(c as u8);
+                state.refilter();
+            }
+            _ => {}
+        }
+    };
+
+    // Back to the header line, then clear everything below it
+    let _ = write!(err, "\r\x1b[{}A\x1b[J\x1b[?25h", state.visible);
+    let _ = err.flush();
+    state.filter.zeroize();
+    choice
+}
+
+/// Every candidate on its own numbered line, then the number of the one
+/// wanted, asked until it is valid. An empty line or end of input cancels.
+fn pick_numbered(candidates: &[&[u8]]) -> Option<usize> {
+    let width = candidates.len().to_string().len();
+    let mut err = console::stderr();
+    for (i, text) in candidates.iter().enumerate() {
+        let _ = write!(err, "{:>width$}  ", i + 1)
+            .and_then(|_| err.write_all(text))
+            .and_then(|_| err.write_all(b"\n"));
+    }
+    let mut input = String::new();
+    loop {
+        let _ = write!(err, "Pick one, 1 to {} (empty cancels): ", candidates.len());
+        input.clear();
+        if console::read_line(&mut input).unwrap_or(0) == 0 {
+            return None;
+        }
+        match input.trim() {
+            "" => return None,
+            n => match n.parse::<usize>() {
+                Ok(n) if (1..=candidates.len()).contains(&n) => return Some(n - 1),
+                _ => {
+                    let _ = writeln!(err, "Not one of the numbers listed: {}", n);
+                }
+            },
+        }
+    }
+}
+
+impl State<'_> {
+    /// Move the cursor `by` rows, stopping at either end, and scroll it
+    /// into view.
+    fn step(&mut self, by: isize) {
+        let last = self.shown.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(by).min(last);
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if self.selected >= self.top + self.visible {
+            self.top = self.selected + 1 - self.visible;
+        }
+    }
+
+    fn refilter(&mut self) {
+        self.shown = matches(self.candidates, &self.filter);
+        self.selected = 0;
+        self.top = 0;
+    }
+
+    /// One frame into `frame`: a header with the filter, then the visible
+    /// rows, the selected one in reverse video. A redraw first moves back
+    /// up over the previous frame.
+    fn draw(&self, frame: &mut Vec<u8>, redraw: bool) {
+        if redraw {
+            frame.extend_from_slice(format!("\x1b[{}A", self.visible).as_bytes());
+        }
+        let header = format!(
+            "Pick one ({}/{}) ↑/↓ Enter, Esc cancels > ",
+            self.shown.len(),
+            self.candidates.len()
+        );
+        frame.extend_from_slice(b"\r\x1b[K");
+        frame.extend_from_slice(header.as_bytes());
+        let room = self.width.saturating_sub(header.chars().count());
+        frame.extend_from_slice(&self.filter[self.filter.len().saturating_sub(room)..]);
+
+        for row in self.top..self.top + self.visible {
+            frame.extend_from_slice(b"\r\n\x1b[K");
+            let Some(&i) = self.shown.get(row) else {
+                continue;
+            };
+            if row == self.selected {
+                frame.extend_from_slice(b"\x1b[7m> ");
+            } else {
+                frame.extend_from_slice(b"  ");
+            }
+            let text = self.candidates[i];
+            let cut = fit(text, self.width - 2);
+            frame.extend_from_slice(&text[..cut]);
+            if cut < text.len() {
+                frame.extend_from_slice("…".as_bytes());
+            }
+            if row == self.selected {
+                frame.extend_from_slice(b"\x1b[0m");
+            }
+        }
+    }
+}
+
+/// Bytes of `text` that fit in `columns`, cut on a character boundary
+/// with room left for an ellipsis.
+fn fit(text: &[u8], columns: usize) -> usize {
+    let Ok(s) = std::str::from_utf8(text) else {
+        return text.len().min(columns.saturating_sub(1));
+    };
+    if s.chars().count() <= columns {
+        return text.len();
+    }
+    s.char_indices()
+        .nth(columns.saturating_sub(1))
+        .map_or(text.len(), |(i, _)| i)
+}
+
+/// Indices of the candidates matching `filter`, best first. A candidate
+/// matches when it holds the filter's characters in order, ignoring
+/// case; the shorter the stretch they span, the better the match.
+fn matches(candidates: &[&[u8]], filter: &[u8]) -> Vec<usize> {
+    let mut hits: Vec<(usize, usize)> = candidates
+        .iter()
+        .enumerate()
+        .filter_map(|(i, text)| Some((span(text, filter)?, i)))
+        .collect();
+    hits.sort_unstable();
+    hits.into_iter().map(|(_, i)| i).collect()
+}
+
+/// Length of the shortest stretch of `text` holding `filter` in order.
+fn span(text: &[u8], filter: &[u8]) -> Option<usize> {
+    let Some(&first) = filter.first() else {
+        return Some(0);
+    };
+    let same = |a: u8, b: u8| a.eq_ignore_ascii_case(&b);
+    (0..text.len())
+        .filter(|&start| same(text[start], first))
+        .filter_map(|start| {
+            // Taking each character as early as it comes is shortest
+            let mut want = filter.iter().peekable();
+            for (j, &b) in text[start..].iter().enumerate() {
+                if want.next_if(|&&f| same(b, f)).is_some() && want.peek().is_none() {
+                    return Some(j + 1);
+                }
+            }
+            None
+        })
+        .min()
+}
@@ -1,9 +1,41 @@
 use crate::settings::Settings;
+use crate::terminal::console::outln;
 use crate::terminal::{
-    RESET, UNDERLINE, box_bottom, box_line, box_line_center, box_opt, box_top, clear, flush,
-    format_number, print_error, print_rule,
+    RED, RESET, UNDERLINE, YELLOW, box_bottom, box_line, box_line_center, box_opt, box_top, clear,
+    flush, format_number, print_error, print_rule,
 };
 
+/// Settings fields that conflict with the active policy, with why.
+type Conflicts = [(&'static str, String)];
+
+/// Print a settings line, in red when `field` conflicts with the policy.
+fn setting_line(text: &str, field: &str, conflicts: &Conflicts) {
+    if conflicts.iter().any(|&(f, _)| f == field) {
+        box_line(&format!("{RED}{text}{RESET}"));
+        print_conflicts(field, conflicts);
+    } else {
+        box_line(text);
+    }
+}
+
+/// Print why `field` conflicts with the policy, one line per reason.
+fn print_conflicts(field: &str, conflicts: &Conflicts) {
+    for (_, reason) in conflicts.iter().filter(|&&(f, _)| f == field) {
+        box_line(&format!("{RED}      - {reason}{RESET}"));
+    }
+}
+
+/// Caution under the length when the settings make weak passwords.
+fn print_weaknesses(settings: &Settings) {
+    let mut settings = settings.clone();
+    settings.passphrase = crate::pass::passphrase::from_settings(&settings)
+        .ok()
+        .flatten();
+    for reason in crate::pass::weaknesses(&settings, None) {
+        box_line(&format!("{YELLOW}      - Caution: {reason}{RESET}"));
+    }
+}
+
 /// Format special chars as ['a', 'b', ...] with wrapping across multiple lines
 fn print_special_chars_wrapped(settings: &Settings) {
     let prefix = "  4) Special Character List: ";
@@ -74,6 +106,125 @@ pub fn print_help() {
     box_line("");
     box_line("USAGE:");
     box_line("  randpass [OPTIONS]");
+    box_line("  randpass <COMMAND> [ARGS]");
+    box_line("");
+    box_line("COMMANDS:");
+    box_opt(
+        "  ssh-keygen [OPTIONS]",
+        "Create an SSH key with a generated passphrase (passed via stdin); options -t -f -C -l -b, --store to keep the passphrase for vault-pass",
+    );
+    box_opt(
+        "  protect-key <FILE>",
+        "Re-encrypt an age identity or PGP secret key with a generated passphrase; --store keeps it for vault-pass",
+    );
+    box_opt(
+        "  systemd-cred <NAME>",
+        "Store a generated secret via systemd-creds encrypt in /etc/credstore.encrypted; --plain for a 0600 file",
+    );
+    box_opt(
+        "  vault-pass [OPTIONS]",
+        "Print an ansible-vault password, generated and stored on first use; --vault-id ID, --no-store",
+    );
+    box_opt(
+        "  git-credential <ACTION>",
+        "git credential helper (get/store/erase); --generate answers an unknown host with a new stored password",
+    );
+    box_opt(
+        "  policy check [FILE]",
+        "Report which stdin/FILE passwords break a --policy FILE or --preset NAME, rule by rule",
+    );
+    box_opt(
+        "  policy explain <POLICY>",
+        "Summarize a policy file, installed name or --preset, with its entropy range; policy list shows them all",
+    );
+    box_opt(
+        "  policy export <POLICY>",
+        "Print a policy (presets too) in the file format (-o FILE); policy import FILE installs one as --policy NAME",
+    );
+    box_opt(
+        "  config show [--json]",
+        "Print saved settings; config get <KEY> / config set <KEY> <VALUE> edit one",
+    );
+    box_opt(
+        "  doctor",
+        "Check config, mlock limit, urandom, clipboard, terminal and entropy health",
+    );
+    box_opt(
+        "  bench [--json]",
+        "Time pool building, shuffling, sampling and each entropy source on this machine",
+    );
+    box_opt(
+        "  compare-rng [--bytes N]",
+        "Draw N bytes (default 16M) from each RNG backend; throughput, bit balance and byte chi-square side by side",
+    );
+    box_opt(
+        "  entropy-audit",
+        "Run SP 800-90B min-entropy estimators on raw hardware counter samples; --samples N (default 1M)",
+    );
+    box_opt(
+        "  entropy",
+        "Estimate a password you type (hidden) or pipe in: Shannon, charset and pattern-adjusted bits",
+    );
+    box_opt(
+        "  stats <FILE|->",
+        "Character frequency, class mix by position, lengths and a chi-square uniformity test of a generated file",
+    );
+    box_opt(
+        "  dedupe FILES... [-o FILE]",
+        "Merge files into one sorted file without duplicate lines; --memory SIZE (default 64M) bounds each run",
+    );
+    box_opt(
+        "  log show | clear",
+        "Print or empty the provenance log kept when audit_log is on (time, count, length, entropy, output, RNG)",
+    );
+    box_opt(
+        "  history enable | list",
+        "Keep generated passwords encrypted (Argon2id passphrase); show ID, copy ID, purge [ID], disable",
+    );
+    box_opt(
+        "  --protocol",
+        "Serve JSON-lines requests on stdin ({\"cmd\":\"generate\",\"length\":32,\"count\":5}) until quit",
+    );
+    box_opt(
+        "  --script <FILE>",
+        "Run the menu headless: type FILE's lines and keys (<esc>, <ctrl-u>), wait on ? TEXT, print to stdout",
+    );
+    box_opt(
+        "  keyfile <PATH> [--size]",
+        "Create a 0400 random keyfile (default 4096 bytes); --digest prints its hash",
+    );
+    box_opt(
+        "  dice [--words N]",
+        "Passphrase from physical dice rolls typed in (5 dice per word); -b, --list, --wordlist",
+    );
+    box_opt(
+        "  username [--style S]",
+        "Non-identifying username (adjective-noun, pronounceable, alnum); --max-len, -n, --with-pass",
+    );
+    box_opt(
+        "  pattern add NAME PAT",
+        "Save a --pattern for --pattern-name NAME (--force replaces); pattern list, show <NAME>, remove <NAME>",
+    );
+    box_opt(
+        "  wordlist list",
+        "Show embedded and installed passphrase wordlists",
+    );
+    box_opt(
+        "  wordlist add NAME SRC",
+        "Install a list from a file or https URL, recording its SHA-256 (--sha256 HEX to pin); use with --list NAME",
+    );
+    box_opt(
+        "  wordlist check SRC",
+        "Screen a list before adopting it: repeats, words one edit apart, words inside others, short words",
+    );
+    box_opt(
+        "  wordlist verify [NAME]",
+        "Check installed lists against their recorded SHA-256; wordlist remove <NAME> deletes one",
+    );
+    box_opt(
+        "  wipe <DEVICE> [--bs]",
+        "Overwrite an unmounted block device with random bytes (asks to confirm)",
+    );
     box_line("");
     box_line("OPTIONS:");
     box_line(" Password:");
@@ -83,7 +234,7 @@ pub fn print_help() {
     );
     box_opt(
         "  -n, --number <N>",
-        "How many to generate. With --bytes, this is byte count and supports K/M/G suffixes.",
+        "How many to generate, 0 streams until the reader stops. With --bytes, this is byte count and supports K/M/G suffixes.",
     );
     box_opt("      --hex", "Hex charset only (0-9, a-f)");
     box_opt(
@@ -91,35 +242,306 @@ pub fn print_help() {
         "Alphanumeric only, no special characters",
     );
     box_opt("      --special <CHARS>", "Override special character set");
+    box_opt(
+        "      --start-with <CLASS>",
+        "First character from letter, lower, upper or alnum; the rest unchanged",
+    );
+    box_opt(
+        "      --pattern <PAT>",
+        "Every password from a template: d digit, l/u/L letter, a/A alnum, h/H hex, s special, * any; X{N} repeats, \\ literal",
+    );
+    box_opt(
+        "      --pattern-name <NAME>",
+        "Use a pattern saved with randpass pattern add",
+    );
+    box_opt(
+        "      --acronym <PHRASE>",
+        "Initials of a phrase with random case and symbols, padded to -l (default 8 more); - asks for the phrase",
+    );
+    box_opt(
+        "      --pad <CHAR>",
+        "With --pad-to LEN: pad a short -l core (default 10) with CHAR, or one random --special char; padding counts ~0 bits",
+    );
+    box_opt(
+        "      --policy <FILE>",
+        "Make every password satisfy a TOML policy (lengths, required classes, forbidden text, regexes)",
+    );
+    box_opt(
+        "      --preset <NAME>",
+        "Built-in policy: aws-iam, active-directory, mysql or pin4",
+    );
+    box_opt(
+        "      --must-match <RE>",
+        "Every password must match RE (repeatable); --must-not-match <RE> rejects matches",
+    );
+    box_opt(
+        "      --digit-at <POS>",
+        "Digit at first, last or N, none there with !POS (comma list); --lower-at, --upper-at, --special-at too",
+    );
+    box_opt(
+        "      --forbid <FILE>",
+        "Reject passwords containing a listed word (any case, leetspeak too) or a --for-each name",
+    );
+    box_opt(
+        "      --min-entropy <BITS>",
+        "Refuse passwords under BITS of entropy (saved: min_entropy_bits); --allow-weak overrides",
+    );
+    box_opt(
+        "      --force",
+        "Skip the caution for weak settings (under 60 bits, or one character class)",
+    );
+    box_opt(
+        "      --chain",
+        "Derive the batch from one random seed (printed to stderr) via HMAC-SHA256",
+    );
+    box_opt(
+        "      --chain-seed <HEX>",
+        "Re-derive a --chain batch from its recorded seed",
+    );
+    box_opt(
+        "      --stretch <KDF>",
+        "Also print salt and derived key: pbkdf2[:ITER] or scrypt[:LOG_N,R,P]",
+    );
+    box_opt(
+        "      --mnemonic",
+        "Print a spelling hint under each password (NATO words, UPPER for capitals)",
+    );
+    box_opt(
+        "      --pick-from <N>",
+        "Choose one of N candidates in an arrow-key list (type to filter); only it is printed or copied",
+    );
+    box_opt(
+        "      --board-select",
+        "Number the batch on the terminal, then copy the entry whose number you type (Enter to finish)",
+    );
+    box_opt(
+        "      --label <TEXT>",
+        "File this run's passwords under TEXT in the encrypted history",
+    );
+    box_line("");
+    box_line(" Passphrase:");
+    box_opt(
+        "      --words <N>",
+        "Generate N-word passphrases instead of passwords (default with --list: 7)",
+    );
+    box_opt(
+        "      --pseudo-words <N>",
+        "N pronounceable made-up words (5-8 letters, 26 bits each, never a BIP39 English word; other English words may occur)",
+    );
+    box_opt(
+        "      --pronounceable [N]",
+        "One word of N syllables in --lang en, de or ja (romaji); default 60 bits, --words for more",
+    );
+    box_opt(
+        "      --list <NAME>",
+        "Wordlist to draw from (default: bip39-english, see wordlist list)",
+    );
+    box_opt(
+        "      --lang <CODE>",
+        "Embedded list by language: en, es, fr, it, pt or cs",
+    );
+    box_opt(
+        "      --wordlist <PATH>",
+        "Draw from a file, one word per line (or diceware ROLL WORD); saved as `wordlist`",
+    );
+    box_opt(
+        "      --word-sep <SEP>",
+        "Between words: one character (default: -), none, or camel (CorrectHorse); saved as `word_sep`",
+    );
+    box_opt(
+        "      --caps <MODE>",
+        "Capitalize word initials: none, first (every word) or random (+1 bit/word)",
+    );
+    box_opt(
+        "      --digits <N>",
+        "Append N random digits to random words",
+    );
+    box_opt(
+        "      --symbols <N>",
+        "Append N random symbols (from --special) to random words",
+    );
+    box_opt(
+        "      --memorable [1-4]",
+        "Word7!word hybrids; higher levels add words and digits (28-73 bits, default 2)",
+    );
+    box_opt(
+        "      --leet <MODE>",
+        "Random leetspeak in words: light, heavy or a map like a=@4,e=3 (counted in entropy)",
+    );
     box_line("");
     box_line(" Output:");
+    box_opt(
+        "      --for-each <FILE>",
+        "One password per line of FILE, printed as record:password",
+    );
+    box_opt(
+        "      --hash <SCHEME>",
+        "Print crypt(3) hashes (sha512-crypt or yescrypt); plaintext goes to -o",
+    );
+    box_opt(
+        "      --format <FORMAT>",
+        "k8s-secret (Secret manifest, needs --name) or env (KEY=value)",
+    );
+    box_opt(
+        "      --key-name <KEY>",
+        "With --format: the data key or variable name (password / PASSWORD)",
+    );
+    box_opt(
+        "      --template <TEXT>",
+        "Line per password from {index} {password} {entropy} {length} {timestamp} {record}; \\t is a tab",
+    );
     box_opt(
         "  -o, --output [FILE]",
         "Write to file (default: rand_pass.txt)",
     );
+    box_opt(
+        "      --countdown <SECS>",
+        "Count down SECS before writing over 500,000 passwords to -o (default: none; the menu uses countdown_secs)",
+    );
+    box_opt(
+        "      --notify",
+        "Desktop notification when the run finishes or is interrupted (terminal bell without one)",
+    );
+    box_opt(
+        "      --meta json",
+        "Run metadata on stderr at the end: count, RNG, time, raw entropy per backend",
+    );
     box_opt("  -b, --board", "Copy to clipboard instead of printing");
+    box_opt(
+        "      --color-classes",
+        "Color digits, uppercase and symbols on a terminal (plain in pipes and files)",
+    );
     box_opt(
         "  -q, --quiet",
         "Suppress all output except passwords/bytes",
     );
+    box_opt(
+        "      --dry-run",
+        "Print the resolved settings, entropy and destination; generate and write nothing",
+    );
+    box_opt(
+        "  -V, --verbose",
+        "Report decisions on stderr (config layers, RNG, policy draws); -VV adds settings, pool and timings",
+    );
     box_line("");
     box_line(" Settings:");
     box_opt("  -c, --command", "Show saved command (alias for -c get)");
     box_opt("  -c get", "Show saved command");
     box_opt("  -c set [FLAGS]", "Save flags as default command");
     box_opt("  -c unset", "Clear saved command");
+    box_opt(
+        "      --no-command",
+        "Don't apply the saved command (a run without other flags uses it, with a notice)",
+    );
     box_opt("  -d, --default", "Use default settings");
     box_opt("  -s, --saved", "Use saved settings from config file");
+    box_opt(
+        "      --profile <NAME>",
+        "Apply flags saved by profile set NAME [FLAGS]; flags given here win (profile show/list/remove)",
+    );
+    box_opt(
+        "      --no-save",
+        "Never create or modify ~/.config/randpass (also RANDPASS_EPHEMERAL=1)",
+    );
+    box_opt(
+        "      --accessible",
+        "Plain lines for screen readers: no boxes, redraws or cursor moves (also RANDPASS_ACCESSIBLE=1)",
+    );
+    box_opt(
+        "      --session-stats",
+        "At exit, print on stderr what the session did: passwords, random bytes, source switches, time",
+    );
     box_line("");
     box_line(" Entropy:");
     box_opt(
         "  -u, --urandom",
         "Use /dev/urandom pool instead of hardware",
     );
+    box_opt(
+        "      --rng <SOURCE>",
+        "Entropy source: counter (default), urandom (-u) or hwrng, a hardware TRNG device",
+    );
+    box_opt(
+        "      --rng <A,B,..>",
+        "Failover list: use the first that starts, the next when it fails (default: rng)",
+    );
+    box_opt(
+        "      --warmup <N>",
+        "RNG outputs discarded after each seeding (default: warmup_discard, 64)",
+    );
+    box_opt(
+        "      --hwrng-device <PATH>",
+        "TRNG character device for --rng hwrng (default: hwrng_device, /dev/hwrng)",
+    );
+    box_opt(
+        "      --hwrng-read <SIZE>",
+        "Bytes asked of the TRNG per read, up to 64K (default: hwrng_read_size, 4096)",
+    );
+    box_opt(
+        "      --prefetch",
+        "Draw RNG words from a ring filled ahead by a background thread",
+    );
     box_opt(
         "      --bytes",
         "Output raw bytes. Use -n for limit, -o for file.",
     );
+    box_opt(
+        "      --bs <SIZE>",
+        "With --bytes: write size per syscall, K/M/G suffixes (default: 64K)",
+    );
+    box_opt(
+        "      --count <N>",
+        "With --bytes: write N blocks of --bs (instead of -n)",
+    );
+    box_opt(
+        "      --direct",
+        "With --bytes -o: open with O_DIRECT (aligned, bypasses page cache)",
+    );
+    box_opt(
+        "      --io-uring",
+        "Write -o files via io_uring (writev fallback), overlapping disk and generation",
+    );
+    box_opt(
+        "      --mmap",
+        "Write -o files through a memory mapping (no write syscalls); not with --io-uring",
+    );
+    box_opt(
+        "      --fsync <POLICY>",
+        "Sync -o files: never (default), end, or interval[:SIZE] (every 256M by default)",
+    );
+    box_opt(
+        "      --split-bytes <SIZE>",
+        "With --bytes -n -o PREFIX: write PREFIX.000, PREFIX.001, ... of SIZE each",
+    );
+    box_opt(
+        "      --checksum <ALG>",
+        "With --bytes: digest the output (sha256 or blake3) into FILE.sha256/.blake3",
+    );
+    box_opt(
+        "      --no-health-check",
+        "With --bytes: skip the startup test that refuses a stuck or biased source",
+    );
+    box_opt(
+        "      --stats",
+        "With --bytes: print bytes written, elapsed time and MB/s to stderr",
+    );
+    box_opt(
+        "      --salt <BYTES>",
+        "Print a random salt of BYTES length, up to 1024 (hex)",
+    );
+    box_opt(
+        "      --key <128|256>",
+        "Print a random 128- or 256-bit key (hex)",
+    );
+    box_opt(
+        "      --license-key [GxL]",
+        "Serial keys, G groups of L (default 4x5) from 31 unambiguous characters; --check-char, -n",
+    );
+    box_opt(
+        "      --encode <ENC>",
+        "Encode --bytes/--salt/--key output: hex, base64 or base32",
+    );
+    box_opt("      --base64", "Shorthand for --encode base64");
     box_line("");
     box_line(" Info:");
     box_opt("  -h, --help", "Display this help message");
@@ -135,26 +557,39 @@ pub fn print_help() {
     box_line("  randpass --bytes -n 1M   1MB of random bytes to stdout");
     box_line("");
     box_bottom();
-    println!();
+    outln!();
 }
 
 pub fn print_file_exists(file_name: &str) {
     print_error(&format!("File {file_name} already exists."));
-    println!();
+    outln!();
     box_top("");
     box_line_center("a) append | o) overwrite");
     box_bottom();
-    println!();
+    outln!();
     flush();
 }
 
+/// What this session has done so far (`session::rows`).
+pub fn print_session_stats() {
+    box_top("Session Stats");
+    let rows = crate::session::rows();
+    let width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+    for (label, value) in rows {
+        box_line(&format!("  {:<width$}  {}", label, value));
+    }
+    box_bottom();
+    outln!();
+}
+
 pub fn print_main_menu(print_invalid: &mut bool) {
     box_top("Main Menu");
     box_line("");
     box_line("  1) settings");
     box_line("  2) clear");
     box_line("  3) help");
-    box_line("  4) quit");
+    box_line("  4) stats");
+    box_line("  5) quit");
     box_line("");
     box_bottom();
 
@@ -163,7 +598,7 @@ pub fn print_main_menu(print_invalid: &mut bool) {
         print_error("Invalid option.");
         *print_invalid = false;
     } else {
-        println!();
+        outln!();
     }
     flush();
 }
@@ -171,15 +606,31 @@ pub fn print_main_menu(print_invalid: &mut bool) {
 pub fn print_settings_menu(settings: &Settings, print_error_code: i32, error_txt: &str) {
     clear();
     box_top("Settings Menu");
-    box_line_center("Esc/CTRL+Q: cancel | CTRL+U: clear input");
+    if crate::terminal::accessible::enabled() {
+        box_line("Empty keeps the current value | Ctrl+D: cancel");
+    } else {
+        box_line_center("Esc/CTRL+Q: cancel | CTRL+U: clear input");
+    }
     box_line("");
 
     // General section
+    // Settings the active policy can't be met with, shown in red
+    let conflicts = settings
+        .policy
+        .as_ref()
+        .map(|p| p.conflicts(settings))
+        .unwrap_or_default();
+
     box_line(&format!("{UNDERLINE}General{RESET}:"));
-    box_line(&format!(
-        "  1) Password Length: {}",
-        format_number(settings.pass_length)
-    ));
+    setting_line(
+        &format!(
+            "  1) Password Length: {}",
+            format_number(settings.pass_length)
+        ),
+        "pass_length",
+        &conflicts,
+    );
+    print_weaknesses(settings);
     box_line(&format!(
         "  2) View Seed Strings: {}",
         settings.view_chars_str
@@ -189,26 +640,43 @@ pub fn print_settings_menu(settings: &Settings, print_error_code: i32, error_txt
         format_number(settings.number_of_passwords)
     ));
     print_special_chars_wrapped(settings);
+    print_conflicts("special_chars", &conflicts);
 
     // Character Density section
     box_line("");
     box_line(&format!("{UNDERLINE}Character Density Multiplier{RESET}:"));
-    box_line(&format!(
-        "  5) Special: {}",
-        format_number(settings.special_char_density)
-    ));
-    box_line(&format!(
-        "  6) Numeric: {}",
-        format_number(settings.numeric_char_density)
-    ));
-    box_line(&format!(
-        "  7) Lowercase: {}",
-        format_number(settings.lowercase_char_density)
-    ));
-    box_line(&format!(
-        "  8) Uppercase: {}",
-        format_number(settings.uppercase_char_density)
-    ));
+    setting_line(
+        &format!(
+            "  5) Special: {}",
+            format_number(settings.special_char_density)
+        ),
+        "special_char_density",
+        &conflicts,
+    );
+    setting_line(
+        &format!(
+            "  6) Numeric: {}",
+            format_number(settings.numeric_char_density)
+        ),
+        "numeric_char_density",
+        &conflicts,
+    );
+    setting_line(
+        &format!(
+            "  7) Lowercase: {}",
+            format_number(settings.lowercase_char_density)
+        ),
+        "lowercase_char_density",
+        &conflicts,
+    );
+    setting_line(
+        &format!(
+            "  8) Uppercase: {}",
+            format_number(settings.uppercase_char_density)
+        ),
+        "uppercase_char_density",
+        &conflicts,
+    );
 
     // Output section
     box_line("");
@@ -222,8 +690,11 @@ pub fn print_settings_menu(settings: &Settings, print_error_code: i32, error_txt
         settings.output_file_path
     ));
     box_line(&format!(
-        "  11) Skip Pre-Generation Countdown: {}",
-        settings.skip_countdown
+        "  11) Pre-Generation Countdown: {}",
+        match settings.countdown() {
+            0 => "off".to_string(),
+            secs => format!("{}s", secs),
+        }
     ));
     box_line("      - Occurs when #3 (Number of Passwords) > 500,000");
 
@@ -241,11 +712,44 @@ pub fn print_settings_menu(settings: &Settings, print_error_code: i32, error_txt
     box_line(&format!("{UNDERLINE}Entropy{RESET}:"));
     box_line(&format!("  13) Source: {}", crate::rand::entropy_source()));
 
+    // Passphrase section
+    box_line("");
+    box_line(&format!("{UNDERLINE}Passphrase{RESET}:"));
+    box_line(&format!(
+        "  14) Words per passphrase: {}",
+        settings.passphrase_words
+    ));
+    box_line("      - 0 generates character passwords");
+    box_line(&format!("  15) Word separator: {}", settings.word_sep));
+    let wordlist = if settings.wordlist.is_empty() {
+        crate::pass::words::DEFAULT
+    } else {
+        &settings.wordlist
+    };
+    box_line(&format!("  16) Wordlist: {}", wordlist));
+
+    // Policy section
+    box_line("");
+    box_line(&format!("{UNDERLINE}Policy{RESET}:"));
+    let policy = settings
+        .policy
+        .as_ref()
+        .map_or("none", |p| p.source.as_str());
+    box_line(&format!("  17) Policy: {}", policy));
+    if !conflicts.is_empty() {
+        box_line(&format!(
+            "{RED}      - generating is blocked until the settings in red are fixed{RESET}"
+        ));
+    }
+    for (name, summary, _) in crate::pass::policy::preset::PRESETS {
+        box_line(&format!("      - {}: {}", name, summary));
+    }
+
     // Footer
     box_line("");
     print_rule();
     box_line("     r) load defaults  |  f) load saved  |  s) save  |  e) exit");
-    box_line("     d) delete output file");
+    box_line("     u) undo  |  x) discard changes  |  d) delete output file");
     box_bottom();
 
     // Error messages (or blank line if no error)
@@ -258,7 +762,7 @@ pub fn print_settings_menu(settings: &Settings, print_error_code: i32, error_txt
         3 => print_error("Invalid input, please enter a valid file path..."),
         998 => print_error("Invalid input, please enter a valid menu option..."),
         999 => print_error(error_txt),
-        _ => println!(),
+        _ => outln!(),
     }
     flush();
 }
@@ -0,0 +1,12 @@
+//! Helpers shared by the integration tests.
+
+use std::path::PathBuf;
+
+/// A fresh directory for one test, used as HOME or for its files.
+/// `name` must be unique across the test files.
+pub fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("randpass-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -0,0 +1,79 @@
+//! `randpass --script` end to end: the built binary runs the menu from a
+//! script file, in a scratch HOME so no saved settings get in the way.
+
+mod common;
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+use common::scratch;
+
+/// Run `script` in `dir` with plain accessible output.
+fn run(dir: &Path, script: &str) -> Output {
+    std::fs::write(dir.join("script.txt"), script).unwrap();
+    Command::new(env!("CARGO_BIN_EXE_randpass"))
+        .args(["--script", "script.txt", "--no-save", "--accessible"])
+        .current_dir(dir)
+        .env("HOME", dir)
+        .env_remove("RANDPASS_ACCESSIBLE")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn file_exists_dialog_appends() {
+    let dir = scratch("append");
+    std::fs::write(dir.join("out.txt"), "old\n").unwrap();
+
+    let output = run(
+        &dir,
+        "# point the output at a file that exists, then append to it\n\
+         1\n\
+         10\n\
+         <ctrl-u>out.txt\n\
+         ? Password output file path: out.txt\n\
+         <enter>\n\
+         ? already exists\n\
+         a\n\
+         ? Complete\n\
+         5\n",
+    );
+    assert!(output.status.success(), "{:?}", output);
+
+    let written = std::fs::read_to_string(dir.join("out.txt")).unwrap();
+    let lines: Vec<&str> = written.lines().collect();
+    assert_eq!(lines.len(), 20);
+    assert_eq!(lines[0], "old");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("a) append | o) overwrite"));
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn unmet_expectation_fails_the_run() {
+    let dir = scratch("expect");
+    let output = run(&dir, "? no such text\n5\n");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("expected \"no such text\", which was not shown"));
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn running_out_of_keys_fails_the_run() {
+    let dir = scratch("short");
+    let output = run(&dir, "1\n");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("ran out of keys"));
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn unknown_key_names_are_rejected() {
+    let dir = scratch("keys");
+    let output = run(&dir, "1\n<escape>\n");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("line 2: unknown key <escape>"));
+    let _ = std::fs::remove_dir_all(dir);
+}
@@ -0,0 +1,108 @@
+//! The settings file under concurrent writers: the built binary runs in a
+//! scratch HOME, several `config set` processes at once.
+
+mod common;
+
+use std::path::Path;
+use std::process::{Child, Command};
+
+use common::scratch;
+
+fn config(home: &Path, args: &[&str]) -> Child {
+    Command::new(env!("CARGO_BIN_EXE_randpass"))
+        .arg("config")
+        .args(args)
+        .env("HOME", home)
+        .env_remove("RANDPASS_EPHEMERAL")
+        .spawn()
+        .unwrap()
+}
+
+fn get(home: &Path, key: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_randpass"))
+        .args(["config", "get", key])
+        .env("HOME", home)
+        .env_remove("RANDPASS_EPHEMERAL")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+#[test]
+fn parallel_saves_leave_a_whole_file() {
+    let home = scratch("whole");
+    let values: Vec<String> = (10..50).map(|n| n.to_string()).collect();
+
+    let savers: Vec<Child> = values
+        .iter()
+        .map(|v| config(&home, &["set", "pass_length", v]))
+        .collect();
+    for mut saver in savers {
+        assert!(saver.wait().unwrap().success());
+    }
+
+    assert!(values.contains(&get(&home, "pass_length")));
+    let dir = home.join(".config/randpass");
+    let text = std::fs::read_to_string(dir.join("settings")).unwrap();
+    assert!(text.starts_with("version="), "{:?}", text);
+    assert!(text.ends_with('\n'), "{:?}", text);
+    // Every temp file was renamed into place
+    let mut names: Vec<String> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["settings", "settings.lock"]);
+    let _ = std::fs::remove_dir_all(home);
+}
+
+#[test]
+fn parallel_savers_keep_every_change() {
+    let home = scratch("parallel");
+    let fields = [
+        "pass_length",
+        "number_of_passwords",
+        "countdown_secs",
+        "special_char_density",
+        "numeric_char_density",
+        "lowercase_char_density",
+        "uppercase_char_density",
+        "passphrase_words",
+    ];
+
+    for round in 1..=5 {
+        let savers: Vec<Child> = fields
+            .iter()
+            .enumerate()
+            .map(|(i, key)| config(&home, &["set", key, &(round * 10 + i).to_string()]))
+            .collect();
+        for mut saver in savers {
+            assert!(saver.wait().unwrap().success());
+        }
+        for (i, key) in fields.iter().enumerate() {
+            assert_eq!(get(&home, key), (round * 10 + i).to_string(), "{}", key);
+        }
+    }
+    let _ = std::fs::remove_dir_all(home);
+}
+
+#[test]
+fn newer_file_is_not_saved_over() {
+    let home = scratch("newer");
+    let dir = home.join(".config/randpass");
+    std::fs::create_dir_all(&dir).unwrap();
+    let text = "version=4\npass_length=30\nfuture_key=kept\n";
+    std::fs::write(dir.join("settings"), text).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_randpass"))
+        .args(["config", "set", "pass_length", "20"])
+        .env("HOME", &home)
+        .env_remove("RANDPASS_EPHEMERAL")
+        .output()
+        .unwrap();
+    assert!(!output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("newer randpass"));
+    assert_eq!(std::fs::read_to_string(dir.join("settings")).unwrap(), text);
+    let _ = std::fs::remove_dir_all(home);
+}
@@ -0,0 +1,183 @@
+//! Interactive flows driven through a scripted console: keys go in as
+//! events, and what the menus, prompts, countdown and progress view draw
+//! is read back from the script's output.
+
+mod common;
+
+use common::scratch;
+use crossterm::event::KeyCode;
+use randpass::pass::output::with_progress;
+use randpass::settings::{Settings, ephemeral};
+use randpass::terminal::accessible;
+use randpass::terminal::console::{Script, ctrl, key, line};
+use randpass::tui::{get_editable_input, get_numeric_input, pick, update_settings};
+
+/// A script of `events`, in the boxed layout. Runs are ephemeral, so
+/// nothing is recorded under the real HOME.
+fn boxed(events: impl IntoIterator<Item = crossterm::event::Event>) -> Script {
+    let script = Script::new(events);
+    accessible::set(false);
+    ephemeral::set(true);
+    script
+}
+
+#[test]
+fn editable_input_inserts_at_the_cursor() {
+    let mut events = line("abd");
+    events.insert(3, key(KeyCode::Left));
+    events.insert(4, key(KeyCode::Char('c')));
+    let script = boxed(events);
+
+    assert_eq!(get_editable_input("Name", ""), Some("abcd".to_string()));
+    let output = script.output();
+    assert!(output.starts_with("Name: "));
+    assert!(output.ends_with("\rName: abcd\x1b[10G\n"));
+}
+
+#[test]
+fn editable_input_cancels_on_esc() {
+    let script = boxed([key(KeyCode::Char('x')), key(KeyCode::Esc)]);
+    assert_eq!(get_editable_input("Name", "keep"), None);
+    assert_eq!(script.pending(), 0);
+}
+
+#[test]
+fn numeric_input_groups_digits() {
+    let mut events = vec![ctrl('u')];
+    events.extend(line("1234567"));
+    let script = boxed(events);
+
+    assert_eq!(get_numeric_input("Count", 19), Some(1_234_567));
+    assert!(script.output().contains("Count: 1,234,567"));
+}
+
+#[test]
+fn accessible_input_reads_a_line() {
+    let script = Script::new(line("").into_iter().chain(line("42")));
+    accessible::set(true);
+    let kept = get_editable_input("Name", "keep");
+    let count = get_numeric_input("Count", 7);
+    accessible::set(false);
+
+    assert_eq!(kept, Some("keep".to_string()));
+    assert_eq!(count, Some(42));
+    assert_eq!(script.output(), "Name [keep]: \nCount [7]: 42\n");
+}
+
+#[test]
+fn picker_moves_filters_and_cancels() {
+    let candidates: [&[u8]; 3] = [b"alpha", b"beta", b"gamma"];
+
+    let _script = boxed([key(KeyCode::Down), key(KeyCode::Enter)]);
+    assert_eq!(pick(&candidates), Some(1));
+    drop(_script);
+
+    let _script = boxed(line("gm"));
+    assert_eq!(pick(&candidates), Some(2));
+    drop(_script);
+
+    let script = boxed([key(KeyCode::Char('a')), key(KeyCode::Esc)]);
+    assert_eq!(pick(&candidates), None);
+    assert!(script.output().contains("Pick one (3/3)"));
+}
+
+#[test]
+fn settings_menu_edits_and_undoes() {
+    let mut events = line("1");
+    events.push(ctrl('u'));
+    events.extend(line("24"));
+    let mut settings = Settings::default();
+
+    let script = boxed(events.iter().cloned().chain([key(KeyCode::Esc)]));
+    update_settings(&mut settings);
+    assert_eq!(settings.pass_length, 24);
+    assert!(script.output().contains("1) Password Length: 24"));
+    drop(script);
+
+    let mut settings = Settings::default();
+    let _script = boxed(
+        events
+            .into_iter()
+            .chain(line("u"))
+            .chain([key(KeyCode::Esc)]),
+    );
+    update_settings(&mut settings);
+    assert_eq!(settings.pass_length, Settings::default().pass_length);
+}
+
+#[test]
+fn short_terminal_gets_the_compact_menu() {
+    let script = boxed([key(KeyCode::Esc)]).with_size(80, 20);
+    update_settings(&mut Settings::default());
+    let output = script.output();
+    assert!(output.contains("1) Password Length: 74"));
+    assert!(!output.contains('┌'));
+}
+
+#[test]
+fn countdown_cancels_on_esc() {
+    let dir = scratch("countdown");
+    let settings = Settings {
+        number_of_passwords: 600_000,
+        output_to_terminal: false,
+        output_file_path: dir.join("out.txt").display().to_string(),
+        countdown_secs: 5,
+        ..Default::default()
+    };
+
+    let script = boxed([key(KeyCode::Esc)]);
+    with_progress(&settings);
+    let output = script.output();
+    drop(script);
+
+    assert!(output.contains("[Esc/Ctrl+C] to interrupt"));
+    assert!(output.contains("Generation aborted by user"));
+    assert_eq!(std::fs::metadata(dir.join("out.txt")).unwrap().len(), 0);
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn progress_run_writes_the_file() {
+    let dir = scratch("progress");
+    let settings = Settings {
+        number_of_passwords: 1000,
+        pass_length: 16,
+        output_to_terminal: false,
+        output_file_path: dir.join("out.txt").display().to_string(),
+        ..Default::default()
+    };
+
+    let script = boxed([]);
+    with_progress(&settings);
+    let output = script.output();
+    drop(script);
+
+    assert!(output.contains("1000 password(s) generated in"));
+    let written = std::fs::read_to_string(dir.join("out.txt")).unwrap();
+    assert_eq!(written.lines().count(), 1000);
+    assert!(written.lines().all(|l| l.len() == 16));
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn terminal_run_prints_each_password() {
+    let settings = Settings {
+        number_of_passwords: 3,
+        pass_length: 12,
+        ..Default::default()
+    };
+
+    let script = boxed([]);
+    with_progress(&settings);
+    let output = script.output();
+    drop(script);
+
+    // Each password is its own line, written as \r, password, \r\n
+    let printed: Vec<&str> = output
+        .split("\r\n")
+        .filter_map(|l| l.rsplit('\r').next())
+        .filter(|l| l.len() == 12)
+        .collect();
+    assert_eq!(printed.len(), 3);
+    assert!(output.contains("3 password(s) generated in"));
+}